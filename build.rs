@@ -3,16 +3,19 @@
 //! Scans `src/layouts/<theme>/` at build time and generates a Rust
 //! file (`$OUT_DIR/themes.rs`) that hardcodes `include_str!` /
 //! `include_bytes!` calls for every theme found. The generated file
-//! exposes three lookups consumed by the binary:
+//! exposes these lookups consumed by the binary:
 //!
-//!   - `layout_for(theme)`  -> Option<&'static str>
-//!   - `css_for(theme)`     -> Option<&'static str>
-//!   - `fonts_for(theme)`   -> &'static [(&'static str, &'static [u8])]
-//!   - `THEMES`             -> &'static [&'static str]
+//!   - `layout_for(theme)`   -> Option<&'static str>
+//!   - `css_for(theme)`      -> Option<&'static str>
+//!   - `manifest_for(theme)` -> Option<&'static str>
+//!   - `fonts_for(theme)`    -> &'static [(&'static str, &'static [u8])]
+//!   - `THEMES`              -> &'static [&'static str]
 //!
 //! A theme is just a directory under `src/layouts/` containing
 //! `layout.resume` and `style.css`. Any files under `<theme>/fonts/`
 //! are bundled as font assets and emitted relative to that subtree.
+//! An optional `theme.toml` carries theme metadata; a theme whose
+//! manifest `extends` another may omit `layout.resume` and inherit it.
 //!
 //! To add a new theme: create the directory with the two required
 //! files (and optionally fonts), recompile. No code changes needed.
@@ -43,8 +46,10 @@ fn main() {
             let name = path.file_name()?.to_str()?.to_string();
             // Require both layout + css. Anything missing one is
             // an in-progress theme and we skip it rather than
-            // crashing the build.
-            let has_layout = path.join("layout.resume").is_file();
+            // crashing the build. Overlay themes (with a theme.toml)
+            // can inherit the layout from the theme they extend.
+            let has_layout = path.join("layout.resume").is_file()
+                || path.join("theme.toml").is_file();
             let has_css = path.join("style.css").is_file();
             if has_layout && has_css {
                 Some(name)
@@ -64,18 +69,7 @@ fn main() {
     out.push_str("];\n\n");
 
     // layout_for
-    out.push_str("pub fn layout_for(theme: &str) -> Option<&'static str> {\n");
-    out.push_str("    match theme {\n");
-    for theme in &themes {
-        let abs = canonicalize_for_include(&layouts_dir.join(theme).join("layout.resume"));
-        println!("cargo:rerun-if-changed={}", abs);
-        out.push_str(&format!(
-            "        {:?} => Some(include_str!({:?})),\n",
-            theme, abs,
-        ));
-    }
-    out.push_str("        _ => None,\n");
-    out.push_str("    }\n}\n\n");
+    push_optional_text_lookup(&mut out, &layouts_dir, &themes, "layout_for", "layout.resume");
 
     // css_for
     out.push_str("pub fn css_for(theme: &str) -> Option<&'static str> {\n");
@@ -91,6 +85,9 @@ fn main() {
     out.push_str("        _ => None,\n");
     out.push_str("    }\n}\n\n");
 
+    // manifest_for
+    push_optional_text_lookup(&mut out, &layouts_dir, &themes, "manifest_for", "theme.toml");
+
     // fonts_for: each theme gets a slice of (relative_path, bytes).
     // Relative path is relative to the theme's fonts/ dir so the
     // consumer can drop them into out_dir/fonts/<same path>.
@@ -129,6 +126,36 @@ fn main() {
         .unwrap_or_else(|e| panic!("write {}: {}", out_file.display(), e));
 }
 
+/// Emit `fn <name>(theme) -> Option<&'static str>` returning the
+/// contents of `<theme>/<file>` for every theme that has the file.
+fn push_optional_text_lookup(
+    out: &mut String,
+    layouts_dir: &Path,
+    themes: &[String],
+    fn_name: &str,
+    file: &str,
+) {
+    out.push_str(&format!(
+        "pub fn {}(theme: &str) -> Option<&'static str> {{\n",
+        fn_name
+    ));
+    out.push_str("    match theme {\n");
+    for theme in themes {
+        let path = layouts_dir.join(theme).join(file);
+        if !path.is_file() {
+            continue;
+        }
+        let abs = canonicalize_for_include(&path);
+        println!("cargo:rerun-if-changed={}", abs);
+        out.push_str(&format!(
+            "        {:?} => Some(include_str!({:?})),\n",
+            theme, abs,
+        ));
+    }
+    out.push_str("        _ => None,\n");
+    out.push_str("    }\n}\n\n");
+}
+
 fn collect_files(root: &Path, dir: &Path, out: &mut Vec<(String, String)>) {
     let entries = match fs::read_dir(dir) {
         Ok(e) => e,
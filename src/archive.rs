@@ -0,0 +1,355 @@
+//! Zip bundles of a build (`--archive`).
+//!
+//! The bundle holds everything in the output directory plus the JOBL
+//! files it was built from, so one upload (or one file in an
+//! applications folder) captures exactly what was sent. The zip
+//! writer is a small hand-rolled one on top of flate2: srg only ever
+//! writes a handful of deflated files, which doesn't justify a zip
+//! crate. [`read_zip`] is its counterpart, for the exports `srg
+//! import` reads.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// File name of the bundle inside the output directory.
+pub const BUNDLE_FILE: &str = "resume-bundle.zip";
+
+/// Zip `out_dir` (minus any previous bundle) and `sources` into
+/// `out_dir/resume-bundle.zip`, the sources under `source/`. Layered
+/// sources are numbered in the order they merge (`source/1-base.jobl`,
+/// `source/2-freelance.jobl`). Returns the bundle's path.
+pub fn bundle(out_dir: &Path, sources: &[PathBuf]) -> Result<PathBuf> {
+    let bundle_path = out_dir.join(BUNDLE_FILE);
+
+    let mut files = Vec::new();
+    collect_files(out_dir, out_dir, &mut files)?;
+    files.retain(|(name, _)| name != BUNDLE_FILE);
+    files.sort();
+
+    let mut zip = ZipWriter::new(Vec::new());
+    for (name, path) in &files {
+        let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        zip.add_file(name, &bytes)?;
+    }
+
+    for (i, source) in sources.iter().enumerate() {
+        let source_name = source
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("resume.jobl");
+        let source_bytes =
+            fs::read(source).with_context(|| format!("reading {}", source.display()))?;
+        let name = match sources.len() {
+            1 => format!("source/{}", source_name),
+            _ => format!("source/{}-{}", i + 1, source_name),
+        };
+        zip.add_file(&name, &source_bytes)?;
+    }
+
+    crate::build::write_atomic(&bundle_path, zip.finish()?)
+        .with_context(|| format!("writing {}", bundle_path.display()))?;
+    Ok(bundle_path)
+}
+
+/// Every regular file under `dir`, with its `/`-separated path
+/// relative to `root`.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if path.is_file() {
+            let rel = path.strip_prefix(root).unwrap_or(&path);
+            let name = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+/// Minimal zip writer: deflated or stored entries, no directories, no
+/// zip64.
+///
+/// Every entry gets the same fixed timestamp (1980-01-01, the zip
+/// epoch) so bundles of the same build are byte-for-byte identical.
+pub struct ZipWriter<W: Write> {
+    out: W,
+    offset: u32,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL: u32 = 0x0605_4b50;
+const VERSION: u16 = 20;
+/// General purpose flag bit 11: names are UTF-8.
+const UTF8_NAMES: u16 = 1 << 11;
+const STORED: u16 = 0;
+const DEFLATE: u16 = 8;
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            offset: 0,
+            central: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    /// Add a file named `name` (use `/` as the separator).
+    pub fn add_file(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        self.add_entry(name, data, DEFLATE, &compressed)
+    }
+
+    /// Add a file without compressing it, for formats that read an
+    /// entry in place (an OpenDocument file's `mimetype`).
+    pub fn add_stored(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        self.add_entry(name, data, STORED, data)
+    }
+
+    fn add_entry(&mut self, name: &str, data: &[u8], method: u16, compressed: &[u8]) -> Result<()> {
+        let crc = crc32fast::hash(data);
+
+        let name_len = u16::try_from(name.len()).context("zip entry name too long")?;
+        let compressed_len = u32::try_from(compressed.len()).context("zip entry too large")?;
+        let data_len = u32::try_from(data.len()).context("zip entry too large")?;
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        put_u32(&mut local, LOCAL_HEADER);
+        put_u16(&mut local, VERSION);
+        put_u16(&mut local, UTF8_NAMES);
+        put_u16(&mut local, method);
+        put_u16(&mut local, DOS_TIME);
+        put_u16(&mut local, DOS_DATE);
+        put_u32(&mut local, crc);
+        put_u32(&mut local, compressed_len);
+        put_u32(&mut local, data_len);
+        put_u16(&mut local, name_len);
+        put_u16(&mut local, 0);
+        local.extend_from_slice(name.as_bytes());
+
+        put_u32(&mut self.central, CENTRAL_HEADER);
+        // Made by: Unix (3), so the permission bits below are honored.
+        put_u16(&mut self.central, (3 << 8) | VERSION);
+        put_u16(&mut self.central, VERSION);
+        put_u16(&mut self.central, UTF8_NAMES);
+        put_u16(&mut self.central, method);
+        put_u16(&mut self.central, DOS_TIME);
+        put_u16(&mut self.central, DOS_DATE);
+        put_u32(&mut self.central, crc);
+        put_u32(&mut self.central, compressed_len);
+        put_u32(&mut self.central, data_len);
+        put_u16(&mut self.central, name_len);
+        put_u16(&mut self.central, 0); // extra field length
+        put_u16(&mut self.central, 0); // comment length
+        put_u16(&mut self.central, 0); // disk number
+        put_u16(&mut self.central, 0); // internal attributes
+        put_u32(&mut self.central, 0o100644 << 16);
+        put_u32(&mut self.central, self.offset);
+        self.central.extend_from_slice(name.as_bytes());
+
+        self.out.write_all(&local)?;
+        self.out.write_all(compressed)?;
+        self.offset = self
+            .offset
+            .checked_add(local.len() as u32 + compressed_len)
+            .context("zip archive too large")?;
+        self.entries = self.entries.checked_add(1).context("too many zip entries")?;
+        Ok(())
+    }
+
+    /// Write the central directory and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        let central_len = u32::try_from(self.central.len()).context("zip archive too large")?;
+
+        let mut end = Vec::with_capacity(22);
+        put_u32(&mut end, END_OF_CENTRAL);
+        put_u16(&mut end, 0); // this disk
+        put_u16(&mut end, 0); // disk with the central directory
+        put_u16(&mut end, self.entries);
+        put_u16(&mut end, self.entries);
+        put_u32(&mut end, central_len);
+        put_u32(&mut end, self.offset);
+        put_u16(&mut end, 0); // comment length
+
+        self.out.write_all(&self.central)?;
+        self.out.write_all(&end)?;
+        Ok(self.out)
+    }
+}
+
+/// Every file in a zip archive, with its name, in the order the
+/// central directory lists them. Reads stored and deflated entries,
+/// as zip tools and services write them; not zip64 or encryption.
+pub fn read_zip(zip: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    // The end record is last, unless a comment (up to 64 KiB) follows.
+    let end = (0..=zip.len().saturating_sub(22))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&at| u32_at(zip, at) == Some(END_OF_CENTRAL))
+        .context("not a zip archive")?;
+    let count = u16_at(zip, end + 10).context("truncated zip archive")?;
+    let mut at = u32_at(zip, end + 16).context("truncated zip archive")? as usize;
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let field = |offset: usize| u32_at(zip, at + offset).context("truncated zip archive");
+        if field(0)? != CENTRAL_HEADER {
+            bail!("damaged zip archive: bad central directory");
+        }
+        let method = u16_at(zip, at + 10).context("truncated zip archive")?;
+        let flags = u16_at(zip, at + 8).context("truncated zip archive")?;
+        let crc = field(16)?;
+        let compressed_len = field(20)? as usize;
+        let name_len = u16_at(zip, at + 28).context("truncated zip archive")? as usize;
+        let extra_len = u16_at(zip, at + 30).context("truncated zip archive")? as usize;
+        let comment_len = u16_at(zip, at + 32).context("truncated zip archive")? as usize;
+        let local = field(42)? as usize;
+        let name = zip.get(at + 46..at + 46 + name_len).context("truncated zip archive")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        at += 46 + name_len + extra_len + comment_len;
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & 1 != 0 {
+            bail!("{} in the zip archive is encrypted", name);
+        }
+
+        if u32_at(zip, local) != Some(LOCAL_HEADER) {
+            bail!("damaged zip archive: no local header for {}", name);
+        }
+        let local_name_len = u16_at(zip, local + 26).context("truncated zip archive")? as usize;
+        let local_extra_len = u16_at(zip, local + 28).context("truncated zip archive")? as usize;
+        let start = local + 30 + local_name_len + local_extra_len;
+        let raw = zip.get(start..start + compressed_len).context("truncated zip archive")?;
+        let mut data = Vec::new();
+        match method {
+            STORED => data.extend_from_slice(raw),
+            DEFLATE => {
+                flate2::read::DeflateDecoder::new(raw)
+                    .read_to_end(&mut data)
+                    .with_context(|| format!("inflating {} from the zip archive", name))?;
+            }
+            _ => bail!("{} in the zip archive uses an unsupported compression method ({})", name, method),
+        }
+        if crc32fast::hash(&data) != crc {
+            bail!("{} in the zip archive is damaged (checksum mismatch)", name);
+        }
+        entries.push((name, data));
+    }
+    Ok(entries)
+}
+
+fn u16_at(buf: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(buf.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(buf: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(buf.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_entries(zip: &[u8]) -> Vec<(String, Vec<u8>)> {
+        read_zip(zip).unwrap()
+    }
+
+    #[test]
+    fn zip_round_trips_entries() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("index.html", b"<h1>Hi</h1>").unwrap();
+        zip.add_file("fonts/a.woff2", &[0u8; 1000]).unwrap();
+        let bytes = zip.finish().unwrap();
+
+        let entries = read_entries(&bytes);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], ("index.html".to_string(), b"<h1>Hi</h1>".to_vec()));
+        assert_eq!(entries[1].0, "fonts/a.woff2");
+        assert_eq!(entries[1].1.len(), 1000);
+    }
+
+    #[test]
+    fn stored_entries_are_readable_in_place() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_stored("mimetype", b"application/vnd.oasis.opendocument.text").unwrap();
+        zip.add_file("content.xml", b"<x/>").unwrap();
+        let bytes = zip.finish().unwrap();
+
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..77], b"application/vnd.oasis.opendocument.text");
+        assert_eq!(read_entries(&bytes)[1], ("content.xml".to_string(), b"<x/>".to_vec()));
+    }
+
+    #[test]
+    fn bundle_includes_outputs_and_source() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("dist");
+        fs::create_dir_all(out.join("fonts")).unwrap();
+        fs::write(out.join("index.html"), "html").unwrap();
+        fs::write(out.join("fonts/x.woff2"), "font").unwrap();
+        fs::write(out.join(BUNDLE_FILE), "stale").unwrap();
+        let source = dir.path().join("me.jobl");
+        fs::write(&source, "[person]").unwrap();
+
+        let path = bundle(&out, std::slice::from_ref(&source)).unwrap();
+        let names: Vec<String> = read_entries(&fs::read(path).unwrap())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["fonts/x.woff2", "index.html", "source/me.jobl"]);
+    }
+
+    #[test]
+    fn bundle_includes_every_layer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("dist");
+        fs::create_dir_all(&out).unwrap();
+        fs::write(out.join("index.html"), "html").unwrap();
+        let layers = [dir.path().join("base.jobl"), dir.path().join("freelance").join("base.jobl")];
+        fs::create_dir_all(dir.path().join("freelance")).unwrap();
+        fs::write(&layers[0], "[person]").unwrap();
+        fs::write(&layers[1], "[person]\nheadline = \"Freelance\"").unwrap();
+
+        let entries = read_entries(&fs::read(bundle(&out, &layers).unwrap()).unwrap());
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["index.html", "source/1-base.jobl", "source/2-base.jobl"]);
+        assert_eq!(entries[2].1, b"[person]\nheadline = \"Freelance\"");
+    }
+
+    #[test]
+    fn read_zip_rejects_what_it_cant_read() {
+        assert!(read_zip(b"not a zip").is_err());
+
+        let mut bytes = ZipWriter::new(Vec::new());
+        bytes.add_stored("a.txt", b"abc").unwrap();
+        let mut bytes = bytes.finish().unwrap();
+        // Flip a byte of the stored data: the checksum no longer matches.
+        bytes[30 + 5] = b'x';
+        let err = read_zip(&bytes).unwrap_err().to_string();
+        assert!(err.contains("checksum"), "{}", err);
+    }
+}
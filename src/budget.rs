@@ -0,0 +1,190 @@
+//! Length budgets for summaries and highlights (`[budget]` in
+//! srg.toml).
+//!
+//! A master resume can keep the long version of everything; a budget
+//! says how long each summary or highlight may be in this build. Every
+//! overrun is reported, and with `truncate` it's also cut to fit, with
+//! an ellipsis, when the outputs are rendered. The JOBL file itself is
+//! never changed.
+
+use jobl::JoblDocument;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::lint::{texts, TextKind};
+
+/// The `[budget]` table.
+///
+/// ```toml
+/// [budget]
+/// summary = { words = 60 }
+/// highlights = { words = 25, chars = 160 }
+/// truncate = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budgets {
+    /// Budget for each summary: the person's, each role's, and each
+    /// project's.
+    pub summary: Option<Budget>,
+    /// Budget for each experience highlight.
+    pub highlights: Option<Budget>,
+    /// Cut overruns to fit instead of only warning about them.
+    #[serde(default)]
+    pub truncate: bool,
+}
+
+/// Limits for one kind of text. A text must fit both that are set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    pub words: Option<usize>,
+    pub chars: Option<usize>,
+}
+
+impl Budget {
+    pub fn fits(&self, text: &str) -> bool {
+        self.words.is_none_or(|max| text.split_whitespace().count() <= max)
+            && self.chars.is_none_or(|max| text.trim().chars().count() <= max)
+    }
+
+    /// `text` cut at a word boundary to fit, ending in `…`. Text that
+    /// already fits comes back unchanged.
+    pub fn truncate(&self, text: &str) -> String {
+        if self.fits(text) {
+            return text.to_string();
+        }
+
+        let mut words: Vec<&str> = text.split_whitespace().collect();
+        if let Some(max) = self.words {
+            words.truncate(max);
+        }
+        let mut out = words.join(" ");
+        if let Some(max) = self.chars {
+            // Leave room for the ellipsis.
+            while !out.is_empty() && out.chars().count() + 1 > max {
+                match out.rfind(' ') {
+                    Some(i) => out.truncate(i),
+                    None => out = out.chars().take(max.saturating_sub(1)).collect(),
+                }
+            }
+        }
+        let out = out.trim_end_matches(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ':' | '.' | '-' | '–' | '—'));
+        format!("{}…", out)
+    }
+
+    fn describe(&self) -> String {
+        let mut limits = Vec::new();
+        if let Some(words) = self.words {
+            limits.push(format!("{} words", words));
+        }
+        if let Some(chars) = self.chars {
+            limits.push(format!("{} characters", chars));
+        }
+        limits.join(", ")
+    }
+}
+
+impl Budgets {
+    fn for_kind(&self, kind: TextKind) -> Option<&Budget> {
+        match kind {
+            TextKind::Summary => self.summary.as_ref(),
+            TextKind::Highlight => self.highlights.as_ref(),
+        }
+    }
+}
+
+/// A text longer than its budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overrun {
+    pub location: String,
+    pub words: usize,
+    pub chars: usize,
+    pub budget: Budget,
+}
+
+impl Overrun {
+    pub fn message(&self) -> String {
+        format!(
+            "{} is {} words, {} characters (budget: {})",
+            self.location,
+            self.words,
+            self.chars,
+            self.budget.describe()
+        )
+    }
+}
+
+/// Every summary and highlight in `doc` that's over its budget, in
+/// document order.
+pub fn overruns(doc: &JoblDocument, budgets: &Budgets) -> Vec<Overrun> {
+    texts(doc)
+        .into_iter()
+        .filter_map(|text| {
+            let budget = budgets.for_kind(text.kind)?;
+            (!budget.fits(text.text)).then(|| Overrun {
+                location: text.location,
+                words: text.text.split_whitespace().count(),
+                chars: text.text.trim().chars().count(),
+                budget: *budget,
+            })
+        })
+        .collect()
+}
+
+/// `doc` with every overrun cut to fit when `truncate` is on.
+/// Borrows otherwise.
+pub fn apply<'a>(doc: &'a JoblDocument, budgets: &Budgets) -> Cow<'a, JoblDocument> {
+    if !budgets.truncate || overruns(doc, budgets).is_empty() {
+        return Cow::Borrowed(doc);
+    }
+    let cut = |budget: Option<&Budget>, text: &mut String| {
+        if let Some(budget) = budget {
+            *text = budget.truncate(text);
+        }
+    };
+
+    let mut doc = doc.clone();
+    if let Some(summary) = &mut doc.person.summary {
+        cut(budgets.summary.as_ref(), summary);
+    }
+    for exp in &mut doc.experience {
+        if let Some(summary) = &mut exp.summary {
+            cut(budgets.summary.as_ref(), summary);
+        }
+        for highlight in &mut exp.highlights {
+            cut(budgets.highlights.as_ref(), highlight);
+        }
+    }
+    for proj in &mut doc.projects {
+        if let Some(summary) = &mut proj.summary {
+            cut(budgets.summary.as_ref(), summary);
+        }
+    }
+    Cow::Owned(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_at_word_boundaries() {
+        let words = Budget { words: Some(4), chars: None };
+        assert_eq!(words.truncate("Cut build times by half, saving hours."), "Cut build times by…");
+        assert_eq!(words.truncate("Short enough."), "Short enough.");
+
+        let chars = Budget { words: None, chars: Some(20) };
+        let cut = chars.truncate("Migrated billing to Rust, cutting p99 latency");
+        assert_eq!(cut, "Migrated billing to…");
+        assert!(cut.chars().count() <= 20);
+    }
+
+    #[test]
+    fn budgets_both_limits() {
+        let both = Budget { words: Some(10), chars: Some(12) };
+        assert!(both.fits("Led a team."));
+        assert!(!both.fits("Led a larger team."));
+        assert_eq!(both.describe(), "10 words, 12 characters");
+    }
+}
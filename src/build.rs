@@ -1,29 +1,1084 @@
 use anyhow::{Context, Result};
+use headless_chrome::protocol::cdp::{Emulation, Page};
 use headless_chrome::types::PrintToPdfOptions;
-use headless_chrome::Browser;
+use headless_chrome::Tab;
 use jobl::JoblDocument;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::layout::{FieldPart, Layout};
+use crate::sections::AliasSection;
+
+mod ansi;
+pub mod ascii;
+pub mod booking;
+pub mod chrome;
+mod email;
+pub mod emoji;
+mod epub;
+pub mod fingerprint;
+mod gemtext;
+pub mod history;
+mod jsonld;
+mod markdown;
+pub mod measure;
+mod microformats;
+mod odt;
+mod org;
+mod pdfa;
+mod qr;
+mod rst;
+mod rtf;
+pub mod scripts;
+mod txt;
+mod typst;
+pub mod outline;
+pub mod resolved;
+
+/// Rendering knobs that don't belong in the layout: they change how
+/// the output is delivered, not what's in it.
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    pub css_mode: CssMode,
+    /// Outputs to produce. Defaults to the themed page and its PDF.
+    pub formats: Vec<OutputFormat>,
+    /// Layout fields gathered from outside the JOBL file.
+    pub extras: Extras,
+    /// Spacing scale passed to the theme as `--srg-density`.
+    pub density: Density,
+    /// Marker for highlights and education details. `None` keeps
+    /// the theme's own.
+    pub bullets: Option<Bullets>,
+    /// Base font size (`--srg-font-size`), e.g. `10.5pt`.
+    pub font_size: Option<String>,
+    /// Body line height (`--srg-line-height`), e.g. `1.3`.
+    pub line_height: Option<String>,
+    /// Overrides for the theme's typography knobs, by knob name.
+    pub typography: BTreeMap<String, String>,
+    /// Emoji handling for formats that differ from the default (see
+    /// [`BuildOptions::emoji_policy`]).
+    pub emoji: BTreeMap<OutputFormat, emoji::EmojiPolicy>,
+    /// Fold the plain-text export to ASCII (see [`ascii`]).
+    pub ascii: bool,
+    /// Column the plain-text export wraps at; 0 doesn't wrap.
+    pub txt_width: usize,
+    /// Accent colors by section name. These win over `accent=` in
+    /// the layout.
+    pub accents: BTreeMap<String, String>,
+    /// Print the PDF in shades of gray. The HTML keeps its colors.
+    pub grayscale: bool,
+    /// Text stamped diagonally across every printed page, e.g. `DRAFT`.
+    pub watermark: Option<String>,
+    /// A line printed at the bottom of every PDF page, e.g.
+    /// `Confidential — do not distribute`.
+    pub footer: Option<String>,
+    /// The canonical online version and content hash stamped on
+    /// every PDF page (see [`Verification`]).
+    pub verify: Option<Verification>,
+    /// What prints the PDF (see [`PdfBackend`]).
+    pub pdf_backend: PdfBackend,
+    /// What the PDF conforms to (see [`PdfProfile`]).
+    pub pdf_profile: PdfProfile,
+    /// Have Chrome tag the PDF's structure (headings, lists, reading
+    /// order) for screen readers. Unset leaves it to Chrome.
+    pub tagged_pdf: bool,
+    /// Have Chrome add an outline of the page's headings, which PDF
+    /// viewers show as bookmarks. Chrome builds it from the tags, so
+    /// it tags the PDF too.
+    pub pdf_outline: bool,
+    /// Theme for the PDF when it differs from the web page's
+    /// (`--theme-pdf`), e.g. a plain, ATS-safe one under a rich page.
+    /// `None` prints the page's theme.
+    pub pdf_theme: Option<String>,
+    pub paper: Paper,
+    /// PDF page margins. Themes that lay out their own margins in
+    /// CSS leave these at zero.
+    pub margins: Margins,
+    /// PDF scale factor, 0.1 to 2.
+    pub scale: f64,
+    /// Print background colors and images in the PDF.
+    pub print_background: bool,
+    /// How links print in the PDF. `None` leaves them as the theme
+    /// styles them.
+    pub links: Option<PrintLinks>,
+    /// Repeat a section's title, marked "(continued)", at the top of
+    /// each PDF page the section runs onto (see `CONTINUED_JS`).
+    pub continued: bool,
+    /// Pages the Chrome PDF should fit on. The page is measured
+    /// before it prints (see [`measure`]), and running over is a
+    /// warning, or an error under `strict`.
+    pub max_pages: Option<usize>,
+    /// How `start` and `end` dates are written, e.g. `%b %Y` (see
+    /// [`crate::dates`]). Dates are shown as written when unset.
+    pub date_format: Option<String>,
+    /// Language for month names and date ranges (see
+    /// [`crate::dates::Locale`]), and the page's `lang`.
+    pub locale: Option<crate::dates::Locale>,
+    /// Length budgets for summaries and highlights (see
+    /// [`crate::budget`]).
+    pub budgets: crate::budget::Budgets,
+    /// Fail the build when a generated page isn't well-formed HTML
+    /// (see [`crate::markup`]). Problems are warnings otherwise.
+    pub strict: bool,
+    /// Write index.html as a fragment for embedding in another site's
+    /// page: the resume's sections and scripts, without the document
+    /// around them (see [`generate_fragment`]).
+    pub fragment: bool,
+    /// Name the page's stylesheet and fonts after their content and
+    /// write cache rules for hosts (see [`fingerprint`]).
+    pub fingerprint: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            css_mode: CssMode::default(),
+            formats: vec![OutputFormat::Html, OutputFormat::Pdf],
+            extras: Extras::default(),
+            density: Density::default(),
+            bullets: None,
+            font_size: None,
+            line_height: None,
+            typography: BTreeMap::new(),
+            emoji: BTreeMap::new(),
+            ascii: false,
+            txt_width: TXT_WIDTH,
+            accents: BTreeMap::new(),
+            grayscale: false,
+            watermark: None,
+            footer: None,
+            verify: None,
+            pdf_backend: PdfBackend::default(),
+            pdf_profile: PdfProfile::default(),
+            tagged_pdf: false,
+            pdf_outline: false,
+            pdf_theme: None,
+            paper: Paper::default(),
+            margins: Margins::default(),
+            scale: 1.0,
+            print_background: true,
+            links: None,
+            continued: false,
+            max_pages: None,
+            fingerprint: false,
+            date_format: None,
+            locale: None,
+            budgets: crate::budget::Budgets::default(),
+            strict: false,
+            fragment: false,
+        }
+    }
+}
+
+/// Extra layout fields for individual items, gathered from outside
+/// the JOBL file (such as GitHub metadata for projects). Indexed like
+/// the document's item lists; a field here only fills in names the
+/// item doesn't already have.
+#[derive(Debug, Clone, Default)]
+pub struct Extras {
+    pub projects: Vec<BTreeMap<String, String>>,
+    /// Image for the person `avatar` field, as a `data:` URI (see
+    /// `crate::gravatar`).
+    pub avatar: Option<String>,
+    /// Skills categories in the order the JOBL file lists them (see
+    /// [`skill_categories_in`]). Empty keeps them alphabetical.
+    pub skill_order: Vec<String>,
+    /// Alias sections the JOBL file declares (see `crate::sections`).
+    pub sections: Vec<AliasSection>,
+    /// The person `booking` block (see [`booking`]).
+    pub booking: Option<booking::Booking>,
+    /// The JOBL file's `[[talks]]` (see `crate::sections`).
+    pub talks: Vec<crate::sections::TalkItem>,
+    /// The JOBL file's `[[patents]]`.
+    pub patents: Vec<crate::sections::PatentItem>,
+    /// srg's own `[person]` fields, such as `clearance`.
+    pub person: crate::sections::PersonFields,
+}
+
+impl Extras {
+    /// Value of a person field by its layout name, JOBL's or srg's.
+    pub fn person_field(&self, doc: &JoblDocument, field: &str) -> Option<String> {
+        get_person_field_value(doc, field).or_else(|| self.person.get(field))
+    }
+
+    /// The alias section a layout section name refers to.
+    pub fn section(&self, name: &str) -> Option<&AliasSection> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    fn project(&self, index: usize, field: &str) -> Option<String> {
+        self.projects.get(index)?.get(field).cloned()
+    }
+}
+
+/// Skills categories in the order the JOBL source lists them. The
+/// parsed document keeps skills in a sorted map, so the order has to
+/// come from the file itself.
+pub fn skill_categories_in(source: &str) -> Result<Vec<String>> {
+    let table: toml::Table = toml::from_str(source).context("Failed to read skills order")?;
+    Ok(match table.get("skills") {
+        Some(toml::Value::Table(skills)) => skills.keys().cloned().collect(),
+        _ => Vec::new(),
+    })
+}
 
-/// Build HTML and PDF resume from JOBL document
+/// Skills categories in display order. Categories listed under
+/// `skills` in the layout (`"Languages"`) come first, in that order;
+/// the rest follow in the JOBL file's order when `extras` has it, and
+/// alphabetically otherwise.
+pub fn ordered_skills<'a>(
+    doc: &'a JoblDocument,
+    section: &crate::layout::Section,
+    extras: &Extras,
+) -> Vec<(&'a String, &'a Vec<String>)> {
+    let listed: Vec<&str> = section
+        .fields
+        .iter()
+        .filter_map(|entry| match entry.as_field()?.parts.as_slice() {
+            [FieldPart::Field(name)] | [FieldPart::Literal(name)] => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let rank = |names: &[&str], category: &str| {
+        names.iter().position(|n| n.eq_ignore_ascii_case(category)).unwrap_or(usize::MAX)
+    };
+    let source: Vec<&str> = extras.skill_order.iter().map(String::as_str).collect();
+
+    let mut skills: Vec<_> = doc.skills.iter().flatten().collect();
+    skills.sort_by_key(|(category, _)| (rank(&listed, category), rank(&source, category)));
+    skills
+}
+
+impl BuildOptions {
+    fn wants(&self, format: OutputFormat) -> bool {
+        self.formats.contains(&format)
+    }
+
+    /// Emoji policy for `format`. Plain text is usually headed for
+    /// an ATS, so it strips emoji unless told otherwise; everything
+    /// else keeps them.
+    pub fn emoji_policy(&self, format: OutputFormat) -> emoji::EmojiPolicy {
+        self.emoji.get(&format).copied().unwrap_or(match format {
+            OutputFormat::Txt | OutputFormat::Rtf => emoji::EmojiPolicy::Strip,
+            _ => emoji::EmojiPolicy::Keep,
+        })
+    }
+
+    /// Whether the PDF prints from a page of its own rather than
+    /// index.html: when its emoji, colors, or links differ from the
+    /// web page's. (Another PDF theme always gets its own page.)
+    pub fn pdf_page_differs(&self) -> bool {
+        self.emoji_policy(OutputFormat::Pdf) != self.emoji_policy(OutputFormat::Html)
+            || self.grayscale
+            || self.links.is_some()
+    }
+}
+
+/// An output the build can produce. `html` and `pdf` are the themed
+/// page and its print; the others are standalone exports rendered
+/// from the layout's [`outline::Outline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Themed page, index.html.
+    Html,
+    /// The themed page printed by headless Chrome, resume.pdf.
+    Pdf,
+    /// Inline-styled page for pasting into an email, resume-email.html.
+    EmailHtml,
+    /// Gemini capsule page, resume.gmi.
+    Gemtext,
+    /// Emacs Org-mode document, resume.org.
+    Org,
+    /// reStructuredText document, resume.rst.
+    Rst,
+    /// Markdown document, resume.md.
+    #[value(name = "md")]
+    #[serde(rename = "md")]
+    Markdown,
+    /// OpenDocument Text for editing in LibreOffice, resume.odt.
+    Odt,
+    /// Rich Text Format for any word processor, however old, resume.rtf.
+    Rtf,
+    /// The themed page as an e-book, resume.epub.
+    Epub,
+    /// Open Graph preview image of the page's header, og-image.png.
+    OgImage,
+    /// Plain text for pasting into forms, resume.txt.
+    Txt,
+}
+
+impl OutputFormat {
+    /// File name of this output inside the output directory.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            OutputFormat::Html => "index.html",
+            OutputFormat::Pdf => "resume.pdf",
+            OutputFormat::EmailHtml => "resume-email.html",
+            OutputFormat::Gemtext => "resume.gmi",
+            OutputFormat::Org => "resume.org",
+            OutputFormat::Rst => "resume.rst",
+            OutputFormat::Markdown => "resume.md",
+            OutputFormat::Odt => "resume.odt",
+            OutputFormat::Rtf => "resume.rtf",
+            OutputFormat::Epub => "resume.epub",
+            OutputFormat::OgImage => "og-image.png",
+            OutputFormat::Txt => "resume.txt",
+        }
+    }
+
+    /// Human-readable name for build summaries.
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputFormat::Html => "HTML",
+            OutputFormat::Pdf => "PDF",
+            OutputFormat::EmailHtml => "Email HTML",
+            OutputFormat::Gemtext => "Gemtext",
+            OutputFormat::Org => "Org",
+            OutputFormat::Rst => "reStructuredText",
+            OutputFormat::Markdown => "Markdown",
+            OutputFormat::Odt => "OpenDocument Text",
+            OutputFormat::Rtf => "Rich Text",
+            OutputFormat::Epub => "EPUB",
+            OutputFormat::OgImage => "Preview image",
+            OutputFormat::Txt => "Text",
+        }
+    }
+}
+
+/// How the stylesheet reaches the browser.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CssMode {
+    /// One `<style>` block in index.html. Self-contained, works from disk.
+    #[default]
+    Inline,
+    /// Full stylesheet written to style.css and linked from the page.
+    External,
+    /// Like `external`, but the rules needed for the header are inlined
+    /// and style.css is loaded without blocking first paint.
+    Critical,
+}
+
+/// How much vertical space the theme leaves between things. Themes
+/// scale their margins by the `--srg-density` CSS variable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// Tighter spacing, to fit more on a page.
+    Compact,
+    /// The theme as designed.
+    #[default]
+    Normal,
+    /// Looser spacing, for resumes with room to spare.
+    Relaxed,
+}
+
+/// Default wrap column for the plain-text export: narrow enough for
+/// any form field, wide enough not to break every other line.
+pub const TXT_WIDTH: usize = 80;
+
+/// Marker in front of each highlight and education detail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Bullets {
+    /// A filled circle.
+    Disc,
+    /// An en dash.
+    Dash,
+    /// No marker. Wrapped lines hang indented under the first.
+    None,
+}
+
+impl Bullets {
+    /// Rules applied after the theme's CSS, so they win over it.
+    fn css(self) -> &'static str {
+        match self {
+            Bullets::Disc => BULLETS_DISC_CSS,
+            Bullets::Dash => BULLETS_DASH_CSS,
+            Bullets::None => BULLETS_NONE_CSS,
+        }
+    }
+}
+
+const BULLETS_DISC_CSS: &str = "
+
+/* Bullets: disc */
+.experience-highlights,
+.education-details {
+  list-style: disc outside;
+}
+";
+
+const BULLETS_DASH_CSS: &str = "
+
+/* Bullets: dash */
+.experience-highlights,
+.education-details {
+  list-style: outside;
+  list-style-type: \"\\2013\\00a0\";
+}
+";
+
+const BULLETS_NONE_CSS: &str = "
+
+/* Bullets: none */
+.experience-highlights,
+.education-details {
+  list-style: none;
+  padding-left: 0;
+}
+.experience-highlights > li,
+.education-details > li {
+  padding-left: 1em;
+  text-indent: -1em;
+}
+";
+
+/// How links print in the PDF. A printed copy can't be clicked, so
+/// the address is lost unless it's written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PrintLinks {
+    /// Underlined, so they read as links on paper.
+    Underline,
+    /// Styled like the text around them.
+    Plain,
+    /// Followed by their address in parentheses, unless the link
+    /// text already is the address or it points within the page.
+    Url,
+    /// Not links at all: the text stays, the PDF has nothing to
+    /// click.
+    Strip,
+}
+
+impl PrintLinks {
+    /// Rules for the print page, after the theme's CSS.
+    fn css(self) -> &'static str {
+        match self {
+            PrintLinks::Underline => LINKS_UNDERLINE_CSS,
+            PrintLinks::Plain => LINKS_PLAIN_CSS,
+            PrintLinks::Url | PrintLinks::Strip => "",
+        }
+    }
+}
+
+const LINKS_UNDERLINE_CSS: &str = "
+
+/* Links: underline */
+a {
+  text-decoration: underline;
+}
+";
+
+const LINKS_PLAIN_CSS: &str = "
+
+/* Links: plain */
+a {
+  color: inherit;
+  text-decoration: none;
+}
+";
+
+/// What prints the PDF.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfBackend {
+    /// Headless Chrome prints the themed page.
+    #[default]
+    Chrome,
+    /// The `typst` CLI typesets the layout in a plain style of its own
+    /// (see `build/typst.rs`). Themes don't apply.
+    Typst,
+}
+
+/// What the PDF conforms to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfProfile {
+    /// Whatever the backend prints.
+    #[default]
+    Standard,
+    /// PDF/A-2b, for portals that only take archival PDFs (see
+    /// `build/pdfa.rs`). Chrome's PDF is rewritten by Ghostscript.
+    Pdfa,
+}
+
+/// Where a printed copy can be checked against the current version.
+/// Each PDF page carries the URL, a QR code for it, and a short hash
+/// of the resume's content; the hosted page shows the same hash, so a
+/// recruiter can tell whether the copy in hand is current.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    /// Where the canonical resume is hosted.
+    pub url: String,
+    /// The first 8 hex digits of the MD5 of the document's JSON. A
+    /// fingerprint, not a signature: it changes when the content does.
+    pub hash: String,
+}
+
+impl Verification {
+    pub fn new(url: &str, doc: &JoblDocument) -> Result<Self> {
+        let url = url.trim();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            anyhow::bail!("verify_url must be an http(s) URL, got \"{}\"", url);
+        }
+        qr::QrCode::encode(url.as_bytes()).context("verify_url is too long for the QR code")?;
+        let json = serde_json::to_vec(doc).context("Failed to serialize the resume for its hash")?;
+        Ok(Self {
+            url: url.to_string(),
+            hash: format!("{:x}", md5::compute(json))[..8].to_string(),
+        })
+    }
+
+    /// The stamp's text, e.g. `Current version: https://... · 1a2b3c4d`.
+    pub fn line(&self) -> String {
+        format!("Current version: {} · {}", self.url, self.hash)
+    }
+}
+
+/// PDF paper size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Paper {
+    /// US Letter, 8.5 x 11 in.
+    #[default]
+    Letter,
+    /// A4, 210 x 297 mm.
+    A4,
+    /// US Legal, 8.5 x 14 in.
+    Legal,
+}
+
+impl Paper {
+    /// Width and height in inches.
+    pub fn size(self) -> (f64, f64) {
+        match self {
+            Paper::Letter => (8.5, 11.0),
+            Paper::A4 => (8.27, 11.69),
+            Paper::Legal => (8.5, 14.0),
+        }
+    }
+}
+
+/// PDF page margins in inches.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    /// Parse CSS-style margin shorthand: one to four lengths in `in`,
+    /// `cm`, `mm`, `pt`, or `px` (`0.5in`, `12mm 15mm`), applied
+    /// top, right, bottom, left as in CSS. A bare `0` is allowed.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let lengths = value
+            .split_whitespace()
+            .map(|length| {
+                inches(length).ok_or_else(|| {
+                    format!("expected a margin like 0.5in or 12mm 15mm, got {:?}", value)
+                })
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+        let [top, right, bottom, left] = match lengths[..] {
+            [all] => [all; 4],
+            [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+            [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+            [top, right, bottom, left] => [top, right, bottom, left],
+            _ => return Err(format!("expected one to four margin lengths, got {:?}", value)),
+        };
+        Ok(Self { top, right, bottom, left })
+    }
+}
+
+/// A print length in inches.
+fn inches(length: &str) -> Option<f64> {
+    if length == "0" {
+        return Some(0.0);
+    }
+    let unit_start = length.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = length.split_at(unit_start);
+    let number: f64 = number.parse().ok()?;
+    let per_inch = match unit {
+        "in" => 1.0,
+        "cm" => 2.54,
+        "mm" => 25.4,
+        "pt" => 72.0,
+        "px" => 96.0,
+        _ => return None,
+    };
+    Some(number / per_inch)
+}
+
+/// Check a PDF scale factor; Chrome accepts 0.1 to 2.
+pub fn check_scale(scale: f64) -> Result<f64, String> {
+    if (0.1..=2.0).contains(&scale) {
+        Ok(scale)
+    } else {
+        Err(format!("expected a scale between 0.1 and 2, got {}", scale))
+    }
+}
+
+impl Density {
+    fn scale(self) -> &'static str {
+        match self {
+            Density::Compact => "0.6",
+            Density::Normal => "1",
+            Density::Relaxed => "1.35",
+        }
+    }
+}
+
+/// File name used for the stylesheet in the external CSS modes.
+const STYLESHEET_FILE: &str = "style.css";
+
+/// Prefix of the temporary files outputs are written to before
+/// they're renamed into place.
+const TEMP_PREFIX: &str = ".srg-";
+
+/// Write `contents` to `path` without ever leaving a partial file
+/// there: the bytes go to a temporary file in the same directory,
+/// which is renamed over `path` once complete. If anything fails,
+/// the temporary file is removed and `path` keeps its old contents,
+/// so an interrupted build can't leave a truncated resume.pdf for a
+/// deploy step to pick up.
+///
+/// The file gets the mode a plain write would give it: the old file's,
+/// or what the umask allows for a new one. A temporary file is only
+/// readable by its owner, and a web server serving the output as
+/// another user couldn't read that.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(TEMP_PREFIX);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        // Narrowed by the umask as the file is created.
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+    let mut file = builder.tempfile_in(dir)?;
+    if let Ok(existing) = fs::metadata(path) {
+        file.as_file().set_permissions(existing.permissions())?;
+    }
+    file.write_all(contents.as_ref())?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Outputs built by one job, in the order they were produced.
+type Written = Vec<(OutputFormat, PathBuf)>;
+
+/// A unit of [`build_resume`]'s work: the output it's for, and the
+/// closure that builds it.
+type Job<'a> = (OutputFormat, Box<dyn FnOnce() -> Result<Written> + Send + 'a>);
+
+/// Build the requested outputs from a JOBL document. Returns each
+/// output that was written, in the same order however the threads
+/// finish.
+///
+/// Outputs are built concurrently, on as many threads as there are
+/// CPUs: the themed page with whatever Chrome makes from it (the
+/// Chrome PDF and the preview image, which share one browser) is one
+/// job, and every other format is a job of its own. A format that
+/// fails doesn't stop the others; the error names each one that did.
 pub fn build_resume(
     doc: &JoblDocument,
     out_dir: &Path,
     theme: Option<&str>,
     layout: &Layout,
     custom_css_path: Option<&Path>,
-) -> Result<()> {
+    options: &BuildOptions,
+) -> Result<Written> {
     // Create output directory
     fs::create_dir_all(out_dir)
         .context("Failed to create output directory")?;
 
+    let mut jobs: Vec<Job> = Vec::new();
+
+    // Chrome prints the PDF and shoots the preview from index.html, so
+    // any of them needs the themed page on disk, if only for the
+    // length of the job.
+    let chrome_pdf = options.wants(OutputFormat::Pdf) && options.pdf_backend == PdfBackend::Chrome;
+    let preview = options.wants(OutputFormat::OgImage);
+    if options.wants(OutputFormat::Html) || chrome_pdf || preview {
+        let format = if options.wants(OutputFormat::Html) { OutputFormat::Html } else { OutputFormat::Pdf };
+        jobs.push((format, Box::new(move || {
+            build_chrome_outputs(doc, out_dir, theme, layout, custom_css_path, options, chrome_pdf, preview)
+        })));
+    }
+
+    if options.wants(OutputFormat::Pdf) && options.pdf_backend == PdfBackend::Typst {
+        jobs.push((OutputFormat::Pdf, Box::new(move || {
+            let pdf_path = out_dir.join(OutputFormat::Pdf.file_name());
+            let pdf = typst::compile(&typst_source(doc, layout, options), options.pdf_profile).context("Failed to generate PDF")?;
+            write_atomic(&pdf_path, pdf)
+                .with_context(|| format!("Failed to write {}", pdf_path.display()))?;
+            Ok(vec![(OutputFormat::Pdf, pdf_path)])
+        })));
+    }
+
+    if options.wants(OutputFormat::Odt) {
+        jobs.push((OutputFormat::Odt, Box::new(move || {
+            let path = out_dir.join(OutputFormat::Odt.file_name());
+            write_atomic(&path, render_odt(doc, layout, options)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(vec![(OutputFormat::Odt, path)])
+        })));
+    }
+
+    if options.wants(OutputFormat::Epub) {
+        jobs.push((OutputFormat::Epub, Box::new(move || {
+            let path = out_dir.join(OutputFormat::Epub.file_name());
+            write_atomic(&path, render_epub(doc, layout, theme, custom_css_path, options)?)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(vec![(OutputFormat::Epub, path)])
+        })));
+    }
+
+    for &format in &options.formats {
+        if matches!(format, OutputFormat::Html | OutputFormat::Pdf | OutputFormat::Odt | OutputFormat::Epub | OutputFormat::OgImage) {
+            continue;
+        }
+        jobs.push((format, Box::new(move || {
+            let Some(text) = render_export(format, doc, layout, options) else {
+                return Ok(Vec::new());
+            };
+            if format == OutputFormat::EmailHtml {
+                check_markup(&text, format.file_name(), options)?;
+            }
+            let path = out_dir.join(format.file_name());
+            write_atomic(&path, text)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            Ok(vec![(format, path)])
+        })));
+    }
+
+    let workers = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let mut written = Vec::new();
+    let mut failed = Vec::new();
+    for (format, result) in run_jobs(jobs, workers) {
+        match result {
+            Ok(outputs) => written.extend(outputs),
+            Err(err) => failed.push(format!("  {}: {:#}", format.label(), err)),
+        }
+    }
+    if !failed.is_empty() {
+        anyhow::bail!("{} of the formats failed:\n{}", failed.len(), failed.join("\n"));
+    }
+    Ok(written)
+}
+
+/// Run `jobs` on at most `workers` threads. Returns each job's
+/// format and result, in the order the jobs were given.
+fn run_jobs(jobs: Vec<Job>, workers: usize) -> Vec<(OutputFormat, Result<Written>)> {
+    let count = jobs.len();
+    let formats: Vec<OutputFormat> = jobs.iter().map(|(format, _)| *format).collect();
+    let queue = std::sync::Mutex::new(jobs.into_iter().map(|(_, job)| job).enumerate());
+    let results = std::sync::Mutex::new((0..count).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, count.max(1)) {
+            scope.spawn(|| loop {
+                // Take the next job, releasing the queue before it runs.
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                let Some((index, job)) = next else { break };
+                let result = job();
+                results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+            });
+        }
+    });
+    let results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    formats
+        .into_iter()
+        .zip(results.into_iter().map(|result| result.unwrap_or_else(|| Err(anyhow::anyhow!("the build thread stopped")))))
+        .collect()
+}
+
+/// Build the themed page, then what Chrome makes from it: the preview
+/// image and the Chrome PDF. Ends with the fragment, when asked for,
+/// in the page's place.
+#[allow(clippy::too_many_arguments)]
+fn build_chrome_outputs(
+    doc: &JoblDocument,
+    out_dir: &Path,
+    theme: Option<&str>,
+    layout: &Layout,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+    chrome_pdf: bool,
+    preview: bool,
+) -> Result<Written> {
+    let mut written = Vec::new();
+    let html_policy = options.emoji_policy(OutputFormat::Html);
+    let html_doc = emoji::apply(doc, html_policy);
+    let html_options = alias_pass(options, doc, |d| emoji::apply(d, html_policy).into_owned());
+    // Without HTML among the outputs, Chrome's page and its assets go
+    // in a scratch directory that's removed when the job is done.
+    // It's inside the output directory, which a sandboxed Chrome can
+    // already read.
+    let scratch = match options.wants(OutputFormat::Html) {
+        true => None,
+        false => Some(
+            tempfile::Builder::new()
+                .prefix(TEMP_PREFIX)
+                .tempdir_in(out_dir)
+                .context("Failed to create a directory for the page Chrome prints")?,
+        ),
+    };
+    let page_dir = scratch.as_ref().map_or(out_dir, |dir| dir.path());
+    let html_path = build_themed_page(&html_doc, page_dir, theme, layout, custom_css_path, &html_options)?;
+    if options.wants(OutputFormat::Html) {
+        written.push((OutputFormat::Html, html_path.clone()));
+        if let Some(booking) = &options.extras.booking {
+            booking::write_ics(out_dir, booking, &doc.person.name)?;
+        }
+    }
+
+    if chrome_pdf || preview {
+        // One Chrome for both: the preview is shot first, before
+        // the PDF's page (if it differs) replaces index.html.
+        let (_session, tab) = open_page(PdfSource::File(&html_path))?;
+        if preview {
+            let path = out_dir.join(OutputFormat::OgImage.file_name());
+            let png = capture_preview(&tab).context("Failed to capture the preview image")?;
+            write_atomic(&path, png)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push((OutputFormat::OgImage, path));
+        }
+        if chrome_pdf {
+            if let Some(pdf_theme) = &options.pdf_theme {
+                // Another theme's fonts aren't in the output
+                // directory, so its page carries them inline.
+                let page = print_page(doc, layout, Some(pdf_theme), custom_css_path, options, true)?;
+                load_content(&tab, &page)?;
+            } else if options.pdf_page_differs() {
+                // The PDF's page differs from index.html. Load it
+                // into the tab in its place, so relative font paths
+                // still resolve and nothing extra hits the disk.
+                let page = print_page(doc, layout, theme, custom_css_path, options, false)?;
+                load_content(&tab, &page)?;
+            }
+            let pdf_path = out_dir.join(OutputFormat::Pdf.file_name());
+            let pdf = print_tab(&tab, options).context("Failed to generate PDF")?;
+            write_atomic(&pdf_path, pdf)
+                .context("Failed to write PDF file")?;
+            written.push((OutputFormat::Pdf, pdf_path));
+        }
+    }
+
+    // Chrome is done with the full page; the fragment takes its
+    // place.
+    if options.fragment && options.wants(OutputFormat::Html) {
+        let fragment = generate_fragment(&html_doc, layout, &html_options);
+        check_markup(&fragment, OutputFormat::Html.file_name(), options)?;
+        write_atomic(&html_path, fragment).context("Failed to write HTML file")?;
+    }
+
+    // Last, once Chrome has printed from the plain names.
+    if options.fingerprint && options.wants(OutputFormat::Html) {
+        let fonts = match theme {
+            Some(theme) => crate::themes::ThemeStack::resolve(theme)?.fonts(),
+            None => Vec::new(),
+        };
+        let fonts: Vec<&str> = fonts.iter().map(|(rel, _)| *rel).collect();
+        let stylesheet = Some(STYLESHEET_FILE).filter(|_| options.css_mode != CssMode::Inline);
+        fingerprint::apply(out_dir, OutputFormat::Html.file_name(), stylesheet, &fonts)
+            .context("Failed to fingerprint the page's assets")?;
+    }
+    Ok(written)
+}
+
+/// Render a standalone text export, or `None` for the themed
+/// formats, ODT, EPUB, and the preview image, which `build_resume`
+/// produces itself.
+pub fn render_export(
+    format: OutputFormat,
+    doc: &JoblDocument,
+    layout: &Layout,
+    options: &BuildOptions,
+) -> Option<String> {
+    let outline = || export_outline(format, doc, layout, options);
+    match format {
+        OutputFormat::Html | OutputFormat::Pdf | OutputFormat::Odt | OutputFormat::Epub | OutputFormat::OgImage => None,
+        OutputFormat::EmailHtml => Some(email::render(&outline())),
+        OutputFormat::Gemtext => Some(gemtext::render(&outline())),
+        OutputFormat::Org => Some(org::render(&outline())),
+        OutputFormat::Rst => Some(rst::render(&outline())),
+        OutputFormat::Markdown => Some(markdown::render(&outline())),
+        OutputFormat::Rtf => Some(rtf::render(&outline(), options)),
+        OutputFormat::Txt if options.ascii => Some(ascii::transliterate(&txt::render(&outline(), options.txt_width))),
+        OutputFormat::Txt => Some(txt::render(&outline(), options.txt_width)),
+    }
+}
+
+/// The outline an export renders: `doc` with the format's emoji
+/// policy, the length budgets, and the date format applied.
+fn export_outline(format: OutputFormat, doc: &JoblDocument, layout: &Layout, options: &BuildOptions) -> outline::Outline {
+    let prepare = |doc: &JoblDocument| {
+        let doc = emoji::apply(doc, options.emoji_policy(format));
+        let doc = crate::budget::apply(&doc, &options.budgets);
+        crate::dates::apply(&doc, options.date_format.as_deref(), options.locale).into_owned()
+    };
+    let extras = &alias_pass(options, doc, prepare).extras;
+    outline::Outline::build(&prepare(doc), layout, extras)
+}
+
+/// The resume as an OpenDocument Text file (see `build/odt.rs`).
+pub fn render_odt(doc: &JoblDocument, layout: &Layout, options: &BuildOptions) -> Result<Vec<u8>> {
+    odt::render(&export_outline(OutputFormat::Odt, doc, layout, options), options)
+}
+
+/// The resume as an EPUB book (see `build/epub.rs`): the themed
+/// page's sections and stylesheet under EPUB's emoji policy.
+pub fn render_epub(
+    doc: &JoblDocument,
+    layout: &Layout,
+    theme: Option<&str>,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<Vec<u8>> {
+    let policy = options.emoji_policy(OutputFormat::Epub);
+    let epub_doc = emoji::apply(doc, policy);
+    let options = &*alias_pass(options, doc, |d| emoji::apply(d, policy).into_owned());
+    let mut css = themed_css(&epub_doc, layout, theme, custom_css_path, options)?;
+    if resolved::any_collapsed(layout) {
+        css.push_str(EXPANDED_CSS);
+    }
+    let resolved = resolve_page(&epub_doc, layout, options, None);
+    let mut body = String::new();
+    for section in &resolved.sections {
+        push_section(&mut body, section);
+    }
+    let person = &doc.person;
+    let id = md5::compute(format!("{}\n{}", person.name, person.email.as_deref().unwrap_or_default()));
+    epub::render(&epub::Book {
+        title: &person.name,
+        lang: options.locale.map_or("en", crate::dates::Locale::code),
+        identifier: format!("urn:srg:{:x}", id),
+        modified: format!("{}T00:00:00Z", crate::dates::today()),
+        body: &body,
+        css: &css,
+        contents: resolved
+            .sections
+            .iter()
+            .filter_map(|section| Some((section.name.clone(), section.title.clone()?)))
+            .collect(),
+        fonts: match theme {
+            Some(theme) => crate::themes::ThemeStack::resolve(theme)?.fonts(),
+            None => Vec::new(),
+        },
+    })
+}
+
+/// The Typst document `--pdf-backend typst` compiles.
+pub fn typst_source(doc: &JoblDocument, layout: &Layout, options: &BuildOptions) -> String {
+    typst::render(&export_outline(OutputFormat::Pdf, doc, layout, options), options)
+}
+
+/// The resume styled for a terminal `width` columns wide (see
+/// `build/ansi.rs`). Emoji follow the web page's policy: a terminal
+/// shows them as well as a browser does.
+pub fn render_terminal(doc: &JoblDocument, layout: &Layout, options: &BuildOptions, width: usize, color: bool) -> String {
+    ansi::render(&export_outline(OutputFormat::Html, doc, layout, options), width, color)
+}
+
+/// What `--ascii` changes in the plain-text export: each non-ASCII
+/// word and its transliteration.
+pub fn ascii_changes(doc: &JoblDocument, layout: &Layout, options: &BuildOptions) -> Vec<(String, String)> {
+    let options = BuildOptions { ascii: false, ..options.clone() };
+    render_export(OutputFormat::Txt, doc, layout, &options)
+        .map(|text| ascii::changes(&text))
+        .unwrap_or_default()
+}
+
+/// Write the themed index.html along with its fonts and, in the
+/// external CSS modes, its stylesheet. Returns the page's path.
+fn build_themed_page(
+    doc: &JoblDocument,
+    out_dir: &Path,
+    theme: Option<&str>,
+    layout: &Layout,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<PathBuf> {
     // Copy theme fonts to output directory if theme is specified
     if let Some(theme_name) = theme {
         copy_theme_fonts(theme_name, out_dir)?;
     }
 
+    let css = themed_css(doc, layout, theme, custom_css_path, options)?;
+
+    if options.css_mode != CssMode::Inline {
+        write_atomic(&out_dir.join(STYLESHEET_FILE), &css)
+            .context("Failed to write CSS file")?;
+    }
+
+    // Generate HTML
+    let html = generate_html(doc, &css, layout, options, None)?;
+    check_markup(&html, OutputFormat::Html.file_name(), options)?;
+    let html_path = out_dir.join(OutputFormat::Html.file_name());
+    write_atomic(&html_path, html)
+        .context("Failed to write HTML file")?;
+
+    Ok(html_path)
+}
+
+/// Check a generated page's markup (see [`crate::markup`]). Under
+/// `strict` a problem fails the build; otherwise it's a warning.
+pub(crate) fn check_markup(html: &str, page: &str, options: &BuildOptions) -> Result<()> {
+    let problems = crate::markup::check(html);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let list: Vec<String> = problems.iter().map(|p| format!("  {}", p)).collect();
+    if options.strict {
+        anyhow::bail!("{} isn't well-formed HTML:\n{}", page, list.join("\n"));
+    }
+    crate::diagnostics::warn(format!("{} isn't well-formed HTML:\n{}", page, list.join("\n")));
+    Ok(())
+}
+
+/// Check the measured page against `max_pages`. Under `strict`
+/// running over fails the build; otherwise it's a warning.
+fn check_fit(measured: &measure::Measurements, max_pages: usize, options: &BuildOptions) -> Result<()> {
+    let pages = measured.pages();
+    if pages <= max_pages {
+        return Ok(());
+    }
+    let over: Vec<&str> = measured.past(max_pages).iter().map(|section| section.label()).collect();
+    let message = format!(
+        "the PDF runs to {} pages, over the {} allowed; past page {}: {}",
+        pages,
+        max_pages,
+        max_pages,
+        over.join(", ")
+    );
+    if options.strict {
+        anyhow::bail!("{}", message);
+    }
+    crate::diagnostics::warn(message);
+    Ok(())
+}
+
+/// The page's full stylesheet: theme CSS, the build's settings, font
+/// fallbacks for the scripts in `doc`, section accents, then any
+/// custom CSS.
+fn themed_css(
+    doc: &JoblDocument,
+    layout: &Layout,
+    theme: Option<&str>,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<String> {
     // Load CSS - combine theme CSS and custom CSS
     let mut css = String::new();
 
@@ -32,6 +1087,20 @@ pub fn build_resume(
         css.push_str(&load_theme_css(theme_name)?);
     }
 
+    css.push_str(&settings_css(options));
+    css.push_str(&scripts::fallback_css(doc, theme, options)?);
+    css.push_str(&accent_css(layout, options)?);
+    if options.watermark.is_some() {
+        css.push_str(WATERMARK_CSS);
+    }
+    if options.verify.is_some() {
+        css.push_str(VERIFY_CSS);
+    }
+    css.push_str(&layout_css(layout));
+    if let Some(bullets) = options.bullets {
+        css.push_str(bullets.css());
+    }
+
     // Load and append custom CSS if specified
     if let Some(css_path) = custom_css_path {
         if !css.is_empty() {
@@ -42,27 +1111,434 @@ pub fn build_resume(
         css.push_str(&custom_css);
     }
 
-    // Generate HTML
-    let html = generate_html(doc, &css, layout)?;
-    let html_path = out_dir.join("index.html");
-    fs::write(&html_path, html)
-        .context("Failed to write HTML file")?;
+    Ok(css)
+}
 
-    // Generate PDF from HTML
-    let pdf_path = out_dir.join("resume.pdf");
-    generate_pdf(&html_path, &pdf_path)
-        .context("Failed to generate PDF")?;
+/// Added to the print page's CSS under `grayscale`; photos can't be
+/// recolored through the stylesheet.
+const GRAYSCALE_IMAGES: &str = "\n\n/* Grayscale */\nimg { filter: grayscale(1); }\n";
 
-    Ok(())
+/// The page the PDF is printed from when it differs from index.html:
+/// `doc` under the PDF's emoji policy and, with `grayscale`, every
+/// color in the stylesheet turned gray. The CSS is always inlined so
+/// the page can't pick up the colored `style.css`. A `standalone`
+/// page also carries the theme's fonts, so it needs nothing on disk.
+fn print_page(
+    doc: &JoblDocument,
+    layout: &Layout,
+    theme: Option<&str>,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+    standalone: bool,
+) -> Result<String> {
+    let pdf_doc = emoji::apply(doc, options.emoji_policy(OutputFormat::Pdf));
+    let options = &*alias_pass(options, doc, |d| emoji::apply(d, options.emoji_policy(OutputFormat::Pdf)).into_owned());
+    let mut css = themed_css(&pdf_doc, layout, theme, custom_css_path, options)?;
+    if options.grayscale {
+        css = crate::css::grayscale(&css);
+        css.push_str(GRAYSCALE_IMAGES);
+    }
+    if let Some(links) = options.links {
+        css.push_str(links.css());
+    }
+    if let (true, Some(theme)) = (standalone, theme) {
+        css = inline_theme_fonts(&css, theme)?;
+    }
+    let print_options = BuildOptions {
+        css_mode: CssMode::Inline,
+        ..options.clone()
+    };
+    let page = generate_html(&pdf_doc, &css, layout, &print_options, options.links)?;
+    check_markup(&page, "the PDF's page", options)?;
+    Ok(page)
+}
+
+/// CSS variables for the build's style settings, as a `:root` rule
+/// placed after the theme (so it wins) and before custom CSS (so
+/// that still can). Empty when every setting is at its default.
+pub fn settings_css(options: &BuildOptions) -> String {
+    let mut vars = Vec::new();
+    if options.density != Density::Normal {
+        vars.push(("--srg-density".to_string(), options.density.scale().to_string()));
+    }
+    if let Some(size) = &options.font_size {
+        vars.push(("--srg-font-size".to_string(), size.clone()));
+    }
+    if let Some(height) = &options.line_height {
+        vars.push(("--srg-line-height".to_string(), height.clone()));
+    }
+    for (name, value) in &options.typography {
+        vars.push((format!("--srg-{}", name), value.clone()));
+    }
+    crate::css::root_rule("srg settings", &vars)
+}
+
+/// Per-section accent colors, from `accent=` in the layout and
+/// `options.accents`. Each section gets a scoped `--srg-accent`
+/// variable, which colors its headings and title rule.
+pub fn accent_css(layout: &Layout, options: &BuildOptions) -> Result<String> {
+    let mut accents: BTreeMap<String, String> = layout
+        .sections
+        .iter()
+        .filter_map(|s| Some((s.name.clone(), s.modifier_value("accent")?.to_string())))
+        .collect();
+    accents.extend(options.accents.clone());
+
+    let mut css = String::new();
+    for (section, color) in &accents {
+        if section.is_empty() || !section.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("Invalid section name for an accent: {:?}", section);
+        }
+        crate::css::check_value(color)
+            .map_err(|e| anyhow::anyhow!("accent for {}: {}", section, e))?;
+        if css.is_empty() {
+            css.push_str("\n\n/* Section accents */\n");
+        }
+        css.push_str(&format!(
+            ".section-{s} {{\n  --srg-accent: {c};\n}}\n\
+             .section-{s} h2,\n.section-{s} h3 {{\n  color: var(--srg-accent);\n}}\n\
+             .section-{s} h2 {{\n  border-bottom-color: var(--srg-accent);\n}}\n",
+            s = section,
+            c = color
+        ));
+    }
+    Ok(css)
+}
+
+/// Divider between the items of a `contactrow` line. Themes pick
+/// its text with the `contact-divider` typography knob.
+const CONTACT_ROW_CSS: &str = "
+
+/* Contact row */
+.contact-divider::before {
+  content: var(--srg-contact-divider, \"·\");
+}
+";
+
+/// Lays out `split` lines: the left side, then the right side
+/// pushed to the far edge.
+const SPLIT_ROW_CSS: &str = "
+
+/* Split rows */
+.split-row {
+  display: flex;
+  justify-content: space-between;
+  align-items: baseline;
+  gap: 1em;
+}
+.split-right {
+  margin-left: auto;
+  text-align: right;
+  white-space: nowrap;
+}
+";
+
+/// Whether any field line in `layout` passes `test`.
+fn any_field(layout: &Layout, test: impl Fn(&crate::layout::Field) -> bool) -> bool {
+    layout.sections.iter().flat_map(|s| &s.fields).any(|entry| match entry {
+        crate::layout::FieldOrContainer::Field(field) => test(field),
+        crate::layout::FieldOrContainer::Container(container) => container.fields.iter().any(&test),
+    })
+}
+
+/// CSS the layout's own constructs (`contactrow`, `split`, the
+/// patents list, `booking`, linked skills, the search box) need,
+/// whatever the theme.
+fn layout_css(layout: &Layout) -> String {
+    let mut css = String::new();
+    if any_field(layout, |field| field.contact_row) {
+        css.push_str(CONTACT_ROW_CSS);
+    }
+    if any_field(layout, |field| field.split.is_some()) {
+        css.push_str(SPLIT_ROW_CSS);
+    }
+    if layout.sections.iter().any(|s| s.name == "patents") {
+        css.push_str(PATENTS_CSS);
+    }
+    if resolved::skills_linked(layout) {
+        css.push_str(SKILL_LINKS_CSS);
+    }
+    if resolved::any_collapsed(layout) {
+        css.push_str(COLLAPSE_CSS);
+    }
+    if layout.settings.search == Some(true) {
+        css.push_str(SEARCH_CSS);
+    }
+    if layout.sections.iter().any(|s| s.name == "person")
+        && any_field(layout, |field| matches!(field.parts.as_slice(), [FieldPart::Field(name)] if name == "booking"))
+    {
+        css.push_str(BOOKING_CSS);
+    }
+    css
+}
+
+/// The patents section is one line per patent, its fields run
+/// together with a dot between them.
+const PATENTS_CSS: &str = "
+
+/* Patents */
+.patents-list {
+  list-style: none;
+  padding-left: 0;
+  margin-left: 0;
+}
+.patents-item > * + *::before {
+  content: \"· \";
+}
+";
+
+/// Linked skills read as skills, not navigation, and the entries a
+/// clicked skill matches are marked.
+const SKILL_LINKS_CSS: &str = "
+
+/* Linked skills */
+.skills-link {
+  color: inherit;
+  text-decoration: underline dotted;
+}
+.skill-match {
+  outline: 2px solid currentColor;
+  outline-offset: 4px;
+}
+";
+
+/// Marks every experience entry tagged with a clicked skill, so the
+/// jump to the first one also shows where else it was used.
+const SKILL_LINKS_JS: &str = "  <script>
+    document.querySelectorAll(\".skills-link\").forEach(function (link) {
+      link.addEventListener(\"click\", function () {
+        var skill = link.getAttribute(\"data-skill\");
+        document.querySelectorAll(\"[data-skills]\").forEach(function (item) {
+          var skills = item.getAttribute(\"data-skills\").split(\" \");
+          item.classList.toggle(\"skill-match\", skills.indexOf(skill) !== -1);
+        });
+      });
+    });
+  </script>
+";
+
+/// `collapse=N` hides a section's later entries on screen until its
+/// toggle opens the section. Print always shows every entry.
+const COLLAPSE_CSS: &str = "
+
+/* Collapsed entries */
+@media screen {
+  .section:not(.srg-open) > .srg-more {
+    display: none;
+  }
+}
+@media print {
+  .srg-toggle {
+    display: none;
+  }
+}
+";
+
+/// Opens and closes a collapsed section. Without scripts the entries
+/// are all shown instead (see the page's `<noscript>`).
+const COLLAPSE_JS: &str = "  <script>
+    document.querySelectorAll(\".srg-toggle\").forEach(function (toggle) {
+      var section = toggle.closest(\".section\");
+      var label = toggle.textContent;
+      toggle.addEventListener(\"click\", function () {
+        var open = section.classList.toggle(\"srg-open\");
+        toggle.textContent = open ? \"Show fewer\" : label;
+        toggle.setAttribute(\"aria-expanded\", String(open));
+      });
+    });
+  </script>
+";
+
+/// Collapsed entries shown and their toggles hidden, for pages that
+/// run no scripts: the page's `<noscript>` and the EPUB.
+const EXPANDED_CSS: &str = "
+.srg-more { display: revert !important; } .srg-toggle { display: none; }
+";
+
+/// The search box, and what it filters out. Entries filtered out on
+/// screen still print, and so do collapsed ones while a search runs.
+const SEARCH_CSS: &str = "
+
+/* Search */
+.srg-search input {
+  width: 100%;
+  box-sizing: border-box;
+  padding: 0.4em 0.6em;
+  font: inherit;
+}
+@media screen {
+  .srg-miss {
+    display: none !important;
+  }
+  .srg-searching .section > .srg-more {
+    display: revert;
+  }
+}
+@media print {
+  .srg-search {
+    display: none;
+  }
+}
+";
+
+/// Builds the search box (so a page without scripts has no dead box)
+/// and filters entries as the visitor types. An entry stays when its
+/// text has the keyword; in one that stays, only the list lines with
+/// the keyword are kept, unless it's elsewhere in the entry. Sections
+/// with nothing left are hidden.
+const SEARCH_JS: &str = "  <script>
+    (function () {
+      var main = document.querySelector(\"main\");
+      var box = document.createElement(\"div\");
+      box.className = \"srg-search\";
+      var input = document.createElement(\"input\");
+      input.type = \"search\";
+      input.placeholder = \"Filter by keyword\";
+      input.setAttribute(\"aria-label\", \"Filter entries by keyword\");
+      box.appendChild(input);
+      var header = main.querySelector(\".section-person\");
+      main.insertBefore(box, header ? header.nextSibling : main.firstChild);
+
+      var sections = main.querySelectorAll(\".section:not(.section-person)\");
+      input.addEventListener(\"input\", function () {
+        var query = input.value.trim().toLowerCase();
+        var has = function (el) { return el.textContent.toLowerCase().indexOf(query) !== -1; };
+        document.body.classList.toggle(\"srg-searching\", query !== \"\");
+        sections.forEach(function (section) {
+          var entries = section.querySelectorAll(\"[id]\");
+          var found = entries.length ? false : has(section);
+          entries.forEach(function (entry) {
+            var hit = has(entry);
+            entry.classList.toggle(\"srg-miss\", !hit);
+            found = found || hit;
+            var lines = Array.prototype.slice.call(entry.querySelectorAll(\"li\"));
+            var narrow = query !== \"\" && lines.some(has);
+            lines.forEach(function (line) {
+              line.classList.toggle(\"srg-miss\", narrow && !has(line));
+            });
+          });
+          section.classList.toggle(\"srg-miss\", !found);
+        });
+      });
+    })();
+  </script>
+";
+
+/// Lays out the `booking` block's link, hours, and calendar link on
+/// one line, wrapping when the header is narrow.
+const BOOKING_CSS: &str = "
+
+/* Booking */
+.person-booking {
+  display: flex;
+  flex-wrap: wrap;
+  align-items: baseline;
+  gap: 0.25em 1em;
+}
+";
+
+/// Styles for the `--watermark` stamp. It only shows in print;
+/// Chrome repeats fixed elements on every printed page, so each page
+/// of the PDF carries it.
+const WATERMARK_CSS: &str = "
+
+/* Watermark */
+.srg-watermark {
+  display: none;
+}
+@media print {
+  .srg-watermark {
+    display: block;
+    position: fixed;
+    top: 50%;
+    left: 50%;
+    transform: translate(-50%, -50%) rotate(-35deg);
+    font-size: 7rem;
+    font-weight: bold;
+    letter-spacing: 0.1em;
+    white-space: nowrap;
+    color: rgba(0, 0, 0, 0.08);
+    pointer-events: none;
+    z-index: 1000;
+  }
+}
+";
+
+/// Styles for the `--verify-url` version line on the hosted page.
+/// Printed copies carry it in the page footer instead.
+const VERIFY_CSS: &str = "
+
+/* Version line */
+.srg-verify {
+  margin: 2em 0 1em;
+  text-align: center;
+  font-size: 0.75em;
+  opacity: 0.6;
+}
+@media print {
+  .srg-verify {
+    display: none;
+  }
+}
+";
+
+/// `css` with every reference to one of the theme's bundled fonts
+/// (`url("fonts/...")`) replaced by a `data:` URI.
+fn inline_theme_fonts(css: &str, theme: &str) -> Result<String> {
+    use base64::Engine;
+
+    let mut css = css.to_string();
+    for (rel, bytes) in crate::themes::ThemeStack::resolve(theme)?.fonts() {
+        let mime = match Path::new(rel).extension().and_then(|e| e.to_str()) {
+            Some("woff2") => "font/woff2",
+            Some("woff") => "font/woff",
+            Some("otf") => "font/otf",
+            _ => "font/ttf",
+        };
+        let uri = format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes));
+        css = css.replace(&format!("fonts/{}", rel), &uri);
+    }
+    Ok(css)
+}
+
+/// Print the resume straight to PDF bytes without writing anything
+/// to the output directory: the print page, with its CSS and fonts
+/// inlined, is streamed into the browser tab (Typst compiles in a
+/// scratch directory). For `--pdf-only` builds and other callers that
+/// want the PDF in memory.
+pub fn render_pdf(
+    doc: &JoblDocument,
+    theme: Option<&str>,
+    layout: &Layout,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<Vec<u8>> {
+    if options.pdf_backend == PdfBackend::Typst {
+        return typst::compile(&typst_source(doc, layout, options), options.pdf_profile);
+    }
+    let theme = options.pdf_theme.as_deref().or(theme);
+    let page = standalone_print_page(doc, theme, layout, custom_css_path, options)?;
+    print_pdf(PdfSource::Html { html: &page, base: None }, options)
+}
+
+/// The self-contained page [`render_pdf`] prints.
+pub fn standalone_print_page(
+    doc: &JoblDocument,
+    theme: Option<&str>,
+    layout: &Layout,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<String> {
+    print_page(doc, layout, theme, custom_css_path, options, true)
 }
 
 /// Copy a theme's bundled font assets (if any) to the output dir.
 /// Themes declare fonts simply by placing files under
 /// `src/layouts/<theme>/fonts/`; the build script bundles them and
 /// `crate::themes::fonts_for` exposes them as (relative_path, bytes)
-/// tuples.
+/// tuples. Layered themes contribute the fonts of every layer.
 fn copy_theme_fonts(theme: &str, out_dir: &Path) -> Result<()> {
-    let fonts = crate::themes::fonts_for(theme);
+    let fonts = crate::themes::ThemeStack::resolve(theme)?.fonts();
     if fonts.is_empty() {
         return Ok(());
     }
@@ -72,16 +1548,15 @@ fn copy_theme_fonts(theme: &str, out_dir: &Path) -> Result<()> {
         if let Some(parent) = dest.parent() {
             fs::create_dir_all(parent)?;
         }
-        fs::write(&dest, bytes)?;
+        write_atomic(&dest, bytes)?;
     }
     Ok(())
 }
 
-/// Load CSS from the auto-generated theme registry.
+/// Load CSS from the auto-generated theme registry. Layered specs
+/// concatenate each layer's CSS, base first.
 fn load_theme_css(theme: &str) -> Result<String> {
-    crate::themes::css_for(theme)
-        .map(|s| s.to_string())
-        .ok_or_else(|| anyhow::anyhow!("Unknown theme: {}", theme))
+    crate::themes::ThemeStack::resolve(theme)?.css()
 }
 
 /// Generate HTML from JOBL document
@@ -89,215 +1564,316 @@ fn generate_html(
     doc: &JoblDocument,
     css: &str,
     layout: &Layout,
+    options: &BuildOptions,
+    links: Option<PrintLinks>,
 ) -> Result<String> {
+    // From the document as written: dates in a locale no longer say
+    // "Present".
+    let structured_data = jsonld::script(doc, layout);
     let mut html = String::new();
 
     html.push_str("<!DOCTYPE html>\n");
-    html.push_str("<html lang=\"en\">\n");
+    let lang = options.locale.map_or("en", crate::dates::Locale::code);
+    html.push_str(&format!("<html lang=\"{}\">\n", lang));
     html.push_str("<head>\n");
     html.push_str("  <meta charset=\"UTF-8\">\n");
     html.push_str(
         "  <meta name=\"viewport\" content=\"width=device-width, \
          initial-scale=1.0\">\n",
     );
-    html.push_str(&format!("  <title>{}</title>\n", doc.person.name));
-    html.push_str("  <style>\n");
-    html.push_str(css);
-    html.push_str("  </style>\n");
+    html.push_str(&format!("  <title>{}</title>\n", escape_html(&doc.person.name)));
+    html.push_str(&structured_data);
+    push_stylesheet(&mut html, css, options.css_mode);
+    if resolved::any_collapsed(layout) {
+        html.push_str(&format!("  <noscript><style>{}</style></noscript>\n", EXPANDED_CSS.trim()));
+    }
     html.push_str("</head>\n");
     html.push_str("<body>\n");
-    html.push_str("  <main>\n");
-
-    for section in &layout.sections {
-        match section.name.as_str() {
-            "person" => {
-                render_person_section(&mut html, doc, section);
-            }
-            "summary" => {
-                render_summary_section(&mut html, doc);
-            }
-            "skills" => {
-                render_skills_section(&mut html, doc);
-            }
-            "experience" => {
-                render_experience_section(&mut html, doc, section);
-            }
-            "projects" => {
-                render_projects_section(&mut html, doc, section);
-            }
-            "education" => {
-                render_education_section(&mut html, doc, section);
-            }
-            _ => {}
-        }
-    }
-
+    html.push_str("  <main class=\"h-resume\">\n");
+    html.push_str(&main_sections(doc, layout, options, links));
     html.push_str("  </main>\n");
+    if let Some(text) = &options.watermark {
+        html.push_str(&format!(
+            "  <div class=\"srg-watermark\" aria-hidden=\"true\">{}</div>\n",
+            escape_html(text)
+        ));
+    }
+    if let Some(verify) = &options.verify {
+        html.push_str(&format!("  <footer class=\"srg-verify\">Version {}</footer>\n", verify.hash));
+    }
+    push_scripts(&mut html, layout);
     html.push_str("</body>\n");
     html.push_str("</html>\n");
 
     Ok(html)
 }
 
+/// The resume for embedding in another site's page (`--fragment`):
+/// what the themed page has inside `<main>`, then the scripts its
+/// collapsing, search, and skill links need. The theme's rules for
+/// the page itself (`body`, `main`) have nothing to apply to, so the
+/// site styles it, or links style.css with an external CSS mode.
+pub fn generate_fragment(doc: &JoblDocument, layout: &Layout, options: &BuildOptions) -> String {
+    let mut html = main_sections(doc, layout, options, None);
+    push_scripts(&mut html, layout);
+    html
+}
+
+/// The page's sections, as they go inside `<main>`.
+fn main_sections(doc: &JoblDocument, layout: &Layout, options: &BuildOptions, links: Option<PrintLinks>) -> String {
+    let mut html = String::new();
+    for section in &resolve_page(doc, layout, options, links).sections {
+        push_section(&mut html, section);
+    }
+    html
+}
+
+/// The resolved tree the themed page writes: `doc` with the budgets
+/// and date format applied, marked up with microformats.
+fn resolve_page(doc: &JoblDocument, layout: &Layout, options: &BuildOptions, links: Option<PrintLinks>) -> resolved::Resolved {
+    let options = &*alias_pass(options, doc, |d| {
+        let d = crate::budget::apply(d, &options.budgets);
+        crate::dates::apply(&d, options.date_format.as_deref(), options.locale).into_owned()
+    });
+    let doc = &*crate::budget::apply(doc, &options.budgets);
+    let doc = &*crate::dates::apply(doc, options.date_format.as_deref(), options.locale);
+
+    let mut resolved = resolved::Resolved::build(doc, layout, &options.extras);
+    microformats::annotate(&mut resolved);
+    if let Some(links) = links {
+        resolved.print_links(links);
+    }
+    resolved
+}
+
+fn push_scripts(html: &mut String, layout: &Layout) {
+    if resolved::skills_linked(layout) {
+        html.push_str(SKILL_LINKS_JS);
+    }
+    if resolved::any_collapsed(layout) {
+        html.push_str(COLLAPSE_JS);
+    }
+    if layout.settings.search == Some(true) {
+        html.push_str(SEARCH_JS);
+    }
+}
+
+/// Element ids for every experience, project, and education item.
+///
+/// Ids are slugs of the item's most identifying field (company,
+/// project name, institution) so links like `#acme-corp` survive
+/// edits to the rest of the item. They're assigned in document order
+/// regardless of the layout, so reordering sections never changes
+/// them. Repeats get a numeric suffix (`acme-corp-2`).
+pub struct Anchors {
+    pub experience: Vec<String>,
+    pub projects: Vec<String>,
+    pub education: Vec<String>,
+}
+
+impl Anchors {
+    pub fn for_document(doc: &JoblDocument) -> Self {
+        // Section ids are taken up front so an item can't shadow them.
+        let mut used: Vec<String> = crate::layout::SECTION_NAMES
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut unique = |text: &str| -> String {
+            let base = slugify(text);
+            let mut candidate = base.clone();
+            let mut n = 2;
+            while used.contains(&candidate) {
+                candidate = format!("{}-{}", base, n);
+                n += 1;
+            }
+            used.push(candidate.clone());
+            candidate
+        };
+
+        let experience = doc.experience.iter().map(|e| unique(&e.company)).collect();
+        let projects = doc.projects.iter().map(|p| unique(&p.name)).collect();
+        let education = doc.education.iter().map(|e| unique(&e.institution)).collect();
+
+        Self { experience, projects, education }
+    }
+}
+
+/// Lowercase `text` and collapse everything that isn't a letter or
+/// digit into single dashes: "Acme Corp." -> "acme-corp".
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        slug.push_str("item");
+    }
+    slug
+}
+
 /// Generate HTML for testing (public for integration tests)
 pub fn generate_test_html(
     doc: &JoblDocument,
     theme: Option<&str>,
     layout: &Layout,
 ) -> Result<String> {
-    let css = if let Some(theme_name) = theme {
-        load_theme_css(theme_name)?
-    } else {
-        String::new()
+    generate_test_html_with_options(doc, theme, layout, &BuildOptions::default())
+}
+
+/// Like `generate_test_html`, with explicit build options.
+pub fn generate_test_html_with_options(
+    doc: &JoblDocument,
+    theme: Option<&str>,
+    layout: &Layout,
+    options: &BuildOptions,
+) -> Result<String> {
+    let mut css = match theme {
+        Some(theme_name) => load_theme_css(theme_name)?,
+        None => String::new(),
     };
-    generate_html(doc, &css, layout)
+    css.push_str(&settings_css(options));
+    css.push_str(&scripts::fallback_css(doc, theme, options)?);
+    css.push_str(&accent_css(layout, options)?);
+    if options.watermark.is_some() {
+        css.push_str(WATERMARK_CSS);
+    }
+    if options.verify.is_some() {
+        css.push_str(VERIFY_CSS);
+    }
+    css.push_str(&layout_css(layout));
+    if let Some(bullets) = options.bullets {
+        css.push_str(bullets.css());
+    }
+    generate_html(doc, &css, layout, options, None)
 }
 
-fn render_person_section(
-    html: &mut String,
+/// The page the PDF would be printed from (public for integration
+/// tests).
+pub fn generate_test_print_html(
     doc: &JoblDocument,
-    section: &crate::layout::Section,
-) {
-    html.push_str("    <header id=\"person\" class=\"section section-person\">\n");
+    theme: Option<&str>,
+    layout: &Layout,
+    options: &BuildOptions,
+) -> Result<String> {
+    print_page(doc, layout, theme, None, options, false)
+}
 
-    for field_or_container in &section.fields {
-        match field_or_container {
-            crate::layout::FieldOrContainer::Field(field) => {
-                render_person_field(html, doc, field);
-            }
-            crate::layout::FieldOrContainer::Container(container) => {
-                html.push_str(&format!("      <div class=\"{}\">\n", container.class_name));
-                for field in &container.fields {
-                    render_person_field(html, doc, field);
-                }
-                html.push_str("      </div>\n");
-            }
+/// Emit the `<head>` markup for the stylesheet according to the mode.
+///
+/// Critical mode uses the `media="print"` swap rather than
+/// `rel="preload"`: the full sheet still applies when Chrome prints
+/// the PDF (print media matches), and the load event waits for it, so
+/// the PDF never renders with only the critical subset.
+fn push_stylesheet(html: &mut String, css: &str, mode: CssMode) {
+    match mode {
+        CssMode::Inline => {
+            html.push_str("  <style>\n");
+            html.push_str(css);
+            html.push_str("  </style>\n");
+        }
+        CssMode::External => {
+            html.push_str(&format!(
+                "  <link rel=\"stylesheet\" href=\"{}\">\n",
+                STYLESHEET_FILE
+            ));
+        }
+        CssMode::Critical => {
+            html.push_str("  <style>\n");
+            html.push_str(&crate::css::critical_css(css));
+            html.push_str("  </style>\n");
+            html.push_str(&format!(
+                "  <link rel=\"stylesheet\" href=\"{}\" media=\"print\" \
+                 onload=\"this.media='all'\">\n",
+                STYLESHEET_FILE
+            ));
+            html.push_str(&format!(
+                "  <noscript><link rel=\"stylesheet\" href=\"{}\"></noscript>\n",
+                STYLESHEET_FILE
+            ));
         }
     }
+}
 
-    html.push_str("    </header>\n");
+/// Write a resolved section as the themed page's markup.
+fn push_section(html: &mut String, section: &resolved::ResolvedSection) {
+    html.push_str(&format!(
+        "    <{} id=\"{}\" class=\"{}\">\n",
+        section.element,
+        escape_html(&section.name),
+        escape_html(&section.classes.join(" "))
+    ));
+    if let Some(title) = &section.title {
+        html.push_str(&format!("      <h2>{}</h2>\n", escape_html(title)));
+    }
+    for node in &section.nodes {
+        push_node(html, 6, node);
+    }
+    html.push_str(&format!("    </{}>\n", section.element));
 }
 
-fn render_person_field(
-    html: &mut String,
-    doc: &JoblDocument,
-    field: &crate::layout::Field,
-) {
-    // If field has single part that's a known field, render it specially
-    if field.parts.len() == 1 {
-        if let FieldPart::Field(name) = &field.parts[0] {
-            match name.as_str() {
-                "name" => {
-                    html.push_str(
-                        &format!("      <h1 class=\"person-name\">{}</h1>\n", doc.person.name),
-                    );
-                    return;
-                }
-                "headline" => {
-                    if let Some(headline) = &doc.person.headline {
-                        html.push_str(&format!(
-                            "      <p class=\"person-headline\">{}</p>\n",
-                            escape_html(headline)
-                        ));
-                    }
-                    return;
-                }
-                "email" => {
-                    if let Some(email) = &doc.person.email {
-                        html.push_str(&format!(
-                            "      <span class=\"person-email\">{}</span>\n",
-                            escape_html(email)
-                        ));
-                    }
-                    return;
-                }
-                "phone" => {
-                    if let Some(phone) = &doc.person.phone {
-                        html.push_str(&format!(
-                            "      <span class=\"person-phone\">{}</span>\n",
-                            escape_html(phone)
-                        ));
-                    }
-                    return;
-                }
-                "location" => {
-                    if let Some(location) = &doc.person.location {
-                        html.push_str(&format!(
-                            "      <span class=\"person-location\">{}</span>\n",
-                            escape_html(location)
-                        ));
-                    }
-                    return;
-                }
-                "website" => {
-                    if let Some(website) = &doc.person.website {
-                        html.push_str(&format!(
-                            "      <a class=\"person-website\" href=\"{}\">{}</a>\n",
-                            escape_html(website),
-                            escape_html(website)
-                        ));
-                    }
-                    return;
-                }
-                "github" => {
-                    if let Some(github) = &doc.person.github {
-                        html.push_str(&format!(
-                            "      <a class=\"person-github\" href=\"{}\">{}</a>\n",
-                            escape_html(github),
-                            escape_html(github)
-                        ));
-                    }
-                    return;
-                }
-                "linkedin" => {
-                    if let Some(linkedin) = &doc.person.linkedin {
-                        html.push_str(&format!(
-                            "      <a class=\"person-linkedin\" href=\"{}\">{}</a>\n",
-                            escape_html(linkedin),
-                            escape_html(linkedin)
-                        ));
-                    }
-                    return;
-                }
-                "summary" => {
-                    if let Some(summary) = &doc.person.summary {
-                        html.push_str(&format!(
-                            "      <p class=\"person-summary\">{}</p>\n",
-                            escape_html(summary)
-                        ));
-                    }
-                    return;
-                }
-                _ => {}
+/// Write a node at `indent`, its block children indented under it.
+fn push_node(html: &mut String, indent: usize, node: &resolved::Node) {
+    let pad = " ".repeat(indent);
+    match &node.content {
+        resolved::Content::Block(children) => {
+            html.push_str(&format!("{}{}\n", pad, start_tag(node)));
+            for child in children {
+                push_node(html, indent + 2, child);
             }
+            html.push_str(&format!("{}</{}>\n", pad, node.element));
         }
+        _ => html.push_str(&format!("{}{}\n", pad, inline_html(node))),
     }
+}
 
-    // Otherwise, render as inline mixed content
-    let class_str = if let Some(class_name) = &field.class_name {
-        format!(" class=\"{}\"", class_name)
-    } else {
-        String::new()
-    };
-
-    html.push_str(&format!("      <p{}>\n", class_str));
-    for part in &field.parts {
-        match part {
-            FieldPart::Literal(text) => {
-                html.push_str(&escape_html(text));
+/// A node written on one line.
+fn inline_html(node: &resolved::Node) -> String {
+    let mut html = start_tag(node);
+    match &node.content {
+        resolved::Content::Void => return html,
+        resolved::Content::Block(children) => {
+            for child in children {
+                html.push_str(&inline_html(child));
             }
-            FieldPart::Field(name) => {
-                let value = get_person_field_value(doc, name);
-                if let Some(v) = value {
-                    html.push_str(&escape_html(&v));
+        }
+        resolved::Content::Inline(inlines) => {
+            for inline in inlines {
+                match inline {
+                    resolved::Inline::Text(text) => html.push_str(&escape_html(text)),
+                    resolved::Inline::Break => html.push_str("<br>\n"),
+                    resolved::Inline::Element(child) => html.push_str(&inline_html(child)),
                 }
             }
         }
     }
-    html.push_str("</p>\n");
+    html.push_str(&format!("</{}>", node.element));
+    html
 }
 
-fn get_person_field_value(doc: &JoblDocument, field: &str) -> Option<String> {
+fn start_tag(node: &resolved::Node) -> String {
+    let mut tag = format!("<{}", node.element);
+    if let Some(id) = &node.id {
+        tag.push_str(&format!(" id=\"{}\"", escape_html(id)));
+    }
+    if !node.classes.is_empty() {
+        tag.push_str(&format!(" class=\"{}\"", escape_html(&node.classes.join(" "))));
+    }
+    for (name, value) in &node.attributes {
+        tag.push_str(&format!(" {}=\"{}\"", name, escape_html(value)));
+    }
+    tag.push('>');
+    tag
+}
+
+/// Value of a person field by its layout name (`name`, `email`, ...).
+pub fn get_person_field_value(doc: &JoblDocument, field: &str) -> Option<String> {
     match field {
         "name" => Some(doc.person.name.clone()),
         "headline" => doc.person.headline.clone(),
@@ -312,449 +1888,337 @@ fn get_person_field_value(doc: &JoblDocument, field: &str) -> Option<String> {
     }
 }
 
-fn render_summary_section(html: &mut String, doc: &JoblDocument) {
-    if let Some(summary) = &doc.person.summary {
-        html.push_str("    <section id=\"summary\" class=\"section section-summary\">\n");
-        html.push_str("      <h2>Summary</h2>\n");
-        html.push_str(
-            &format!("      <p class=\"summary-text\">{}</p>\n", escape_html(summary)),
-        );
-        html.push_str("    </section>\n");
-    }
-}
-
-fn render_skills_section(html: &mut String, doc: &JoblDocument) {
-    if let Some(skills) = &doc.skills {
-        if !skills.is_empty() {
-            html.push_str("    <section id=\"skills\" class=\"section section-skills\">\n");
-            html.push_str("      <h2>Skills</h2>\n");
-            for (category, items) in skills {
-                html.push_str(&format!(
-                    "      <p class=\"skills-category\"><strong class=\"skills-category-name\">{}:</strong> <span class=\"skills-items\">{}</span></p>\n",
-                    escape_html(category),
-                    items
-                        .iter()
-                        .map(|s| escape_html(s))
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ));
-            }
-            html.push_str("    </section>\n");
-        }
-    }
-}
-
-fn render_experience_section(
-    html: &mut String,
+/// `options` with `pass` also run over every alias section and srg's
+/// own sections and person fields, so the document-wide passes (emoji,
+/// budgets, dates) reach them. Borrows when the JOBL file has none.
+fn alias_pass<'a>(
+    options: &'a BuildOptions,
     doc: &JoblDocument,
-    section: &crate::layout::Section,
-) {
-    if doc.experience.is_empty() {
-        return;
+    pass: impl Fn(&JoblDocument) -> JoblDocument,
+) -> std::borrow::Cow<'a, BuildOptions> {
+    let extras = &options.extras;
+    if extras.sections.is_empty() && extras.talks.is_empty() && extras.patents.is_empty() && extras.person.is_empty() {
+        return std::borrow::Cow::Borrowed(options);
     }
+    let mut options = options.clone();
+    options.extras.sections = options.extras.sections.iter().map(|alias| alias.map(doc, &pass)).collect();
+    options.extras.talks = crate::sections::map_talks(&options.extras.talks, doc, &pass);
+    options.extras.patents = crate::sections::map_patents(&options.extras.patents, doc, &pass);
+    options.extras.person = crate::sections::map_person(&options.extras.person, doc, &pass);
+    std::borrow::Cow::Owned(options)
+}
 
-    html.push_str("    <section id=\"experience\" class=\"section section-experience\">\n");
-    html.push_str("      <h2>Experience</h2>\n");
+/// Every highlight filed under one technology, in the order
+/// technologies first appear. A highlight goes under the first of
+/// its job's `technologies` it names, else the job's first
+/// technology; jobs without any land under "Other".
+pub fn highlights_by_skill(doc: &JoblDocument) -> Vec<(String, Vec<(&jobl::ExperienceItem, &String)>)> {
+    let mut groups: Vec<(String, Vec<(&jobl::ExperienceItem, &String)>)> = Vec::new();
+    let mut other = Vec::new();
 
     for exp in &doc.experience {
-        html.push_str("      <div class=\"experience-item\">\n");
-
-        for field_or_container in &section.fields {
-            match field_or_container {
-                crate::layout::FieldOrContainer::Field(field) => {
-                    render_experience_field(html, exp, field);
-                }
-                crate::layout::FieldOrContainer::Container(container) => {
-                    html.push_str(&format!("        <div class=\"{}\">\n", container.class_name));
-                    for field in &container.fields {
-                        render_experience_field(html, exp, field);
-                    }
-                    html.push_str("        </div>\n");
-                }
+        for highlight in &exp.highlights {
+            let skill = exp
+                .technologies
+                .iter()
+                .find(|tech| mentions_word(highlight, tech))
+                .or_else(|| exp.technologies.first());
+            match skill {
+                Some(skill) => match groups.iter_mut().find(|(name, _)| name.eq_ignore_ascii_case(skill)) {
+                    Some((_, items)) => items.push((exp, highlight)),
+                    None => groups.push((skill.clone(), vec![(exp, highlight)])),
+                },
+                None => other.push((exp, highlight)),
             }
         }
-
-        html.push_str("      </div>\n");
     }
 
-    html.push_str("    </section>\n");
+    if !other.is_empty() {
+        groups.push(("Other".to_string(), other));
+    }
+    groups
 }
 
-fn render_experience_field(
-    html: &mut String,
-    exp: &jobl::ExperienceItem,
-    field: &crate::layout::Field,
-) {
-    if field.parts.is_empty() {
-        return;
+/// Case-insensitive whole-word search, so "Go" doesn't match "good".
+pub fn mentions_word(text: &str, word: &str) -> bool {
+    let text = text.to_lowercase();
+    let word = word.to_lowercase();
+    if word.is_empty() {
+        return false;
     }
+    text.match_indices(&word).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
 
-    // Check for single-field special cases
-    if field.parts.len() == 1 {
-        if let FieldPart::Field(name) = &field.parts[0] {
-            match name.as_str() {
-                "title" => {
-                    html.push_str(&format!(
-                        "        <h3 class=\"experience-title\">{}</h3>\n",
-                        escape_html(&exp.title)
-                    ));
-                    return;
-                }
-                "company" => {
-                    html.push_str(&format!(
-                        "        <p class=\"experience-company\">{}</p>\n",
-                        escape_html(&exp.company)
-                    ));
-                    return;
-                }
-                "summary" => {
-                    if let Some(summary) = &exp.summary {
-                        html.push_str(&format!(
-                            "        <p class=\"experience-summary\">{}</p>\n",
-                            escape_html(summary)
-                        ));
-                    }
-                    return;
-                }
-                "highlights" => {
-                    if !exp.highlights.is_empty() {
-                        html.push_str("        <ul class=\"experience-highlights\">\n");
-                        for highlight in &exp.highlights {
-                            html.push_str(&format!(
-                                "          <li>{}</li>\n",
-                                escape_html_with_breaks(highlight)
-                            ));
-                        }
-                        html.push_str("        </ul>\n");
-                    }
-                    return;
-                }
-                _ => {}
-            }
-        }
-    }
+/// Chrome's page footer for `text`. Header and footer templates
+/// don't inherit the page's styles, so everything is set inline.
+pub fn footer_template(text: &str) -> String {
+    format!(
+        "<div style=\"width: 100%; font-family: sans-serif; font-size: 8px; \
+         color: #666; text-align: center;\">{}</div>",
+        escape_html(text)
+    )
+}
 
-    // Render as inline mixed content
-    let class_str = if let Some(class_name) = &field.class_name {
-        format!(" class=\"{}\"", class_name)
-    } else {
-        String::new()
-    };
+/// Chrome's page footer for a [`Verification`]: the URL and hash
+/// beside a QR code for the URL, with the `--footer` text, if any, to
+/// the left.
+pub fn verify_footer_template(footer: Option<&str>, verify: &Verification) -> String {
+    let qr = qr::QrCode::encode(verify.url.as_bytes()).map(|qr| qr.svg(48)).unwrap_or_default();
+    format!(
+        "<div style=\"width: 100%; padding: 0 0.4in; box-sizing: border-box; display: flex; \
+         align-items: center; justify-content: space-between; gap: 12px; font-family: sans-serif; \
+         font-size: 8px; color: #666;\"><span>{}</span><span style=\"display: flex; \
+         align-items: center; gap: 6px; text-align: right;\"><span>Current version: {}<br>{}</span>{}</span></div>",
+        footer.map(escape_html).unwrap_or_default(),
+        escape_html(&verify.url),
+        verify.hash,
+        qr
+    )
+}
 
-    html.push_str(&format!("        <p{}>\n", class_str));
-    for part in &field.parts {
-        match part {
-            FieldPart::Literal(text) => {
-                html.push_str(&escape_html(text));
-            }
-            FieldPart::Field(name) => {
-                let value = get_experience_field_value(exp, name);
-                if let Some(v) = value {
-                    html.push_str(&escape_html(&v));
-                }
-            }
+/// Places "(continued)" headers for `BuildOptions::continued`. Chrome
+/// has no running headers that know which section a page is in, so
+/// srg paginates the page itself: laid out one page's content box
+/// wide, each entry that would cross onto the next page gets its
+/// section's title, marked "(continued)", inserted before it with a
+/// page break ahead. Sections that fit on their page are untouched,
+/// and so is a section's first entry, which moves with the title.
+/// Pages that break elsewhere are estimated at the page height.
+const CONTINUED_JS: &str = r#"function (width, pageHeight) {
+  var root = document.documentElement;
+  var saved = root.style.width;
+  root.style.width = width + "px";
+  var top = function (el) { return el.getBoundingClientRect().top + window.scrollY; };
+  var bottom = function (el) { return el.getBoundingClientRect().bottom + window.scrollY; };
+  var pageEnd = pageHeight;
+  var reach = function (y) { while (y > pageEnd) { pageEnd += pageHeight; } };
+  document.querySelectorAll("main > .section").forEach(function (section) {
+    var title = section.querySelector("h2");
+    if (title) {
+      reach(bottom(title));
+      var entries = Array.prototype.filter.call(section.children, function (el) { return el.id; });
+      entries.forEach(function (entry, i) {
+        if (i > 0 && bottom(entry) > pageEnd) {
+          var header = document.createElement(title.tagName);
+          header.className = "srg-continued";
+          header.setAttribute("aria-hidden", "true");
+          header.textContent = title.textContent + " (continued)";
+          header.style.breakBefore = "page";
+          section.insertBefore(header, entry);
+          pageEnd = top(header) + pageHeight;
         }
+        reach(bottom(entry));
+      });
     }
-    html.push_str("</p>\n");
+    reach(bottom(section));
+  });
+  root.style.width = saved;
+}"#;
+
+/// Size of the `og-image` preview in CSS pixels: the 1.91:1 image
+/// Open Graph and most link previews expect.
+pub const PREVIEW_SIZE: (u32, u32) = (1200, 630);
+
+/// Screenshot the top of the tab's page, where the resume's header
+/// is, as a PNG of [`PREVIEW_SIZE`].
+fn capture_preview(tab: &Tab) -> Result<Vec<u8>> {
+    let (width, height) = PREVIEW_SIZE;
+    tab.call_method(Emulation::SetDeviceMetricsOverride {
+        width,
+        height,
+        device_scale_factor: 1.0,
+        mobile: false,
+        scale: None,
+        screen_width: None,
+        screen_height: None,
+        position_x: None,
+        position_y: None,
+        dont_set_visible_size: None,
+        screen_orientation: None,
+        viewport: None,
+        display_feature: None,
+        device_posture: None,
+    })?;
+    tab.evaluate("window.scrollTo(0, 0)", false)?;
+    let clip = Page::Viewport { x: 0.0, y: 0.0, width: width.into(), height: height.into(), scale: 1.0 };
+    let png = tab.capture_screenshot(Page::CaptureScreenshotFormatOption::Png, None, Some(clip), true)?;
+    tab.call_method(Emulation::ClearDeviceMetricsOverride(None))?;
+    Ok(png)
 }
 
-fn get_experience_field_value(
-    exp: &jobl::ExperienceItem,
-    field: &str,
-) -> Option<String> {
-    match field {
-        "title" => Some(exp.title.clone()),
-        "company" => Some(exp.company.clone()),
-        "location" => exp.location.clone(),
-        "start" => exp.start.clone(),
-        "end" => exp.end.clone(),
-        "summary" => exp.summary.clone(),
-        _ => None,
-    }
+/// Room left at the bottom of each page for the footer, in inches.
+/// Without a margin Chrome has nowhere to draw it.
+const FOOTER_MARGIN: f64 = 0.4;
+
+/// Room for the verification footer, QR code included.
+const VERIFY_MARGIN: f64 = 0.75;
+
+/// Where the page a PDF is printed from comes from.
+#[derive(Debug, Clone, Copy)]
+pub enum PdfSource<'a> {
+    /// A page on disk, loaded by its `file://` URL.
+    File(&'a Path),
+    /// Page HTML, set as the tab's content with CDP's
+    /// `Page.setDocumentContent` rather than written to a temp file.
+    /// With `base`, that page is loaded first so relative links
+    /// (fonts, `style.css`) resolve against its directory; without
+    /// it the HTML should be self-contained.
+    Html { html: &'a str, base: Option<&'a Path> },
 }
 
-fn render_projects_section(
-    html: &mut String,
-    doc: &JoblDocument,
-    section: &crate::layout::Section,
-) {
-    if doc.projects.is_empty() {
-        return;
-    }
-
-    html.push_str("    <section id=\"projects\" class=\"section section-projects\">\n");
-    html.push_str("      <h2>Projects</h2>\n");
-
-    for proj in &doc.projects {
-        html.push_str("      <div class=\"projects-item\">\n");
-
-        for field_or_container in &section.fields {
-            match field_or_container {
-                crate::layout::FieldOrContainer::Field(field) => {
-                    render_project_field(html, proj, field);
-                }
-                crate::layout::FieldOrContainer::Container(container) => {
-                    html.push_str(&format!("        <div class=\"{}\">\n", container.class_name));
-                    for field in &container.fields {
-                        render_project_field(html, proj, field);
-                    }
-                    html.push_str("        </div>\n");
-                }
-            }
-        }
-
-        html.push_str("      </div>\n");
-    }
-
-    html.push_str("    </section>\n");
+/// Print `source` to PDF with Chrome and return the PDF bytes.
+pub fn print_pdf(source: PdfSource, options: &BuildOptions) -> Result<Vec<u8>> {
+    let (_session, tab) = open_page(source)?;
+    print_tab(&tab, options)
 }
 
-fn render_project_field(
-    html: &mut String,
-    proj: &jobl::ProjectItem,
-    field: &crate::layout::Field,
-) {
-    if field.parts.is_empty() {
-        return;
-    }
+/// Launch Chrome and load `source` into a tab. The browser closes
+/// when the returned session is dropped.
+fn open_page(source: PdfSource) -> Result<(chrome::Session, Arc<Tab>)> {
+    let session = chrome::launch()
+        .context("Failed to launch Chrome browser")?;
 
-    if field.parts.len() == 1 {
-        if let FieldPart::Field(name) = &field.parts[0] {
-            match name.as_str() {
-                "name" => {
-                    html.push_str(&format!(
-                        "        <h3 class=\"projects-name\">{}</h3>\n",
-                        escape_html(&proj.name)
-                    ));
-                    return;
-                }
-                "url" => {
-                    if let Some(url) = &proj.url {
-                        html.push_str(&format!(
-                            "        <p class=\"projects-url\"><a href=\"{}\">{}</a></p>\n",
-                            escape_html(url),
-                            escape_html(url)
-                        ));
-                    }
-                    return;
-                }
-                "summary" => {
-                    if let Some(summary) = &proj.summary {
-                        html.push_str(&format!(
-                            "        <p class=\"projects-summary\">{}</p>\n",
-                            escape_html(summary)
-                        ));
-                    }
-                    return;
-                }
-                _ => {}
-            }
-        }
-    }
+    let tab = session.browser.new_tab()
+        .context("Failed to create new browser tab")?;
 
-    let class_str = if let Some(class_name) = &field.class_name {
-        format!(" class=\"{}\"", class_name)
-    } else {
-        String::new()
+    let (page, content) = match source {
+        PdfSource::File(path) => (Some(path), None),
+        PdfSource::Html { html, base } => (base, Some(html)),
     };
 
-    html.push_str(&format!("        <p{}>\n", class_str));
-    for part in &field.parts {
-        match part {
-            FieldPart::Literal(text) => {
-                html.push_str(&escape_html(text));
-            }
-            FieldPart::Field(name) => {
-                let value = get_project_field_value(proj, name);
-                if let Some(v) = value {
-                    html.push_str(&escape_html(&v));
-                }
-            }
-        }
+    if let Some(path) = page {
+        let path = path.canonicalize()
+            .context("Failed to resolve HTML path")?;
+        tab.navigate_to(&file_url(&path.to_string_lossy()))
+            .context("Failed to navigate to HTML file")?;
+        tab.wait_until_navigated()
+            .context("Failed to wait for page load")?;
     }
-    html.push_str("</p>\n");
-}
 
-fn get_project_field_value(
-    proj: &jobl::ProjectItem,
-    field: &str,
-) -> Option<String> {
-    match field {
-        "name" => Some(proj.name.clone()),
-        "url" => proj.url.clone(),
-        "summary" => proj.summary.clone(),
-        _ => None,
+    if let Some(html) = content {
+        load_content(&tab, html)?;
     }
+    Ok((session, tab))
 }
 
-fn render_education_section(
-    html: &mut String,
-    doc: &JoblDocument,
-    section: &crate::layout::Section,
-) {
-    if doc.education.is_empty() {
-        return;
-    }
-
-    html.push_str("    <section id=\"education\" class=\"section section-education\">\n");
-    html.push_str("      <h2>Education</h2>\n");
-
-    for edu in &doc.education {
-        html.push_str("      <div class=\"education-item\">\n");
-
-        for field_or_container in &section.fields {
-            match field_or_container {
-                crate::layout::FieldOrContainer::Field(field) => {
-                    render_education_field(html, edu, field);
-                }
-                crate::layout::FieldOrContainer::Container(container) => {
-                    html.push_str(&format!("        <div class=\"{}\">\n", container.class_name));
-                    for field in &container.fields {
-                        render_education_field(html, edu, field);
-                    }
-                    html.push_str("        </div>\n");
-                }
-            }
-        }
-
-        html.push_str("      </div>\n");
-    }
-
-    html.push_str("    </section>\n");
+/// Replace the tab's page with `html`, keeping its URL (and so the
+/// base relative links resolve against).
+fn load_content(tab: &Tab, html: &str) -> Result<()> {
+    let frame = tab.call_method(Page::GetFrameTree(None))
+        .context("Failed to find the page's frame")?
+        .frame_tree
+        .frame
+        .id;
+    tab.call_method(Page::SetDocumentContent { frame_id: frame, html: html.to_string() })
+        .context("Failed to load HTML into the page")?;
+    // Fonts start loading once the content is in; print after.
+    tab.evaluate("document.fonts.ready.then(() => true)", true)
+        .context("Failed to wait for fonts")?;
+    Ok(())
 }
 
-fn render_education_field(
-    html: &mut String,
-    edu: &jobl::EducationItem,
-    field: &crate::layout::Field,
-) {
-    if field.parts.is_empty() {
-        return;
-    }
+/// Print the tab's page to PDF bytes.
+fn print_tab(tab: &Tab, options: &BuildOptions) -> Result<Vec<u8>> {
+    let footer = options.footer.as_deref();
+    let (paper_width, paper_height) = options.paper.size();
+    let margins = options.margins;
 
-    if field.parts.len() == 1 {
-        if let FieldPart::Field(name) = &field.parts[0] {
-            match name.as_str() {
-                "degree" => {
-                    html.push_str(&format!(
-                        "        <h3 class=\"education-degree\">{}</h3>\n",
-                        escape_html(&edu.degree)
-                    ));
-                    return;
-                }
-                "institution" => {
-                    html.push_str(&format!(
-                        "        <p class=\"education-institution\">{}</p>\n",
-                        escape_html(&edu.institution)
-                    ));
-                    return;
-                }
-                "details" => {
-                    if !edu.details.is_empty() {
-                        html.push_str("        <ul class=\"education-details\">\n");
-                        for detail in &edu.details {
-                            html.push_str(&format!(
-                                "          <li>{}</li>\n",
-                                escape_html_with_breaks(detail)
-                            ));
-                        }
-                        html.push_str("        </ul>\n");
-                    }
-                    return;
-                }
-                _ => {}
-            }
-        }
-    }
-
-    let class_str = if let Some(class_name) = &field.class_name {
-        format!(" class=\"{}\"", class_name)
-    } else {
-        String::new()
+    let template = match &options.verify {
+        Some(verify) => Some(verify_footer_template(footer, verify)),
+        None => footer.map(footer_template),
     };
-
-    html.push_str(&format!("        <p{}>\n", class_str));
-    for part in &field.parts {
-        match part {
-            FieldPart::Literal(text) => {
-                html.push_str(&escape_html(text));
-            }
-            FieldPart::Field(name) => {
-                let value = get_education_field_value(edu, name);
-                if let Some(v) = value {
-                    html.push_str(&escape_html(&v));
-                }
-            }
+    let margin_bottom = match (&options.verify, footer) {
+        (Some(_), _) => margins.bottom.max(VERIFY_MARGIN),
+        (None, Some(_)) => margins.bottom.max(FOOTER_MARGIN),
+        (None, None) => margins.bottom,
+    };
+    if options.continued || options.max_pages.is_some() {
+        // The first pass: lay the page out as it will print, one
+        // page's content box wide, let the script place the
+        // continuation headers, and measure the result.
+        let to_px = |inches: f64| inches * 96.0 / options.scale;
+        let width = to_px(paper_width - margins.left - margins.right);
+        let height = to_px(paper_height - margins.top - margin_bottom);
+        tab.call_method(Emulation::SetEmulatedMedia { media: Some("print".to_string()), features: None })
+            .context("Failed to emulate print media")?;
+        if options.continued {
+            tab.evaluate(&format!("({})({}, {})", CONTINUED_JS, width, height), false)
+                .context("Failed to add continuation headers")?;
         }
+        if let Some(max_pages) = options.max_pages {
+            check_fit(&measure::measure(tab, width, height)?, max_pages, options)?;
+        }
+        tab.call_method(Emulation::SetEmulatedMedia { media: Some(String::new()), features: None })
+            .context("Failed to reset emulated media")?;
     }
-    html.push_str("</p>\n");
-}
-
-fn get_education_field_value(
-    edu: &jobl::EducationItem,
-    field: &str,
-) -> Option<String> {
-    match field {
-        "degree" => Some(edu.degree.clone()),
-        "institution" => Some(edu.institution.clone()),
-        "location" => edu.location.clone(),
-        "start" => edu.start.clone(),
-        "end" => edu.end.clone(),
-        _ => None,
-    }
-}
-
-/// Generate PDF from HTML file using headless Chrome
-fn generate_pdf(html_path: &Path, pdf_path: &Path) -> Result<()> {
-    let browser = Browser::default()
-        .context("Failed to launch Chrome browser")?;
-
-    let tab = browser.new_tab()
-        .context("Failed to create new browser tab")?;
-
-    // Convert path to file:// URL
-    let html_url = format!(
-        "file://{}",
-        html_path.canonicalize()
-            .context("Failed to resolve HTML path")?
-            .display()
-    );
-
-    tab.navigate_to(&html_url)
-        .context("Failed to navigate to HTML file")?;
-
-    tab.wait_until_navigated()
-        .context("Failed to wait for page load")?;
 
     let pdf_data = tab.print_to_pdf(Some(PrintToPdfOptions {
         landscape: Some(false),
-        display_header_footer: Some(false),
-        print_background: Some(true),
-        scale: Some(1.0),
-        paper_width: Some(8.5),
-        paper_height: Some(11.0),
-        margin_top: Some(0.0),
-        margin_bottom: Some(0.0),
-        margin_left: Some(0.0),
-        margin_right: Some(0.0),
+        display_header_footer: Some(template.is_some()),
+        print_background: Some(options.print_background),
+        scale: Some(options.scale),
+        paper_width: Some(paper_width),
+        paper_height: Some(paper_height),
+        margin_top: Some(margins.top),
+        margin_bottom: Some(margin_bottom),
+        margin_left: Some(margins.left),
+        margin_right: Some(margins.right),
         page_ranges: None,
         ignore_invalid_page_ranges: None,
-        header_template: None,
-        footer_template: None,
+        // An empty header; Chrome's default prints the date and title.
+        header_template: template.as_ref().map(|_| "<span></span>".to_string()),
+        footer_template: template,
         prefer_css_page_size: Some(false),
         transfer_mode: None,
-        generate_document_outline: None,
-        generate_tagged_pdf: None,
+        generate_document_outline: options.pdf_outline.then_some(true),
+        generate_tagged_pdf: (options.tagged_pdf || options.pdf_outline).then_some(true),
     })).context("Failed to generate PDF")?;
 
-    fs::write(pdf_path, pdf_data)
-        .context("Failed to write PDF file")?;
+    match options.pdf_profile {
+        PdfProfile::Standard => Ok(pdf_data),
+        PdfProfile::Pdfa => pdfa::convert(&pdf_data),
+    }
+}
 
-    Ok(())
+/// The `file://` URL for an absolute path as `canonicalize` returns
+/// it. Windows paths lose their `\\?\` verbatim prefix (UNC paths
+/// become `file://server/share/...`) and use forward slashes, and
+/// anything outside the URL-safe set, such as spaces and `#`, is
+/// percent-encoded.
+pub fn file_url(path: &str) -> String {
+    let unc = path.strip_prefix(r"\\?\UNC\").or_else(|| {
+        path.strip_prefix(r"\\").filter(|rest| !rest.starts_with(r"?\"))
+    });
+    let (host, path) = match unc {
+        Some(unc) => match unc.split_once('\\') {
+            Some((host, rest)) => (host, format!("/{}", rest)),
+            None => (unc, String::new()),
+        },
+        None => ("", path.trim_start_matches(r"\\?\").to_string()),
+    };
+
+    let mut path = path.replace('\\', "/");
+    if path.as_bytes().get(1) == Some(&b':') {
+        path.insert(0, '/');
+    }
+
+    let mut url = format!("file://{}", host);
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
 }
 
 /// Escape HTML special characters
-fn escape_html(s: &str) -> String {
+pub(crate) fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
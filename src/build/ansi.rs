@@ -0,0 +1,110 @@
+//! Terminal rendering (`srg view`).
+//!
+//! The plain-text export with some styling for reading in a
+//! terminal: section titles bold and underlined, entry headings bold,
+//! highlights as bullets, links underlined. Wrapping works on the
+//! plain text, so the escape codes never count towards the width.
+//! Without color the codes are left out and what's left is plain
+//! text.
+
+use super::outline::{one_line, Block, Outline};
+use super::txt::push_wrapped;
+
+const BOLD: &str = "\x1b[1m";
+const UNDERLINE: &str = "\x1b[4m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Render the outline for a terminal `width` columns wide, styled
+/// with ANSI escape codes when `color` is set.
+pub fn render(outline: &Outline, width: usize, color: bool) -> String {
+    let style = |codes: &str, text: &str| if color { format!("{}{}{}", codes, text, RESET) } else { text.to_string() };
+    let mut out = String::new();
+
+    for section in &outline.sections {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if let Some(title) = &section.title {
+            out.push_str(&style(&format!("{}{}", BOLD, UNDERLINE), &one_line(title).to_uppercase()));
+            out.push('\n');
+        }
+        for (i, item) in section.items.iter().enumerate() {
+            if i > 0 && item.heading.is_some() {
+                out.push('\n');
+            }
+            if let Some(heading) = &item.heading {
+                out.push_str(&style(BOLD, &one_line(heading)));
+                out.push('\n');
+            }
+            push_blocks(&mut out, &item.blocks, width, &style);
+        }
+    }
+
+    out
+}
+
+fn push_blocks(out: &mut String, blocks: &[Block], width: usize, style: &dyn Fn(&str, &str) -> String) {
+    for block in blocks {
+        match block {
+            Block::Text(text) => push_wrapped(out, &one_line(text), width, "", ""),
+            Block::Link { href, text } if href == text => {
+                out.push_str(&style(UNDERLINE, href));
+                out.push('\n');
+            }
+            Block::Link { href, text } => {
+                out.push_str(&format!("{} {}\n", style(UNDERLINE, &one_line(text)), style(DIM, href)));
+            }
+            Block::List(entries) => {
+                for entry in entries {
+                    let mut lines = entry.lines();
+                    let mut wrapped = String::new();
+                    push_wrapped(&mut wrapped, lines.next().unwrap_or_default().trim(), width, "  • ", "    ");
+                    for line in lines {
+                        push_wrapped(&mut wrapped, line.trim(), width, "    ", "    ");
+                    }
+                    out.push_str(&wrapped.replacen('•', &style(BOLD, "•"), 1));
+                }
+            }
+            Block::Term { term, text } => {
+                let term = format!("{}:", one_line(term));
+                let mut wrapped = String::new();
+                push_wrapped(&mut wrapped, &format!("{} {}", term, one_line(text)), width, "", "  ");
+                out.push_str(&wrapped.replacen(&term, &style(BOLD, &term), 1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::outline::{OutlineItem, OutlineSection};
+
+    #[test]
+    fn styles_headings_and_bullets() {
+        let outline = Outline {
+            sections: vec![OutlineSection {
+                name: "experience".to_string(),
+                title: Some("Experience".to_string()),
+                items: vec![OutlineItem {
+                    heading: Some("Engineer".to_string()),
+                    blocks: vec![
+                        Block::List(vec!["Cut build times by half across every service".to_string()]),
+                        Block::Term { term: "Stack".to_string(), text: "Rust".to_string() },
+                    ],
+                    ..Default::default()
+                }],
+            }],
+        };
+
+        assert_eq!(
+            render(&outline, 30, true),
+            "\x1b[1m\x1b[4mEXPERIENCE\x1b[0m\n\x1b[1mEngineer\x1b[0m\n  \x1b[1m•\x1b[0m Cut build times by half\n    across every service\n\x1b[1mStack:\x1b[0m Rust\n"
+        );
+        assert_eq!(
+            render(&outline, 0, false),
+            "EXPERIENCE\nEngineer\n  • Cut build times by half across every service\nStack: Rust\n"
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! ASCII transliteration for the plain-text export (`--ascii`).
+//!
+//! Some applicant tracking systems mangle anything outside ASCII, so
+//! the text export can be folded to it: accents dropped (José →
+//! Jose), other scripts romanized, and typographic punctuation
+//! (curly quotes, dashes, non-breaking spaces) turned into its
+//! plain equivalent. Each changed word is reported so nothing is
+//! altered silently.
+
+/// Shown for characters that have no ASCII equivalent at all.
+const PLACEHOLDER: &str = "?";
+
+/// `text` folded to ASCII.
+pub fn transliterate(text: &str) -> String {
+    deunicode::deunicode_with_tofu(text, PLACEHOLDER)
+}
+
+/// Every distinct word of `text` that transliteration changes, with
+/// its replacement, in order of first appearance.
+pub fn changes(text: &str) -> Vec<(String, String)> {
+    let mut changes: Vec<(String, String)> = Vec::new();
+    for word in text.split(|c: char| c.is_ascii_whitespace()) {
+        if word.is_ascii() || changes.iter().any(|(from, _)| from == word) {
+            continue;
+        }
+        changes.push((word.to_string(), transliterate(word)));
+    }
+    changes
+}
@@ -0,0 +1,255 @@
+//! The "book a chat" block (`[booking]` in srg.toml), for resumes
+//! that double as a consultant's landing page.
+//!
+//! A `booking` line in the layout's person section shows a link to
+//! the person's calendaring page. When `[booking]` also gives office
+//! hours, the block lists them and links to `availability.ics`, a
+//! weekly recurring event srg writes next to `index.html` so a
+//! reader can drop the hours into their own calendar.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::write_atomic;
+
+/// File name of the calendar in the output directory.
+pub const ICS_FILE: &str = "availability.ics";
+
+/// Link text when `label` isn't set.
+const DEFAULT_LABEL: &str = "Book a chat";
+
+/// `[booking]` in srg.toml.
+///
+/// ```toml
+/// [booking]
+/// url = "https://cal.com/ada/intro"
+/// label = "Book a 30-minute intro"
+/// days = ["tue", "thu"]
+/// hours = "14:00-16:00"
+/// timezone = "Europe/London"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Booking {
+    /// The calendaring page (Calendly, Cal.com, ...).
+    pub url: String,
+    /// Link text. Defaults to "Book a chat".
+    pub label: Option<String>,
+    /// Days the hours apply to. Defaults to Monday to Friday.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Office hours as `HH:MM-HH:MM`. Without them there's no
+    /// calendar file.
+    pub hours: Option<String>,
+    /// IANA time zone the hours are in, e.g. "Europe/London". Unset
+    /// means the reader's own local time.
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    const WORKWEEK: [Weekday; 5] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+
+    fn label(self) -> &'static str {
+        match self {
+            Weekday::Mon => "Mon",
+            Weekday::Tue => "Tue",
+            Weekday::Wed => "Wed",
+            Weekday::Thu => "Thu",
+            Weekday::Fri => "Fri",
+            Weekday::Sat => "Sat",
+            Weekday::Sun => "Sun",
+        }
+    }
+
+    /// The two-letter code RFC 5545 uses in `BYDAY`.
+    fn code(self) -> &'static str {
+        match self {
+            Weekday::Mon => "MO",
+            Weekday::Tue => "TU",
+            Weekday::Wed => "WE",
+            Weekday::Thu => "TH",
+            Weekday::Fri => "FR",
+            Weekday::Sat => "SA",
+            Weekday::Sun => "SU",
+        }
+    }
+}
+
+/// A time of day, in minutes since midnight.
+type Minutes = u32;
+
+impl Booking {
+    /// Check the URL, hours, and time zone, so a typo fails the build
+    /// instead of producing a calendar nobody can import.
+    pub fn check(&self) -> Result<()> {
+        if !(self.url.starts_with("https://") || self.url.starts_with("http://")) {
+            anyhow::bail!("booking.url must be an http(s) URL, got {:?}", self.url);
+        }
+        if self.label.as_deref().is_some_and(|label| label.trim().is_empty()) {
+            anyhow::bail!("booking.label is empty");
+        }
+        self.office_hours()?;
+        if let Some(zone) = &self.timezone {
+            let valid = !zone.is_empty()
+                && zone.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+            if !valid {
+                anyhow::bail!("booking.timezone {:?} isn't an IANA zone name like \"Europe/London\"", zone);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(DEFAULT_LABEL)
+    }
+
+    /// The days the hours apply to, in week order.
+    pub fn days(&self) -> Vec<Weekday> {
+        let mut days = if self.days.is_empty() { Weekday::WORKWEEK.to_vec() } else { self.days.clone() };
+        days.sort();
+        days.dedup();
+        days
+    }
+
+    /// Start and end of the office hours, or `None` without `hours`.
+    fn office_hours(&self) -> Result<Option<(Minutes, Minutes)>> {
+        let Some(hours) = &self.hours else {
+            return Ok(None);
+        };
+        let bad = || anyhow::anyhow!("booking.hours must look like \"14:00-16:00\", got {:?}", hours);
+        let (start, end) = hours.split_once('-').ok_or_else(bad)?;
+        let (start, end) = (parse_time(start).ok_or_else(bad)?, parse_time(end).ok_or_else(bad)?);
+        if start >= end {
+            anyhow::bail!("booking.hours end before they start: {:?}", hours);
+        }
+        Ok(Some((start, end)))
+    }
+
+    /// The hours as the page shows them, e.g. "Mon–Fri 09:00–11:00
+    /// (Europe/London)". `None` without `hours`.
+    pub fn hours_text(&self) -> Option<String> {
+        let (start, end) = self.office_hours().ok()??;
+        let mut text = format!("{} {}–{}", days_text(&self.days()), clock(start, ":"), clock(end, ":"));
+        if let Some(zone) = &self.timezone {
+            text.push_str(&format!(" ({})", zone));
+        }
+        Some(text)
+    }
+
+    /// The office hours as an iCalendar file: one weekly event on
+    /// each of `days`, linking to the booking page. `None` without
+    /// `hours`.
+    pub fn ics(&self, name: &str) -> Option<String> {
+        let (start, end) = self.office_hours().ok()??;
+        let days = self.days();
+        // Recurrences start in the week of Monday 2024-01-01, on the
+        // first of `days`. A fixed date keeps rebuilds identical;
+        // calendars show the event from the current week either way.
+        let date = format!("202401{:02}", days[0] as u32 + 1);
+        let zone = match &self.timezone {
+            Some(zone) => format!(";TZID={}", zone),
+            None => String::new(),
+        };
+        let by_day: Vec<&str> = days.iter().map(|day| day.code()).collect();
+        let lines = [
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//srg//availability//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{:x}@srg", md5::compute(&self.url)),
+            "DTSTAMP:20240101T000000Z".to_string(),
+            format!("DTSTART{}:{}T{}00", zone, date, clock(start, "")),
+            format!("DTEND{}:{}T{}00", zone, date, clock(end, "")),
+            format!("RRULE:FREQ=WEEKLY;BYDAY={}", by_day.join(",")),
+            format!("SUMMARY:{}", escape_text(&format!("Office hours: {}", name))),
+            format!("DESCRIPTION:{}", escape_text(&format!("{}: {}", self.label(), self.url))),
+            format!("URL:{}", self.url),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+            "END:VCALENDAR".to_string(),
+        ];
+        Some(lines.iter().map(|line| fold(line) + "\r\n").collect())
+    }
+}
+
+/// Write `availability.ics` to `out_dir` when `booking` has hours.
+pub fn write_ics(out_dir: &Path, booking: &Booking, name: &str) -> Result<Option<PathBuf>> {
+    let Some(ics) = booking.ics(name) else {
+        return Ok(None);
+    };
+    let path = out_dir.join(ICS_FILE);
+    write_atomic(&path, ics).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+fn parse_time(text: &str) -> Option<Minutes> {
+    let (hours, minutes) = text.trim().split_once(':')?;
+    if hours.is_empty() || hours.len() > 2 || minutes.len() != 2 {
+        return None;
+    }
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+fn clock(minutes: Minutes, separator: &str) -> String {
+    format!("{:02}{}{:02}", minutes / 60, separator, minutes % 60)
+}
+
+/// Days as a reader would write them: runs of three or more become
+/// a range ("Mon–Fri"), the rest are listed ("Tue, Thu").
+fn days_text(days: &[Weekday]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < days.len() {
+        let mut j = i;
+        while j + 1 < days.len() && days[j + 1] as u32 == days[j] as u32 + 1 {
+            j += 1;
+        }
+        if j - i >= 2 {
+            parts.push(format!("{}–{}", days[i].label(), days[j].label()));
+        } else {
+            parts.extend(days[i..=j].iter().map(|day| day.label().to_string()));
+        }
+        i = j + 1;
+    }
+    parts.join(", ")
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets (RFC 5545 §3.1), without
+/// splitting a UTF-8 character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
@@ -0,0 +1,113 @@
+//! Chrome profiles for PDF builds.
+//!
+//! Left to itself, headless_chrome starts every browser with a fresh
+//! profile in a new temp directory, so each build pays Chrome's
+//! first-run setup again. srg keeps its own profiles instead, under
+//! `<temp>/srg-chrome-<install>/`, one directory per slot. A build
+//! takes the first slot whose lock file it can lock and holds the lock
+//! until its browser has closed, so concurrent builds never share a
+//! profile (Chrome refuses a profile another Chrome has open). The OS
+//! releases the lock when a build exits, crashed or not, so there are
+//! no stale locks to clean up. When every slot is busy the build falls
+//! back to a throwaway profile.
+//!
+//! `srg doctor --clean` removes the whole directory.
+
+use anyhow::{Context, Result};
+use headless_chrome::{Browser, LaunchOptions};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+/// How many builds can have a cached profile at once.
+const SLOTS: usize = 4;
+
+/// A running browser and the profile slot it holds.
+pub struct Session {
+    // Fields drop in order: the browser closes before the slot's lock
+    // is released.
+    pub browser: Browser,
+    _slot: Option<File>,
+}
+
+/// The profiles directory for this srg install. Installs are told
+/// apart by the executable's path, so two versions side by side
+/// don't share a profile.
+pub fn profiles_dir() -> PathBuf {
+    let exe = std::env::current_exe().map(|p| p.display().to_string()).unwrap_or_default();
+    let install = format!("{:x}", md5::compute(exe));
+    std::env::temp_dir().join(format!("srg-chrome-{}", &install[..8]))
+}
+
+/// Launch headless Chrome on a cached profile when a slot is free.
+pub fn launch() -> Result<Session> {
+    let slot = claim_slot(&profiles_dir());
+    let path = headless_chrome::browser::default_executable().map_err(anyhow::Error::msg)?;
+    let options = LaunchOptions {
+        path: Some(path),
+        user_data_dir: slot.as_ref().map(|(dir, _)| dir.clone()),
+        ..Default::default()
+    };
+    let browser = Browser::new(options)?;
+    Ok(Session { browser, _slot: slot.map(|(_, lock)| lock) })
+}
+
+/// The first free slot's profile directory under `root` and its held
+/// lock, or `None` when every slot is busy or the directory can't be
+/// written.
+fn claim_slot(root: &Path) -> Option<(PathBuf, File)> {
+    std::fs::create_dir_all(root).ok()?;
+    (0..SLOTS).find_map(|slot| {
+        let path = root.join(format!("{}.lock", slot));
+        let lock = OpenOptions::new().create(true).write(true).truncate(false).open(path).ok()?;
+        lock.try_lock().ok()?;
+        Some((root.join(slot.to_string()), lock))
+    })
+}
+
+/// Remove the cached profiles. Fails without removing anything while
+/// a build is using one. Returns the directory when there was one.
+pub fn clean() -> Result<Option<PathBuf>> {
+    clean_in(profiles_dir())
+}
+
+fn clean_in(root: PathBuf) -> Result<Option<PathBuf>> {
+    if !root.exists() {
+        return Ok(None);
+    }
+    for slot in 0..SLOTS {
+        let path = root.join(format!("{}.lock", slot));
+        let Ok(lock) = OpenOptions::new().write(true).open(&path) else { continue };
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                anyhow::bail!("A build is using the Chrome profile in {}; try again when it's done", root.display())
+            }
+            Err(TryLockError::Error(e)) => return Err(e).with_context(|| format!("locking {}", path.display())),
+        }
+    }
+    std::fs::remove_dir_all(&root).with_context(|| format!("removing {}", root.display()))?;
+    Ok(Some(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_not_shared() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("profiles");
+        let first = claim_slot(&root).unwrap();
+        let second = claim_slot(&root).unwrap();
+        assert_eq!((first.0.file_name().unwrap(), second.0.file_name().unwrap()), ("0".as_ref(), "1".as_ref()));
+        drop(first);
+        // The freed slot is the first one taken again.
+        assert_eq!(claim_slot(&root).unwrap().0, root.join("0"));
+
+        // Nothing is removed while a build holds a slot.
+        assert!(clean_in(root.clone()).is_err());
+        drop(second);
+        assert_eq!(clean_in(root.clone()).unwrap(), Some(root.clone()));
+        assert!(!root.exists());
+    }
+}
@@ -0,0 +1,142 @@
+//! Email-friendly HTML export (`--format email-html`).
+//!
+//! Mail clients strip `<style>` blocks, ignore classes, and mangle
+//! anything clever, so this page is deliberately plain: no tables,
+//! no stylesheet, no web fonts, every style inline on the element
+//! it applies to. The result pastes into a compose window and keeps
+//! its formatting.
+
+use super::outline::{Block, Outline, OutlineItem};
+use super::{escape_html, escape_html_with_breaks};
+
+const WRAPPER: &str = "font-family: Arial, Helvetica, sans-serif; font-size: 14px; \
+                       line-height: 1.5; color: #222222; max-width: 640px;";
+const NAME: &str = "font-size: 24px; font-weight: bold; margin: 0 0 4px 0;";
+const HEADLINE: &str = "font-size: 16px; color: #555555; margin: 0 0 4px 0;";
+const CONTACT: &str = "font-size: 13px; color: #555555; margin: 0 0 16px 0;";
+const SECTION: &str = "font-size: 15px; font-weight: bold; text-transform: uppercase; \
+                       letter-spacing: 1px; margin: 20px 0 8px 0; padding: 0 0 4px 0; \
+                       border-bottom: 1px solid #cccccc;";
+const HEADING: &str = "font-size: 15px; font-weight: bold; margin: 12px 0 2px 0;";
+const TEXT: &str = "margin: 0 0 4px 0;";
+const LIST: &str = "margin: 4px 0 8px 0; padding: 0 0 0 20px;";
+const LIST_ITEM: &str = "margin: 0 0 2px 0;";
+const LINK: &str = "color: #1a5fb4; text-decoration: underline;";
+
+/// Render the outline as a standalone, inline-styled HTML page.
+pub fn render(outline: &Outline) -> String {
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str("<html lang=\"en\">\n");
+    html.push_str("<head>\n");
+    html.push_str("  <meta charset=\"UTF-8\">\n");
+    if let Some(name) = outline.name() {
+        html.push_str(&format!("  <title>{}</title>\n", escape_html(name)));
+    }
+    html.push_str("</head>\n");
+    html.push_str("<body style=\"margin: 0; padding: 16px;\">\n");
+    html.push_str(&format!("  <div style=\"{}\">\n", WRAPPER));
+
+    for section in &outline.sections {
+        match &section.title {
+            None => push_header(&mut html, section.items.first()),
+            Some(title) => {
+                html.push_str(&format!(
+                    "    <h2 style=\"{}\">{}</h2>\n",
+                    SECTION,
+                    escape_html(title)
+                ));
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        html.push_str(&format!(
+                            "    <h3 style=\"{}\">{}</h3>\n",
+                            HEADING,
+                            escape_html(heading)
+                        ));
+                    }
+                    for block in &item.blocks {
+                        push_block(&mut html, block);
+                    }
+                }
+            }
+        }
+    }
+
+    html.push_str("  </div>\n");
+    html.push_str("</body>\n");
+    html.push_str("</html>\n");
+    html
+}
+
+/// The person header: name, the first line of text as a headline,
+/// and everything else folded into one compact contact line.
+fn push_header(html: &mut String, item: Option<&OutlineItem>) {
+    let Some(item) = item else {
+        return;
+    };
+    if let Some(name) = &item.heading {
+        html.push_str(&format!(
+            "    <h1 style=\"{}\">{}</h1>\n",
+            NAME,
+            escape_html(name)
+        ));
+    }
+
+    let mut blocks = item.blocks.iter().peekable();
+    if let Some(Block::Text(headline)) = blocks.peek() {
+        html.push_str(&format!(
+            "    <p style=\"{}\">{}</p>\n",
+            HEADLINE,
+            escape_html(headline)
+        ));
+        blocks.next();
+    }
+
+    let contact: Vec<String> = blocks.map(inline_block).collect();
+    if !contact.is_empty() {
+        html.push_str(&format!(
+            "    <p style=\"{}\">{}</p>\n",
+            CONTACT,
+            contact.join(" &middot; ")
+        ));
+    }
+}
+
+fn push_block(html: &mut String, block: &Block) {
+    match block {
+        Block::List(entries) => {
+            html.push_str(&format!("    <ul style=\"{}\">\n", LIST));
+            for entry in entries {
+                html.push_str(&format!(
+                    "      <li style=\"{}\">{}</li>\n",
+                    LIST_ITEM,
+                    escape_html_with_breaks(entry)
+                ));
+            }
+            html.push_str("    </ul>\n");
+        }
+        _ => html.push_str(&format!(
+            "    <p style=\"{}\">{}</p>\n",
+            TEXT,
+            inline_block(block)
+        )),
+    }
+}
+
+/// Escaped inline markup for a block that fits on one line.
+fn inline_block(block: &Block) -> String {
+    match block {
+        Block::Text(text) => escape_html(text),
+        Block::Link { href, text } => format!(
+            "<a href=\"{}\" style=\"{}\">{}</a>",
+            escape_html(href),
+            LINK,
+            escape_html(text)
+        ),
+        Block::List(entries) => escape_html(&entries.join("; ")),
+        Block::Term { term, text } => {
+            format!("<strong>{}:</strong> {}", escape_html(term), escape_html(text))
+        }
+    }
+}
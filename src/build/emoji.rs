@@ -0,0 +1,126 @@
+//! Emoji policy per output format.
+//!
+//! Emoji read fine in a browser but trip up applicant tracking
+//! systems and show up as boxes in PDF fonts without them. Each
+//! output can keep them, strip them, or swap the handful that carry
+//! meaning on a resume (contact icons, checkmarks) for plain words.
+
+use jobl::JoblDocument;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/// What to do with emoji in one output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EmojiPolicy {
+    /// Leave text as written.
+    Keep,
+    /// Remove emoji (and the space they leave behind).
+    Strip,
+    /// Replace common resume emoji with words, strip the rest.
+    Replace,
+}
+
+/// Plain-text stand-ins for `replace`.
+const REPLACEMENTS: &[(char, &str)] = &[
+    ('📧', "Email:"),
+    ('✉', "Email:"),
+    ('📞', "Phone:"),
+    ('☎', "Phone:"),
+    ('📱', "Phone:"),
+    ('📍', "Location:"),
+    ('🔗', "Link:"),
+    ('🌐', "Web:"),
+    ('✅', "-"),
+    ('✔', "-"),
+    ('☑', "-"),
+    ('⭐', "*"),
+    ('🌟', "*"),
+];
+
+/// Whether `c` is an emoji or part of an emoji sequence (joiners,
+/// variation selectors, skin tones, keycaps).
+pub fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF   // pictographs, emoticons, transport, flags, ...
+            | 0x2600..=0x27BF // miscellaneous symbols and dingbats
+            | 0x2B50..=0x2B55 // stars and circles
+            | 0x231A..=0x231B
+            | 0x23E9..=0x23FA
+            | 0x200D          // zero-width joiner
+            | 0xFE0F          // emoji presentation selector
+            | 0x20E3          // combining keycap
+    )
+}
+
+/// Apply `policy` to one piece of text.
+pub fn apply_text(text: &str, policy: EmojiPolicy) -> String {
+    if policy == EmojiPolicy::Keep || !text.chars().any(is_emoji) {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !is_emoji(c) {
+            out.push(c);
+            continue;
+        }
+        let replacement = match policy {
+            EmojiPolicy::Replace => REPLACEMENTS.iter().find(|(e, _)| *e == c).map(|(_, r)| *r),
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+            out.push_str(replacement);
+        }
+    }
+
+    // Emoji usually sit next to a space; don't leave doubled or
+    // leading ones behind.
+    out.split(' ').filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// `doc` with `policy` applied to every text field. Borrows when
+/// there's nothing to change.
+pub fn apply(doc: &JoblDocument, policy: EmojiPolicy) -> Cow<'_, JoblDocument> {
+    if policy == EmojiPolicy::Keep {
+        return Cow::Borrowed(doc);
+    }
+
+    let text = |s: &String| apply_text(s, policy);
+    let opt = |s: &Option<String>| s.as_ref().map(text);
+    let list = |v: &Vec<String>| v.iter().map(text).collect::<Vec<_>>();
+
+    let mut doc = doc.clone();
+    let person = &mut doc.person;
+    person.name = text(&person.name);
+    person.headline = opt(&person.headline);
+    person.location = opt(&person.location);
+    person.summary = opt(&person.summary);
+
+    if let Some(skills) = &doc.skills {
+        doc.skills = Some(skills.iter().map(|(k, v)| (text(k), list(v))).collect());
+    }
+    for exp in &mut doc.experience {
+        exp.title = text(&exp.title);
+        exp.company = text(&exp.company);
+        exp.location = opt(&exp.location);
+        exp.summary = opt(&exp.summary);
+        exp.highlights = list(&exp.highlights);
+        exp.technologies = list(&exp.technologies);
+    }
+    for proj in &mut doc.projects {
+        proj.name = text(&proj.name);
+        proj.summary = opt(&proj.summary);
+    }
+    for edu in &mut doc.education {
+        edu.degree = text(&edu.degree);
+        edu.institution = text(&edu.institution);
+        edu.location = opt(&edu.location);
+        edu.details = list(&edu.details);
+    }
+    Cow::Owned(doc)
+}
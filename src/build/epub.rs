@@ -0,0 +1,205 @@
+//! EPUB export (`--format epub`).
+//!
+//! For reading a long CV on an e-reader. The book is the themed page
+//! itself: the same sections, classes, and stylesheet, with the
+//! theme's fonts, as one XHTML chapter. Its table of contents lists
+//! the layout's titled sections. Nothing in it runs scripts, so
+//! collapsed entries are shown in full.
+//!
+//! The file is a zip written with [`crate::archive::ZipWriter`]:
+//! `mimetype` (stored, first, as the spec requires), the container,
+//! the package document, the navigation document, the chapter, the
+//! stylesheet, and the fonts.
+
+use anyhow::Result;
+
+use super::escape_html;
+use crate::archive::ZipWriter;
+
+const MIMETYPE: &str = "application/epub+zip";
+
+const CONTAINER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">
+ <rootfiles>
+  <rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>
+ </rootfiles>
+</container>
+";
+
+/// What goes into the book.
+pub struct Book<'a> {
+    /// The person's name: the book's title and author.
+    pub title: &'a str,
+    /// Language code, e.g. "en".
+    pub lang: &'a str,
+    /// `dc:identifier`, stable across builds of the same resume.
+    pub identifier: String,
+    /// `dcterms:modified`, e.g. "2026-10-16T00:00:00Z".
+    pub modified: String,
+    /// The themed page's sections, as they go inside `<main>`.
+    pub body: &'a str,
+    pub css: &'a str,
+    /// Section ids and titles for the table of contents.
+    pub contents: Vec<(String, String)>,
+    /// The theme's fonts, relative to the stylesheet.
+    pub fonts: Vec<(&'static str, &'static [u8])>,
+}
+
+/// Render the book as an EPUB 3 file.
+pub fn render(book: &Book) -> Result<Vec<u8>> {
+    let fonts: Vec<_> = book.fonts.iter().filter(|(rel, _)| font_type(rel).is_some()).collect();
+    let mut zip = ZipWriter::new(Vec::new());
+    zip.add_stored("mimetype", MIMETYPE.as_bytes())?;
+    zip.add_file("META-INF/container.xml", CONTAINER.as_bytes())?;
+    zip.add_file("OEBPS/content.opf", package(book).as_bytes())?;
+    zip.add_file("OEBPS/nav.xhtml", nav(book).as_bytes())?;
+    zip.add_file("OEBPS/resume.xhtml", chapter(book).as_bytes())?;
+    zip.add_file("OEBPS/style.css", book.css.as_bytes())?;
+    for (rel, bytes) in fonts {
+        zip.add_file(&format!("OEBPS/fonts/{}", rel), bytes)?;
+    }
+    zip.finish()
+}
+
+/// Media type of a font file, or `None` for files the book leaves
+/// out (licenses, readmes).
+fn font_type(rel: &str) -> Option<&'static str> {
+    Some(match rel.rsplit_once('.')?.1.to_ascii_lowercase().as_str() {
+        "woff2" => "font/woff2",
+        "woff" => "font/woff",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        _ => return None,
+    })
+}
+
+fn package(book: &Book) -> String {
+    let mut manifest = String::from(
+        "  <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+    );
+    let remote = if book.body.contains("src=\"http") { " properties=\"remote-resources\"" } else { "" };
+    manifest.push_str(&format!(
+        "  <item id=\"resume\" href=\"resume.xhtml\" media-type=\"application/xhtml+xml\"{}/>\n",
+        remote
+    ));
+    manifest.push_str("  <item id=\"css\" href=\"style.css\" media-type=\"text/css\"/>\n");
+    for (i, (rel, _)) in book.fonts.iter().enumerate() {
+        if let Some(media_type) = font_type(rel) {
+            manifest.push_str(&format!(
+                "  <item id=\"font{}\" href=\"fonts/{}\" media-type=\"{}\"/>\n",
+                i,
+                escape_html(rel),
+                media_type
+            ));
+        }
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id" xml:lang="{lang}">
+ <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <dc:identifier id="id">{identifier}</dc:identifier>
+  <dc:title>{title}</dc:title>
+  <dc:creator>{title}</dc:creator>
+  <dc:language>{lang}</dc:language>
+  <meta property="dcterms:modified">{modified}</meta>
+ </metadata>
+ <manifest>
+{manifest} </manifest>
+ <spine>
+  <itemref idref="resume"/>
+ </spine>
+</package>
+"#,
+        lang = escape_html(book.lang),
+        identifier = escape_html(&book.identifier),
+        title = escape_html(book.title),
+        modified = book.modified,
+        manifest = manifest,
+    )
+}
+
+fn nav(book: &Book) -> String {
+    let mut entries = format!("    <li><a href=\"resume.xhtml\">{}</a></li>\n", escape_html(book.title));
+    for (id, title) in &book.contents {
+        entries.push_str(&format!(
+            "    <li><a href=\"resume.xhtml#{}\">{}</a></li>\n",
+            escape_html(id),
+            escape_html(title)
+        ));
+    }
+    format!(
+        "{}<body>\n <nav epub:type=\"toc\" id=\"toc\">\n  <h1>Contents</h1>\n  <ol>\n{}  </ol>\n </nav>\n</body>\n</html>\n",
+        head(book, "Contents", false),
+        entries
+    )
+}
+
+fn chapter(book: &Book) -> String {
+    format!(
+        "{}<body>\n  <main class=\"h-resume\">\n{}  </main>\n</body>\n</html>\n",
+        head(book, book.title, true),
+        xhtml(book.body)
+    )
+}
+
+fn head(book: &Book, title: &str, stylesheet: bool) -> String {
+    let link = if stylesheet { "  <link rel=\"stylesheet\" href=\"style.css\"/>\n" } else { "" };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" xml:lang=\"{0}\" lang=\"{0}\">\n<head>\n  <title>{1}</title>\n{2}</head>\n",
+        escape_html(book.lang),
+        escape_html(title),
+        link
+    )
+}
+
+/// The page's markup as XHTML. Text and attributes are already
+/// escaped the XML way; the only elements the page leaves open are
+/// `<br>` and `<img>`, which XHTML needs self-closed.
+fn xhtml(html: &str) -> String {
+    let html = html.replace("<br>", "<br/>");
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html.as_str();
+    while let Some(start) = rest.find("<img ") {
+        let end = start + rest[start..].find('>').unwrap_or(rest.len() - start);
+        out.push_str(&rest[..end]);
+        if !rest[..end].ends_with('/') {
+            out.push('/');
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn void_elements_are_self_closed() {
+        assert_eq!(
+            xhtml("<p>a<br>\nb</p><img class=\"avatar\" src=\"a.png\"><img src=\"b.png\"/>"),
+            "<p>a<br/>\nb</p><img class=\"avatar\" src=\"a.png\"/><img src=\"b.png\"/>"
+        );
+    }
+
+    #[test]
+    fn package_lists_everything_in_the_book() {
+        let book = Book {
+            title: "Ada & Co",
+            lang: "en",
+            identifier: "urn:uuid:1".to_string(),
+            modified: "2026-10-16T00:00:00Z".to_string(),
+            body: "<section id=\"experience\"></section>",
+            css: "",
+            contents: vec![("experience".to_string(), "Experience".to_string())],
+            fonts: vec![("inter.woff2", b""), ("LICENSE.txt", b"")],
+        };
+        let opf = package(&book);
+        assert!(opf.contains("<dc:title>Ada &amp; Co</dc:title>"));
+        assert!(opf.contains("href=\"fonts/inter.woff2\" media-type=\"font/woff2\""));
+        assert!(!opf.contains("LICENSE"));
+        assert!(!opf.contains("remote-resources"));
+        assert!(nav(&book).contains("<li><a href=\"resume.xhtml#experience\">Experience</a></li>"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Content-hashed asset names (`--fingerprint`).
+//!
+//! A hosted resume's stylesheet and fonts only change when the build
+//! changes them, so they can be cached for good, as long as a new
+//! version gets a new name. With `fingerprint` on, the themed page's
+//! assets are renamed after their content (`style.css` becomes
+//! `style.3f2a9c1e0b.css`) and the page is rewritten to match. Two
+//! files tell the host what it can cache:
+//!
+//! - `_headers`, read by Netlify and Cloudflare Pages: the renamed
+//!   assets are immutable for a year, the page is revalidated on
+//!   every visit.
+//! - `asset-manifest.json`: each asset's plain name and its renamed
+//!   one, for other hosts and deploy scripts.
+//!
+//! The assets a previous fingerprinted build left behind are removed,
+//! so the output directory doesn't collect old versions.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::write_atomic;
+
+/// The plain and renamed name of each asset, relative to the output
+/// directory.
+pub const MANIFEST_FILE: &str = "asset-manifest.json";
+
+/// Cache rules for Netlify and Cloudflare Pages.
+pub const HEADERS_FILE: &str = "_headers";
+
+/// Hex digits of the content hash in a renamed asset.
+const HASH_LEN: usize = 10;
+
+/// How long a renamed asset can be cached: a year, the longest
+/// `max-age` browsers honor.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// The page itself: cached, but checked with the host on every visit.
+const REVALIDATE: &str = "public, max-age=0, must-revalidate";
+
+/// `name` with a hash of `content` before its extension:
+/// `fonts/inter.woff2` is `fonts/inter.1a2b3c4d5e.woff2`.
+pub fn hashed_name(name: &str, content: &[u8]) -> String {
+    let hash = format!("{:x}", md5::compute(content));
+    let hash = &hash[..HASH_LEN];
+    let file_start = name.rfind('/').map_or(0, |i| i + 1);
+    match name[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = file_start + dot;
+            format!("{}.{}{}", &name[..dot], hash, &name[dot..])
+        }
+        _ => format!("{}.{}", name, hash),
+    }
+}
+
+/// Rename `fonts` (paths under `fonts/`) and `stylesheet`, if the
+/// build wrote one, in `out_dir` after their content, and point `page`
+/// at the new names. Writes the manifest and `_headers`, and returns
+/// the manifest.
+pub fn apply(out_dir: &Path, page: &str, stylesheet: Option<&str>, fonts: &[&str]) -> Result<BTreeMap<String, String>> {
+    let previous = read_manifest(out_dir);
+    let mut manifest = BTreeMap::new();
+
+    // Fonts first: the stylesheet names them, so its hash depends on
+    // theirs.
+    for rel in fonts {
+        let name = format!("fonts/{}", rel);
+        let path = out_dir.join(&name);
+        let bytes = fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+        let hashed = hashed_name(&name, &bytes);
+        fs::rename(&path, out_dir.join(&hashed)).with_context(|| format!("renaming {}", path.display()))?;
+        manifest.insert(name, hashed);
+    }
+
+    // Only this build's stylesheet: one an earlier build left behind
+    // isn't linked from the page.
+    if let Some(stylesheet) = stylesheet {
+        let css_path = out_dir.join(stylesheet);
+        let css = rewrite(&fs::read_to_string(&css_path).context("Failed to read the stylesheet")?, &manifest);
+        let hashed = hashed_name(stylesheet, css.as_bytes());
+        write_atomic(&out_dir.join(&hashed), &css).with_context(|| format!("Failed to write {}", hashed))?;
+        fs::remove_file(&css_path).context("Failed to remove the plain stylesheet")?;
+        manifest.insert(stylesheet.to_string(), hashed);
+    }
+
+    let page_path = out_dir.join(page);
+    let html = fs::read_to_string(&page_path).with_context(|| format!("reading {}", page_path.display()))?;
+    write_atomic(&page_path, rewrite(&html, &manifest)).with_context(|| format!("Failed to write {}", page))?;
+
+    for stale in previous.values().filter(|name| !manifest.values().any(|new| new == *name)) {
+        // Already gone is fine; it's only tidying.
+        let _ = fs::remove_file(out_dir.join(stale));
+    }
+    let json = serde_json::to_string_pretty(&manifest).context("Failed to write the asset manifest")? + "\n";
+    write_atomic(&out_dir.join(MANIFEST_FILE), json).context("Failed to write the asset manifest")?;
+    write_atomic(&out_dir.join(HEADERS_FILE), headers(page, &manifest)).context("Failed to write _headers")?;
+    Ok(manifest)
+}
+
+/// `text` with each reference the build writes to an asset, `href="..."`
+/// or `url(...)`, pointed at its renamed file. The name on its own is
+/// left alone: a resume can mention `style.css` in its text.
+fn rewrite(text: &str, manifest: &BTreeMap<String, String>) -> String {
+    manifest.iter().fold(text.to_string(), |text, (name, hashed)| {
+        ["href=\"{}\"", "url(\"{}\")", "url('{}')", "url({})"].iter().fold(text, |text, form| {
+            text.replace(&form.replace("{}", name), &form.replace("{}", hashed))
+        })
+    })
+}
+
+/// `_headers` for the page and the renamed assets. Paths are from the
+/// site's root, which the output directory is taken to be.
+fn headers(page: &str, manifest: &BTreeMap<String, String>) -> String {
+    let mut out = String::from("# Written by srg --fingerprint. Renamed assets never change.\n");
+    for hashed in manifest.values() {
+        out.push_str(&format!("/{}\n  Cache-Control: {}\n", hashed, IMMUTABLE));
+    }
+    out.push_str(&format!("/\n  Cache-Control: {}\n/{}\n  Cache-Control: {}\n", REVALIDATE, page, REVALIDATE));
+    out
+}
+
+/// The manifest a previous build wrote, if any.
+fn read_manifest(out_dir: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(out_dir.join(MANIFEST_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_carry_the_hash() {
+        let hashed = hashed_name("fonts/inter/Inter.woff2", b"font");
+        assert!(hashed.starts_with("fonts/inter/Inter."), "{}", hashed);
+        assert!(hashed.ends_with(".woff2"));
+        assert_eq!(hashed.len(), "fonts/inter/Inter.woff2".len() + HASH_LEN + 1);
+        assert_ne!(hashed, hashed_name("fonts/inter/Inter.woff2", b"other font"));
+        assert_eq!(hashed_name("LICENSE", b"x").len(), "LICENSE".len() + HASH_LEN + 1);
+    }
+
+    #[test]
+    fn renames_assets_and_the_references_to_them() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path();
+        fs::create_dir_all(out.join("fonts")).unwrap();
+        fs::write(out.join("fonts/a.woff"), "woff").unwrap();
+        fs::write(out.join("fonts/a.woff2"), "woff2").unwrap();
+        fs::write(out.join("style.css"), "src: url(\"fonts/a.woff2\"), url(\"fonts/a.woff\");").unwrap();
+        fs::write(out.join("index.html"), "<link rel=\"stylesheet\" href=\"style.css\">").unwrap();
+
+        let manifest = apply(out, "index.html", Some("style.css"), &["a.woff", "a.woff2"]).unwrap();
+        let css_name = &manifest["style.css"];
+        let css = fs::read_to_string(out.join(css_name)).unwrap();
+        assert!(css.contains(&manifest["fonts/a.woff2"]) && css.contains(&manifest["fonts/a.woff"]), "{}", css);
+        assert!(!out.join("style.css").exists() && !out.join("fonts/a.woff2").exists());
+        let html = fs::read_to_string(out.join("index.html")).unwrap();
+        assert_eq!(html, format!("<link rel=\"stylesheet\" href=\"{}\">", css_name));
+
+        let headers = fs::read_to_string(out.join(HEADERS_FILE)).unwrap();
+        assert!(headers.contains(&format!("/{}\n  Cache-Control: {}\n", css_name, IMMUTABLE)), "{}", headers);
+        assert!(headers.contains(&format!("/index.html\n  Cache-Control: {}\n", REVALIDATE)));
+
+        // A rebuild with other content replaces the old versions.
+        fs::write(out.join("style.css"), "body {}").unwrap();
+        fs::write(out.join("index.html"), "<link rel=\"stylesheet\" href=\"style.css\">").unwrap();
+        let rebuilt = apply(out, "index.html", Some("style.css"), &[]).unwrap();
+        assert_ne!(&rebuilt["style.css"], css_name);
+        assert!(!out.join(css_name).exists());
+        assert!(!out.join(&manifest["fonts/a.woff"]).exists());
+
+        // With the CSS inlined, a stylesheet left from an earlier build
+        // is neither renamed nor listed.
+        fs::write(out.join("style.css"), "body {}").unwrap();
+        fs::write(out.join("index.html"), "<style>body {}</style>").unwrap();
+        let inline = apply(out, "index.html", None, &[]).unwrap();
+        assert!(inline.is_empty());
+        assert!(out.join("style.css").exists());
+        assert!(!fs::read_to_string(out.join(HEADERS_FILE)).unwrap().contains("style"));
+    }
+
+    #[test]
+    fn rewrites_only_references() {
+        let manifest = BTreeMap::from([
+            ("style.css".to_string(), "style.0123456789.css".to_string()),
+            ("fonts/a.woff".to_string(), "fonts/a.abcdef0123.woff".to_string()),
+        ]);
+        let html = "<link href=\"style.css\"><style>src: url(fonts/a.woff)</style><p>Wrote style.css and fonts/a.woff2</p>";
+        assert_eq!(
+            rewrite(html, &manifest),
+            "<link href=\"style.0123456789.css\"><style>src: url(fonts/a.abcdef0123.woff)</style><p>Wrote style.css and fonts/a.woff2</p>"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Gemini gemtext export (`--format gemtext`).
+//!
+//! Gemtext is line-oriented: headings, list items, and links each
+//! take a whole line, and there is no inline markup. Every block of
+//! the outline therefore maps to one or more full lines, and links
+//! are pulled out onto their own `=>` lines.
+
+use super::outline::{one_line, Block, Outline};
+
+/// Render the outline as a gemtext document.
+pub fn render(outline: &Outline) -> String {
+    let mut gmi = String::new();
+
+    for section in &outline.sections {
+        if !gmi.is_empty() {
+            gmi.push('\n');
+        }
+        match &section.title {
+            // The person header is the document title.
+            None => {
+                for item in &section.items {
+                    if let Some(name) = &item.heading {
+                        gmi.push_str(&format!("# {}\n", one_line(name)));
+                    }
+                    push_blocks(&mut gmi, &item.blocks);
+                }
+            }
+            Some(title) => {
+                gmi.push_str(&format!("## {}\n", one_line(title)));
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        gmi.push_str(&format!("\n### {}\n", one_line(heading)));
+                    }
+                    push_blocks(&mut gmi, &item.blocks);
+                }
+            }
+        }
+    }
+
+    gmi
+}
+
+fn push_blocks(gmi: &mut String, blocks: &[Block]) {
+    for block in blocks {
+        match block {
+            Block::Text(text) => {
+                gmi.push_str(&one_line(text));
+                gmi.push('\n');
+            }
+            Block::Link { href, text } if href == text => {
+                gmi.push_str(&format!("=> {}\n", href));
+            }
+            Block::Link { href, text } => {
+                gmi.push_str(&format!("=> {} {}\n", href, one_line(text)));
+            }
+            Block::List(entries) => {
+                for entry in entries {
+                    gmi.push_str(&format!("* {}\n", one_line(entry)));
+                }
+            }
+            Block::Term { term, text } => {
+                gmi.push_str(&format!("{}: {}\n", one_line(term), one_line(text)));
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! The history page (`--history`): how the resume changed over time.
+//!
+//! Each commit that touched the JOBL file becomes an entry with its
+//! date, its message, and a short summary of what changed in the
+//! resume itself (roles added, highlights rewritten, skills dropped),
+//! worked out by comparing the parsed documents rather than the text.
+//! Collecting the revisions from git happens in the binary; this
+//! module only compares and renders them.
+
+use anyhow::{Context, Result};
+use jobl::JoblDocument;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use super::{escape_html, push_stylesheet, themed_css, write_atomic, BuildOptions, CssMode};
+use crate::layout::Layout;
+
+/// File name of the history page in the output directory.
+pub const HISTORY_FILE: &str = "history.html";
+
+/// One commit that touched the resume.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub commit: String,
+    /// Commit date as `YYYY-MM-DD`.
+    pub date: String,
+    pub subject: String,
+    /// The resume as of this commit, or `None` if that version
+    /// doesn't parse.
+    pub doc: Option<JoblDocument>,
+}
+
+/// What changed between two versions of a resume, one line per
+/// change. `old` is `None` for the first version.
+pub fn changes(old: Option<&JoblDocument>, new: &JoblDocument) -> Vec<String> {
+    let Some(old) = old else {
+        return vec!["First version".to_string()];
+    };
+    let mut changes = Vec::new();
+
+    if old.person.headline != new.person.headline {
+        if let Some(headline) = &new.person.headline {
+            changes.push(format!("Headline: {}", headline));
+        }
+    }
+    if old.person.summary != new.person.summary {
+        changes.push("Rewrote summary".to_string());
+    }
+
+    let role = |e: &jobl::ExperienceItem| format!("{} at {}", e.title, e.company);
+    for exp in &new.experience {
+        match old.experience.iter().find(|o| role(o) == role(exp)) {
+            None => changes.push(format!("Added role: {}", role(exp))),
+            Some(prev) => {
+                let (added, removed) = list_diff(&prev.highlights, &exp.highlights);
+                if added + removed > 0 {
+                    changes.push(format!("{}: {}", role(exp), counts(added, removed, "highlight")));
+                }
+                if prev.end != exp.end {
+                    if let Some(end) = &exp.end {
+                        changes.push(format!("{}: ended {}", role(exp), end));
+                    }
+                }
+            }
+        }
+    }
+    for exp in &old.experience {
+        if !new.experience.iter().any(|n| role(n) == role(exp)) {
+            changes.push(format!("Removed role: {}", role(exp)));
+        }
+    }
+
+    let names = |doc: &JoblDocument| doc.projects.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+    push_set_changes(&mut changes, "project", &names(old), &names(new));
+
+    let degrees = |doc: &JoblDocument| {
+        doc.education
+            .iter()
+            .map(|e| format!("{}, {}", e.degree, e.institution))
+            .collect::<Vec<_>>()
+    };
+    push_set_changes(&mut changes, "education", &degrees(old), &degrees(new));
+
+    let skills = |doc: &JoblDocument| {
+        doc.skills
+            .iter()
+            .flat_map(|s| s.values().flatten().cloned())
+            .collect::<Vec<_>>()
+    };
+    let (old_skills, new_skills) = (skills(old), skills(new));
+    let added: Vec<&str> = new_skills.iter().filter(|s| !old_skills.contains(s)).map(String::as_str).collect();
+    let removed: Vec<&str> = old_skills.iter().filter(|s| !new_skills.contains(s)).map(String::as_str).collect();
+    if !added.is_empty() {
+        changes.push(format!("Added skills: {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        changes.push(format!("Removed skills: {}", removed.join(", ")));
+    }
+
+    if changes.is_empty() {
+        changes.push("No content changes".to_string());
+    }
+    changes
+}
+
+/// Items added and removed between two lists, ignoring order.
+fn list_diff(old: &[String], new: &[String]) -> (usize, usize) {
+    let old: BTreeSet<&String> = old.iter().collect();
+    let new: BTreeSet<&String> = new.iter().collect();
+    (new.difference(&old).count(), old.difference(&new).count())
+}
+
+fn counts(added: usize, removed: usize, noun: &str) -> String {
+    let plural = |n: usize| if n == 1 { noun.to_string() } else { format!("{}s", noun) };
+    match (added, removed) {
+        (a, 0) => format!("{} {} added", a, plural(a)),
+        (0, r) => format!("{} {} removed", r, plural(r)),
+        (a, r) => format!("{} {} added, {} removed", a, plural(a), r),
+    }
+}
+
+fn push_set_changes(changes: &mut Vec<String>, noun: &str, old: &[String], new: &[String]) {
+    for item in new.iter().filter(|i| !old.contains(i)) {
+        changes.push(format!("Added {}: {}", noun, item));
+    }
+    for item in old.iter().filter(|i| !new.contains(i)) {
+        changes.push(format!("Removed {}: {}", noun, item));
+    }
+}
+
+/// The history page for `revisions` (oldest first), newest entry at
+/// the top, styled like the resume.
+pub fn generate_history_html(
+    doc: &JoblDocument,
+    revisions: &[Revision],
+    theme: Option<&str>,
+    layout: &Layout,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<String> {
+    let css = themed_css(doc, layout, theme, custom_css_path, options)?;
+    let name = escape_html(&doc.person.name);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str("<html lang=\"en\">\n");
+    html.push_str("<head>\n");
+    html.push_str("  <meta charset=\"UTF-8\">\n");
+    html.push_str(
+        "  <meta name=\"viewport\" content=\"width=device-width, \
+         initial-scale=1.0\">\n",
+    );
+    html.push_str(&format!("  <title>{} — History</title>\n", name));
+    push_stylesheet(&mut html, &css, CssMode::Inline);
+    html.push_str("</head>\n");
+    html.push_str("<body>\n");
+    html.push_str("  <main>\n");
+    html.push_str("    <header class=\"person\">\n");
+    html.push_str(&format!("      <h1 class=\"person-name\">{}</h1>\n", name));
+    html.push_str("      <p class=\"history-back\"><a href=\"index.html\">Back to the resume</a></p>\n");
+    html.push_str("    </header>\n");
+    html.push_str("    <section class=\"section-history\" id=\"history\">\n");
+    html.push_str("      <h2>History</h2>\n");
+    html.push_str("      <ol class=\"history\">\n");
+
+    let mut previous: Option<&JoblDocument> = None;
+    let mut entries = Vec::new();
+    for revision in revisions {
+        let lines = match &revision.doc {
+            Some(doc) => {
+                let lines = changes(previous, doc);
+                previous = Some(doc);
+                lines
+            }
+            None => vec!["This version doesn't parse".to_string()],
+        };
+        entries.push((revision, lines));
+    }
+
+    for (revision, lines) in entries.iter().rev() {
+        html.push_str("        <li class=\"history-entry\">\n");
+        html.push_str(&format!(
+            "          <h3><time datetime=\"{d}\">{d}</time> <span class=\"history-subject\">{}</span></h3>\n",
+            escape_html(&revision.subject),
+            d = escape_html(&revision.date)
+        ));
+        html.push_str("          <ul class=\"history-changes\">\n");
+        for line in lines {
+            html.push_str(&format!("            <li>{}</li>\n", escape_html(line)));
+        }
+        html.push_str("          </ul>\n");
+        html.push_str("        </li>\n");
+    }
+
+    html.push_str("      </ol>\n");
+    html.push_str("    </section>\n");
+    html.push_str("  </main>\n");
+    html.push_str("</body>\n");
+    html.push_str("</html>\n");
+    Ok(html)
+}
+
+/// Write the history page to `out_dir`. Returns its path.
+pub fn write_history_page(
+    out_dir: &Path,
+    doc: &JoblDocument,
+    revisions: &[Revision],
+    theme: Option<&str>,
+    layout: &Layout,
+    custom_css_path: Option<&Path>,
+    options: &BuildOptions,
+) -> Result<PathBuf> {
+    let html = generate_history_html(doc, revisions, theme, layout, custom_css_path, options)?;
+    super::check_markup(&html, HISTORY_FILE, options)?;
+    let path = out_dir.join(HISTORY_FILE);
+    write_atomic(&path, html).context("Failed to write history page")?;
+    Ok(path)
+}
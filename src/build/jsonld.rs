@@ -0,0 +1,161 @@
+//! schema.org structured data for the themed page.
+//!
+//! Search engines read a `<script type="application/ld+json">` block
+//! to understand who a page is about. For someone hosting their resume
+//! as their personal page, a `Person` with their current employers
+//! (`worksFor`) and schools (`alumniOf`) is what links the page to
+//! their name in results.
+//!
+//! The data says no more than the page does: a person field is only
+//! included when the layout's `person` section shows it (a layout
+//! that leaves out the phone number keeps it out of the markup too),
+//! and employers, schools, and skills only when the layout has that
+//! section.
+
+use jobl::JoblDocument;
+use serde_json::{json, Map, Value};
+
+use super::resolved::shows_person_field;
+use crate::dates::is_present;
+use crate::layout::Layout;
+
+/// `doc` as a schema.org `Person`, limited to what `layout` shows.
+pub fn person(doc: &JoblDocument, layout: &Layout) -> Value {
+    let person = &doc.person;
+    let shown = |field: &str, value: &Option<String>| value.clone().filter(|_| shows_person_field(layout, field));
+    let has_section = |name: &str| layout.sections.iter().any(|s| s.name == name);
+
+    // Roles without an end, or ending "Present", are current.
+    let current: Vec<_> = doc
+        .experience
+        .iter()
+        .filter(|exp| has_section("experience") && exp.end.as_deref().is_none_or(is_present))
+        .collect();
+    let mut employers: Vec<&str> = Vec::new();
+    for exp in &current {
+        if !employers.contains(&exp.company.as_str()) {
+            employers.push(&exp.company);
+        }
+    }
+    let mut schools: Vec<&str> = Vec::new();
+    for edu in doc.education.iter().filter(|_| has_section("education")) {
+        if !schools.contains(&edu.institution.as_str()) {
+            schools.push(&edu.institution);
+        }
+    }
+
+    let same_as: Vec<String> = [shown("github", &person.github), shown("linkedin", &person.linkedin)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let knows_about: Vec<&String> = doc
+        .skills
+        .iter()
+        .filter(|_| has_section("skills"))
+        .flat_map(|skills| skills.values())
+        .flatten()
+        .collect();
+
+    let mut fields = Map::new();
+    fields.insert("@context".to_string(), json!("https://schema.org"));
+    fields.insert("@type".to_string(), json!("Person"));
+    fields.insert("name".to_string(), json!(person.name));
+    let job_title = shown("headline", &person.headline).or(current.first().map(|exp| exp.title.clone()));
+    let summary = if has_section("summary") { person.summary.clone() } else { shown("summary", &person.summary) };
+    for (key, value) in [
+        ("jobTitle", job_title.map(|t| json!(t))),
+        ("description", summary.map(|s| json!(s))),
+        ("email", shown("email", &person.email).map(|e| json!(e))),
+        ("telephone", shown("phone", &person.phone).map(|p| json!(p))),
+        ("url", shown("website", &person.website).map(|w| json!(w))),
+        (
+            "address",
+            shown("location", &person.location).map(|l| json!({ "@type": "PostalAddress", "addressLocality": l })),
+        ),
+        ("sameAs", (!same_as.is_empty()).then(|| json!(same_as))),
+        ("knowsAbout", (!knows_about.is_empty()).then(|| json!(knows_about))),
+        ("worksFor", organizations("Organization", &employers)),
+        ("alumniOf", organizations("EducationalOrganization", &schools)),
+    ] {
+        if let Some(value) = value {
+            fields.insert(key.to_string(), value);
+        }
+    }
+    Value::Object(fields)
+}
+
+fn organizations(kind: &str, names: &[&str]) -> Option<Value> {
+    let list: Vec<Value> = names.iter().map(|name| json!({ "@type": kind, "name": name })).collect();
+    (!list.is_empty()).then_some(Value::Array(list))
+}
+
+/// The `<script>` block for `doc`. `<` is escaped so nothing in the
+/// data can close the script element.
+pub fn script(doc: &JoblDocument, layout: &Layout) -> String {
+    format!(
+        "  <script type=\"application/ld+json\">{}</script>\n",
+        person(doc, layout).to_string().replace('<', "\\u003c")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jobl::{EducationItem, ExperienceItem, Person};
+
+    fn role(company: &str, end: Option<&str>) -> ExperienceItem {
+        ExperienceItem {
+            title: "Engineer".to_string(),
+            company: company.to_string(),
+            location: None,
+            start: Some("2020-01".to_string()),
+            end: end.map(str::to_string),
+            summary: None,
+            technologies: Vec::new(),
+            highlights: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn current_employers_and_schools() {
+        let doc = JoblDocument {
+            person: Person {
+                name: "Ada </script>".to_string(),
+                headline: None,
+                location: Some("London".to_string()),
+                email: None,
+                website: None,
+                github: Some("https://github.com/ada".to_string()),
+                linkedin: None,
+                phone: None,
+                summary: None,
+            },
+            skills: None,
+            experience: vec![role("Acme", Some("Present")), role("Initech", None), role("Globex", Some("2019-12"))],
+            projects: Vec::new(),
+            education: vec![EducationItem {
+                institution: "Cambridge".to_string(),
+                degree: "BA".to_string(),
+                location: None,
+                start: None,
+                end: None,
+                details: Vec::new(),
+            }],
+        };
+        let layout = Layout::parse("person\n  name\n  contactrow location github\nexperience\n  title\neducation\n  degree\n").unwrap();
+        let value = person(&doc, &layout);
+
+        assert_eq!(value["@type"], "Person");
+        assert_eq!(value["jobTitle"], "Engineer");
+        assert_eq!(value["address"]["addressLocality"], "London");
+        assert_eq!(value["sameAs"], json!(["https://github.com/ada"]));
+        assert_eq!(value["worksFor"].as_array().unwrap().len(), 2);
+        assert_eq!(value["worksFor"][1]["name"], "Initech");
+        assert_eq!(value["alumniOf"][0], json!({ "@type": "EducationalOrganization", "name": "Cambridge" }));
+        assert!(value.get("knowsAbout").is_none());
+        assert!(script(&doc, &layout).contains("Ada \\u003c/script>"));
+
+        let value = person(&doc, &Layout::parse("person\n  name\n").unwrap());
+        assert!(value.get("address").is_none() && value.get("worksFor").is_none() && value.get("alumniOf").is_none());
+    }
+}
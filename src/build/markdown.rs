@@ -0,0 +1,108 @@
+//! Markdown export (`--format md`).
+//!
+//! CommonMark with nothing exotic, so it reads well on GitHub, in
+//! a static site generator, or pasted into a form that accepts
+//! Markdown. The person's name is the `#` heading, layout sections
+//! are `##`, entries are `###`.
+
+use super::outline::{one_line, Block, Outline, OutlineItem};
+
+/// Render the outline as a Markdown document.
+pub fn render(outline: &Outline) -> String {
+    let mut md = String::new();
+
+    for section in &outline.sections {
+        match &section.title {
+            None => push_header(&mut md, section.items.first()),
+            Some(title) => {
+                md.push_str(&format!("## {}\n\n", escape(&one_line(title))));
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        md.push_str(&format!("### {}\n\n", escape(&one_line(heading))));
+                    }
+                    push_blocks(&mut md, &item.blocks);
+                }
+            }
+        }
+    }
+
+    md.truncate(md.trim_end().len());
+    md.push('\n');
+    md
+}
+
+/// Name, headline, then the remaining person fields on one line.
+fn push_header(md: &mut String, item: Option<&OutlineItem>) {
+    let Some(item) = item else {
+        return;
+    };
+    if let Some(name) = &item.heading {
+        md.push_str(&format!("# {}\n\n", escape(&one_line(name))));
+    }
+
+    let mut blocks = item.blocks.iter().peekable();
+    if let Some(Block::Text(headline)) = blocks.peek() {
+        md.push_str(&format!("{}\n\n", escape(&one_line(headline))));
+        blocks.next();
+    }
+
+    let contact: Vec<String> = blocks.map(inline).collect();
+    if !contact.is_empty() {
+        md.push_str(&format!("{}\n\n", contact.join(" · ")));
+    }
+}
+
+fn push_blocks(md: &mut String, blocks: &[Block]) {
+    for block in blocks {
+        match block {
+            Block::List(entries) => {
+                for entry in entries {
+                    let mut lines = entry.lines();
+                    md.push_str(&format!("- {}\n", escape(lines.next().unwrap_or_default().trim())));
+                    for line in lines {
+                        md.push_str(&format!("  {}\n", escape(line.trim())));
+                    }
+                }
+                md.push('\n');
+            }
+            Block::Term { .. } => {
+                md.push_str(&format!("- {}\n", inline(block)));
+            }
+            _ => md.push_str(&format!("{}\n\n", inline(block))),
+        }
+    }
+    // Close a run of terms with a blank line.
+    if matches!(blocks.last(), Some(Block::Term { .. })) {
+        md.push('\n');
+    }
+}
+
+/// Inline markup for a block that fits on one line.
+fn inline(block: &Block) -> String {
+    match block {
+        Block::Text(text) => escape(&one_line(text)),
+        Block::Link { href, text } if href == text => format!("<{}>", href),
+        Block::Link { href, text } => format!("[{}]({})", escape(&one_line(text)), href),
+        Block::List(entries) => escape(&one_line(&entries.join("; "))),
+        Block::Term { term, text } => {
+            format!("**{}:** {}", escape(&one_line(term)), escape(&one_line(text)))
+        }
+    }
+}
+
+/// Backslash-escape characters that Markdown would read as inline
+/// markup, and a leading character that would start a block
+/// (heading, quote, list item).
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    if out.starts_with(['#', '-', '+']) {
+        out.insert(0, '\\');
+    }
+    out
+}
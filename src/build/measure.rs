@@ -0,0 +1,144 @@
+//! Measuring the page in Chrome before it prints.
+//!
+//! The PDF is printed in two passes over the same tab: the first lays
+//! the page out as it will print, one page's content box wide, and
+//! reads back where each section sits and where the pages break; the
+//! second prints. What the first pass finds goes back to the build
+//! (see `check_fit` in `build.rs`), so decisions about length are made
+//! on the page Chrome actually laid out rather than on word counts.
+//!
+//! Page breaks are worked out the way `CONTINUED_JS` does: a page
+//! every page height, restarting at each forced break. Chrome's own
+//! breaks around entries it keeps whole can move a boundary by a few
+//! lines, so the numbers are close, not exact.
+
+use anyhow::{Context, Result};
+use headless_chrome::Tab;
+use serde::Deserialize;
+
+/// Reads the laid-out page: where its sections are and where each
+/// page starts, in CSS pixels from the top. `width` and `pageHeight`
+/// are one page's content box.
+const MEASURE_JS: &str = r#"function (width, pageHeight) {
+  var root = document.documentElement;
+  var saved = root.style.width;
+  root.style.width = width + "px";
+  var top = function (el) { return el.getBoundingClientRect().top + window.scrollY; };
+  var bottom = function (el) { return el.getBoundingClientRect().bottom + window.scrollY; };
+  var height = bottom(document.body);
+  var breaks = Array.prototype.filter.call(document.body.querySelectorAll("*"), function (el) {
+    var style = getComputedStyle(el);
+    return style.breakBefore === "page" || style.pageBreakBefore === "always";
+  }).map(top).sort(function (a, b) { return a - b; });
+  var starts = [0];
+  for (;;) {
+    var start = starts[starts.length - 1];
+    var next = start + pageHeight;
+    breaks.forEach(function (y) { if (y > start && y < next) { next = y; } });
+    if (next >= height) { break; }
+    starts.push(next);
+  }
+  var sections = Array.prototype.map.call(document.querySelectorAll("main > .section"), function (section) {
+    var title = section.querySelector("h2");
+    return { name: section.id, title: title ? title.textContent : null, top: top(section), bottom: bottom(section) };
+  });
+  root.style.width = saved;
+  return JSON.stringify({ page_height: pageHeight, height: height, page_starts: starts, sections: sections });
+}"#;
+
+/// The page as Chrome laid it out for print.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Measurements {
+    /// Height of a page's content box.
+    pub page_height: f64,
+    /// Height of the whole page.
+    pub height: f64,
+    /// Where each printed page starts. The first is 0.
+    pub page_starts: Vec<f64>,
+    /// The resume's sections, in page order.
+    pub sections: Vec<SectionBox>,
+}
+
+/// Where a section sits on the laid-out page.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SectionBox {
+    /// The section's name (its element's id).
+    pub name: String,
+    pub title: Option<String>,
+    pub top: f64,
+    pub bottom: f64,
+}
+
+impl SectionBox {
+    /// The title, or the name for an untitled section.
+    pub fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl Measurements {
+    /// How many pages the PDF runs to.
+    pub fn pages(&self) -> usize {
+        self.page_starts.len().max(1)
+    }
+
+    /// The page, counted from 1, that `y` falls on.
+    pub fn page_at(&self, y: f64) -> usize {
+        self.page_starts.iter().filter(|&&start| start <= y).count().max(1)
+    }
+
+    /// Sections that run past page `page`, in page order.
+    pub fn past(&self, page: usize) -> Vec<&SectionBox> {
+        self.sections.iter().filter(|section| self.page_at(section.bottom) > page).collect()
+    }
+
+    /// Sections that break across pages.
+    pub fn split(&self) -> Vec<&SectionBox> {
+        self.sections.iter().filter(|section| self.page_at(section.top) != self.page_at(section.bottom)).collect()
+    }
+}
+
+/// Measure the tab's page, laid out `width` wide with pages
+/// `page_height` tall. The caller sets up print media.
+pub fn measure(tab: &Tab, width: f64, page_height: f64) -> Result<Measurements> {
+    let result = tab
+        .evaluate(&format!("({})({}, {})", MEASURE_JS, width, page_height), false)
+        .context("Failed to measure the page")?;
+    let json = result.value.as_ref().and_then(|value| value.as_str()).context("Measuring the page returned nothing")?;
+    serde_json::from_str(json).context("Failed to read the page's measurements")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(name: &str, top: f64, bottom: f64) -> SectionBox {
+        SectionBox { name: name.to_string(), title: None, top, bottom }
+    }
+
+    fn measured() -> Measurements {
+        Measurements {
+            page_height: 1000.0,
+            height: 2400.0,
+            page_starts: vec![0.0, 1000.0, 1900.0],
+            sections: vec![section("summary", 100.0, 300.0), section("experience", 300.0, 1500.0), section("education", 1900.0, 2400.0)],
+        }
+    }
+
+    #[test]
+    fn finds_pages() {
+        let m = measured();
+        assert_eq!(m.pages(), 3);
+        assert_eq!((m.page_at(0.0), m.page_at(999.0), m.page_at(1000.0), m.page_at(2000.0)), (1, 1, 2, 3));
+    }
+
+    #[test]
+    fn finds_sections_past_a_page() {
+        let m = measured();
+        let names = |sections: Vec<&SectionBox>| sections.iter().map(|s| s.label().to_string()).collect::<Vec<_>>();
+        assert_eq!(names(m.past(1)), ["experience", "education"]);
+        assert_eq!(names(m.past(2)), ["education"]);
+        assert!(m.past(3).is_empty());
+        assert_eq!(names(m.split()), ["experience"]);
+    }
+}
@@ -0,0 +1,156 @@
+//! microformats2 classes for the themed page.
+//!
+//! The page is an `h-resume` (<https://microformats.org/wiki/h-resume>):
+//! the person header is its `p-contact h-card`, each job a
+//! `p-experience h-event`, each degree a `p-education h-event`, each
+//! skill a `p-skill`. Parsers and crawlers read structured data off
+//! these classes without a separate copy of the content, so only what
+//! the layout shows is marked. The classes are added to the resolved
+//! tree on its way to HTML; themes don't style them and the text
+//! exports never see them.
+
+use super::resolved::{Content, Inline, Node, Resolved, Role};
+
+/// Add the classes to `resolved`'s sections.
+pub fn annotate(resolved: &mut Resolved) {
+    for section in &mut resolved.sections {
+        match section.kind.as_str() {
+            "person" => {
+                section.classes.extend(["p-contact".to_string(), "h-card".to_string()]);
+                walk(&mut section.nodes, &person_property);
+            }
+            "summary" => walk(&mut section.nodes, &|node| field(node).filter(|f| *f == "summary").map(|_| "p-summary")),
+            "experience" => mark_items(&mut section.nodes, "p-experience h-event", experience_property),
+            "education" => mark_items(&mut section.nodes, "p-education h-event", education_property),
+            "skills" => section.nodes.iter_mut().for_each(mark_skills),
+            _ => {}
+        }
+    }
+}
+
+/// Add `property(node)`'s classes to `nodes` and everything in them.
+fn walk(nodes: &mut [Node], property: &dyn Fn(&Node) -> Option<&'static str>) {
+    for node in nodes {
+        if let Some(classes) = property(node) {
+            add(node, classes);
+        }
+        for_children(node, &mut |children| walk(children, property));
+    }
+}
+
+/// Add `root` to each job or degree in `nodes`, and its fields'
+/// classes to the fields in it. A field outside an item (a grouped
+/// company's heading) belongs to no event, so it's left alone.
+fn mark_items(nodes: &mut [Node], root: &str, fields: fn(&str) -> Option<&'static str>) {
+    for node in nodes {
+        if let Role::Item { .. } = node.role {
+            add(node, root);
+            for_children(node, &mut |children| walk(children, &|child| field(child).and_then(fields)));
+        } else {
+            for_children(node, &mut |children| mark_items(children, root, fields));
+        }
+    }
+}
+
+fn add(node: &mut Node, classes: &str) {
+    node.classes.extend(classes.split(' ').map(str::to_string));
+}
+
+fn for_children(node: &mut Node, f: &mut dyn FnMut(&mut [Node])) {
+    match &mut node.content {
+        Content::Block(children) => f(children),
+        Content::Inline(inlines) => {
+            for inline in inlines {
+                if let Inline::Element(child) = inline {
+                    f(std::slice::from_mut(child));
+                }
+            }
+        }
+        Content::Void => {}
+    }
+}
+
+/// Each skill in a category's list becomes its own `p-skill`. The
+/// list was joined with ", ", so that's where it splits.
+fn mark_skills(category: &mut Node) {
+    let Content::Inline(inlines) = &mut category.content else { return };
+    for inline in inlines {
+        if let Inline::Element(items) = inline {
+            if items.classes.iter().any(|c| c == "skills-items") {
+                split_skills(items);
+            }
+        }
+    }
+}
+
+fn split_skills(items: &mut Node) {
+    let Content::Inline(inlines) = &mut items.content else { return };
+    let mut marked = Vec::new();
+    for inline in inlines.drain(..) {
+        match inline {
+            Inline::Text(text) if text != ", " => {
+                for (i, skill) in text.split(", ").enumerate() {
+                    if i > 0 {
+                        marked.push(Inline::Text(", ".to_string()));
+                    }
+                    marked.push(Inline::Element(Node::text("span", vec!["p-skill".to_string()], skill)));
+                }
+            }
+            Inline::Element(mut link) => {
+                link.classes.push("p-skill".to_string());
+                marked.push(Inline::Element(link));
+            }
+            other => marked.push(other),
+        }
+    }
+    *inlines = marked;
+}
+
+fn field(node: &Node) -> Option<&str> {
+    match &node.role {
+        Role::Field(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// A person field, by its `person-*` class so the items of a
+/// `contactrow` count too.
+fn person_property(node: &Node) -> Option<&'static str> {
+    let name = node.classes.iter().find_map(|c| c.strip_prefix("person-"))?;
+    Some(match name {
+        "name" => "p-name",
+        "headline" => "p-job-title",
+        "email" => "u-email",
+        "phone" => "p-tel",
+        "location" => "p-locality",
+        "website" | "github" | "linkedin" => "u-url",
+        "summary" => "p-note",
+        "avatar" => "u-photo",
+        _ => return None,
+    })
+}
+
+fn experience_property(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "title" => "p-name",
+        "company" => "p-org",
+        "location" => "p-location",
+        "summary" => "p-summary",
+        "highlights" => "e-description",
+        "start" => "dt-start",
+        "end" => "dt-end",
+        _ => return None,
+    })
+}
+
+fn education_property(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "degree" => "p-name",
+        "institution" => "p-org",
+        "location" => "p-location",
+        "details" => "e-description",
+        "start" => "dt-start",
+        "end" => "dt-end",
+        _ => return None,
+    })
+}
@@ -0,0 +1,246 @@
+//! OpenDocument Text export (`--format odt`).
+//!
+//! For LibreOffice users who want to adjust the generated resume by
+//! hand. The layout's structure maps onto named styles rather than
+//! direct formatting: the name, sections, entry headings, text, and
+//! lists each have a paragraph style ("Section Title", "Entry
+//! Heading", ...), so restyling every section heading is one edit in
+//! the Styles sidebar. Entries carry a bookmark named after the same
+//! anchor the themed HTML uses.
+//!
+//! The file is a zip written with [`crate::archive::ZipWriter`]:
+//! `mimetype` (stored, first, as the spec requires), the manifest,
+//! `meta.xml`, `styles.xml`, and `content.xml`.
+
+use anyhow::Result;
+
+use super::outline::{Block, Outline};
+use super::{escape_html, BuildOptions};
+use crate::archive::ZipWriter;
+
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.text";
+
+/// Page margin when the build sets none, as for the Typst PDF.
+const DEFAULT_MARGIN: f64 = 0.6;
+
+const NAMESPACES: &str = "xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\" \
+xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" \
+xmlns:fo=\"urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0\" \
+xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+xmlns:meta=\"urn:oasis:names:tc:opendocument:xmlns:meta:1.0\" \
+office:version=\"1.3\"";
+
+/// Render the outline as an ODT file.
+pub fn render(outline: &Outline, options: &BuildOptions) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Vec::new());
+    zip.add_stored("mimetype", MIMETYPE.as_bytes())?;
+    zip.add_file("META-INF/manifest.xml", MANIFEST.as_bytes())?;
+    zip.add_file("meta.xml", meta(outline).as_bytes())?;
+    zip.add_file("styles.xml", styles(options).as_bytes())?;
+    zip.add_file("content.xml", content(outline).as_bytes())?;
+    zip.finish()
+}
+
+const MANIFEST: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"1.3\">
+ <manifest:file-entry manifest:full-path=\"/\" manifest:version=\"1.3\" manifest:media-type=\"application/vnd.oasis.opendocument.text\"/>
+ <manifest:file-entry manifest:full-path=\"meta.xml\" manifest:media-type=\"text/xml\"/>
+ <manifest:file-entry manifest:full-path=\"styles.xml\" manifest:media-type=\"text/xml\"/>
+ <manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/>
+</manifest:manifest>
+";
+
+fn meta(outline: &Outline) -> String {
+    let mut fields = String::from("<meta:generator>srg</meta:generator>");
+    if let Some(name) = outline.name() {
+        fields.push_str(&format!("<dc:title>{}</dc:title>", escape_html(name)));
+        fields.push_str(&format!("<meta:initial-creator>{}</meta:initial-creator>", escape_html(name)));
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<office:document-meta {}><office:meta>{}</office:meta></office:document-meta>\n",
+        NAMESPACES, fields
+    )
+}
+
+/// The named styles the content uses, and the page.
+fn styles(options: &BuildOptions) -> String {
+    let (width, height) = options.paper.size();
+    let m = options.margins;
+    let margin = |side: f64| if m.top + m.right + m.bottom + m.left == 0.0 { DEFAULT_MARGIN } else { side };
+    let size = options
+        .font_size
+        .as_deref()
+        .filter(|size| size.strip_suffix("pt").is_some_and(|n| n.parse::<f64>().is_ok_and(|n| n > 0.0)))
+        .unwrap_or("10.5pt");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-styles {ns}>
+ <office:styles>
+  <style:default-style style:family="paragraph">
+   <style:paragraph-properties fo:margin-top="0cm" fo:margin-bottom="0.1cm"/>
+   <style:text-properties fo:font-family="'Liberation Sans', Arial, sans-serif" fo:font-size="{size}"/>
+  </style:default-style>
+  <style:style style:name="Standard" style:family="paragraph" style:class="text"/>
+  <style:style style:name="Resume_20_Name" style:display-name="Resume Name" style:family="paragraph" style:parent-style-name="Standard" style:class="chapter">
+   <style:paragraph-properties fo:text-align="center" fo:margin-bottom="0.15cm"/>
+   <style:text-properties fo:font-size="200%" fo:font-weight="bold"/>
+  </style:style>
+  <style:style style:name="Resume_20_Contact" style:display-name="Resume Contact" style:family="paragraph" style:parent-style-name="Standard">
+   <style:paragraph-properties fo:text-align="center" fo:margin-bottom="0.05cm"/>
+  </style:style>
+  <style:style style:name="Section_20_Title" style:display-name="Section Title" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Entry_20_Heading" style:default-outline-level="1" style:class="text">
+   <style:paragraph-properties fo:margin-top="0.4cm" fo:margin-bottom="0.15cm" fo:keep-with-next="always" fo:border-bottom="0.5pt solid #000000" fo:padding-bottom="0.05cm"/>
+   <style:text-properties fo:font-size="120%" fo:font-weight="bold" fo:text-transform="uppercase"/>
+  </style:style>
+  <style:style style:name="Entry_20_Heading" style:display-name="Entry Heading" style:family="paragraph" style:parent-style-name="Standard" style:next-style-name="Entry_20_Text" style:default-outline-level="2" style:class="text">
+   <style:paragraph-properties fo:margin-top="0.2cm" fo:keep-with-next="always"/>
+   <style:text-properties fo:font-weight="bold"/>
+  </style:style>
+  <style:style style:name="Entry_20_Text" style:display-name="Entry Text" style:family="paragraph" style:parent-style-name="Standard"/>
+  <style:style style:name="Entry_20_List" style:display-name="Entry List" style:family="paragraph" style:parent-style-name="Standard" style:list-style-name="Resume_20_Bullets">
+   <style:paragraph-properties fo:margin-bottom="0.05cm"/>
+  </style:style>
+  <style:style style:name="Term" style:family="text">
+   <style:text-properties fo:font-weight="bold"/>
+  </style:style>
+  <text:list-style style:name="Resume_20_Bullets" style:display-name="Resume Bullets">
+   <text:list-level-style-bullet text:level="1" text:bullet-char="•">
+    <style:list-level-properties text:list-level-position-and-space-mode="label-alignment">
+     <style:list-level-label-alignment text:label-followed-by="listtab" fo:text-indent="-0.4cm" fo:margin-left="0.6cm"/>
+    </style:list-level-properties>
+   </text:list-level-style-bullet>
+  </text:list-style>
+ </office:styles>
+ <office:automatic-styles>
+  <style:page-layout style:name="Page">
+   <style:page-layout-properties fo:page-width="{width}in" fo:page-height="{height}in" fo:margin-top="{top}in" fo:margin-right="{right}in" fo:margin-bottom="{bottom}in" fo:margin-left="{left}in"/>
+  </style:page-layout>
+ </office:automatic-styles>
+ <office:master-styles>
+  <style:master-page style:name="Standard" style:page-layout-name="Page"/>
+ </office:master-styles>
+</office:document-styles>
+"#,
+        ns = NAMESPACES,
+        size = size,
+        width = width,
+        height = height,
+        top = margin(m.top),
+        right = margin(m.right),
+        bottom = margin(m.bottom),
+        left = margin(m.left),
+    )
+}
+
+fn content(outline: &Outline) -> String {
+    let mut body = String::new();
+    for section in &outline.sections {
+        match &section.title {
+            None => {
+                for item in &section.items {
+                    if let Some(name) = &item.heading {
+                        body.push_str(&format!("<text:p text:style-name=\"Resume_20_Name\">{}</text:p>\n", text(name)));
+                    }
+                    push_blocks(&mut body, &item.blocks, "Resume_20_Contact");
+                }
+            }
+            Some(title) => {
+                body.push_str(&format!(
+                    "<text:h text:style-name=\"Section_20_Title\" text:outline-level=\"1\">{}</text:h>\n",
+                    text(title)
+                ));
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        let bookmark = item
+                            .anchor
+                            .as_ref()
+                            .map(|anchor| format!("<text:bookmark text:name=\"{}\"/>", escape_html(anchor)))
+                            .unwrap_or_default();
+                        body.push_str(&format!(
+                            "<text:h text:style-name=\"Entry_20_Heading\" text:outline-level=\"2\">{}{}</text:h>\n",
+                            bookmark,
+                            text(heading)
+                        ));
+                    }
+                    push_blocks(&mut body, &item.blocks, "Entry_20_Text");
+                }
+            }
+        }
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<office:document-content {}>\n<office:body>\n<office:text>\n{}</office:text>\n</office:body>\n</office:document-content>\n",
+        NAMESPACES, body
+    )
+}
+
+fn push_blocks(body: &mut String, blocks: &[Block], style: &str) {
+    for block in blocks {
+        match block {
+            Block::Text(value) => {
+                body.push_str(&format!("<text:p text:style-name=\"{}\">{}</text:p>\n", style, text(value)));
+            }
+            Block::Link { href, text: label } => body.push_str(&format!(
+                "<text:p text:style-name=\"{}\"><text:a xlink:type=\"simple\" xlink:href=\"{}\">{}</text:a></text:p>\n",
+                style,
+                escape_html(href),
+                text(label)
+            )),
+            Block::List(entries) => {
+                body.push_str("<text:list text:style-name=\"Resume_20_Bullets\">\n");
+                for entry in entries {
+                    body.push_str(&format!(
+                        "<text:list-item><text:p text:style-name=\"Entry_20_List\">{}</text:p></text:list-item>\n",
+                        text(entry)
+                    ));
+                }
+                body.push_str("</text:list>\n");
+            }
+            Block::Term { term, text: value } => body.push_str(&format!(
+                "<text:p text:style-name=\"{}\"><text:span text:style-name=\"Term\">{}:</text:span> {}</text:p>\n",
+                style,
+                text(term),
+                text(value)
+            )),
+        }
+    }
+}
+
+/// Escaped paragraph text. ODF collapses newlines like HTML does, so
+/// they become explicit line breaks.
+fn text(value: &str) -> String {
+    escape_html(value.trim()).replace('\n', "<text:line-break/>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::outline::{OutlineItem, OutlineSection};
+
+    #[test]
+    fn content_uses_named_styles() {
+        let outline = Outline {
+            sections: vec![OutlineSection {
+                name: "experience".to_string(),
+                title: Some("Experience".to_string()),
+                items: vec![OutlineItem {
+                    anchor: Some("acme".to_string()),
+                    heading: Some("Engineer at <Acme>".to_string()),
+                    blocks: vec![
+                        Block::Text("Line one\nLine two".to_string()),
+                        Block::List(vec!["R&D".to_string()]),
+                    ],
+                    ..Default::default()
+                }],
+            }],
+        };
+        let xml = content(&outline);
+
+        assert!(xml.contains("<text:h text:style-name=\"Section_20_Title\" text:outline-level=\"1\">Experience</text:h>"));
+        assert!(xml.contains("<text:bookmark text:name=\"acme\"/>Engineer at &lt;Acme&gt;</text:h>"));
+        assert!(xml.contains(">Line one<text:line-break/>Line two</text:p>"));
+        assert!(xml.contains("<text:list text:style-name=\"Resume_20_Bullets\">"));
+        assert!(xml.contains(">R&amp;D</text:p></text:list-item>"));
+    }
+}
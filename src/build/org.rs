@@ -0,0 +1,112 @@
+//! Org-mode export (`--format org`).
+//!
+//! Each layout section becomes a top-level heading and each entry a
+//! second-level heading, so the document folds and navigates like any
+//! other Org file. Dated entries carry a `:PROPERTIES:` drawer with
+//! their dates and the same `CUSTOM_ID` the themed HTML uses as an
+//! anchor, which keeps `[[#acme-corp]]` links working in both.
+
+use super::outline::{one_line, Block, Outline, OutlineItem};
+
+/// Render the outline as an Org document.
+pub fn render(outline: &Outline) -> String {
+    let mut org = String::new();
+
+    if let Some(name) = outline.name() {
+        org.push_str(&format!("#+TITLE: {}\n", one_line(name)));
+        org.push_str(&format!("#+AUTHOR: {}\n", one_line(name)));
+    }
+
+    for section in &outline.sections {
+        match &section.title {
+            // The person header's name is the document title; the
+            // rest of it is body text before the first heading.
+            None => {
+                for item in &section.items {
+                    push_blocks(&mut org, &item.blocks);
+                }
+            }
+            Some(title) => {
+                org.push_str(&format!("\n* {}\n", one_line(title)));
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        org.push_str(&format!("** {}\n", one_line(heading)));
+                        push_drawer(&mut org, item);
+                    }
+                    push_blocks(&mut org, &item.blocks);
+                }
+            }
+        }
+    }
+
+    org
+}
+
+fn push_drawer(org: &mut String, item: &OutlineItem) {
+    let mut properties = Vec::new();
+    if let Some(anchor) = &item.anchor {
+        properties.push(("CUSTOM_ID", anchor));
+    }
+    // `:END:` closes the drawer, so the dates can't use that name.
+    if let Some(start) = &item.start {
+        properties.push(("START_DATE", start));
+    }
+    if let Some(end) = &item.end {
+        properties.push(("END_DATE", end));
+    }
+    if properties.is_empty() {
+        return;
+    }
+
+    org.push_str(":PROPERTIES:\n");
+    for (key, value) in properties {
+        org.push_str(&format!(":{}: {}\n", key, one_line(value)));
+    }
+    org.push_str(":END:\n");
+}
+
+fn push_blocks(org: &mut String, blocks: &[Block]) {
+    for block in blocks {
+        match block {
+            Block::Text(text) => {
+                org.push_str(&body_line(text));
+                org.push('\n');
+            }
+            Block::Link { href, text } => {
+                org.push_str(&link(href, text));
+                org.push('\n');
+            }
+            Block::List(entries) => {
+                for entry in entries {
+                    let mut lines = entry.lines();
+                    org.push_str(&format!("- {}\n", lines.next().unwrap_or_default().trim()));
+                    for line in lines {
+                        org.push_str(&format!("  {}\n", line.trim()));
+                    }
+                }
+            }
+            Block::Term { term, text } => {
+                org.push_str(&format!("- {} :: {}\n", one_line(term), one_line(text)));
+            }
+        }
+    }
+}
+
+fn link(href: &str, text: &str) -> String {
+    if href == text {
+        format!("[[{}]]", href)
+    } else {
+        format!("[[{}][{}]]", href, one_line(text))
+    }
+}
+
+/// A line of body text. A leading `*` would turn it into a heading,
+/// so such lines are indented by one space.
+fn body_line(text: &str) -> String {
+    let line = one_line(text);
+    if line.starts_with('*') {
+        format!(" {}", line)
+    } else {
+        line
+    }
+}
@@ -0,0 +1,182 @@
+//! Format-neutral view of a rendered resume.
+//!
+//! The themed HTML page writes the whole [`Resolved`] tree because it
+//! needs containers, classes, and element choices. Text-oriented
+//! exports only need to know *what* the layout shows and in which
+//! order, so they share this outline of the same tree instead:
+//! sections in layout order, each item's fields in layout order,
+//! containers flattened.
+
+use jobl::JoblDocument;
+
+use super::resolved::{Content, Node, Resolved, Role};
+use super::Extras;
+use crate::layout::{Layout, Section};
+
+/// A resume reduced to headings, text, links, and lists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outline {
+    pub sections: Vec<OutlineSection>,
+}
+
+/// One layout section with content. Sections with nothing to show
+/// (no experience entries, no summary) are left out entirely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineSection {
+    /// Layout section name: "person", "experience", ...
+    pub name: String,
+    /// Display heading. `None` for the person header, which is
+    /// introduced by the person's name instead.
+    pub title: Option<String>,
+    pub items: Vec<OutlineItem>,
+}
+
+/// One entry in a section: the person header, a job, a project, a
+/// degree, or a skills category.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutlineItem {
+    /// Same id the themed HTML uses for this item, if it has one.
+    pub anchor: Option<String>,
+    /// The item's name line (person name, job title, project name,
+    /// degree) when the layout shows it.
+    pub heading: Option<String>,
+    pub blocks: Vec<Block>,
+    /// Raw start and end dates for dated entries (experience and
+    /// education), whether or not the layout shows them. Exports
+    /// that keep metadata alongside the text use these.
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// A piece of item content, in layout order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// A line of plain text: a single field or a mixed line such as
+    /// `start " - " end`.
+    Text(String),
+    /// A URL field (website, github, linkedin, project url).
+    Link { href: String, text: String },
+    /// A bulleted field (highlights, details).
+    List(Vec<String>),
+    /// A labelled value, e.g. a skills category and its items.
+    Term { term: String, text: String },
+}
+
+impl Outline {
+    /// Build the outline for `doc` as `layout` would show it. The
+    /// `toc` section is skipped: every export has its own notion of
+    /// navigation. So are `grouped` and `functional`, which only
+    /// rearrange the themed page: exports list each entry on its own.
+    pub fn build(doc: &JoblDocument, layout: &Layout, extras: &Extras) -> Self {
+        let layout = Layout {
+            sections: layout
+                .sections
+                .iter()
+                .map(|section| Section { modifiers: Vec::new(), ..section.clone() })
+                .collect(),
+            ..layout.clone()
+        };
+        Self::from_resolved(&Resolved::build(doc, &layout, extras))
+    }
+
+    /// Reduce a resolved document to its outline.
+    pub fn from_resolved(resolved: &Resolved) -> Self {
+        let mut sections = Vec::new();
+
+        for section in resolved.sections.iter().filter(|s| s.kind != "toc") {
+            let heading = match section.kind.as_str() {
+                "person" | "projects" => Some("name"),
+                "experience" | "talks" => Some("title"),
+                "education" => Some("degree"),
+                _ => None,
+            };
+            let items: Vec<OutlineItem> = if section.nodes.iter().any(|n| matches!(n.role, Role::Item { .. })) {
+                section
+                    .nodes
+                    .iter()
+                    .filter_map(|node| match &node.role {
+                        Role::Item { start, end } => Some(OutlineItem {
+                            anchor: node.id.clone(),
+                            start: start.clone(),
+                            end: end.clone(),
+                            ..build_item(node.children(), heading)
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            } else {
+                vec![build_item(&section.nodes, heading)]
+            };
+
+            let items: Vec<OutlineItem> = items
+                .into_iter()
+                .filter(|item| item.heading.is_some() || !item.blocks.is_empty())
+                .collect();
+            if items.is_empty() {
+                continue;
+            }
+
+            sections.push(OutlineSection {
+                name: section.name.clone(),
+                title: section.title.clone(),
+                items,
+            });
+        }
+
+        Self { sections }
+    }
+
+    /// The person's name, if the layout shows a person header.
+    pub fn name(&self) -> Option<&str> {
+        self.sections
+            .iter()
+            .find(|s| s.name == "person")
+            .and_then(|s| s.items.first())
+            .and_then(|item| item.heading.as_deref())
+    }
+}
+
+/// Turn one item's nodes into blocks, with containers flattened in
+/// place. The first `heading` field becomes the item heading; links
+/// become links; lists become lists; anything else is a line of
+/// text.
+fn build_item(nodes: &[Node], heading: Option<&str>) -> OutlineItem {
+    let mut item = OutlineItem::default();
+    push_nodes(&mut item, nodes, heading);
+    item
+}
+
+fn push_nodes(item: &mut OutlineItem, nodes: &[Node], heading: Option<&str>) {
+    for node in nodes {
+        match &node.role {
+            Role::None => push_nodes(item, node.children(), heading),
+            Role::Item { .. } => {}
+            Role::Field(name) if Some(name.as_str()) == heading && item.heading.is_none() => {
+                item.heading = Some(node.text_content());
+            }
+            Role::Field(_) => {
+                if matches!(node.element.as_str(), "ul" | "ol") {
+                    item.blocks.push(Block::List(node.children().iter().map(Node::text_content).collect()));
+                } else if let Some((href, text)) = node.link_target() {
+                    item.blocks.push(Block::Link { href: href.to_string(), text });
+                } else if node.content != Content::Void {
+                    item.blocks.push(Block::Text(node.text_content()));
+                }
+            }
+            Role::Line => {
+                let text = node.text_content().trim().to_string();
+                if !text.is_empty() {
+                    item.blocks.push(Block::Text(text));
+                }
+            }
+            Role::Term { term, text } => item.blocks.push(Block::Term { term: term.clone(), text: text.clone() }),
+        }
+    }
+}
+
+/// Collapse whitespace, including line breaks, to single spaces.
+/// Line-oriented formats use this so a value can't spill onto a new
+/// line and be read as markup (a heading, a link line).
+pub fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,120 @@
+//! PDF/A output (`--pdf-profile pdfa`).
+//!
+//! Some application portals only accept archival PDFs. The profile is
+//! PDF/A-2b: every font embedded, colors tied to an sRGB output
+//! intent, and XMP metadata alongside the document info. (PDF/A-2
+//! allows transparency, so watermarks and translucent theme colors
+//! survive as they are.)
+//!
+//! Typst writes PDF/A itself (`--pdf-standard a-2b`). Chrome can't,
+//! so its PDF is rewritten by Ghostscript, which converts colors,
+//! writes the XMP metadata, and adds the output intent from a small
+//! PostScript prologue.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The standard Typst is asked for.
+pub const TYPST_STANDARD: &str = "a-2b";
+
+/// Where Ghostscript's own sRGB profile lives on common installs.
+/// `*` is the Ghostscript version.
+const ICC_LOCATIONS: &[&str] = &[
+    "/usr/share/color/icc/ghostscript/srgb.icc",
+    "/usr/share/ghostscript/*/iccprofiles/srgb.icc",
+    "/usr/local/share/ghostscript/*/iccprofiles/srgb.icc",
+    "/opt/homebrew/share/ghostscript/*/iccprofiles/srgb.icc",
+];
+
+/// Rewrite `pdf` as PDF/A-2b with the `gs` CLI, in a scratch
+/// directory that's removed afterwards.
+pub fn convert(pdf: &[u8]) -> Result<Vec<u8>> {
+    let dir = tempfile::TempDir::new().context("Failed to create a scratch directory for Ghostscript")?;
+    let input = dir.path().join("print.pdf");
+    let output = dir.path().join("resume.pdf");
+    let prologue = dir.path().join("pdfa.ps");
+    std::fs::write(&input, pdf).context("Failed to write the PDF for Ghostscript")?;
+
+    let icc = icc_profile();
+    let icc_name = match &icc {
+        Some(path) => path.display().to_string(),
+        // Builds with their resources compiled in keep them here.
+        None => "%rom%iccprofiles/srgb.icc".to_string(),
+    };
+    std::fs::write(&prologue, definitions(&icc_name)).context("Failed to write the PDF/A definitions")?;
+
+    let mut gs = Command::new("gs");
+    gs.args(["-q", "-dBATCH", "-dNOPAUSE", "-dPDFA=2", "-dPDFACompatibilityPolicy=1"])
+        .args(["-sDEVICE=pdfwrite", "-sColorConversionStrategy=RGB"])
+        .arg(format!("-sOutputFile={}", output.display()));
+    if let Some(path) = &icc {
+        gs.arg(format!("--permit-file-read={}", path.display()));
+    }
+    let result = gs
+        .arg(&prologue)
+        .arg(&input)
+        .output()
+        .context("Failed to run gs. Install Ghostscript (https://ghostscript.com) for --pdf-profile pdfa, or use --pdf-backend typst")?;
+    if !result.status.success() {
+        let message = [result.stderr, result.stdout].concat();
+        anyhow::bail!("Ghostscript couldn't write PDF/A:\n{}", String::from_utf8_lossy(&message).trim());
+    }
+    std::fs::read(&output).context("Failed to read the PDF Ghostscript wrote")
+}
+
+/// The first sRGB profile found in [`ICC_LOCATIONS`], newest
+/// Ghostscript first, or the one `SRG_ICC_PROFILE` names.
+fn icc_profile() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("SRG_ICC_PROFILE") {
+        return Some(PathBuf::from(path));
+    }
+    ICC_LOCATIONS.iter().find_map(|pattern| match pattern.split_once("/*/") {
+        None => Some(PathBuf::from(pattern)).filter(|p| p.is_file()),
+        Some((root, rest)) => {
+            let mut versions: Vec<PathBuf> =
+                std::fs::read_dir(root).ok()?.flatten().map(|entry| entry.path().join(rest)).collect();
+            versions.sort();
+            versions.into_iter().rev().find(|p| p.is_file())
+        }
+    })
+}
+
+/// The PostScript prologue that gives the document its sRGB output
+/// intent, after Ghostscript's own `PDFA_def.ps`.
+fn definitions(icc: &str) -> String {
+    format!(
+        "%!
+/ICCProfile ({}) def
+[/_objdef {{icc_PDFA}} /type /stream /OBJ pdfmark
+[{{icc_PDFA}} << /N 3 >> /PUT pdfmark
+[{{icc_PDFA}} ICCProfile (r) file /PUT pdfmark
+[/_objdef {{OutputIntent_PDFA}} /type /dict /OBJ pdfmark
+[{{OutputIntent_PDFA}} <<
+  /Type /OutputIntent
+  /S /GTS_PDFA1
+  /DestOutputProfile {{icc_PDFA}}
+  /OutputConditionIdentifier (sRGB)
+>> /PUT pdfmark
+[{{Catalog}} << /OutputIntents [ {{OutputIntent_PDFA}} ] >> /PUT pdfmark
+",
+        postscript_string(icc)
+    )
+}
+
+/// `text` escaped for a PostScript `(...)` string.
+fn postscript_string(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prologue_names_the_profile_safely() {
+        let ps = definitions(r"C:\gs (x64)\srgb.icc");
+        assert!(ps.contains(r"/ICCProfile (C:\\gs \(x64\)\\srgb.icc) def"));
+        assert!(ps.contains("/OutputIntents [ {OutputIntent_PDFA} ]"));
+    }
+}
@@ -0,0 +1,457 @@
+//! QR codes for the verification stamp (see [`super::Verification`]).
+//!
+//! A minimal encoder: byte mode at error correction level M, versions
+//! 1 to 10, which holds up to 213 bytes, plenty for a URL. Like the
+//! zip writer, it's hand-rolled because one small, fixed use doesn't
+//! justify a dependency. The structure follows ISO/IEC 18004: data
+//! and Reed-Solomon codewords in interleaved blocks, placed in the
+//! zigzag order around the function patterns, under whichever of the
+//! eight masks scores the lowest penalty.
+
+use anyhow::Result;
+
+/// Highest version supported. Version 10 is 57 modules square.
+const MAX_VERSION: usize = 10;
+
+/// Error correction codewords per block, by version (level M).
+const EC_PER_BLOCK: [usize; MAX_VERSION] = [10, 16, 26, 18, 24, 16, 18, 22, 22, 26];
+
+/// Data codewords in each block, by version (level M). Blocks of the
+/// second size come after the first.
+const BLOCKS: [&[usize]; MAX_VERSION] = [
+    &[16],
+    &[28],
+    &[44],
+    &[32, 32],
+    &[43, 43],
+    &[27, 27, 27, 27],
+    &[31, 31, 31, 31],
+    &[38, 38, 39, 39],
+    &[36, 36, 36, 37, 37],
+    &[43, 43, 43, 43, 44],
+];
+
+/// Centers of the alignment patterns, by version; patterns sit at
+/// every pairing of these except where they'd cover a finder.
+const ALIGNMENT: [&[usize]; MAX_VERSION] = [
+    &[],
+    &[6, 18],
+    &[6, 22],
+    &[6, 26],
+    &[6, 30],
+    &[6, 34],
+    &[6, 22, 38],
+    &[6, 24, 42],
+    &[6, 26, 46],
+    &[6, 28, 50],
+];
+
+/// A QR code's modules, `true` for dark.
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `data` in the smallest version it fits.
+    pub fn encode(data: &[u8]) -> Result<Self> {
+        let version = (1..=MAX_VERSION)
+            .find(|&v| 4 + count_bits(v) + data.len() * 8 <= data_codewords(v) * 8)
+            .ok_or_else(|| {
+                anyhow::anyhow!("{} bytes is too long for a QR code here (at most 213)", data.len())
+            })?;
+
+        let mut qr = QrCode {
+            size: version * 4 + 17,
+            modules: vec![false; (version * 4 + 17).pow(2)],
+            function: vec![false; (version * 4 + 17).pow(2)],
+        };
+        qr.draw_function_patterns(version);
+        qr.place(&codewords(version, data));
+
+        let best = (0..8)
+            .min_by_key(|&mask| {
+                qr.apply_mask(mask);
+                qr.draw_format(mask);
+                let penalty = qr.penalty();
+                qr.apply_mask(mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(best);
+        qr.draw_format(best);
+        Ok(qr)
+    }
+
+    /// Whether the module at column `x`, row `y` is dark.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// The code as an SVG image `px` pixels square, with the
+    /// four-module quiet zone scanners expect.
+    pub fn svg(&self, px: u32) -> String {
+        let side = self.size + 8;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.get(x, y) {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x + 4, y + 4));
+                }
+            }
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {side} {side}\" width=\"{px}\" height=\"{px}\" \
+             shape-rendering=\"crispEdges\"><rect width=\"{side}\" height=\"{side}\" fill=\"#fff\"/>\
+             <path d=\"{path}\" fill=\"#000\"/></svg>",
+            side = side,
+            px = px,
+            path = path
+        )
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            // The finder plus its light separator, clipped at the edge.
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let ring = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, ring != 2 && ring != 4);
+                    }
+                }
+            }
+        }
+
+        let centers = ALIGNMENT[version - 1];
+        let last = centers.len().saturating_sub(1);
+        // Every corner but the bottom right has a finder.
+        let edge = |k: usize| k == 0 || k == last;
+        let finder = |i: usize, j: usize| edge(i) && edge(j) && (i == 0 || j == 0);
+        for (i, &cx) in centers.iter().enumerate() {
+            for (j, &cy) in centers.iter().enumerate() {
+                if finder(i, j) {
+                    continue;
+                }
+                for dy in -2i32..=2 {
+                    for dx in -2i32..=2 {
+                        let ring = dx.abs().max(dy.abs());
+                        self.set_function((cx as i32 + dx) as usize, (cy as i32 + dy) as usize, ring != 1);
+                    }
+                }
+            }
+        }
+
+        // Reserve the format areas; the real bits go in per mask.
+        self.draw_format(0);
+
+        if version >= 7 {
+            let bits = version_bits(version);
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    /// The format information (level M, `mask`), in both copies, and
+    /// the dark module beside the lower one.
+    fn draw_format(&mut self, mask: usize) {
+        let size = self.size;
+        let bits = format_bits(mask);
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Lay the codewords into the non-function modules: two-column
+    /// strips from the right edge, alternately upward and downward,
+    /// skipping the vertical timing pattern.
+    fn place(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let total = codewords.len() * 8;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < total {
+                        self.modules[y * size + x] = (codewords[i / 8] >> (7 - i % 8)) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    /// XOR the data modules with `mask`; applying it twice undoes it.
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let index = y * self.size + x;
+                if flip && !self.function[index] {
+                    self.modules[index] = !self.modules[index];
+                }
+            }
+        }
+    }
+
+    /// The standard's penalty score: long runs, 2×2 blocks,
+    /// finder-like patterns, and an unbalanced share of dark modules.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut score = 0;
+
+        let lines = (0..size).flat_map(|i| {
+            let row: Vec<bool> = (0..size).map(|x| self.get(x, i)).collect();
+            let column: Vec<bool> = (0..size).map(|y| self.get(i, y)).collect();
+            [row, column]
+        });
+        for line in lines {
+            let mut run = 1;
+            for k in 1..=line.len() {
+                if k < line.len() && line[k] == line[k - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    score += run - 2;
+                }
+                run = 1;
+            }
+            const FINDER: [bool; 7] = [true, false, true, true, true, false, true];
+            for k in 0..line.len().saturating_sub(6) {
+                if line[k..k + 7] == FINDER {
+                    let light = |range: std::ops::Range<usize>| range.clone().all(|i| !line[i]);
+                    if (k >= 4 && light(k - 4..k)) || (k + 11 <= line.len() && light(k + 7..k + 11)) {
+                        score += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.get(x, y);
+                if dark == self.get(x + 1, y) && dark == self.get(x, y + 1) && dark == self.get(x + 1, y + 1) {
+                    score += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let k = (dark * 20).abs_diff(total * 10).div_ceil(total).saturating_sub(1);
+        score + k * 10
+    }
+}
+
+/// Length of the byte-mode character count field.
+fn count_bits(version: usize) -> usize {
+    if version < 10 {
+        8
+    } else {
+        16
+    }
+}
+
+fn data_codewords(version: usize) -> usize {
+    BLOCKS[version - 1].iter().sum()
+}
+
+/// The final codeword sequence: the mode, length, data, and padding,
+/// split into blocks, each followed by its error correction, and
+/// interleaved.
+fn codewords(version: usize, data: &[u8]) -> Vec<u8> {
+    let capacity = data_codewords(version);
+    let mut bits: Vec<bool> = Vec::with_capacity(capacity * 8);
+    let mut push = |value: usize, len: usize| {
+        for i in (0..len).rev() {
+            bits.push((value >> i) & 1 == 1);
+        }
+    };
+    push(0b0100, 4);
+    push(data.len(), count_bits(version));
+    for &byte in data {
+        push(byte as usize, 8);
+    }
+    let terminator = (capacity * 8 - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    while !bits.len().is_multiple_of(8) {
+        bits.push(false);
+    }
+
+    let mut bytes: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |byte, &bit| (byte << 1) | bit as u8))
+        .collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if bytes.len() == capacity {
+            break;
+        }
+        bytes.push(pad);
+    }
+
+    let divisor = rs_divisor(EC_PER_BLOCK[version - 1]);
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    for &len in BLOCKS[version - 1] {
+        let block = &bytes[start..start + len];
+        blocks.push((block, rs_remainder(block, &divisor)));
+        start += len;
+    }
+
+    let mut out = Vec::new();
+    let longest = BLOCKS[version - 1].iter().copied().max().unwrap_or(0);
+    for i in 0..longest {
+        out.extend(blocks.iter().filter_map(|(block, _)| block.get(i)));
+    }
+    for i in 0..EC_PER_BLOCK[version - 1] {
+        out.extend(blocks.iter().map(|(_, ec)| ec[i]));
+    }
+    out
+}
+
+/// The 15 format bits for level M and `mask`, BCH-protected and
+/// XORed so they're never all light.
+fn format_bits(mask: usize) -> u32 {
+    // Level M is 00.
+    let data = mask as u32;
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    ((data << 10) | rem) ^ 0x5412
+}
+
+/// The 18 version bits for version 7 and up.
+fn version_bits(version: usize) -> u32 {
+    let data = version as u32;
+    let mut rem = data;
+    for _ in 0..12 {
+        rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+    }
+    (data << 12) | rem
+}
+
+/// Multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+/// The Reed-Solomon generator polynomial of `degree`, highest term
+/// first and its leading 1 left out.
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_mul(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+    result
+}
+
+/// The error correction codewords for `data`.
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_correction_matches_the_standard_example() {
+        // "HELLO WORLD" at 1-M, from the worked example in common
+        // QR references.
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17];
+        assert_eq!(rs_remainder(&data, &rs_divisor(10)), [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn format_and_version_bits() {
+        assert_eq!(format!("{:015b}", format_bits(0)), "101010000010010");
+        assert_eq!(format!("{:015b}", format_bits(5)), "100000011001110");
+        assert_eq!(format!("{:018b}", version_bits(7)), "000111110010010100");
+    }
+
+    #[test]
+    fn picks_the_smallest_version() {
+        let qr = QrCode::encode(b"https://example.com/resume").unwrap();
+        assert_eq!(qr.size, 25);
+        // Finder corners and the dark module.
+        assert!(qr.get(0, 0) && qr.get(24, 0) && qr.get(0, 24) && qr.get(8, 17));
+        assert!(!qr.get(7, 7));
+
+        let qr = QrCode::encode(&[b'a'; 200]).unwrap();
+        assert_eq!(qr.size, 57);
+        assert!(QrCode::encode(&[b'a'; 214]).is_err());
+    }
+}
@@ -0,0 +1,1120 @@
+//! The resolved document: a resume as its layout shows it, before
+//! any format writes it down.
+//!
+//! [`Resolved::build`] is the one place that decides what each layout
+//! line shows: which value a field name means, which element and
+//! classes it gets, which literals survive missing values, which
+//! items a `[2-]` line applies to. The result is a tree of sections
+//! and nodes carrying plain text. The themed page (and so the PDF)
+//! writes the tree as HTML; the text exports read it through
+//! [`super::outline::Outline`].
+
+use jobl::JoblDocument;
+
+use super::booking::{Booking, ICS_FILE};
+use super::{get_person_field_value, highlights_by_skill, ordered_skills, slugify, Anchors, Extras, PrintLinks};
+use crate::dates::Locale;
+use crate::layout::{Field, FieldOrContainer, FieldPart, Layout, Section};
+use crate::sections::{AliasSection, Kind, PatentItem, TalkItem};
+
+/// Every section the layout shows, in layout order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resolved {
+    pub sections: Vec<ResolvedSection>,
+}
+
+/// One layout section. Sections with nothing to show (no experience
+/// entries, no summary) are left out, except the person header,
+/// which the themed page always has.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSection {
+    /// Layout section name, also the element id: "experience",
+    /// "research", ...
+    pub name: String,
+    /// The built-in section it renders as. Differs from `name` only
+    /// for alias sections.
+    pub kind: String,
+    /// "header", "section", or "nav".
+    pub element: &'static str,
+    pub classes: Vec<String>,
+    /// Heading above the section. `None` for the person header.
+    pub title: Option<String>,
+    pub nodes: Vec<Node>,
+}
+
+/// An element and what's in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub element: String,
+    pub classes: Vec<String>,
+    pub id: Option<String>,
+    /// Other attributes (`href`, `src`, ...), unescaped.
+    pub attributes: Vec<(&'static str, String)>,
+    pub content: Content,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+    /// Text and inline elements, written on one line.
+    Inline(Vec<Inline>),
+    /// Child elements, each on a line of its own.
+    Block(Vec<Node>),
+    /// Nothing, and no end tag (`img`).
+    Void,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    /// A line break inside a value (a multi-line highlight).
+    Break,
+    Element(Node),
+}
+
+/// What a node stands for, for formats that don't keep the markup.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Role {
+    /// Structure only: a container, a group, a list entry.
+    None,
+    /// A job, project, or degree, with its raw dates.
+    Item { start: Option<String>, end: Option<String> },
+    /// A layout line that's one bare field (`title`, `highlights`).
+    Field(String),
+    /// Any other layout line: mixed literals and values, or a
+    /// `contactrow`.
+    Line,
+    /// A labelled value: a skills category and its items.
+    Term { term: String, text: String },
+}
+
+impl Node {
+    fn new(element: &str, classes: Vec<String>, content: Content) -> Self {
+        Node {
+            element: element.to_string(),
+            classes,
+            id: None,
+            attributes: Vec::new(),
+            content,
+            role: Role::None,
+        }
+    }
+
+    /// An element holding one piece of text.
+    pub(super) fn text(element: &str, classes: Vec<String>, text: &str) -> Self {
+        Node::new(element, classes, Content::Inline(vec![Inline::Text(text.to_string())]))
+    }
+
+    fn link(classes: Vec<String>, href: &str, text: &str) -> Self {
+        Node::text("a", classes, text).with_attribute("href", href)
+    }
+
+    fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    fn with_attribute(mut self, name: &'static str, value: &str) -> Self {
+        self.attributes.push((name, value.to_string()));
+        self
+    }
+
+    fn with_role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// The node's text with the markup dropped. Block children that
+    /// have text are joined with " · ", which is how a `contactrow`
+    /// reads as one line.
+    pub fn text_content(&self) -> String {
+        match &self.content {
+            Content::Inline(inlines) => inlines
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(text) => text.clone(),
+                    Inline::Break => "\n".to_string(),
+                    Inline::Element(node) => node.text_content(),
+                })
+                .collect(),
+            Content::Block(children) => children
+                .iter()
+                .map(Node::text_content)
+                .filter(|text| !text.trim().is_empty())
+                .collect::<Vec<_>>()
+                .join(" · "),
+            Content::Void => String::new(),
+        }
+    }
+
+    /// The URL and text of the link this node is, or wraps.
+    pub fn link_target(&self) -> Option<(&str, String)> {
+        if let Some(href) = self.attribute("href") {
+            return Some((href, self.text_content()));
+        }
+        match &self.content {
+            Content::Inline(inlines) => inlines.iter().find_map(|inline| match inline {
+                Inline::Element(node) => node.link_target(),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Add `data-skills` to the experience items `tags` lists, wherever
+    /// they are in this node.
+    fn tag_skills(&mut self, tags: &[(String, String)]) {
+        if let Some((_, skills)) = tags.iter().find(|(anchor, _)| self.id.as_ref() == Some(anchor)) {
+            self.attributes.push(("data-skills", skills.clone()));
+        }
+        if let Content::Block(children) = &mut self.content {
+            children.iter_mut().for_each(|child| child.tag_skills(tags));
+        }
+    }
+
+    pub fn children(&self) -> &[Node] {
+        match &self.content {
+            Content::Block(children) => children,
+            _ => &[],
+        }
+    }
+
+    fn print_links(&mut self, links: PrintLinks) {
+        let Some(href) = self.attribute("href").map(str::to_string) else {
+            match &mut self.content {
+                Content::Block(children) => children.iter_mut().for_each(|child| child.print_links(links)),
+                Content::Inline(inlines) => {
+                    for inline in inlines {
+                        if let Inline::Element(node) = inline {
+                            node.print_links(links);
+                        }
+                    }
+                }
+                Content::Void => {}
+            }
+            return;
+        };
+        match links {
+            PrintLinks::Strip => {
+                self.element = "span".to_string();
+                self.attributes.retain(|(name, _)| *name != "href");
+            }
+            PrintLinks::Url => {
+                let address = printed_address(&href);
+                let text = self.text_content();
+                if !href.starts_with('#') && text != address && text != href {
+                    if let Content::Inline(inlines) = &mut self.content {
+                        let url = format!(" ({})", address);
+                        inlines.push(Inline::Element(Node::text("span", vec!["srg-link-url".to_string()], &url)));
+                    }
+                }
+            }
+            PrintLinks::Underline | PrintLinks::Plain => {}
+        }
+    }
+}
+
+/// `href` as a reader would type it: no `mailto:`, `tel:`, or web
+/// scheme, and no trailing slash.
+fn printed_address(href: &str) -> &str {
+    let address = ["mailto:", "tel:", "https://", "http://"]
+        .iter()
+        .find_map(|scheme| href.strip_prefix(scheme))
+        .unwrap_or(href);
+    address.strip_suffix('/').unwrap_or(address)
+}
+
+impl Resolved {
+    /// Rewrite the links for a printed page (see [`PrintLinks`]).
+    pub fn print_links(&mut self, links: PrintLinks) {
+        for node in self.sections.iter_mut().flat_map(|section| &mut section.nodes) {
+            node.print_links(links);
+        }
+    }
+
+    /// Resolve `doc` as `layout` shows it. Alias sections come from
+    /// `extras`.
+    pub fn build(doc: &JoblDocument, layout: &Layout, extras: &Extras) -> Self {
+        let anchors = Anchors::for_document(doc);
+        let mut sections = Vec::new();
+
+        for section in &layout.sections {
+            let resolved = match section.name.as_str() {
+                "toc" => Some(toc_section(doc, layout, &anchors, extras)),
+                "person" => Some(person_section(doc, section, extras)),
+                "summary" => summary_section(doc),
+                "skills" => skills_section(doc, section, extras, &anchors),
+                "experience" => {
+                    let heading = Heading::builtin("experience", "Experience");
+                    experience_section(doc, section, &heading, &anchors.experience, layout.settings.locale)
+                }
+                "projects" => {
+                    let heading = Heading::builtin("projects", "Projects");
+                    projects_section(doc, section, &heading, &anchors.projects, extras)
+                }
+                "education" => {
+                    let heading = Heading::builtin("education", "Education");
+                    education_section(doc, section, &heading, &anchors.education, layout.settings.locale)
+                }
+                "talks" => talks_section(&extras.talks, section),
+                "patents" => patents_section(&extras.patents, section),
+                name => extras.section(name).and_then(|alias| alias_section(doc, section, alias, extras, layout.settings.locale)),
+            };
+            sections.extend(resolved);
+        }
+
+        for section in &layout.sections {
+            let Some(keep) = section.collapse() else { continue };
+            if let Some(resolved) = sections.iter_mut().find(|s| s.name == section.name) {
+                resolved.collapse(keep);
+            }
+        }
+
+        if skills_linked(layout) {
+            let tags = skill_tags(doc, &anchors);
+            for node in sections.iter_mut().filter(|s| s.name == "experience").flat_map(|s| &mut s.nodes) {
+                node.tag_skills(&tags);
+            }
+        }
+
+        Self { sections }
+    }
+}
+
+impl ResolvedSection {
+    /// Mark the entries after the first `keep` as `srg-more`, hidden
+    /// on screen until the toggle added after them is clicked. Print
+    /// shows everything.
+    fn collapse(&mut self, keep: usize) {
+        let hidden = self.nodes.len().saturating_sub(keep);
+        if hidden == 0 {
+            return;
+        }
+        for node in &mut self.nodes[keep..] {
+            node.classes.push("srg-more".to_string());
+        }
+        let label = format!("Show {} more", hidden);
+        let toggle = Node::text("button", vec!["srg-toggle".to_string()], &label)
+            .with_attribute("type", "button")
+            .with_attribute("aria-expanded", "false");
+        self.nodes.push(toggle);
+    }
+}
+
+/// Whether the layout uses `collapse=N` on any section.
+pub fn any_collapsed(layout: &Layout) -> bool {
+    layout.sections.iter().any(|s| s.collapse().is_some())
+}
+
+/// Whether the layout's `person` section shows `field`.
+pub fn shows_person_field(layout: &Layout, field: &str) -> bool {
+    layout
+        .sections
+        .iter()
+        .filter(|s| s.name == "person")
+        .flat_map(|s| &s.fields)
+        .any(|entry| mentions_field(entry, field))
+}
+
+/// Whether the layout asks for `skills linked`: each skill a link to
+/// the experience entries whose `technologies` list it.
+pub fn skills_linked(layout: &Layout) -> bool {
+    layout.sections.iter().any(|s| s.name == "skills" && s.has_modifier("linked"))
+}
+
+/// Each experience entry's anchor with its technologies as slugs, the
+/// `data-skills` a skill link matches against.
+fn skill_tags(doc: &JoblDocument, anchors: &Anchors) -> Vec<(String, String)> {
+    doc.experience
+        .iter()
+        .zip(&anchors.experience)
+        .filter(|(exp, _)| !exp.technologies.is_empty())
+        .map(|(exp, anchor)| {
+            let slugs: Vec<String> = exp.technologies.iter().map(|tech| slugify(tech)).collect();
+            (anchor.clone(), slugs.join(" "))
+        })
+        .collect()
+}
+
+/// Classes for a built-in field: the renderer's own plus any the
+/// layout gave the line, so custom CSS can target layout-defined
+/// structure without losing the theme's hooks.
+fn classes(base: &str, field: &Field) -> Vec<String> {
+    let mut classes = vec![base.to_string()];
+    classes.extend(field.class_name.clone());
+    classes
+}
+
+/// A built-in field as `<tag class="...">text</tag>`. The layout's
+/// element choice (`small: location`) replaces the default tag.
+fn element(default_tag: &str, base_class: &str, field: &Field, text: &str) -> Node {
+    let tag = field.element.as_deref().unwrap_or(default_tag);
+    Node::text(tag, classes(base_class, field), text)
+}
+
+/// A link field. Without an element override it's a bare `<a>`;
+/// with one, the link is wrapped so the element carries the classes
+/// and the anchor stays clickable.
+fn link(base_class: &str, field: &Field, href: &str, text: &str) -> Node {
+    match field.element.as_deref() {
+        Some(tag) => Node::new(
+            tag,
+            classes(base_class, field),
+            Content::Inline(vec![Inline::Element(Node::link(Vec::new(), href, text))]),
+        ),
+        None => Node::link(classes(base_class, field), href, text),
+    }
+}
+
+/// A bulleted list field, or `None` when there's nothing in it.
+/// Only list elements (`ol`, `ul`) are honored as overrides since
+/// anything else can't contain `<li>`.
+fn list(base_class: &str, field: &Field, items: &[String]) -> Option<Node> {
+    if items.is_empty() {
+        return None;
+    }
+    let tag = field
+        .element
+        .as_deref()
+        .filter(|tag| matches!(*tag, "ol" | "ul"))
+        .unwrap_or("ul");
+    let entries = items
+        .iter()
+        .map(|item| {
+            let mut inlines = Vec::new();
+            for (i, line) in item.split('\n').enumerate() {
+                if i > 0 {
+                    inlines.push(Inline::Break);
+                }
+                inlines.push(Inline::Text(line.to_string()));
+            }
+            Node::new("li", Vec::new(), Content::Inline(inlines))
+        })
+        .collect();
+    Some(Node::new(tag, classes(base_class, field), Content::Block(entries)))
+}
+
+/// A line mixing literals and field values, e.g. `start " - " end`.
+/// Literals next to missing values are dropped (see
+/// [`Field::mixed_text`]), and a line with no values at all is
+/// `None`.
+fn mixed(field: &Field, lookup: impl Fn(&str) -> Option<String>) -> Option<Node> {
+    if let Some(split) = field.split {
+        return split_row(field, split, lookup);
+    }
+    let text = field.mixed_text(lookup)?;
+    let tag = field.element.as_deref().unwrap_or("p");
+    let role = match single_field_name(field) {
+        Some(name) => Role::Field(name.to_string()),
+        None => Role::Line,
+    };
+    Some(Node::text(tag, field.class_name.iter().cloned().collect(), &text).with_role(role))
+}
+
+/// A `split` line: the left-hand side, then the right-hand side
+/// pushed to the far edge of the same row (title left, dates right).
+/// Each side drops its literals on its own, and a side with nothing
+/// to show is left out; the theme-independent CSS that lays out the
+/// row is added to the page whenever a layout has one.
+fn split_row(field: &Field, split: usize, lookup: impl Fn(&str) -> Option<String>) -> Option<Node> {
+    let side = |parts: &[FieldPart], class: &str| {
+        let text = Field { helpers: field.helpers.clone(), ..Field::new(parts.to_vec()) }.mixed_text(&lookup)?;
+        (!text.trim().is_empty()).then(|| Node::text("span", vec![class.to_string()], &text))
+    };
+    let sides: Vec<Node> = [side(&field.parts[..split], "split-left"), side(&field.parts[split..], "split-right")]
+        .into_iter()
+        .flatten()
+        .collect();
+    if sides.is_empty() {
+        return None;
+    }
+    let tag = field.element.as_deref().unwrap_or("div");
+    Some(Node::new(tag, classes("split-row", field), Content::Block(sides)).with_role(Role::Line))
+}
+
+/// The field name when a layout line is a single bare field, which is
+/// when a section uses its built-in markup for it.
+fn single_field_name(field: &Field) -> Option<&str> {
+    match field.parts.as_slice() {
+        [FieldPart::Field(name)] if !field.contact_row && field.split.is_none() => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+/// Resolve one layout entry with `resolve` for its fields, a
+/// container becoming a `<div>` around its fields.
+fn entry(entry: &FieldOrContainer, resolve: &impl Fn(&Field) -> Option<Node>) -> Option<Node> {
+    match entry {
+        FieldOrContainer::Field(field) => resolve(field),
+        FieldOrContainer::Container(container) => Some(Node::new(
+            "div",
+            vec![container.class_name.clone()],
+            Content::Block(container.fields.iter().filter_map(resolve).collect()),
+        )),
+    }
+}
+
+/// Whether a layout entry (or any field in a container) uses `name`.
+fn mentions_field(entry: &FieldOrContainer, name: &str) -> bool {
+    let uses = |field: &Field| {
+        field
+            .parts
+            .iter()
+            .any(|part| matches!(part, FieldPart::Field(f) if f == name))
+    };
+    match entry {
+        FieldOrContainer::Field(field) => uses(field),
+        FieldOrContainer::Container(container) => container.fields.iter().any(uses),
+    }
+}
+
+/// A table of contents linking to each section that follows in the
+/// layout and to the items inside it. Sections without content are
+/// skipped, matching what the section builders leave out.
+fn toc_section(doc: &JoblDocument, layout: &Layout, anchors: &Anchors, extras: &Extras) -> ResolvedSection {
+    let mut entries = Vec::new();
+
+    for section in &layout.sections {
+        let alias_anchors;
+        let talk_anchors;
+        let (title, items): (&str, Vec<(&str, &String)>) = match section.name.as_str() {
+            "summary" if doc.person.summary.is_some() => ("Summary", Vec::new()),
+            "skills" if doc.skills.as_ref().is_some_and(|s| !s.is_empty()) => ("Skills", Vec::new()),
+            "experience" if !doc.experience.is_empty() => (
+                "Experience",
+                doc.experience.iter().map(|e| e.company.as_str()).zip(&anchors.experience).collect(),
+            ),
+            "projects" if !doc.projects.is_empty() => (
+                "Projects",
+                doc.projects.iter().map(|p| p.name.as_str()).zip(&anchors.projects).collect(),
+            ),
+            "education" if !doc.education.is_empty() => (
+                "Education",
+                doc.education.iter().map(|e| e.institution.as_str()).zip(&anchors.education).collect(),
+            ),
+            "talks" if !extras.talks.is_empty() => {
+                talk_anchors = crate::sections::own_anchors("talks", extras.talks.iter().map(|t| t.title.as_str()));
+                ("Talks", extras.talks.iter().map(|t| t.title.as_str()).zip(&talk_anchors).collect())
+            }
+            "patents" if !extras.patents.is_empty() => ("Patents", Vec::new()),
+            name => match extras.section(name) {
+                Some(alias) if !alias.is_empty() => {
+                    alias_anchors = alias.anchors(&alias.view(doc));
+                    (alias.title.as_str(), alias.labels().into_iter().zip(&alias_anchors).collect())
+                }
+                _ => continue,
+            },
+        };
+
+        let section_link = Node::link(Vec::new(), &format!("#{}", section.name), title);
+        let entry = if items.is_empty() {
+            Node::new("li", Vec::new(), Content::Inline(vec![Inline::Element(section_link)]))
+        } else {
+            let items = items
+                .into_iter()
+                .map(|(label, anchor)| {
+                    let link = Node::link(Vec::new(), &format!("#{}", anchor), label);
+                    Node::new("li", Vec::new(), Content::Inline(vec![Inline::Element(link)]))
+                })
+                .collect();
+            let items = Node::new("ul", vec!["toc-items".to_string()], Content::Block(items));
+            Node::new("li", Vec::new(), Content::Block(vec![section_link, items]))
+        };
+        entries.push(entry);
+    }
+
+    ResolvedSection {
+        name: "toc".to_string(),
+        kind: "toc".to_string(),
+        element: "nav",
+        classes: vec!["section".to_string(), "section-toc".to_string()],
+        title: Some("Contents".to_string()),
+        nodes: vec![Node::new("ul", vec!["toc-sections".to_string()], Content::Block(entries))],
+    }
+}
+
+fn person_section(doc: &JoblDocument, section: &Section, extras: &Extras) -> ResolvedSection {
+    let resolve = |field: &Field| person_field(doc, field, extras);
+    ResolvedSection {
+        name: "person".to_string(),
+        kind: "person".to_string(),
+        element: "header",
+        classes: vec!["section".to_string(), "section-person".to_string()],
+        title: None,
+        nodes: section.fields.iter().filter_map(|e| entry(e, &resolve)).collect(),
+    }
+}
+
+fn person_field(doc: &JoblDocument, field: &Field, extras: &Extras) -> Option<Node> {
+    if field.contact_row {
+        return contact_row(doc, field, extras);
+    }
+
+    let lookup = |name: &str| extras.person_field(doc, name);
+    let Some(name) = single_field_name(field) else {
+        return mixed(field, lookup);
+    };
+    let person = &doc.person;
+    let class = format!("person-{}", name);
+    let node = match name {
+        "name" => element("h1", &class, field, &person.name),
+        "headline" | "summary" => {
+            let value = if name == "headline" { &person.headline } else { &person.summary };
+            element("p", &class, field, value.as_ref()?)
+        }
+        "email" | "phone" | "location" => {
+            element("span", &class, field, &field.shown(name, &get_person_field_value(doc, name)?))
+        }
+        "website" | "github" | "linkedin" => {
+            let url = get_person_field_value(doc, name)?;
+            link(&class, field, &url, &field.shown(name, &url))
+        }
+        "avatar" => Node::new("img", classes(&class, field), Content::Void)
+            .with_attribute("src", extras.avatar.as_ref()?)
+            .with_attribute("alt", &person.name),
+        "booking" => booking(&class, field, extras.booking.as_ref()?),
+        // srg's own fields and `[person.meta]`.
+        _ => element("p", &class, field, &lookup(name)?),
+    };
+    Some(node.with_role(Role::Field(name.to_string())))
+}
+
+/// The `booking` block: the link to the calendaring page, then, when
+/// `[booking]` has office hours, the hours and a link to their
+/// calendar file.
+fn booking(base_class: &str, field: &Field, booking: &Booking) -> Node {
+    let mut children = vec![Node::link(vec!["booking-link".to_string()], &booking.url, booking.label())];
+    if let Some(hours) = booking.hours_text() {
+        children.push(Node::text("span", vec!["booking-hours".to_string()], &hours));
+        children.push(Node::link(vec!["booking-calendar".to_string()], ICS_FILE, "Add to calendar"));
+    }
+    let tag = field.element.as_deref().unwrap_or("div");
+    Node::new(tag, classes(base_class, field), Content::Block(children))
+}
+
+/// A `contactrow` line: each item that has a value, in order, with a
+/// divider between neighbours. The divider's text is the theme's
+/// (`contact-divider`, "·" by default), drawn by CSS so it never
+/// outlives the items around it. A row with no values is `None`.
+fn contact_row(doc: &JoblDocument, field: &Field, extras: &Extras) -> Option<Node> {
+    let mut items = Vec::new();
+    for part in &field.parts {
+        let item = match part {
+            FieldPart::Field(name) => {
+                let Some(value) = extras.person_field(doc, name).filter(|v| !v.trim().is_empty()) else { continue };
+                let class = vec![format!("person-{}", name)];
+                let shown = field.shown(name, &value);
+                match name.as_str() {
+                    "website" | "github" | "linkedin" => Node::link(class, &value, &shown),
+                    _ => Node::text("span", class, &shown),
+                }
+            }
+            FieldPart::Literal(text) | FieldPart::Variable { value: Some(text), .. } => {
+                Node::text("span", vec!["contact-text".to_string()], text)
+            }
+            FieldPart::Variable { value: None, .. } => continue,
+        };
+        if !items.is_empty() {
+            let divider = Node::new("span", vec!["contact-divider".to_string()], Content::Inline(Vec::new()));
+            items.push(divider.with_attribute("aria-hidden", "true"));
+        }
+        items.push(item);
+    }
+    if items.is_empty() {
+        return None;
+    }
+
+    let tag = field.element.as_deref().unwrap_or("p");
+    Some(Node::new(tag, classes("contact-row", field), Content::Block(items)).with_role(Role::Line))
+}
+
+fn summary_section(doc: &JoblDocument) -> Option<ResolvedSection> {
+    let summary = doc.person.summary.as_ref()?;
+    Some(ResolvedSection {
+        name: "summary".to_string(),
+        kind: "summary".to_string(),
+        element: "section",
+        classes: vec!["section".to_string(), "section-summary".to_string()],
+        title: Some("Summary".to_string()),
+        nodes: vec![Node::text("p", vec!["summary-text".to_string()], summary).with_role(Role::Field("summary".to_string()))],
+    })
+}
+
+fn skills_section(doc: &JoblDocument, section: &Section, extras: &Extras, anchors: &Anchors) -> Option<ResolvedSection> {
+    if doc.skills.as_ref().is_none_or(|skills| skills.is_empty()) {
+        return None;
+    }
+    let linked = section.has_modifier("linked");
+    let nodes = ordered_skills(doc, section, extras)
+        .into_iter()
+        .map(|(category, items)| {
+            let text = items.join(", ");
+            let skills = if linked {
+                Node::new("span", vec!["skills-items".to_string()], Content::Inline(skill_links(doc, anchors, items)))
+            } else {
+                Node::text("span", vec!["skills-items".to_string()], &text)
+            };
+            let content = vec![
+                Inline::Element(Node::text("strong", vec!["skills-category-name".to_string()], &format!("{}:", category))),
+                Inline::Text(" ".to_string()),
+                Inline::Element(skills),
+            ];
+            Node::new("p", vec!["skills-category".to_string()], Content::Inline(content))
+                .with_role(Role::Term { term: category.clone(), text })
+        })
+        .collect();
+    Some(ResolvedSection {
+        name: "skills".to_string(),
+        kind: "skills".to_string(),
+        element: "section",
+        classes: vec!["section".to_string(), "section-skills".to_string()],
+        title: Some("Skills".to_string()),
+        nodes,
+    })
+}
+
+/// A category's skills for `skills linked`, comma-separated. A skill
+/// some experience entry lists under `technologies` links to the
+/// first such entry; the page's script highlights the rest.
+fn skill_links(doc: &JoblDocument, anchors: &Anchors, skills: &[String]) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for skill in skills {
+        if !inlines.is_empty() {
+            inlines.push(Inline::Text(", ".to_string()));
+        }
+        let first = doc
+            .experience
+            .iter()
+            .zip(&anchors.experience)
+            .find(|(exp, _)| exp.technologies.iter().any(|tech| tech.eq_ignore_ascii_case(skill)));
+        inlines.push(match first {
+            Some((_, anchor)) => Inline::Element(
+                Node::link(vec!["skills-link".to_string()], &format!("#{}", anchor), skill)
+                    .with_attribute("data-skill", &slugify(skill)),
+            ),
+            None => Inline::Text(skill.clone()),
+        });
+    }
+    inlines
+}
+
+/// Id and title of a list section: its own for `experience`, the
+/// JOBL file's for an alias section (`research` as experience).
+struct Heading<'a> {
+    kind: &'a str,
+    id: &'a str,
+    title: &'a str,
+}
+
+impl<'a> Heading<'a> {
+    fn builtin(kind: &'a str, title: &'a str) -> Self {
+        Heading { kind, id: kind, title }
+    }
+
+    /// The section around `nodes`. An alias section keeps its kind's
+    /// class, so the theme styles it the same.
+    fn section(&self, nodes: Vec<Node>) -> ResolvedSection {
+        let mut classes = vec!["section".to_string(), format!("section-{}", self.kind)];
+        if self.id != self.kind {
+            classes.push(format!("section-{}", self.id));
+        }
+        ResolvedSection {
+            name: self.id.to_string(),
+            kind: self.kind.to_string(),
+            element: "section",
+            classes,
+            title: Some(self.title.to_string()),
+            nodes,
+        }
+    }
+}
+
+/// An alias section, built by the builder of the section it's an
+/// alias of.
+fn alias_section(
+    doc: &JoblDocument,
+    section: &Section,
+    alias: &AliasSection,
+    extras: &Extras,
+    locale: Option<Locale>,
+) -> Option<ResolvedSection> {
+    let view = &alias.view(doc);
+    let anchors = alias.anchors(view);
+    let heading = Heading { kind: alias.kind().name(), id: &alias.name, title: &alias.title };
+    match alias.kind() {
+        Kind::Experience => experience_section(view, section, &heading, &anchors, locale),
+        Kind::Projects => {
+            // GitHub metadata is indexed by the document's own projects.
+            let extras = Extras { projects: Vec::new(), ..extras.clone() };
+            projects_section(view, section, &heading, &anchors, &extras)
+        }
+        Kind::Education => education_section(view, section, &heading, &anchors, locale),
+    }
+}
+
+/// An item's `<div>` around its resolved fields.
+fn item(classes: &[&str], anchor: &str, start: &Option<String>, end: &Option<String>, nodes: Vec<Node>) -> Node {
+    Node::new("div", classes.iter().map(|c| c.to_string()).collect(), Content::Block(nodes))
+        .with_id(anchor)
+        .with_role(Role::Item { start: start.clone(), end: end.clone() })
+}
+
+fn experience_section(
+    doc: &JoblDocument,
+    section: &Section,
+    heading: &Heading,
+    anchors: &[String],
+    locale: Option<Locale>,
+) -> Option<ResolvedSection> {
+    if doc.experience.is_empty() {
+        return None;
+    }
+
+    if section.has_modifier("grouped") {
+        return Some(heading.section(grouped_experience(doc, section, anchors, locale)));
+    }
+    if section.has_modifier("functional") {
+        return Some(heading.section(functional_experience(doc, section, anchors, locale)));
+    }
+
+    let nodes = doc
+        .experience
+        .iter()
+        .zip(anchors)
+        .enumerate()
+        .map(|(i, (exp, anchor))| {
+            let resolve = |field: &Field| experience_field(exp, field, locale);
+            let fields = section.for_item(i).fields.iter().filter_map(|e| entry(e, &resolve)).collect();
+            item(&["experience-item"], anchor, &exp.start, &exp.end, fields)
+        })
+        .collect();
+    Some(heading.section(nodes))
+}
+
+/// `experience grouped`: consecutive roles at the same company share
+/// one block. Layout entries that mention `company` show once in the
+/// group's header; everything else shows for each role.
+fn grouped_experience(doc: &JoblDocument, section: &Section, anchors: &[String], locale: Option<Locale>) -> Vec<Node> {
+    let items: Vec<_> = doc.experience.iter().zip(anchors).enumerate().collect();
+    items
+        .chunk_by(|(_, (a, _)), (_, (b, _))| a.company == b.company)
+        .map(|group| {
+            let (index, (first, _)) = group[0];
+            let resolve = |field: &Field| experience_field(first, field, locale);
+            let mut nodes: Vec<Node> = section
+                .for_item(index)
+                .fields
+                .iter()
+                .filter(|e| mentions_field(e, "company"))
+                .filter_map(|e| entry(e, &resolve))
+                .collect();
+
+            for &(i, (exp, anchor)) in group {
+                let resolve = |field: &Field| experience_field(exp, field, locale);
+                let fields = section
+                    .for_item(i)
+                    .fields
+                    .iter()
+                    .filter(|e| !mentions_field(e, "company"))
+                    .filter_map(|e| entry(e, &resolve))
+                    .collect();
+                nodes.push(item(&["experience-item", "experience-role"], anchor, &exp.start, &exp.end, fields));
+            }
+            Node::new("div", vec!["experience-group".to_string()], Content::Block(nodes))
+        })
+        .collect()
+}
+
+/// `experience functional`: highlights grouped by technology, then a
+/// short employment history (the layout's fields minus `highlights`).
+fn functional_experience(doc: &JoblDocument, section: &Section, anchors: &[String], locale: Option<Locale>) -> Vec<Node> {
+    let mut nodes: Vec<Node> = highlights_by_skill(doc)
+        .into_iter()
+        .map(|(skill, highlights)| {
+            let entries = highlights
+                .into_iter()
+                .map(|(exp, highlight)| {
+                    let source = format!("({}, {})", exp.title, exp.company);
+                    let content = vec![
+                        Inline::Text(format!("{} ", highlight)),
+                        Inline::Element(Node::text("span", vec!["experience-source".to_string()], &source)),
+                    ];
+                    Node::new("li", Vec::new(), Content::Inline(content))
+                })
+                .collect();
+            let children = vec![
+                Node::text("h3", vec!["experience-skill-name".to_string()], &skill),
+                Node::new("ul", vec!["experience-highlights".to_string()], Content::Block(entries)),
+            ];
+            Node::new("div", vec!["experience-skill".to_string()], Content::Block(children))
+        })
+        .collect();
+
+    let history = doc
+        .experience
+        .iter()
+        .zip(anchors)
+        .enumerate()
+        .map(|(i, (exp, anchor))| {
+            let resolve = |field: &Field| experience_field(exp, field, locale);
+            let fields = section
+                .for_item(i)
+                .fields
+                .iter()
+                .filter(|e| !mentions_field(e, "highlights"))
+                .filter_map(|e| entry(e, &resolve))
+                .collect();
+            item(&["experience-item"], anchor, &exp.start, &exp.end, fields)
+        })
+        .collect();
+    nodes.push(Node::new("div", vec!["experience-history".to_string()], Content::Block(history)));
+    nodes
+}
+
+fn experience_field(exp: &jobl::ExperienceItem, field: &Field, locale: Option<Locale>) -> Option<Node> {
+    if field.parts.is_empty() {
+        return None;
+    }
+    let Some(name) = single_field_name(field) else {
+        return mixed(field, |name| experience_field_value(exp, name, locale));
+    };
+    let node = match name {
+        "title" => element("h3", "experience-title", field, &exp.title),
+        "company" => element("p", "experience-company", field, &exp.company),
+        "summary" => element("p", "experience-summary", field, exp.summary.as_ref()?),
+        "highlights" => list("experience-highlights", field, &exp.highlights)?,
+        _ => return mixed(field, |name| experience_field_value(exp, name, locale)),
+    };
+    Some(node.with_role(Role::Field(name.to_string())))
+}
+
+fn experience_field_value(exp: &jobl::ExperienceItem, field: &str, locale: Option<Locale>) -> Option<String> {
+    match field {
+        "title" => Some(exp.title.clone()),
+        "company" => Some(exp.company.clone()),
+        "location" => exp.location.clone(),
+        "start" => exp.start.clone(),
+        "end" => exp.end.clone(),
+        "dates" => crate::dates::range(exp.start.as_deref(), exp.end.as_deref(), locale),
+        "summary" => exp.summary.clone(),
+        _ => None,
+    }
+}
+
+fn projects_section(
+    doc: &JoblDocument,
+    section: &Section,
+    heading: &Heading,
+    anchors: &[String],
+    extras: &Extras,
+) -> Option<ResolvedSection> {
+    if doc.projects.is_empty() {
+        return None;
+    }
+
+    let nodes = doc
+        .projects
+        .iter()
+        .zip(anchors)
+        .enumerate()
+        .map(|(i, (proj, anchor))| {
+            let lookup = |name: &str| project_field_value(proj, name).or_else(|| extras.project(i, name));
+            let resolve = |field: &Field| project_field(proj, field, &lookup);
+            let fields = section.for_item(i).fields.iter().filter_map(|e| entry(e, &resolve)).collect();
+            item(&["projects-item"], anchor, &None, &None, fields)
+        })
+        .collect();
+    Some(heading.section(nodes))
+}
+
+fn project_field(proj: &jobl::ProjectItem, field: &Field, lookup: &impl Fn(&str) -> Option<String>) -> Option<Node> {
+    if field.parts.is_empty() {
+        return None;
+    }
+    let Some(name) = single_field_name(field) else {
+        return mixed(field, lookup);
+    };
+    let node = match name {
+        "name" => element("h3", "projects-name", field, &proj.name),
+        "url" => {
+            let url = proj.url.as_ref()?;
+            let tag = field.element.as_deref().unwrap_or("p");
+            let link = Inline::Element(Node::link(Vec::new(), url, &field.shown(name, url)));
+            Node::new(tag, classes("projects-url", field), Content::Inline(vec![link]))
+        }
+        "summary" => element("p", "projects-summary", field, proj.summary.as_ref()?),
+        // Repository metadata (see `crate::github`).
+        "stars" | "language" | "description" => {
+            let tag = if name == "description" { "p" } else { "span" };
+            element(tag, &format!("projects-{}", name), field, &lookup(name)?)
+        }
+        _ => return mixed(field, lookup),
+    };
+    Some(node.with_role(Role::Field(name.to_string())))
+}
+
+fn project_field_value(proj: &jobl::ProjectItem, field: &str) -> Option<String> {
+    match field {
+        "name" => Some(proj.name.clone()),
+        "url" => proj.url.clone(),
+        "summary" => proj.summary.clone(),
+        _ => None,
+    }
+}
+
+fn talks_section(talks: &[TalkItem], section: &Section) -> Option<ResolvedSection> {
+    if talks.is_empty() {
+        return None;
+    }
+
+    let anchors = crate::sections::own_anchors("talks", talks.iter().map(|t| t.title.as_str()));
+    let nodes = talks
+        .iter()
+        .zip(&anchors)
+        .enumerate()
+        .map(|(i, (talk, anchor))| {
+            let resolve = |field: &Field| talk_field(talk, field);
+            let fields = section.for_item(i).fields.iter().filter_map(|e| entry(e, &resolve)).collect();
+            item(&["talks-item"], anchor, &talk.date, &None, fields)
+        })
+        .collect();
+    Some(Heading::builtin("talks", "Talks").section(nodes))
+}
+
+fn talk_field(talk: &TalkItem, field: &Field) -> Option<Node> {
+    let lookup = |name: &str| talk_field_value(talk, name);
+    let Some(name) = single_field_name(field) else {
+        return mixed(field, lookup);
+    };
+    let class = format!("talks-{}", name);
+    let node = match name {
+        "title" => element("h3", &class, field, &talk.title),
+        "event" | "date" | "location" => element("span", &class, field, &talk_field_value(talk, name)?),
+        // Links read "Slides" and "Video"; the address is the href.
+        "slides" | "video" => {
+            let text = if name == "slides" { "Slides" } else { "Video" };
+            link(&class, field, &talk_field_value(talk, name)?, text)
+        }
+        _ => return mixed(field, lookup),
+    };
+    Some(node.with_role(Role::Field(name.to_string())))
+}
+
+fn talk_field_value(talk: &TalkItem, field: &str) -> Option<String> {
+    match field {
+        "title" => Some(talk.title.clone()),
+        "event" => Some(talk.event.clone()),
+        "date" => talk.date.clone(),
+        "location" => talk.location.clone(),
+        "slides" => talk.slides.clone(),
+        "video" => talk.video.clone(),
+        _ => None,
+    }
+}
+
+/// Patents as a compact list: one `<li>` per patent holding its
+/// fields, which the page runs together on one line.
+fn patents_section(patents: &[PatentItem], section: &Section) -> Option<ResolvedSection> {
+    if patents.is_empty() {
+        return None;
+    }
+
+    let anchors = crate::sections::own_anchors("patents", patents.iter().map(|p| p.title.as_str()));
+    let items = patents
+        .iter()
+        .zip(&anchors)
+        .enumerate()
+        .map(|(i, (patent, anchor))| {
+            let resolve = |field: &Field| patent_field(patent, field);
+            let fields = section.for_item(i).fields.iter().filter_map(|e| entry(e, &resolve)).collect();
+            Node::new("li", vec!["patents-item".to_string()], Content::Block(fields))
+                .with_id(anchor)
+                .with_role(Role::Item { start: patent.year.clone(), end: None })
+        })
+        .collect();
+    let list = Node::new("ul", vec!["patents-list".to_string()], Content::Block(items))
+        .with_role(Role::Field("patents".to_string()));
+    Some(Heading::builtin("patents", "Patents").section(vec![list]))
+}
+
+/// A patent field. Everything is a `<span>`, mixed lines included,
+/// so a patent stays on one line.
+fn patent_field(patent: &PatentItem, field: &Field) -> Option<Node> {
+    let lookup = |name: &str| patent_field_value(patent, name);
+    let Some(name) = single_field_name(field) else {
+        let mut node = mixed(field, lookup)?;
+        if field.element.is_none() && node.element == "p" {
+            node.element = "span".to_string();
+        }
+        return Some(node);
+    };
+    let node = element("span", &format!("patents-{}", name), field, &lookup(name)?);
+    Some(node.with_role(Role::Field(name.to_string())))
+}
+
+fn patent_field_value(patent: &PatentItem, field: &str) -> Option<String> {
+    match field {
+        "title" => Some(patent.title.clone()),
+        "number" => patent.number.clone(),
+        "status" => patent.status.clone(),
+        "year" => patent.year.clone(),
+        _ => None,
+    }
+}
+
+fn education_section(
+    doc: &JoblDocument,
+    section: &Section,
+    heading: &Heading,
+    anchors: &[String],
+    locale: Option<Locale>,
+) -> Option<ResolvedSection> {
+    if doc.education.is_empty() {
+        return None;
+    }
+
+    let nodes = doc
+        .education
+        .iter()
+        .zip(anchors)
+        .enumerate()
+        .map(|(i, (edu, anchor))| {
+            let resolve = |field: &Field| education_field(edu, field, locale);
+            let fields = section.for_item(i).fields.iter().filter_map(|e| entry(e, &resolve)).collect();
+            item(&["education-item"], anchor, &edu.start, &edu.end, fields)
+        })
+        .collect();
+    Some(heading.section(nodes))
+}
+
+fn education_field(edu: &jobl::EducationItem, field: &Field, locale: Option<Locale>) -> Option<Node> {
+    if field.parts.is_empty() {
+        return None;
+    }
+    let Some(name) = single_field_name(field) else {
+        return mixed(field, |name| education_field_value(edu, name, locale));
+    };
+    let node = match name {
+        "degree" => element("h3", "education-degree", field, &edu.degree),
+        "institution" => element("p", "education-institution", field, &edu.institution),
+        "details" => list("education-details", field, &edu.details)?,
+        _ => return mixed(field, |name| education_field_value(edu, name, locale)),
+    };
+    Some(node.with_role(Role::Field(name.to_string())))
+}
+
+fn education_field_value(edu: &jobl::EducationItem, field: &str, locale: Option<Locale>) -> Option<String> {
+    match field {
+        "degree" => Some(edu.degree.clone()),
+        "institution" => Some(edu.institution.clone()),
+        "location" => edu.location.clone(),
+        "start" => edu.start.clone(),
+        "end" => edu.end.clone(),
+        "dates" => crate::dates::range(edu.start.as_deref(), edu.end.as_deref(), locale),
+        _ => None,
+    }
+}
@@ -0,0 +1,146 @@
+//! reStructuredText export (`--format rst`).
+//!
+//! The output is plain docutils markup, so it can be dropped into a
+//! Sphinx site as a page of its own. The person's name is the
+//! document title, layout sections are `=` headings, and entries are
+//! `-` headings preceded by a `.. _anchor:` target that matches the
+//! themed HTML's ids, so `:ref:` links can point at a single job.
+
+use super::outline::{one_line, Block, Outline};
+
+/// Render the outline as a reStructuredText document.
+pub fn render(outline: &Outline) -> String {
+    let mut rst = String::new();
+
+    for section in &outline.sections {
+        match &section.title {
+            None => {
+                for item in &section.items {
+                    if let Some(name) = &item.heading {
+                        push_title(&mut rst, name);
+                    }
+                    push_header_blocks(&mut rst, &item.blocks);
+                }
+            }
+            Some(title) => {
+                push_heading(&mut rst, title, '=');
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        if let Some(anchor) = &item.anchor {
+                            rst.push_str(&format!(".. _{}:\n\n", anchor));
+                        }
+                        push_heading(&mut rst, heading, '-');
+                    }
+                    push_blocks(&mut rst, &item.blocks);
+                }
+            }
+        }
+    }
+
+    rst
+}
+
+/// Document title, over- and underlined so it outranks every
+/// section heading.
+fn push_title(rst: &mut String, text: &str) {
+    let text = escape(&one_line(text));
+    let rule = "=".repeat(text.chars().count());
+    rst.push_str(&format!("{rule}\n{text}\n{rule}\n\n"));
+}
+
+fn push_heading(rst: &mut String, text: &str, underline: char) {
+    let text = escape(&one_line(text));
+    let rule = underline.to_string().repeat(text.chars().count());
+    rst.push_str(&format!("{text}\n{rule}\n\n"));
+}
+
+/// The person header: the first line of text is a paragraph (the
+/// headline); the contact fields after it form a line block so each
+/// keeps its own line.
+fn push_header_blocks(rst: &mut String, blocks: &[Block]) {
+    let mut blocks = blocks.iter().peekable();
+    if let Some(Block::Text(headline)) = blocks.peek() {
+        rst.push_str(&format!("{}\n\n", escape(&one_line(headline))));
+        blocks.next();
+    }
+
+    let mut any = false;
+    for block in blocks {
+        rst.push_str(&format!("| {}\n", inline(block)));
+        any = true;
+    }
+    if any {
+        rst.push('\n');
+    }
+}
+
+fn push_blocks(rst: &mut String, blocks: &[Block]) {
+    let mut in_fields = false;
+    for block in blocks {
+        // Consecutive terms form one field list; anything else closes it.
+        if in_fields && !matches!(block, Block::Term { .. }) {
+            rst.push('\n');
+            in_fields = false;
+        }
+        match block {
+            Block::List(entries) => {
+                for entry in entries {
+                    let mut lines = entry.lines();
+                    rst.push_str(&format!("- {}\n", escape(lines.next().unwrap_or_default().trim())));
+                    for line in lines {
+                        rst.push_str(&format!("  {}\n", escape(line.trim())));
+                    }
+                }
+                rst.push('\n');
+            }
+            Block::Term { term, text } => {
+                rst.push_str(&format!(
+                    ":{}: {}\n",
+                    escape(&one_line(term)).replace(':', "\\:"),
+                    escape(&one_line(text))
+                ));
+                in_fields = true;
+            }
+            _ => rst.push_str(&format!("{}\n\n", inline(block))),
+        }
+    }
+    if in_fields {
+        rst.push('\n');
+    }
+}
+
+/// Inline markup for a block that fits on one line.
+fn inline(block: &Block) -> String {
+    match block {
+        Block::Text(text) => escape(&one_line(text)),
+        // Bare URLs are recognized as links on their own.
+        Block::Link { href, text } if href == text => href.clone(),
+        // Anonymous (`__`) so two links with the same text don't
+        // clash as duplicate targets.
+        Block::Link { href, text } => format!("`{} <{}>`__", escape(&one_line(text)), href),
+        Block::List(entries) => escape(&one_line(&entries.join("; "))),
+        Block::Term { term, text } => {
+            format!("**{}:** {}", escape(&one_line(term)), escape(&one_line(text)))
+        }
+    }
+}
+
+/// Backslash-escape characters that start inline markup, plus a
+/// trailing `_` which would otherwise make a word a reference.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' | '*' | '`' | '|' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '_' if chars.peek().is_none_or(|next| !next.is_alphanumeric()) => {
+                out.push_str("\\_");
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
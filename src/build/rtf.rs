@@ -0,0 +1,218 @@
+//! Rich Text Format export (`--format rtf`).
+//!
+//! RTF is the one document format every word processor opens,
+//! including the old ones some HR departments still run. Like the ODT
+//! export, the layout's structure maps onto named paragraph styles
+//! ("Section Title", "Entry Heading", ...) in the file's style sheet,
+//! and entries carry a bookmark named after the anchor the themed
+//! HTML uses.
+//!
+//! The file is plain 7-bit text: anything outside ASCII is written as
+//! a `\u` escape with `?` as the fallback for readers that predate
+//! Unicode.
+
+use super::outline::{Block, Outline};
+use super::BuildOptions;
+
+/// Page margin when the build sets none, as for the ODT export.
+const DEFAULT_MARGIN: f64 = 0.6;
+
+/// Twips (1/1440 inch), RTF's unit of length.
+const TWIPS: f64 = 1440.0;
+
+/// The paragraph styles, numbered by position: name, what it's based
+/// on and followed by, and formatting. `size` is the body font size
+/// in half points.
+fn styles(size: i64) -> [(&'static str, &'static str, String); 7] {
+    [
+        ("Normal", "", "\\sa60".to_string()),
+        ("Resume Name", "\\sbasedon0\\snext2", format!("\\qc\\sa80\\b\\fs{}", size * 2)),
+        ("Resume Contact", "\\sbasedon0", "\\qc\\sa20".to_string()),
+        (
+            "Section Title",
+            "\\sbasedon0\\snext4",
+            format!("\\outlinelevel0\\keepn\\sb240\\sa80\\brdrb\\brdrs\\brdrw10\\brsp20\\b\\caps\\fs{}", size * 6 / 5),
+        ),
+        ("Entry Heading", "\\sbasedon0\\snext5", "\\outlinelevel1\\keepn\\sb120\\b".to_string()),
+        ("Entry Text", "\\sbasedon0", "\\sa60".to_string()),
+        ("Entry List", "\\sbasedon0", "\\fi-240\\li360\\sa20".to_string()),
+    ]
+}
+
+/// The character style for the label of a `Term: text` line.
+const TERM: &str = "{\\*\\cs7\\b Term;}";
+
+/// Render the outline as an RTF document.
+pub fn render(outline: &Outline, options: &BuildOptions) -> String {
+    let (width, height) = options.paper.size();
+    let m = options.margins;
+    let margin = |side: f64| if m.top + m.right + m.bottom + m.left == 0.0 { DEFAULT_MARGIN } else { side };
+    let twips = |inches: f64| (inches * TWIPS).round() as i64;
+    // RTF sizes are in half points.
+    let size = options
+        .font_size
+        .as_deref()
+        .and_then(|size| size.strip_suffix("pt")?.parse::<f64>().ok())
+        .filter(|size| *size > 0.0)
+        .unwrap_or(10.5);
+    let styles = styles((size * 2.0).round() as i64);
+
+    let mut out = String::from("{\\rtf1\\ansi\\ansicpg1252\\deff0\\uc1\n{\\fonttbl{\\f0\\fswiss Arial;}}\n");
+    out.push_str("{\\stylesheet\n");
+    for (number, (name, links, formatting)) in styles.iter().enumerate() {
+        out.push_str(&format!("{{\\s{}{}{} {};}}\n", number, links, formatting, name));
+    }
+    out.push_str(TERM);
+    out.push_str("\n}\n");
+    if let Some(name) = outline.name() {
+        out.push_str(&format!("{{\\info{{\\title {0}}}{{\\author {0}}}}}\n", text(name)));
+    }
+    out.push_str(&format!(
+        "\\paperw{}\\paperh{}\\margl{}\\margr{}\\margt{}\\margb{}\\fs{}\n",
+        twips(width),
+        twips(height),
+        twips(margin(m.left)),
+        twips(margin(m.right)),
+        twips(margin(m.top)),
+        twips(margin(m.bottom)),
+        (size * 2.0).round() as i64
+    ));
+
+    for section in &outline.sections {
+        match &section.title {
+            None => {
+                for item in &section.items {
+                    if let Some(name) = &item.heading {
+                        out.push_str(&paragraph(&styles, 1, &text(name)));
+                    }
+                    push_blocks(&mut out, &styles, &item.blocks, 2);
+                }
+            }
+            Some(title) => {
+                out.push_str(&paragraph(&styles, 3, &text(title)));
+                for item in &section.items {
+                    if let Some(heading) = &item.heading {
+                        let heading = match &item.anchor {
+                            Some(anchor) => format!(
+                                "{{\\*\\bkmkstart {0}}}{1}{{\\*\\bkmkend {0}}}",
+                                text(anchor),
+                                text(heading)
+                            ),
+                            None => text(heading),
+                        };
+                        out.push_str(&paragraph(&styles, 4, &heading));
+                    }
+                    push_blocks(&mut out, &styles, &item.blocks, 5);
+                }
+            }
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn push_blocks(out: &mut String, styles: &[(&str, &str, String)], blocks: &[Block], style: usize) {
+    for block in blocks {
+        match block {
+            Block::Text(value) => out.push_str(&paragraph(styles, style, &text(value))),
+            Block::Link { href, text: label } => out.push_str(&paragraph(
+                styles,
+                style,
+                &format!(
+                    "{{\\field{{\\*\\fldinst HYPERLINK \"{}\"}}{{\\fldrslt\\ul {}}}}}",
+                    text(href),
+                    text(label)
+                ),
+            )),
+            Block::List(entries) => {
+                for entry in entries {
+                    out.push_str(&paragraph(styles, 6, &format!("\\bullet\\tab {}", text(entry))));
+                }
+            }
+            Block::Term { term, text: value } => out.push_str(&paragraph(
+                styles,
+                style,
+                &format!("{{\\cs7\\b {}:}} {}", text(term), text(value)),
+            )),
+        }
+    }
+}
+
+/// One paragraph in style `style`. The style's formatting is repeated
+/// on the paragraph: readers apply what the paragraph says and only
+/// use the style sheet to name it.
+fn paragraph(styles: &[(&str, &str, String)], style: usize, content: &str) -> String {
+    format!("{{\\pard\\plain\\s{}{} {}\\par}}\n", style, styles[style].2, content)
+}
+
+/// Escaped paragraph text: RTF's special characters, everything
+/// outside ASCII as `\uN?` (UTF-16 code units, signed), and newlines
+/// as line breaks.
+fn text(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.trim().chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\line "),
+            '\t' => out.push_str("\\tab "),
+            ' '..='~' => out.push(c),
+            _ => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::outline::{OutlineItem, OutlineSection};
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(text("{a}\\b"), "\\{a\\}\\\\b");
+        assert_eq!(text("café\nZürich"), "caf\\u233?\\line Z\\u252?rich");
+        // Outside the BMP: a surrogate pair, each half signed.
+        assert_eq!(text("🚀"), "\\u-10179?\\u-8576?");
+    }
+
+    #[test]
+    fn content_uses_named_styles() {
+        let outline = Outline {
+            sections: vec![OutlineSection {
+                name: "experience".to_string(),
+                title: Some("Experience".to_string()),
+                items: vec![OutlineItem {
+                    anchor: Some("acme".to_string()),
+                    heading: Some("Engineer at Acme".to_string()),
+                    blocks: vec![
+                        Block::Link { href: "https://acme.test".to_string(), text: "acme.test".to_string() },
+                        Block::List(vec!["Cut costs".to_string()]),
+                        Block::Term { term: "Stack".to_string(), text: "Rust".to_string() },
+                    ],
+                    ..Default::default()
+                }],
+            }],
+        };
+        let rtf = render(&outline, &BuildOptions::default());
+
+        assert!(rtf.starts_with("{\\rtf1\\ansi"));
+        assert!(rtf.ends_with("}\n"));
+        assert!(rtf.contains("{\\s3\\sbasedon0\\snext4\\outlinelevel0"));
+        assert!(rtf.contains("{\\pard\\plain\\s3\\outlinelevel0\\keepn"));
+        assert!(rtf.contains(" Experience\\par}"));
+        assert!(rtf.contains("{\\*\\bkmkstart acme}Engineer at Acme{\\*\\bkmkend acme}\\par}"));
+        assert!(rtf.contains("{\\field{\\*\\fldinst HYPERLINK \"https://acme.test\"}{\\fldrslt\\ul acme.test}}"));
+        assert!(rtf.contains("\\s6\\fi-240\\li360\\sa20 \\bullet\\tab Cut costs\\par}"));
+        assert!(rtf.contains("{\\cs7\\b Stack:} Rust\\par}"));
+        // Braces balance, or readers reject the file.
+        assert_eq!(rtf.matches('{').count(), rtf.matches('}').count());
+    }
+}
@@ -0,0 +1,152 @@
+//! Font fallbacks for non-Latin scripts.
+//!
+//! Theme fonts cover Latin text. When a resume has a name or place
+//! in Chinese, Hindi, Arabic, and so on, Chrome falls back to
+//! whatever system font it finds first, which on a bare print server
+//! may be none (tofu boxes in the PDF). srg looks for those scripts
+//! in the document and appends fonts known to cover them to the
+//! theme's font knobs (`body-font`, `heading-font`, ...).
+
+use anyhow::Result;
+use jobl::JoblDocument;
+
+use super::BuildOptions;
+
+/// A script srg knows fallbacks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Script {
+    pub name: &'static str,
+    ranges: &'static [(u32, u32)],
+    /// Families to try, most widely installed first.
+    pub families: &'static [&'static str],
+}
+
+const SCRIPTS: &[Script] = &[
+    Script {
+        name: "CJK",
+        ranges: &[
+            (0x3000, 0x30FF), // CJK punctuation, hiragana, katakana
+            (0x3400, 0x4DBF), // CJK extension A
+            (0x4E00, 0x9FFF), // CJK unified ideographs
+            (0xF900, 0xFAFF), // CJK compatibility ideographs
+            (0xFF00, 0xFFEF), // half- and full-width forms
+        ],
+        families: &[
+            "Noto Sans CJK SC",
+            "Noto Sans CJK JP",
+            "Source Han Sans",
+            "PingFang SC",
+            "Hiragino Sans",
+            "Microsoft YaHei",
+            "Yu Gothic",
+        ],
+    },
+    Script {
+        name: "Hangul",
+        ranges: &[(0x1100, 0x11FF), (0x3130, 0x318F), (0xAC00, 0xD7AF)],
+        families: &["Noto Sans CJK KR", "Apple SD Gothic Neo", "Malgun Gothic"],
+    },
+    Script {
+        name: "Devanagari",
+        ranges: &[(0x0900, 0x097F), (0xA8E0, 0xA8FF)],
+        families: &["Noto Sans Devanagari", "Kohinoor Devanagari", "Nirmala UI", "Mangal"],
+    },
+    Script {
+        name: "Arabic",
+        ranges: &[(0x0600, 0x06FF), (0x0750, 0x077F), (0xFB50, 0xFDFF), (0xFE70, 0xFEFF)],
+        families: &["Noto Naskh Arabic", "Noto Sans Arabic", "Geeza Pro", "Segoe UI"],
+    },
+    Script {
+        name: "Hebrew",
+        ranges: &[(0x0590, 0x05FF)],
+        families: &["Noto Sans Hebrew", "Arial Hebrew", "Segoe UI"],
+    },
+    Script {
+        name: "Thai",
+        ranges: &[(0x0E00, 0x0E7F)],
+        families: &["Noto Sans Thai", "Thonburi", "Leelawadee UI"],
+    },
+];
+
+/// Scripts (from the ones srg knows) that appear in `doc`.
+pub fn detect(doc: &JoblDocument) -> Vec<Script> {
+    let texts = texts(doc);
+    SCRIPTS
+        .iter()
+        .filter(|script| {
+            texts.iter().any(|text| {
+                text.chars()
+                    .any(|c| script.ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&(c as u32))))
+            })
+        })
+        .copied()
+        .collect()
+}
+
+/// CSS redefining the theme's font knobs with fallbacks for the
+/// scripts in `doc` appended. Empty if the document is all Latin or
+/// the theme has no font knobs (with a warning in that case).
+pub fn fallback_css(doc: &JoblDocument, theme: Option<&str>, options: &BuildOptions) -> Result<String> {
+    let scripts = detect(doc);
+    if scripts.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut typography = match theme {
+        Some(theme) => crate::themes::ThemeStack::resolve(theme)?.typography()?,
+        None => Default::default(),
+    };
+    typography.extend(options.typography.clone());
+
+    let fallbacks: Vec<String> = scripts
+        .iter()
+        .flat_map(|script| script.families)
+        .map(|family| format!("\"{}\"", family))
+        .collect();
+    let vars: Vec<(String, String)> = typography
+        .into_iter()
+        .filter(|(name, value)| name.ends_with("-font") && !value.trim_start().starts_with("var("))
+        .map(|(name, value)| (format!("--srg-{}", name), format!("{}, {}", value, fallbacks.join(", "))))
+        .collect();
+
+    if vars.is_empty() {
+        let names: Vec<&str> = scripts.iter().map(|s| s.name).collect();
+        crate::diagnostics::warn(format!(
+            "the resume uses {} text but theme {} has no font settings to extend; \
+             add fallback fonts in custom CSS if the PDF shows boxes",
+            names.join(", "),
+            theme.unwrap_or("(none)")
+        ));
+    }
+    Ok(crate::css::root_rule("Script fallbacks", &vars))
+}
+
+/// Every piece of text in `doc` that ends up on the page.
+pub(crate) fn texts(doc: &JoblDocument) -> Vec<&str> {
+    let mut texts: Vec<&str> = Vec::new();
+    let person = &doc.person;
+    texts.push(&person.name);
+    texts.extend([&person.headline, &person.location, &person.summary].into_iter().flatten().map(String::as_str));
+
+    if let Some(skills) = &doc.skills {
+        for (category, items) in skills {
+            texts.push(category);
+            texts.extend(items.iter().map(String::as_str));
+        }
+    }
+    for exp in &doc.experience {
+        texts.extend([&exp.title, &exp.company].map(String::as_str));
+        texts.extend([&exp.location, &exp.summary].into_iter().flatten().map(String::as_str));
+        texts.extend(exp.highlights.iter().map(String::as_str));
+    }
+    for proj in &doc.projects {
+        texts.push(&proj.name);
+        texts.extend(proj.summary.as_deref());
+    }
+    for edu in &doc.education {
+        texts.extend([&edu.degree, &edu.institution].map(String::as_str));
+        texts.extend(edu.location.as_deref());
+        texts.extend(edu.details.iter().map(String::as_str));
+    }
+    texts
+}
@@ -0,0 +1,93 @@
+//! Plain-text export (`--format txt`).
+//!
+//! Meant for pasting into applicant tracking systems and other web
+//! forms that only take text: no markup, one field per line, section
+//! titles in capitals with a rule under them. Text wraps at `width`
+//! columns, list entries with a hanging indent; URLs are never
+//! broken. A width of 0 leaves lines as long as they are.
+
+use super::outline::{one_line, Block, Outline};
+
+/// Render the outline as plain text, wrapped at `width` columns.
+pub fn render(outline: &Outline, width: usize) -> String {
+    let mut txt = String::new();
+
+    for section in &outline.sections {
+        if !txt.is_empty() {
+            txt.push('\n');
+        }
+        if let Some(title) = &section.title {
+            let title = one_line(title).to_uppercase();
+            txt.push_str(&format!("{}\n{}\n", title, "-".repeat(title.chars().count())));
+        }
+        for (i, item) in section.items.iter().enumerate() {
+            // Blank line between entries, not before the first.
+            if i > 0 && item.heading.is_some() {
+                txt.push('\n');
+            }
+            if let Some(heading) = &item.heading {
+                let heading = one_line(heading);
+                if section.title.is_none() {
+                    txt.push_str(&heading.to_uppercase());
+                } else {
+                    txt.push_str(&heading);
+                }
+                txt.push('\n');
+            }
+            push_blocks(&mut txt, &item.blocks, width);
+        }
+    }
+
+    txt
+}
+
+fn push_blocks(txt: &mut String, blocks: &[Block], width: usize) {
+    for block in blocks {
+        match block {
+            Block::Text(text) => push_wrapped(txt, &one_line(text), width, "", ""),
+            Block::Link { href, text } if href == text => {
+                txt.push_str(href);
+                txt.push('\n');
+            }
+            Block::Link { href, text } => {
+                txt.push_str(&format!("{}: {}\n", one_line(text), href));
+            }
+            Block::List(entries) => {
+                for entry in entries {
+                    let mut lines = entry.lines();
+                    push_wrapped(txt, lines.next().unwrap_or_default().trim(), width, "- ", "  ");
+                    for line in lines {
+                        push_wrapped(txt, line.trim(), width, "  ", "  ");
+                    }
+                }
+            }
+            Block::Term { term, text } => {
+                push_wrapped(txt, &format!("{}: {}", one_line(term), one_line(text)), width, "", "  ");
+            }
+        }
+    }
+}
+
+/// Push `text` as lines of at most `width` columns, the first
+/// starting with `first` and the rest with `rest`. A word longer
+/// than a line (a URL) gets a line to itself rather than being cut.
+pub(super) fn push_wrapped(txt: &mut String, text: &str, width: usize, first: &str, rest: &str) {
+    let mut line = first.to_string();
+    let mut empty = true;
+    for word in text.split_whitespace() {
+        let columns = line.chars().count() + word.chars().count() + usize::from(!empty);
+        if width > 0 && !empty && columns > width {
+            txt.push_str(&line);
+            txt.push('\n');
+            line = rest.to_string();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    txt.push_str(&line);
+    txt.push('\n');
+}
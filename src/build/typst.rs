@@ -0,0 +1,197 @@
+//! Typst PDF backend (`--pdf-backend typst`).
+//!
+//! Chrome is a heavy dependency for a CI runner or a small server.
+//! This backend writes the layout's outline as a Typst document and
+//! compiles it with the `typst` CLI instead. Themes are CSS, so they
+//! don't apply: the document has a plain style of its own. The layout
+//! still decides what's shown and in which order, and the paper,
+//! margins, font size, footer, and watermark settings carry over.
+
+use anyhow::{Context, Result};
+use std::process::Command;
+
+use super::outline::{one_line, Block, Outline};
+use super::{BuildOptions, PdfProfile};
+
+/// Margin when the build sets none. Chrome's zero margins suit themes
+/// that pad the page in CSS; this document has nothing of the kind.
+const DEFAULT_MARGIN: f64 = 0.6;
+
+/// Render the outline as a Typst document.
+pub fn render(outline: &Outline, options: &BuildOptions) -> String {
+    let mut typ = String::new();
+    push_setup(&mut typ, options);
+
+    for section in &outline.sections {
+        match &section.title {
+            // The person header: name, then its lines, centered.
+            None => {
+                typ.push_str("\n#align(center)[\n");
+                for item in &section.items {
+                    if let Some(name) = &item.heading {
+                        typ.push_str(&format!("  #text(size: 1.8em, weight: \"bold\", {})\n\n", string(name)));
+                    }
+                    push_blocks(&mut typ, &item.blocks, "  ");
+                }
+                typ.push_str("]\n");
+            }
+            Some(title) => {
+                typ.push_str(&format!("\n#heading({})\n", string(title)));
+                for item in &section.items {
+                    typ.push('\n');
+                    if let Some(heading) = &item.heading {
+                        typ.push_str(&format!("#strong({})\n\n", string(heading)));
+                    }
+                    push_blocks(&mut typ, &item.blocks, "");
+                }
+            }
+        }
+    }
+
+    typ
+}
+
+fn push_setup(typ: &mut String, options: &BuildOptions) {
+    let (width, height) = options.paper.size();
+    let m = options.margins;
+    let margin = |side: f64| if m.top + m.right + m.bottom + m.left == 0.0 { DEFAULT_MARGIN } else { side };
+    typ.push_str(&format!(
+        "#set page(width: {}in, height: {}in, margin: (top: {}in, right: {}in, bottom: {}in, left: {}in))\n",
+        width,
+        height,
+        margin(m.top),
+        margin(m.right),
+        margin(m.bottom),
+        margin(m.left)
+    ));
+    // The verification line goes under the footer text. Typst has no
+    // QR codes without a package, so this PDF carries only the link.
+    let footer: Vec<String> = options
+        .footer
+        .iter()
+        .map(|footer| format!("#{}", string(footer)))
+        .chain(options.verify.iter().map(|verify| {
+            format!("#link({}, {})", string(&verify.url), string(&verify.line()))
+        }))
+        .collect();
+    if !footer.is_empty() {
+        typ.push_str(&format!(
+            "#set page(footer: align(center, text(size: 8pt, fill: luma(40%))[{}]))\n",
+            footer.join(" \\ ")
+        ));
+    }
+    if let Some(watermark) = &options.watermark {
+        typ.push_str(&format!(
+            "#set page(background: rotate(-45deg, text(size: 72pt, fill: luma(90%), {})))\n",
+            string(watermark)
+        ));
+    }
+    let size = options.font_size.as_deref().filter(|size| is_typst_length(size)).unwrap_or("10pt");
+    typ.push_str(&format!("#set text(size: {})\n", size));
+    typ.push_str("#set par(spacing: 0.6em)\n");
+    typ.push_str("#show heading: set block(above: 1.4em, below: 0.8em)\n");
+    typ.push_str("#show heading: it => upper(it.body) + v(-0.5em) + line(length: 100%, stroke: 0.5pt)\n");
+}
+
+fn push_blocks(typ: &mut String, blocks: &[Block], indent: &str) {
+    for block in blocks {
+        match block {
+            Block::Text(text) => typ.push_str(&format!("{}#{}\n\n", indent, string(text))),
+            Block::Link { href, text } => {
+                typ.push_str(&format!("{}#link({}, {})\n\n", indent, string(href), string(text)));
+            }
+            Block::List(entries) => {
+                for entry in entries {
+                    typ.push_str(&format!("{}- #{}\n", indent, string(entry)));
+                }
+                typ.push('\n');
+            }
+            Block::Term { term, text } => {
+                typ.push_str(&format!(
+                    "{}#strong({}) #{}\n\n",
+                    indent,
+                    string(&format!("{}:", term)),
+                    string(text)
+                ));
+            }
+        }
+    }
+}
+
+/// `text` as a Typst string literal, on one line. Text goes into the
+/// document as strings rather than markup so nothing in it (`#`, `*`,
+/// `_`, `$`, `<`) can be read as Typst syntax.
+fn string(text: &str) -> String {
+    format!("\"{}\"", one_line(text).replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Whether `size` is a length Typst reads as written (`10.5pt`).
+fn is_typst_length(size: &str) -> bool {
+    ["pt", "mm", "cm", "in"]
+        .iter()
+        .filter_map(|unit| size.strip_suffix(unit))
+        .any(|number| number.parse::<f64>().is_ok_and(|n| n > 0.0))
+}
+
+/// Compile a Typst document to PDF bytes with the `typst` CLI, in a
+/// scratch directory that's removed afterwards. Typst writes PDF/A
+/// itself.
+pub fn compile(source: &str, profile: PdfProfile) -> Result<Vec<u8>> {
+    let dir = tempfile::TempDir::new().context("Failed to create a scratch directory for Typst")?;
+    let input = dir.path().join("resume.typ");
+    let output = dir.path().join("resume.pdf");
+    std::fs::write(&input, source).context("Failed to write the Typst document")?;
+
+    let mut typst = Command::new("typst");
+    typst.arg("compile");
+    if profile == PdfProfile::Pdfa {
+        typst.args(["--pdf-standard", super::pdfa::TYPST_STANDARD]);
+    }
+    let result = typst
+        .arg(&input)
+        .arg(&output)
+        .output()
+        .context("Failed to run typst. Install it (https://github.com/typst/typst) or use --pdf-backend chrome")?;
+    if !result.status.success() {
+        anyhow::bail!("typst compile failed:\n{}", String::from_utf8_lossy(&result.stderr).trim());
+    }
+    std::fs::read(&output).context("Failed to read the PDF typst wrote")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::outline::{OutlineItem, OutlineSection};
+
+    #[test]
+    fn text_cannot_escape_its_string() {
+        assert_eq!(string("C# \"fast\" \\ #emph[x]"), "\"C# \\\"fast\\\" \\\\ #emph[x]\"");
+    }
+
+    #[test]
+    fn renders_sections_and_settings() {
+        let outline = Outline {
+            sections: vec![OutlineSection {
+                name: "experience".to_string(),
+                title: Some("Experience".to_string()),
+                items: vec![OutlineItem {
+                    heading: Some("Engineer".to_string()),
+                    blocks: vec![Block::List(vec!["Cut costs by 40%".to_string()])],
+                    ..Default::default()
+                }],
+            }],
+        };
+        let options = BuildOptions {
+            font_size: Some("11pt".to_string()),
+            footer: Some("Confidential".to_string()),
+            ..Default::default()
+        };
+        let typ = render(&outline, &options);
+
+        assert!(typ.contains("#set page(width: 8.5in, height: 11in, margin: (top: 0.6in,"));
+        assert!(typ.contains("#set text(size: 11pt)"));
+        assert!(typ.contains("\"Confidential\""));
+        assert!(typ.contains("#heading(\"Experience\")\n\n#strong(\"Engineer\")\n\n- #\"Cut costs by 40%\"\n"));
+        assert!(!is_typst_length("12px") && !is_typst_length("pt"));
+    }
+}
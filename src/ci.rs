@@ -0,0 +1,69 @@
+//! Builds for CI (`srg ci`).
+//!
+//! A workflow shouldn't have to repeat the resume's settings as flags,
+//! or read srg's output to find out what went wrong. `srg ci` takes
+//! everything, the resume included, from `srg.toml`, and reports as a
+//! workflow runner expects (see [`crate::diagnostics`]): warnings and
+//! errors as annotations on the lines they're about, and what was built
+//! as a JSON manifest. It never reads standard input, so nothing waits
+//! on an answer no one will give.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Where the manifest is written in the output directory, unless
+/// `--manifest` says otherwise.
+pub const MANIFEST_FILE: &str = "build-manifest.json";
+
+/// The resume `srg.toml` in `dir` names, or the directory's only
+/// `.jobl` file. `srg.toml` is read from the resume's own directory
+/// by every build, so the resume has to be next to it.
+pub fn find_input(dir: &Path) -> Result<PathBuf> {
+    let loaded = Config::load_in(dir)?
+        .with_context(|| format!("no srg.toml in {}; srg ci takes its settings from one", dir.display()))?;
+    if let Some(input) = &loaded.config.input {
+        let input = loaded.resolve(input);
+        if input.parent() != Some(loaded.base_dir.as_path()) {
+            bail!("input in srg.toml: {} isn't next to srg.toml, where its settings are read from", input.display());
+        }
+        return Ok(input);
+    }
+
+    let entries = std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))?;
+    let mut found: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jobl"))
+        .collect();
+    found.sort();
+    match found.as_slice() {
+        [input] => Ok(input.clone()),
+        [] => bail!("no .jobl file in {}; name the resume with input in srg.toml", dir.display()),
+        _ => bail!("{} .jobl files in {}; pick one with input in srg.toml", found.len(), dir.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_resume() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let dir = dir.path();
+        assert!(find_input(dir).unwrap_err().to_string().contains("no srg.toml"));
+
+        std::fs::write(dir.join("srg.toml"), "theme = \"minimal\"\n").unwrap();
+        assert!(find_input(dir).unwrap_err().to_string().contains("no .jobl file"));
+        std::fs::write(dir.join("resume.jobl"), "").unwrap();
+        assert_eq!(find_input(dir).unwrap(), dir.join("resume.jobl"));
+        std::fs::write(dir.join("short.jobl"), "").unwrap();
+        assert!(find_input(dir).unwrap_err().to_string().contains("2 .jobl files"));
+
+        std::fs::write(dir.join("srg.toml"), "input = \"short.jobl\"\n").unwrap();
+        assert_eq!(find_input(dir).unwrap(), dir.join("short.jobl"));
+        std::fs::write(dir.join("srg.toml"), "input = \"cv/short.jobl\"\n").unwrap();
+        assert!(find_input(dir).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! System clipboard access for `--copy`.
+//!
+//! There's no portable clipboard API in std, and the crates that
+//! provide one pull in a windowing stack. Every desktop already ships
+//! a command-line helper, so srg pipes the text into the first one it
+//! finds instead.
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard helpers to try, in order. Wayland's `wl-copy` comes
+/// first so it wins over an XWayland `xclip` on mixed sessions.
+fn candidates() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(target_os = "windows") {
+        vec![("clip", &[])]
+    } else {
+        let mut list: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            list.push(("wl-copy", &[]));
+        }
+        list.push(("xclip", &["-selection", "clipboard"]));
+        list.push(("xsel", &["--clipboard", "--input"]));
+        list
+    }
+}
+
+/// Put `text` on the system clipboard.
+pub fn copy(text: &str) -> Result<()> {
+    let candidates = candidates();
+    for (program, args) in &candidates {
+        let mut child = match Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            // Not installed; try the next one.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err).with_context(|| format!("running {}", program)),
+        };
+
+        child
+            .stdin
+            .take()
+            .context("clipboard helper has no stdin")?
+            .write_all(text.as_bytes())
+            .with_context(|| format!("writing to {}", program))?;
+
+        let status = child
+            .wait()
+            .with_context(|| format!("waiting for {}", program))?;
+        if !status.success() {
+            anyhow::bail!("{} exited with {}", program, status);
+        }
+        return Ok(());
+    }
+
+    let names: Vec<&str> = candidates.iter().map(|(program, _)| *program).collect();
+    anyhow::bail!("No clipboard helper found (tried {})", names.join(", "))
+}
@@ -0,0 +1,164 @@
+//! Side-by-side comparison of resume variants (`srg compare`).
+//!
+//! People keep several versions of a resume (one per kind of role,
+//! a short one, a long one) and have to pick one to send. The report
+//! puts them next to each other: how long each is, which sections it
+//! has, and which keywords from the job it covers.
+
+use jobl::JoblDocument;
+use std::path::Path;
+
+use crate::build::mentions_word;
+
+/// One variant's numbers for the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    /// Label shown in the report, usually the file name.
+    pub name: String,
+    pub words: usize,
+    pub roles: usize,
+    pub highlights: usize,
+    /// Sections with content, in layout order.
+    pub sections: Vec<&'static str>,
+    /// Whether each keyword (in the order given) appears anywhere in
+    /// the variant.
+    pub keywords: Vec<bool>,
+}
+
+/// Sections the report checks for, in the order it lists them.
+const SECTIONS: &[&str] = &["summary", "skills", "experience", "projects", "education"];
+
+impl Variant {
+    pub fn new(name: &str, doc: &JoblDocument, keywords: &[String]) -> Self {
+        let texts = text_of(doc);
+        let present = |section: &str| match section {
+            "summary" => doc.person.summary.as_ref().is_some_and(|s| !s.trim().is_empty()),
+            "skills" => doc.skills.as_ref().is_some_and(|s| !s.is_empty()),
+            "experience" => !doc.experience.is_empty(),
+            "projects" => !doc.projects.is_empty(),
+            "education" => !doc.education.is_empty(),
+            _ => false,
+        };
+
+        Variant {
+            name: name.to_string(),
+            words: texts.iter().map(|t| t.split_whitespace().count()).sum(),
+            roles: doc.experience.len(),
+            highlights: doc.experience.iter().map(|e| e.highlights.len()).sum(),
+            sections: SECTIONS.iter().copied().filter(|s| present(s)).collect(),
+            keywords: keywords
+                .iter()
+                .map(|k| texts.iter().any(|t| mentions_word(t, k)))
+                .collect(),
+        }
+    }
+
+    /// Share of keywords covered, 0–100. 100 when there are none.
+    pub fn coverage(&self) -> usize {
+        if self.keywords.is_empty() {
+            return 100;
+        }
+        self.keywords.iter().filter(|&&k| k).count() * 100 / self.keywords.len()
+    }
+}
+
+/// Keywords to compare on when none are given: every skill any
+/// variant lists, in first-seen order.
+pub fn default_keywords(docs: &[&JoblDocument]) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+    for skill in docs.iter().flat_map(|d| d.skills.iter().flatten()).flat_map(|(_, s)| s) {
+        if !keywords.iter().any(|k| k.eq_ignore_ascii_case(skill)) {
+            keywords.push(skill.clone());
+        }
+    }
+    keywords
+}
+
+/// Label for a variant file: its name without the extension.
+pub fn label(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// The report page.
+pub fn render_html(variants: &[Variant], keywords: &[String]) -> String {
+    let escape = crate::build::escape_html;
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n");
+    html.push_str("<html lang=\"en\">\n");
+    html.push_str("<head>\n");
+    html.push_str("  <meta charset=\"UTF-8\">\n");
+    html.push_str("  <title>Resume variants</title>\n");
+    html.push_str("  <style>\n");
+    html.push_str(REPORT_CSS);
+    html.push_str("  </style>\n");
+    html.push_str("</head>\n");
+    html.push_str("<body>\n");
+    html.push_str("  <h1>Resume variants</h1>\n");
+    html.push_str("  <table>\n");
+
+    html.push_str("    <thead>\n      <tr><th></th>");
+    for v in variants {
+        html.push_str(&format!("<th>{}</th>", escape(&v.name)));
+    }
+    html.push_str("</tr>\n    </thead>\n    <tbody>\n");
+
+    let mut row = |label: &str, class: &str, cells: Vec<String>| {
+        html.push_str(&format!("      <tr class=\"{}\"><th>{}</th>", class, escape(label)));
+        for cell in cells {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    };
+
+    row("Words", "length", variants.iter().map(|v| v.words.to_string()).collect());
+    row("Roles", "length", variants.iter().map(|v| v.roles.to_string()).collect());
+    row("Highlights", "length", variants.iter().map(|v| v.highlights.to_string()).collect());
+    for section in SECTIONS {
+        row(
+            &format!("Section: {}", section),
+            "section",
+            variants.iter().map(|v| mark(v.sections.contains(section))).collect(),
+        );
+    }
+    if !keywords.is_empty() {
+        row(
+            "Keyword coverage",
+            "coverage",
+            variants.iter().map(|v| format!("{}%", v.coverage())).collect(),
+        );
+        for (i, keyword) in keywords.iter().enumerate() {
+            row(keyword, "keyword", variants.iter().map(|v| mark(v.keywords[i])).collect());
+        }
+    }
+
+    html.push_str("    </tbody>\n  </table>\n");
+    html.push_str("</body>\n");
+    html.push_str("</html>\n");
+    html
+}
+
+fn mark(yes: bool) -> String {
+    if yes {
+        "<span class=\"yes\">✓</span>".to_string()
+    } else {
+        "<span class=\"no\">—</span>".to_string()
+    }
+}
+
+const REPORT_CSS: &str = "    body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; }
+    table { border-collapse: collapse; }
+    th, td { padding: 0.3rem 0.8rem; border-bottom: 1px solid #ddd; text-align: center; }
+    tbody th { text-align: left; font-weight: normal; }
+    tr.coverage { font-weight: bold; }
+    .yes { color: #15803d; }
+    .no { color: #b91c1c; }
+";
+
+/// All the prose and lists in `doc` a keyword could appear in.
+pub(crate) fn text_of(doc: &JoblDocument) -> Vec<&str> {
+    let mut texts = crate::build::scripts::texts(doc);
+    texts.extend(doc.experience.iter().flat_map(|e| &e.technologies).map(String::as_str));
+    texts
+}
@@ -21,18 +21,35 @@
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use crate::build::emoji::EmojiPolicy;
+use crate::build::{Bullets, CssMode, Density, OutputFormat};
+
 /// On-disk shape of `srg.toml`. Every field is optional. Unknown
 /// fields are rejected so typos surface immediately rather than
 /// silently doing nothing.
 #[derive(Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
+    /// The resume `srg ci` builds, a file next to `srg.toml`.
+    /// Defaults to the directory's only `.jobl` file. Everything else
+    /// takes it from `--input`.
+    pub input: Option<PathBuf>,
+
     /// Theme name (e.g. "minimal", "jake", "classic"). Maps to a
-    /// directory under `src/layouts/` built into the binary.
+    /// directory under `src/layouts/` built into the binary. Themes
+    /// can be layered with `+`, e.g. "classic+compact".
     pub theme: Option<String>,
 
+    /// Theme for the web page only, over `theme`.
+    pub theme_html: Option<String>,
+
+    /// Theme for the PDF only, over `theme`, e.g. a plain one for
+    /// applicant tracking systems under a richer web page.
+    pub theme_pdf: Option<String>,
+
     /// Path to a custom layout file. Overrides the theme's layout
     /// when set. Relative paths are resolved against the directory
     /// containing `srg.toml`.
@@ -48,6 +65,185 @@ pub struct Config {
     /// not the current working directory, so `srg.toml` files are
     /// portable.
     pub out: Option<PathBuf>,
+
+    /// How the stylesheet is delivered: "inline" (default),
+    /// "external" (linked style.css), or "critical" (header rules
+    /// inlined, the rest deferred). Useful for hosted resumes.
+    pub css_mode: Option<CssMode>,
+
+    /// Spacing between sections and entries: "compact", "normal"
+    /// (default), or "relaxed".
+    pub density: Option<Density>,
+
+    /// Marker for highlights and education details: "disc",
+    /// "dash", or "none". Defaults to the theme's.
+    pub bullets: Option<Bullets>,
+
+    /// Base font size for the theme, e.g. "10.5pt".
+    pub font_size: Option<String>,
+
+    /// Line height for body text, e.g. "1.3" or "14pt".
+    pub line_height: Option<String>,
+
+    /// Emoji handling per output, e.g. `txt = "strip"`,
+    /// `pdf = "replace"`. Unlisted outputs keep emoji, except txt,
+    /// which strips them.
+    pub emoji: Option<BTreeMap<OutputFormat, EmojiPolicy>>,
+
+    /// Fold the plain-text export to ASCII for applicant tracking
+    /// systems.
+    pub ascii: Option<bool>,
+
+    /// Column the plain-text export wraps at; 0 doesn't wrap.
+    pub txt_width: Option<usize>,
+
+    /// Print the PDF in shades of gray; the HTML keeps its colors.
+    pub grayscale: Option<bool>,
+
+    /// Text stamped across every printed page, e.g. "DRAFT".
+    pub watermark: Option<String>,
+
+    /// A line printed at the bottom of every PDF page, e.g.
+    /// "Confidential — do not distribute".
+    pub footer: Option<String>,
+
+    /// Where the canonical resume is hosted. Every PDF page is stamped
+    /// with it, a QR code, and a hash of the content.
+    pub verify_url: Option<String>,
+
+    /// What prints the PDF: "chrome" (the default) or "typst".
+    pub pdf_backend: Option<crate::build::PdfBackend>,
+
+    /// What the PDF conforms to: "standard" (the default) or "pdfa".
+    pub pdf_profile: Option<crate::build::PdfProfile>,
+
+    /// Tag the PDF's structure for screen readers.
+    pub tagged_pdf: Option<bool>,
+
+    /// Add an outline of the headings (bookmarks) to the PDF.
+    pub pdf_outline: Option<bool>,
+
+    /// PDF print settings, e.g. `paper = "a4"`, `margin = "0.5in"`,
+    /// `scale = 0.95`, `background = false`. These win over the
+    /// layout's frontmatter and the theme's defaults.
+    pub pdf: Option<crate::themes::PrintSettings>,
+
+    /// Accent colors by section, e.g. `experience = "navy"`. These
+    /// win over `accent=` in the layout.
+    pub accents: Option<BTreeMap<String, String>>,
+
+    /// Overrides for the theme's typography knobs, e.g.
+    /// `body-font = "Inter, sans-serif"`. Only knobs the theme
+    /// declares in its theme.toml are accepted.
+    pub typography: Option<BTreeMap<String, String>>,
+
+    /// Outputs to produce, e.g. `["html", "pdf", "email-html"]`.
+    /// Defaults to the themed HTML page and its PDF.
+    pub formats: Option<Vec<OutputFormat>>,
+
+    /// Zip the outputs and the source JOBL file into
+    /// `resume-bundle.zip` after every build.
+    pub archive: Option<bool>,
+
+    /// Write each build to its own `<out>/<date>_<id>/` directory and
+    /// point `<out>/latest` at it.
+    pub versioned: Option<bool>,
+
+    /// Also write history.html from the JOBL file's git history.
+    pub history: Option<bool>,
+
+    /// Fail the build when a generated page isn't well-formed HTML.
+    pub strict: Option<bool>,
+
+    /// Write index.html as a fragment for embedding in another site.
+    pub fragment: Option<bool>,
+
+    /// Name the page's stylesheet and fonts after their content and
+    /// write cache headers for hosts.
+    pub fingerprint: Option<bool>,
+
+    /// Fetch GitHub metadata (stars, language, description) for
+    /// projects with a GitHub URL.
+    pub github: Option<bool>,
+
+    /// Fetch the Gravatar for person.email for the `avatar` field.
+    pub gravatar: Option<bool>,
+
+    /// The person `booking` block: a calendaring link and optional
+    /// office hours, written to availability.ics (`[booking]`).
+    pub booking: Option<crate::build::booking::Booking>,
+
+    /// URL that receives the build manifest as a JSON POST after
+    /// each successful build.
+    pub webhook: Option<String>,
+
+    /// List skills categories in the order the JOBL file has them
+    /// instead of alphabetically.
+    pub keep_skill_order: Option<bool>,
+
+    /// Length budgets for summaries and highlights, e.g.
+    /// `highlights = { words = 25 }`, with `truncate = true` to cut
+    /// overruns instead of only warning.
+    pub budget: Option<crate::budget::Budgets>,
+
+    /// Values for `$name` variables in the layout, e.g.
+    /// `role = "Platform Engineer"`. `--set` wins over these.
+    pub variables: Option<BTreeMap<String, String>>,
+
+    /// Settings for `srg lint`, e.g. `quantify = ["experience",
+    /// "projects"]`.
+    pub lint: Option<crate::lint::LintConfig>,
+
+    /// SMTP and message settings for `srg send`.
+    pub send: Option<SendConfig>,
+
+    /// Named ways to tailor the build for `srg pack --profile`, e.g.
+    /// `[profiles.backend]` with its own layout.
+    pub profiles: Option<BTreeMap<String, Profile>>,
+}
+
+/// One `[profiles.<name>]` table: what a kind of role changes about
+/// the build. Everything is optional; CLI flags still win.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Theme, over `theme` and the per-output themes.
+    pub theme: Option<String>,
+    /// Layout file, over `layout`. Relative to srg.toml.
+    pub layout: Option<PathBuf>,
+    /// Sections to leave out, as `--exclude-section`.
+    #[serde(default)]
+    pub exclude_sections: Vec<String>,
+    /// Sections to add, as `--include-section`.
+    #[serde(default)]
+    pub include_sections: Vec<String>,
+    /// Layout variables, over `[variables]`; `--set` wins over these.
+    pub variables: Option<BTreeMap<String, String>>,
+    /// Cover letter template. Relative to srg.toml.
+    pub cover_letter: Option<PathBuf>,
+}
+
+/// The `[send]` table. Each SMTP setting can also come from the
+/// environment (`SRG_SMTP_URL`, `SRG_SMTP_USERNAME`,
+/// `SRG_SMTP_PASSWORD`, `SRG_SMTP_FROM`), which wins over the file.
+/// Keep the password in the environment rather than in a file that
+/// may be committed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SendConfig {
+    /// Server URL, e.g. "smtps://smtp.example.com:465" or
+    /// "smtp://smtp.example.com:587" (STARTTLS is required).
+    pub smtp_url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender, e.g. "Ada Lovelace <ada@example.com>". Defaults to the
+    /// resume's name and email.
+    pub from: Option<String>,
+    /// Subject template. `{name}`, `{email}` and the other person
+    /// fields are replaced with values from the resume.
+    pub subject: Option<String>,
+    /// Body template, with the same placeholders as `subject`.
+    pub body: Option<String>,
 }
 
 impl Config {
@@ -55,7 +251,11 @@ impl Config {
     /// `Ok(None)` if the file isn't present, an error if it's
     /// present but malformed.
     pub fn load_for(input_path: &Path) -> Result<Option<LoadedConfig>> {
-        let dir = input_path.parent().unwrap_or_else(|| Path::new("."));
+        Self::load_in(input_path.parent().unwrap_or_else(|| Path::new(".")))
+    }
+
+    /// Look for `srg.toml` in `dir`, as [`Config::load_for`] does.
+    pub fn load_in(dir: &Path) -> Result<Option<LoadedConfig>> {
         let candidate = dir.join("srg.toml");
 
         if !candidate.exists() {
@@ -118,6 +318,7 @@ mod tests {
             dir.path().join("srg.toml"),
             r#"
 theme = "jake"
+theme_pdf = "minimal"
 out = "build"
 "#,
         )
@@ -125,9 +326,110 @@ out = "build"
 
         let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
         assert_eq!(loaded.config.theme.as_deref(), Some("jake"));
+        assert_eq!(loaded.config.theme_pdf.as_deref(), Some("minimal"));
         assert_eq!(loaded.config.out, Some(PathBuf::from("build")));
     }
 
+    #[test]
+    fn load_parses_css_mode() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let jobl_path = dir.path().join("resume.jobl");
+        std::fs::write(&jobl_path, "").unwrap();
+        std::fs::write(dir.path().join("srg.toml"), "css_mode = \"critical\"\n").unwrap();
+
+        let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
+        assert_eq!(loaded.config.css_mode, Some(CssMode::Critical));
+    }
+
+    #[test]
+    fn load_parses_pdf_profile() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let jobl_path = dir.path().join("resume.jobl");
+        std::fs::write(&jobl_path, "").unwrap();
+        std::fs::write(dir.path().join("srg.toml"), "pdf_profile = \"pdfa\"\npdf_outline = true\n").unwrap();
+
+        let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
+        assert_eq!(loaded.config.pdf_profile, Some(crate::build::PdfProfile::Pdfa));
+        assert_eq!((loaded.config.tagged_pdf, loaded.config.pdf_outline), (None, Some(true)));
+    }
+
+    #[test]
+    fn load_parses_emoji_policies() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let jobl_path = dir.path().join("resume.jobl");
+        std::fs::write(&jobl_path, "").unwrap();
+        std::fs::write(
+            dir.path().join("srg.toml"),
+            "[emoji]\npdf = \"replace\"\nemail-html = \"strip\"\n",
+        )
+        .unwrap();
+
+        let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
+        let emoji = loaded.config.emoji.unwrap();
+        assert_eq!(emoji[&OutputFormat::Pdf], EmojiPolicy::Replace);
+        assert_eq!(emoji[&OutputFormat::EmailHtml], EmojiPolicy::Strip);
+    }
+
+    #[test]
+    fn load_parses_formats() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let jobl_path = dir.path().join("resume.jobl");
+        std::fs::write(&jobl_path, "").unwrap();
+        std::fs::write(dir.path().join("srg.toml"), "formats = [\"pdf\", \"email-html\", \"og-image\"]\n").unwrap();
+
+        let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
+        assert_eq!(
+            loaded.config.formats,
+            Some(vec![OutputFormat::Pdf, OutputFormat::EmailHtml, OutputFormat::OgImage])
+        );
+    }
+
+    #[test]
+    fn load_parses_send_table() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let jobl_path = dir.path().join("resume.jobl");
+        std::fs::write(&jobl_path, "").unwrap();
+        std::fs::write(
+            dir.path().join("srg.toml"),
+            r#"
+[send]
+smtp_url = "smtps://smtp.example.com:465"
+username = "ada@example.com"
+subject = "Resume: {name}"
+"#,
+        )
+        .unwrap();
+
+        let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
+        let send = loaded.config.send.unwrap();
+        assert_eq!(send.smtp_url.as_deref(), Some("smtps://smtp.example.com:465"));
+        assert_eq!(send.subject.as_deref(), Some("Resume: {name}"));
+        assert!(send.password.is_none());
+    }
+
+    #[test]
+    fn load_parses_profiles() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let jobl_path = dir.path().join("resume.jobl");
+        std::fs::write(&jobl_path, "").unwrap();
+        std::fs::write(
+            dir.path().join("srg.toml"),
+            r#"
+[profiles.backend]
+layout = "backend.layout"
+exclude_sections = ["projects"]
+variables = { role = "Backend Engineer" }
+"#,
+        )
+        .unwrap();
+
+        let loaded = Config::load_for(&jobl_path).unwrap().unwrap();
+        let profile = &loaded.config.profiles.as_ref().unwrap()["backend"];
+        assert_eq!(profile.layout.as_deref(), Some(Path::new("backend.layout")));
+        assert_eq!(profile.exclude_sections, ["projects"]);
+        assert!(profile.include_sections.is_empty() && profile.cover_letter.is_none());
+    }
+
     #[test]
     fn load_rejects_unknown_fields() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -0,0 +1,176 @@
+//! Theme contract: what every theme must do with any resume.
+//!
+//! [`check_theme`] renders [`sample_document`] (and the lists JOBL
+//! doesn't have, [`sample_extras`]) through a theme and
+//! reports each broken promise: a section its layout declares that
+//! doesn't render, an item that's missing, resume text that reaches
+//! the page unescaped, or markup that isn't well-formed (see
+//! `crate::markup`). srg's own tests run it for every bundled theme;
+//! a theme author can do the same from a test of their own:
+//!
+//! ```no_run
+//! assert_eq!(srg::contract::check_theme("my-theme"), Vec::<String>::new());
+//! ```
+
+use std::collections::BTreeMap;
+
+use jobl::{EducationItem, ExperienceItem, JoblDocument, Person, ProjectItem};
+
+use crate::build::{standalone_print_page, Anchors, BuildOptions, Extras};
+use crate::layout::{Layout, SECTION_NAMES};
+use crate::sections::{own_anchors, PatentItem, PersonFields, TalkItem};
+
+/// Text that breaks the page if it isn't escaped. The sample puts it
+/// in every kind of field a layout can show.
+pub const PROBE: &str = "<b class=\"srg-probe\">Q&A</b>";
+
+/// A resume with every section filled in, markup-like text in each
+/// field, and a URL with characters that need escaping in an
+/// attribute.
+pub fn sample_document() -> JoblDocument {
+    let probe = |text: &str| format!("{} {}", text, PROBE);
+    JoblDocument {
+        person: Person {
+            name: probe("Ada Lovelace"),
+            headline: Some(probe("Analyst")),
+            email: Some("ada@example.com".to_string()),
+            phone: Some(probe("+44 20 7946 0000")),
+            location: Some(probe("London")),
+            website: Some("https://example.com/?a=1&b=\"2\"".to_string()),
+            github: Some("https://github.com/ada".to_string()),
+            linkedin: Some("https://linkedin.com/in/ada".to_string()),
+            summary: Some(probe("Wrote the first published algorithm.")),
+        },
+        skills: Some(BTreeMap::from([
+            (probe("Languages"), vec![probe("Rust"), "Python".to_string()]),
+            ("Math".to_string(), vec!["Analysis".to_string()]),
+        ])),
+        experience: vec![
+            ExperienceItem {
+                title: probe("Translator"),
+                company: probe("Analytical Engine"),
+                location: Some(probe("London")),
+                start: Some("1842-01".to_string()),
+                end: Some("1843-09".to_string()),
+                summary: Some(probe("Translated and annotated a memoir.")),
+                highlights: vec![probe("Published Note G."), "Described loops.".to_string()],
+                technologies: vec![probe("Punch cards")],
+            },
+            ExperienceItem {
+                title: "Correspondent".to_string(),
+                company: "Royal Society".to_string(),
+                location: None,
+                start: Some("1840".to_string()),
+                end: None,
+                summary: None,
+                highlights: Vec::new(),
+                technologies: Vec::new(),
+            },
+        ],
+        projects: vec![ProjectItem {
+            name: probe("Note G"),
+            url: Some("https://example.com/note-g?x=1&y=2".to_string()),
+            summary: Some(probe("Computes Bernoulli numbers.")),
+            role: None,
+            start: None,
+            end: None,
+            technologies: Vec::new(),
+        }],
+        education: vec![EducationItem {
+            degree: probe("Mathematics"),
+            institution: probe("Private tutoring"),
+            location: Some(probe("London")),
+            start: Some("1829".to_string()),
+            end: Some("1840".to_string()),
+            details: vec![probe("Studied under De Morgan.")],
+        }],
+    }
+}
+
+/// The sections srg reads from the JOBL file itself, filled in the
+/// same way as [`sample_document`].
+pub fn sample_extras() -> Extras {
+    let probe = |text: &str| format!("{} {}", text, PROBE);
+    Extras {
+        talks: vec![TalkItem {
+            title: probe("Notes on Note G"),
+            event: probe("Analytical Society"),
+            date: Some("1843-10".to_string()),
+            location: Some(probe("London")),
+            slides: Some("https://example.com/slides?talk=1&format=\"pdf\"".to_string()),
+            video: Some("https://example.com/video?v=1&t=2".to_string()),
+        }],
+        patents: vec![PatentItem {
+            title: probe("Method of weaving algebraic patterns"),
+            number: Some(probe("GB 1,843")),
+            status: Some(probe("Granted")),
+            year: Some("1843".to_string()),
+        }],
+        person: PersonFields {
+            work_authorization: Some(probe("UK citizen")),
+            clearance: Some(probe("Active SC")),
+            meta: [("pronouns".to_string(), probe("she/her"))].into_iter().collect(),
+        },
+        ..Extras::default()
+    }
+}
+
+/// Every way `theme` breaks the contract. Empty when it holds.
+pub fn check_theme(theme: &str) -> Vec<String> {
+    let layout = match Layout::from_theme(theme) {
+        Ok(layout) => layout,
+        Err(e) => return vec![format!("layout doesn't load: {:#}", e)],
+    };
+    let doc = sample_document();
+    let options = BuildOptions { extras: sample_extras(), ..BuildOptions::default() };
+    let page = match standalone_print_page(&doc, Some(theme), &layout, None, &options) {
+        Ok(page) => page,
+        Err(e) => return vec![format!("page doesn't render: {:#}", e)],
+    };
+    check_page(&page, &layout, &doc, &options.extras)
+}
+
+/// Every way `page`, rendered from `doc` and `extras` with `layout`,
+/// breaks the contract.
+pub fn check_page(page: &str, layout: &Layout, doc: &JoblDocument, extras: &Extras) -> Vec<String> {
+    let mut violations = Vec::new();
+    let has_id = |id: &str| page.contains(&format!("id=\"{}\"", id));
+
+    let anchors = Anchors::for_document(doc);
+    for section in &layout.sections {
+        let name = section.name.as_str();
+        if !SECTION_NAMES.contains(&name) {
+            violations.push(format!("layout section {:?} isn't one srg renders", name));
+            continue;
+        }
+        if !has_id(name) {
+            violations.push(format!("section {:?} is in the layout but didn't render", name));
+        }
+        let own;
+        let items = match name {
+            "experience" => &anchors.experience,
+            "projects" => &anchors.projects,
+            "education" => &anchors.education,
+            "talks" => {
+                own = own_anchors(name, extras.talks.iter().map(|t| t.title.as_str()));
+                &own
+            }
+            "patents" => {
+                own = own_anchors(name, extras.patents.iter().map(|p| p.title.as_str()));
+                &own
+            }
+            _ => continue,
+        };
+        for anchor in items.iter().filter(|anchor| !has_id(anchor)) {
+            violations.push(format!("{} item {:?} didn't render", name, anchor));
+        }
+    }
+
+    if page.contains("srg-probe\">") {
+        violations.push("resume text reaches the page unescaped".to_string());
+    }
+    for problem in crate::markup::check(page) {
+        violations.push(format!("markup: {}", problem));
+    }
+    violations
+}
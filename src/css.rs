@@ -0,0 +1,570 @@
+//! Small, forgiving CSS utilities.
+//!
+//! This is not a CSS parser. It knows just enough about the syntax
+//! (blocks, comments, strings) to split a stylesheet into top-level
+//! rules and pick the ones that matter for first paint. Theme CSS is
+//! hand-written and well-formed, so a tolerant splitter is plenty.
+
+/// A single top-level rule: everything before the first `{` (or the
+/// whole statement for `@import`-style rules) plus its full text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub prelude: String,
+    pub text: String,
+    /// Inner text between the outermost braces, if the rule has a block.
+    pub body: Option<String>,
+}
+
+/// Split a stylesheet into its top-level rules. Comments are dropped.
+pub fn split_rules(css: &str) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut body_start: Option<usize> = None;
+    let mut prelude: Option<String> = None;
+    let mut chars = css.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '/' if chars.peek() == Some(&'*') => {
+                // Skip the comment entirely, including the closing `*/`.
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' | '\'' => {
+                current.push(ch);
+                while let Some(c) = chars.next() {
+                    current.push(c);
+                    if c == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            current.push(escaped);
+                        }
+                    } else if c == ch {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                if depth == 0 {
+                    prelude = Some(current.trim().to_string());
+                    body_start = Some(current.len() + 1);
+                }
+                depth += 1;
+                current.push(ch);
+            }
+            '}' => {
+                current.push(ch);
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    let body = body_start
+                        .map(|start| current[start..current.len() - 1].to_string());
+                    rules.push(Rule {
+                        prelude: prelude.take().unwrap_or_default(),
+                        text: current.trim().to_string(),
+                        body,
+                    });
+                    current.clear();
+                    body_start = None;
+                }
+            }
+            ';' if depth == 0 => {
+                current.push(ch);
+                let text = current.trim().to_string();
+                if !text.is_empty() {
+                    rules.push(Rule {
+                        prelude: text.trim_end_matches(';').trim().to_string(),
+                        text,
+                        body: None,
+                    });
+                }
+                current.clear();
+            }
+            _ => current.push(ch),
+        }
+    }
+
+    rules
+}
+
+/// Extract the rules needed to paint the top of the resume: global
+/// resets, custom properties, fonts, and the person header. Everything
+/// else (later sections, print styles) can arrive after first paint.
+///
+/// Screen media queries are filtered recursively; print-only blocks are
+/// dropped since they never affect first paint.
+pub fn critical_css(css: &str) -> String {
+    let mut out = String::new();
+    for rule in split_rules(css) {
+        let prelude = rule.prelude.to_ascii_lowercase();
+        if prelude.starts_with("@media") {
+            if prelude.contains("print") && !prelude.contains("screen") {
+                continue;
+            }
+            let inner = critical_css(rule.body.as_deref().unwrap_or(""));
+            if !inner.is_empty() {
+                out.push_str(&format!("{} {{\n{}}}\n", rule.prelude, inner));
+            }
+        } else if prelude.starts_with("@font-face") || prelude.starts_with("@import") {
+            out.push_str(&rule.text);
+            out.push('\n');
+        } else if prelude.starts_with('@') {
+            continue;
+        } else if prelude.split(',').any(is_critical_selector) {
+            out.push_str(&rule.text);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Element types that show up in the header or carry global text styles.
+const CRITICAL_TAGS: &[&str] = &[
+    "html", "body", "main", "header", "h1", "p", "a", "span", "strong", "em",
+];
+
+/// Class names used by the person header across the bundled themes.
+const CRITICAL_CLASSES: &[&str] = &["section-person", "headline", "contact"];
+
+/// A selector is critical when its leftmost compound selector targets
+/// the document root, a global element, or the person header.
+fn is_critical_selector(selector: &str) -> bool {
+    let selector = selector.trim();
+    let first = selector
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '+' || c == '~')
+        .find(|s| !s.is_empty())
+        .unwrap_or("");
+
+    if first == "*" || first.starts_with(":root") {
+        return true;
+    }
+
+    // Drop pseudo-classes/elements and attribute selectors, then look
+    // at the remaining tag, ids, and classes.
+    let compound = first
+        .split([':', '['])
+        .next()
+        .unwrap_or("");
+
+    let tag_end = compound
+        .find(['.', '#'])
+        .unwrap_or(compound.len());
+    let tag = &compound[..tag_end];
+    if !tag.is_empty() && CRITICAL_TAGS.contains(&tag) {
+        return true;
+    }
+
+    let mut rest = &compound[tag_end..];
+    while !rest.is_empty() {
+        let marker = &rest[..1];
+        let name_end = rest[1..]
+            .find(['.', '#'])
+            .map(|i| i + 1)
+            .unwrap_or(rest.len());
+        let name = &rest[1..name_end];
+        match marker {
+            "#" if name == "person" => return true,
+            "." if name.starts_with("person-") || CRITICAL_CLASSES.contains(&name) => {
+                return true
+            }
+            _ => {}
+        }
+        rest = &rest[name_end..];
+    }
+
+    false
+}
+
+/// Units accepted for user-supplied sizes.
+const LENGTH_UNITS: &[&str] = &["pt", "px", "rem", "em", "mm", "cm", "in", "%"];
+
+/// Check a user-supplied CSS length such as `10.5pt` before it goes
+/// into generated CSS. With `unitless`, a bare number (`1.3`, as for
+/// `line-height`) is accepted too. Returns the trimmed value.
+pub fn parse_length(value: &str, unitless: bool) -> Result<String, String> {
+    let value = value.trim();
+    let number_end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(number_end);
+
+    let number_ok = !number.is_empty() && number.parse::<f64>().is_ok_and(|n| n > 0.0);
+    let unit_ok = LENGTH_UNITS.contains(&unit) || (unitless && unit.is_empty());
+    if number_ok && unit_ok {
+        Ok(value.to_string())
+    } else if unitless {
+        Err(format!("expected a number or a length like 1.4 or 16pt, got {:?}", value))
+    } else {
+        Err(format!("expected a length like 10.5pt or 14px, got {:?}", value))
+    }
+}
+
+/// Check a user- or theme-supplied value for a custom property:
+/// anything that could end the declaration or the rule is refused.
+pub fn check_value(value: &str) -> Result<(), String> {
+    match value.chars().find(|c| matches!(c, ';' | '{' | '}' | '<' | '>' | '\\' | '\n' | '\r')) {
+        Some(c) => Err(format!("{:?} is not allowed in {:?}", c, value)),
+        None if value.trim().is_empty() => Err("value is empty".to_string()),
+        None => Ok(()),
+    }
+}
+
+/// A `:root` rule declaring `vars`, preceded by a comment. Empty
+/// when there's nothing to declare.
+pub fn root_rule(comment: &str, vars: &[(String, String)]) -> String {
+    if vars.is_empty() {
+        return String::new();
+    }
+    let mut css = format!("\n\n/* {} */\n:root {{\n", comment);
+    for (name, value) in vars {
+        css.push_str(&format!("  {}: {};\n", name, value));
+    }
+    css.push_str("}\n");
+    css
+}
+
+/// Named colors converted by [`grayscale`]: the sixteen basic ones
+/// plus the names people tend to reach for as accents.
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+    ("aqua", (0, 255, 255)),
+    ("blue", (0, 0, 255)),
+    ("brown", (165, 42, 42)),
+    ("coral", (255, 127, 80)),
+    ("crimson", (220, 20, 60)),
+    ("cyan", (0, 255, 255)),
+    ("darkblue", (0, 0, 139)),
+    ("darkgreen", (0, 100, 0)),
+    ("darkred", (139, 0, 0)),
+    ("firebrick", (178, 34, 34)),
+    ("fuchsia", (255, 0, 255)),
+    ("gold", (255, 215, 0)),
+    ("green", (0, 128, 0)),
+    ("indigo", (75, 0, 130)),
+    ("lime", (0, 255, 0)),
+    ("magenta", (255, 0, 255)),
+    ("maroon", (128, 0, 0)),
+    ("navy", (0, 0, 128)),
+    ("olive", (128, 128, 0)),
+    ("orange", (255, 165, 0)),
+    ("pink", (255, 192, 203)),
+    ("purple", (128, 0, 128)),
+    ("red", (255, 0, 0)),
+    ("royalblue", (65, 105, 225)),
+    ("slateblue", (106, 90, 205)),
+    ("steelblue", (70, 130, 180)),
+    ("teal", (0, 128, 128)),
+    ("tomato", (255, 99, 71)),
+    ("yellow", (255, 255, 0)),
+];
+
+/// `css` with every color in a declaration value (hex, `rgb()`,
+/// `hsl()`, and the names in [`NAMED_COLORS`]) replaced by the gray
+/// of the same luminance, keeping any alpha. Selectors, comments,
+/// strings, and `url()`s are left alone.
+pub fn grayscale(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut segment = String::new();
+    let mut chars = css.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '/' if chars.peek() == Some(&'*') => {
+                out.push_str(&segment);
+                segment.clear();
+                out.push(ch);
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    out.push(c);
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' | '\'' => {
+                segment.push(ch);
+                while let Some(c) = chars.next() {
+                    segment.push(c);
+                    if c == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            segment.push(escaped);
+                        }
+                    } else if c == ch {
+                        break;
+                    }
+                }
+            }
+            // Text before `{` is a selector or an at-rule prelude.
+            '{' => {
+                out.push_str(&segment);
+                out.push(ch);
+                segment.clear();
+            }
+            ';' | '}' => {
+                match segment.find(':') {
+                    Some(colon) => {
+                        out.push_str(&segment[..=colon]);
+                        out.push_str(&grayscale_value(&segment[colon + 1..]));
+                    }
+                    None => out.push_str(&segment),
+                }
+                out.push(ch);
+                segment.clear();
+            }
+            _ => segment.push(ch),
+        }
+    }
+    out.push_str(&segment);
+    out
+}
+
+fn grayscale_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(ch) = rest.chars().next() {
+        if ch == '"' || ch == '\'' {
+            let end = rest[1..].find(ch).map_or(rest.len(), |i| i + 2);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if ch == '#' {
+            let len = rest[1..]
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .map_or(rest.len() - 1, |i| i);
+            let token = &rest[..len + 1];
+            match hex_color(&token[1..]) {
+                Some((rgb, alpha)) => out.push_str(&gray_hex(rgb, alpha)),
+                None => out.push_str(token),
+            }
+            rest = &rest[len + 1..];
+        } else if ch.is_ascii_alphabetic() || ch == '-' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let after = &rest[len..];
+            let lower = word.to_ascii_lowercase();
+
+            if after.starts_with('(') {
+                let close = after.find(')').map_or(after.len(), |i| i + 1);
+                let call = &rest[..len + close];
+                let args = after[1..close].trim_end_matches(')');
+                let converted = match lower.as_str() {
+                    "rgb" | "rgba" => rgb_args(args),
+                    "hsl" | "hsla" => hsl_args(args),
+                    _ => None,
+                };
+                match converted {
+                    Some((rgb, alpha)) => out.push_str(&gray_function(rgb, alpha)),
+                    None => out.push_str(call),
+                }
+                rest = &rest[len + close..];
+            } else {
+                match NAMED_COLORS.iter().find(|(name, _)| *name == lower) {
+                    Some(&(_, rgb)) => out.push_str(&gray_hex(rgb, None)),
+                    None => out.push_str(word),
+                }
+                rest = after;
+            }
+        } else {
+            out.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+    out
+}
+
+type Rgb = (u8, u8, u8);
+
+fn hex_color(digits: &str) -> Option<(Rgb, Option<u8>)> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |s: &str| u8::from_str_radix(s, 16).ok();
+    let short = |i: usize| byte(&digits[i..=i].repeat(2));
+    match digits.len() {
+        3 => Some(((short(0)?, short(1)?, short(2)?), None)),
+        4 => Some(((short(0)?, short(1)?, short(2)?), Some(short(3)?))),
+        6 => Some(((byte(&digits[0..2])?, byte(&digits[2..4])?, byte(&digits[4..6])?), None)),
+        8 => Some((
+            (byte(&digits[0..2])?, byte(&digits[2..4])?, byte(&digits[4..6])?),
+            Some(byte(&digits[6..8])?),
+        )),
+        _ => None,
+    }
+}
+
+/// Color function arguments, comma- or space-separated, with an
+/// optional alpha after `/` or a fourth comma.
+fn split_args(args: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let (channels, alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let mut parts: Vec<&str> = channels
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let alpha = match (alpha, parts.len()) {
+        (Some(alpha), 3) => Some(alpha),
+        (None, 4) => parts.pop(),
+        (None, 3) => None,
+        _ => return None,
+    };
+    Some((parts, alpha))
+}
+
+fn rgb_args(args: &str) -> Option<(Rgb, Option<&str>)> {
+    let (parts, alpha) = split_args(args)?;
+    let channel = |p: &str| -> Option<u8> {
+        let value = match p.strip_suffix('%') {
+            Some(percent) => percent.parse::<f64>().ok()? * 2.55,
+            None => p.parse::<f64>().ok()?,
+        };
+        Some(value.round().clamp(0.0, 255.0) as u8)
+    };
+    Some(((channel(parts[0])?, channel(parts[1])?, channel(parts[2])?), alpha))
+}
+
+fn hsl_args(args: &str) -> Option<(Rgb, Option<&str>)> {
+    let (parts, alpha) = split_args(args)?;
+    let hue = parts[0].trim_end_matches("deg").parse::<f64>().ok()?.rem_euclid(360.0);
+    let percent = |p: &str| Some(p.strip_suffix('%')?.parse::<f64>().ok()? / 100.0);
+    let (s, l) = (percent(parts[1])?, percent(parts[2])?);
+
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    let channel = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Some(((channel(r), channel(g), channel(b)), alpha))
+}
+
+/// Relative luminance weights (Rec. 709) applied to sRGB values.
+fn luminance((r, g, b): Rgb) -> u8 {
+    (0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64).round() as u8
+}
+
+fn gray_hex(rgb: Rgb, alpha: Option<u8>) -> String {
+    let y = luminance(rgb);
+    match alpha {
+        Some(a) => format!("#{:02x}{:02x}{:02x}{:02x}", y, y, y, a),
+        None => format!("#{:02x}{:02x}{:02x}", y, y, y),
+    }
+}
+
+fn gray_function(rgb: Rgb, alpha: Option<&str>) -> String {
+    let y = luminance(rgb);
+    match alpha {
+        Some(a) => format!("rgba({}, {}, {}, {})", y, y, y, a),
+        None => format!("rgb({}, {}, {})", y, y, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_length_accepts_sizes_only() {
+        assert_eq!(parse_length(" 10.5pt ", false).unwrap(), "10.5pt");
+        assert_eq!(parse_length("1.3", true).unwrap(), "1.3");
+        assert!(parse_length("1.3", false).is_err());
+        assert!(parse_length("12pt; color: red", false).is_err());
+        assert!(parse_length("0px", false).is_err());
+    }
+
+    #[test]
+    fn check_value_refuses_rule_breakers() {
+        assert!(check_value("\"Inter\", sans-serif").is_ok());
+        assert!(check_value("red; } body { color: red").is_err());
+        assert!(check_value(" ").is_err());
+    }
+
+    #[test]
+    fn grayscale_converts_colors_in_values_only() {
+        let css = r#"
+/* #f00 stays in comments */
+#add, a:hover { color: #f00; border: 1px solid rgb(0 0 255 / 50%); }
+.x::after { content: "red"; background: url(#bad) teal; }
+h2 { color: hsl(120, 100%, 25%); --srg-accent: Navy; }
+"#;
+        let gray = grayscale(css);
+        assert!(gray.contains("#add, a:hover {"));
+        assert!(gray.contains("/* #f00 stays in comments */"));
+        assert!(gray.contains("color: #363636;"));
+        assert!(gray.contains("rgba(18, 18, 18, 50%)"));
+        assert!(gray.contains("content: \"red\""));
+        assert!(gray.contains("url(#bad) #656565"));
+        assert!(gray.contains("color: rgb(92, 92, 92);"));
+        assert!(gray.contains("--srg-accent: #090909;"));
+    }
+
+    #[test]
+    fn split_handles_nested_blocks_and_comments() {
+        let css = r#"
+/* theme */
+body { color: red; }
+@media print {
+  main { padding: 0; }
+}
+"#;
+        let rules = split_rules(css);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].prelude, "body");
+        assert_eq!(rules[1].prelude, "@media print");
+        assert!(rules[1].body.as_deref().unwrap().contains("main"));
+    }
+
+    #[test]
+    fn split_ignores_braces_inside_strings() {
+        let rules = split_rules(r#".x::after { content: "}"; } .y { color: blue; }"#);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].prelude, ".y");
+    }
+
+    #[test]
+    fn critical_keeps_header_and_globals() {
+        let css = r#"
+:root { --accent: #333; }
+body { margin: 0; }
+.person-name { font-size: 3rem; }
+#experience h2 { color: green; }
+.experience-highlights li { margin: 0; }
+@media print { body { color: black; } }
+"#;
+        let critical = critical_css(css);
+        assert!(critical.contains("--accent"));
+        assert!(critical.contains("body { margin: 0; }"));
+        assert!(critical.contains(".person-name"));
+        assert!(!critical.contains("#experience"));
+        assert!(!critical.contains("experience-highlights"));
+        assert!(!critical.contains("color: black"));
+    }
+
+    #[test]
+    fn critical_filters_inside_screen_media_queries() {
+        let css = r#"
+@media (max-width: 600px) {
+  h1 { font-size: 2rem; }
+  .projects-item { margin: 0; }
+}
+"#;
+        let critical = critical_css(css);
+        assert!(critical.contains("@media (max-width: 600px)"));
+        assert!(critical.contains("h1"));
+        assert!(!critical.contains("projects-item"));
+    }
+}
@@ -0,0 +1,410 @@
+//! Date formatting for `start` and `end` fields.
+//!
+//! JOBL dates are written ISO style (`2021`, `2021-03`, or
+//! `2021-03-15`). A layout can ask for a different rendering with a
+//! `date_format` such as `%b %Y` (Mar 2021). Anything that isn't an
+//! ISO date ("Present", "Summer 2019") is left as written, and so are
+//! year-only dates, which have nothing to reformat.
+//!
+//! A `locale` writes dates in another language: month names, the word
+//! for an ongoing role ("Present" becomes "aujourd'hui"), and the
+//! `dates` range field's punctuation all follow it.
+
+use jobl::JoblDocument;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September",
+    "October", "November", "December",
+];
+
+/// End dates that mean the role is ongoing, as JOBL files (and the
+/// JSON Resume importer) write them.
+const PRESENT: &[&str] = &["present", "current", "now", "today", "ongoing"];
+
+/// A language dates are written in, from `locale` in the layout
+/// frontmatter. Region suffixes are accepted and ignored (`fr-CA` is
+/// `fr`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Locale {
+    En,
+    Fr,
+    De,
+    Es,
+    It,
+    Pt,
+    Nl,
+    Ja,
+}
+
+/// Locale codes srg knows.
+pub const LOCALES: &[&str] = &["en", "fr", "de", "es", "it", "pt", "nl", "ja"];
+
+impl Locale {
+    /// The language code, e.g. `fr`, as the page's `lang` attribute.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+            Locale::De => "de",
+            Locale::Es => "es",
+            Locale::It => "it",
+            Locale::Pt => "pt",
+            Locale::Nl => "nl",
+            Locale::Ja => "ja",
+        }
+    }
+
+    fn months(self) -> [&'static str; 12] {
+        match self {
+            Locale::En => MONTHS,
+            Locale::Fr => [
+                "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre",
+                "novembre", "décembre",
+            ],
+            Locale::De => [
+                "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober",
+                "November", "Dezember",
+            ],
+            Locale::Es => [
+                "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre",
+                "noviembre", "diciembre",
+            ],
+            Locale::It => [
+                "gennaio", "febbraio", "marzo", "aprile", "maggio", "giugno", "luglio", "agosto", "settembre",
+                "ottobre", "novembre", "dicembre",
+            ],
+            Locale::Pt => [
+                "janeiro", "fevereiro", "março", "abril", "maio", "junho", "julho", "agosto", "setembro", "outubro",
+                "novembro", "dezembro",
+            ],
+            Locale::Nl => [
+                "januari", "februari", "maart", "april", "mei", "juni", "juli", "augustus", "september", "oktober",
+                "november", "december",
+            ],
+            Locale::Ja => ["1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月"],
+        }
+    }
+
+    /// Abbreviated month names, as each language abbreviates them
+    /// (`janv.`, `März`), not simply cut to three letters.
+    fn short_months(self) -> [&'static str; 12] {
+        match self {
+            Locale::En => ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"],
+            Locale::Fr => [
+                "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc.",
+            ],
+            Locale::De => [
+                "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.", "Dez.",
+            ],
+            Locale::Es => [
+                "ene.", "feb.", "mar.", "abr.", "may.", "jun.", "jul.", "ago.", "sept.", "oct.", "nov.", "dic.",
+            ],
+            Locale::It => ["gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic"],
+            Locale::Pt => [
+                "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.", "out.", "nov.", "dez.",
+            ],
+            Locale::Nl => [
+                "jan.", "feb.", "mrt.", "apr.", "mei", "jun.", "jul.", "aug.", "sep.", "okt.", "nov.", "dec.",
+            ],
+            Locale::Ja => self.months(),
+        }
+    }
+
+    /// The end of an ongoing range.
+    fn present(self) -> &'static str {
+        match self {
+            Locale::En => "Present",
+            Locale::Fr => "aujourd'hui",
+            Locale::De => "heute",
+            Locale::Es => "actualidad",
+            Locale::It => "oggi",
+            Locale::Pt => "atual",
+            Locale::Nl => "heden",
+            Locale::Ja => "現在",
+        }
+    }
+
+    /// What goes between the two ends of a range.
+    fn range_separator(self) -> &'static str {
+        match self {
+            Locale::Ja => "〜",
+            _ => " – ",
+        }
+    }
+
+    /// The date format when the layout sets a locale but no
+    /// `date_format`.
+    fn default_format(self) -> &'static str {
+        match self {
+            Locale::Ja => "%Y年%-m月",
+            _ => "%b %Y",
+        }
+    }
+}
+
+impl TryFrom<String> for Locale {
+    type Error = String;
+
+    fn try_from(tag: String) -> Result<Self, Self::Error> {
+        let language = tag.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        let locale = match language.as_str() {
+            "en" => Locale::En,
+            "fr" => Locale::Fr,
+            "de" => Locale::De,
+            "es" => Locale::Es,
+            "it" => Locale::It,
+            "pt" => Locale::Pt,
+            "nl" => Locale::Nl,
+            "ja" => Locale::Ja,
+            _ => return Err(format!("unknown locale {:?} (supported: {})", tag, LOCALES.join(", "))),
+        };
+        Ok(locale)
+    }
+}
+
+impl From<Locale> for String {
+    fn from(locale: Locale) -> Self {
+        locale.code().to_string()
+    }
+}
+
+/// Directives `date_format` understands.
+pub const DIRECTIVES: &[&str] = &["%Y", "%y", "%m", "%-m", "%B", "%b", "%d", "%-d", "%%"];
+
+/// Check a format string, naming the first directive srg doesn't
+/// know.
+pub fn check_format(format: &str) -> Result<(), String> {
+    let mut rest = format;
+    while let Some(i) = rest.find('%') {
+        rest = &rest[i..];
+        match DIRECTIVES.iter().find(|d| rest.starts_with(**d)) {
+            Some(d) => rest = &rest[d.len()..],
+            None => {
+                let bad: String = rest.chars().take(3).collect();
+                return Err(format!(
+                    "unknown directive {:?} in date format {:?} (supported: {})",
+                    bad,
+                    format,
+                    DIRECTIVES.join(" ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `raw` rendered with `format`, or `raw` itself when it isn't an
+/// ISO date with at least a month. `%d` renders as nothing for dates
+/// without a day.
+pub fn format_date(raw: &str, format: &str) -> String {
+    format_date_in(raw, format, Locale::En)
+}
+
+/// [`format_date`] with `locale`'s month names.
+pub fn format_date_in(raw: &str, format: &str, locale: Locale) -> String {
+    let Some((year, month, day)) = parse(raw.trim()) else {
+        return raw.to_string();
+    };
+
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let directive = DIRECTIVES.iter().find(|d| rest.starts_with(**d)).copied().unwrap_or("%");
+        match directive {
+            "%Y" => out.push_str(&year.to_string()),
+            "%y" => out.push_str(&format!("{:02}", year % 100)),
+            "%m" => out.push_str(&format!("{:02}", month)),
+            "%-m" => out.push_str(&month.to_string()),
+            "%B" => out.push_str(locale.months()[month as usize - 1]),
+            "%b" => out.push_str(locale.short_months()[month as usize - 1]),
+            "%d" => out.push_str(&day.map(|d| format!("{:02}", d)).unwrap_or_default()),
+            "%-d" => out.push_str(&day.map(|d| d.to_string()).unwrap_or_default()),
+            _ => out.push('%'),
+        }
+        rest = &rest[directive.len()..];
+    }
+    out.push_str(rest);
+
+    // A missing day leaves its separators behind ("Mar , 2021").
+    let out = out.split_whitespace().collect::<Vec<_>>().join(" ");
+    out.trim_matches(|c: char| !c.is_alphanumeric())
+        .replace(" ,", ",")
+        .replace("//", "/")
+        .replace("..", ".")
+}
+
+/// Whether `raw` is an ISO date: `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`.
+pub fn is_iso(raw: &str) -> bool {
+    (raw.len() == 4 && raw.chars().all(|c| c.is_ascii_digit())) || parse(raw).is_some()
+}
+
+/// Whether an end date means the role is ongoing ("Present").
+pub fn is_present(raw: &str) -> bool {
+    PRESENT.contains(&raw.trim().to_lowercase().as_str())
+}
+
+/// A date as resumes print it ("Mar 2021", "March 2021", "03/2021",
+/// "2021"), written the ISO way. An ongoing role's end is "Present".
+/// `None` for anything else.
+pub fn from_written(raw: &str) -> Option<String> {
+    let raw = raw.trim().trim_start_matches('(').trim_end_matches([')', ',', '.']);
+    if is_present(raw) {
+        return Some("Present".to_string());
+    }
+    if is_iso(raw) {
+        return Some(raw.to_string());
+    }
+    let year = |y: &str| (y.len() == 4 && y.chars().all(|c| c.is_ascii_digit())).then(|| y.to_string());
+    if let Some((month, y)) = raw.split_once('/') {
+        let month: u32 = month.parse().ok().filter(|m| (1..=12).contains(m))?;
+        return Some(format!("{}-{:02}", year(y)?, month));
+    }
+    let (month, y) = raw.split_once(char::is_whitespace)?;
+    let month = month.trim_end_matches('.').to_lowercase();
+    let index = MONTHS.iter().position(|m| month.len() >= 3 && m.to_lowercase().starts_with(&month))?;
+    Some(format!("{}-{:02}", year(y.trim())?, index + 1))
+}
+
+/// `YYYY-MM` or `YYYY-MM-DD`.
+fn parse(raw: &str) -> Option<(u32, u32, Option<u32>)> {
+    let mut parts = raw.split('-');
+    let year = parts.next().filter(|y| y.len() == 4)?.parse().ok()?;
+    let month: u32 = parts.next().filter(|m| m.len() == 2)?.parse().ok()?;
+    let day: Option<u32> = match parts.next() {
+        Some(d) if d.len() == 2 => Some(d.parse().ok()?),
+        Some(_) => return None,
+        None => None,
+    };
+    if parts.next().is_some() || !(1..=12).contains(&month) || day.is_some_and(|d| !(1..=31).contains(&d)) {
+        return None;
+    }
+    Some((year, month, day))
+}
+
+/// The `dates` layout field: `start` and `end` joined the way
+/// `locale` writes a range ("mars 2020 – aujourd'hui"). Either end
+/// alone is shown on its own. Expects dates [`apply`] has rendered.
+pub fn range(start: Option<&str>, end: Option<&str>, locale: Option<Locale>) -> Option<String> {
+    let separator = locale.unwrap_or(Locale::En).range_separator();
+    match (start.filter(|s| !s.is_empty()), end.filter(|e| !e.is_empty())) {
+        (Some(start), Some(end)) if start == end => Some(start.to_string()),
+        (Some(start), Some(end)) => Some(format!("{}{}{}", start, separator, end)),
+        (start, end) => start.or(end).map(str::to_string),
+    }
+}
+
+/// `doc` with every `start` and `end` rendered with `format` in
+/// `locale`. A locale without a format uses its usual one, and writes
+/// ongoing ends ("Present") in its own language. Borrows when there's
+/// neither.
+pub fn apply<'a>(doc: &'a JoblDocument, format: Option<&str>, locale: Option<Locale>) -> Cow<'a, JoblDocument> {
+    let Some(format) = format.or(locale.map(Locale::default_format)) else {
+        return Cow::Borrowed(doc);
+    };
+    let date = |d: &Option<String>| {
+        d.as_deref().map(|d| match locale {
+            Some(locale) if is_present(d) => locale.present().to_string(),
+            _ => format_date_in(d, format, locale.unwrap_or(Locale::En)),
+        })
+    };
+
+    let mut doc = doc.clone();
+    for exp in &mut doc.experience {
+        exp.start = date(&exp.start);
+        exp.end = date(&exp.end);
+    }
+    for edu in &mut doc.education {
+        edu.start = date(&edu.start);
+        edu.end = date(&edu.end);
+    }
+    Cow::Owned(doc)
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Calendar date for a count of days since 1970-01-01 (Howard
+/// Hinnant's `civil_from_days`).
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_iso_dates_only() {
+        assert_eq!(format_date("2021-03", "%b %Y"), "Mar 2021");
+        assert_eq!(format_date("2021-03-05", "%B %-d, %Y"), "March 5, 2021");
+        assert_eq!(format_date("2021-03", "%B %-d, %Y"), "March, 2021");
+        assert_eq!(format_date("2021-03", "%m/%y"), "03/21");
+        assert_eq!(format_date("2021", "%b %Y"), "2021");
+        assert_eq!(format_date("Present", "%b %Y"), "Present");
+        assert_eq!(format_date("2021-13", "%b %Y"), "2021-13");
+    }
+
+    #[test]
+    fn localizes_months_and_ranges() {
+        let fr = Locale::try_from("fr-CA".to_string()).unwrap();
+        assert_eq!(format_date_in("2020-03", "%b %Y", fr), "mars 2020");
+        assert_eq!(format_date_in("2020-02", "%B %Y", fr), "février 2020");
+        assert_eq!(format_date_in("2020-03", "%Y年%-m月", Locale::Ja), "2020年3月");
+        assert_eq!(range(Some("mars 2020"), Some("aujourd'hui"), Some(fr)).unwrap(), "mars 2020 – aujourd'hui");
+        assert_eq!(range(Some("2020年3月"), Some("現在"), Some(Locale::Ja)).unwrap(), "2020年3月〜現在");
+        assert_eq!(range(None, Some("2019"), None).unwrap(), "2019");
+        assert!(Locale::try_from("xx".to_string()).is_err());
+    }
+
+    #[test]
+    fn recognizes_iso_dates() {
+        assert!(is_iso("2021") && is_iso("2021-03") && is_iso("2021-03-05"));
+        assert!(!is_iso("Present") && !is_iso("21") && !is_iso("2021-13"));
+    }
+
+    #[test]
+    fn reads_written_dates() {
+        assert_eq!(from_written("Mar 2021").as_deref(), Some("2021-03"));
+        assert_eq!(from_written("Sept. 2019").as_deref(), Some("2019-09"));
+        assert_eq!(from_written("03/2021").as_deref(), Some("2021-03"));
+        assert_eq!(from_written("(2018)").as_deref(), Some("2018"));
+        assert_eq!(from_written("current").as_deref(), Some("Present"));
+        assert_eq!(from_written("Ma 2021"), None);
+        assert_eq!(from_written("Berlin"), None);
+    }
+
+    #[test]
+    fn unknown_directives_are_refused() {
+        assert!(check_format("%b %Y").is_ok());
+        assert!(check_format("%Q %Y").is_err());
+    }
+
+    #[test]
+    fn civil_from_days_handles_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(20_251), (2025, 6, 12));
+    }
+}
@@ -0,0 +1,275 @@
+//! Warnings and errors, for a person at a terminal or for CI.
+//!
+//! By default a warning prints to stderr as `Warning: ...`, as it
+//! always has. `srg ci` switches to GitHub's workflow commands
+//! instead: each warning or error is one `::warning file=...,line=...::`
+//! line on stdout, which Actions shows on the run and next to the
+//! line of the resume it's about. Other CI systems print them as
+//! plain lines.
+//!
+//! The file and line are only used in that form; at a terminal the
+//! message says where it's about the way it always has.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether diagnostics print as workflow commands.
+static ANNOTATIONS: AtomicBool = AtomicBool::new(false);
+
+/// Print every diagnostic from here on as a workflow command.
+pub fn use_annotations() {
+    ANNOTATIONS.store(true, Ordering::Relaxed);
+}
+
+/// Whether [`use_annotations`] was called.
+pub fn annotating() -> bool {
+    ANNOTATIONS.load(Ordering::Relaxed)
+}
+
+/// Print a warning with no place in the resume.
+pub fn warn(message: impl Into<String>) {
+    Diagnostic::warning(message).emit();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Warning => "warning",
+            Level::Error => "error",
+        })
+    }
+}
+
+/// One warning or error, and where in which file it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub message: String,
+    pub file: Option<PathBuf>,
+    /// Counted from 1.
+    pub line: Option<usize>,
+    /// Heading for the annotation, e.g. `srg lint: quantify`.
+    pub title: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self { level: Level::Warning, message: message.into(), file: None, line: None, title: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { level: Level::Error, ..Self::warning(message) }
+    }
+
+    /// This diagnostic, about `line` (if known) of `file`.
+    pub fn at(self, file: &Path, line: Option<usize>) -> Self {
+        Self { file: Some(file.to_path_buf()), line, ..self }
+    }
+
+    pub fn titled(self, title: impl Into<String>) -> Self {
+        Self { title: Some(title.into()), ..self }
+    }
+
+    /// Print the diagnostic: as a workflow command on stdout under
+    /// `srg ci`, as `Warning: ...` on stderr otherwise.
+    pub fn emit(&self) {
+        if annotating() {
+            println!("{}", self.annotation());
+        } else {
+            match self.level {
+                Level::Warning => eprintln!("Warning: {}", self.message),
+                Level::Error => eprintln!("Error: {}", self.message),
+            }
+        }
+    }
+
+    /// The diagnostic as a GitHub workflow command.
+    pub fn annotation(&self) -> String {
+        let mut properties = Vec::new();
+        if let Some(file) = &self.file {
+            let file = file.strip_prefix(".").unwrap_or(file);
+            properties.push(format!("file={}", escape_property(&file.display().to_string())));
+        }
+        if let Some(line) = self.line {
+            properties.push(format!("line={}", line));
+        }
+        if let Some(title) = &self.title {
+            properties.push(format!("title={}", escape_property(title)));
+        }
+        let properties = if properties.is_empty() { String::new() } else { format!(" {}", properties.join(",")) };
+        format!("::{}{}::{}", self.level, properties, escape_data(&self.message))
+    }
+}
+
+/// A message, with the characters that would end a workflow command
+/// encoded.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// A property value, which also can't hold the separators between
+/// properties.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// The line of a JOBL file that `path` (`person.summary`,
+/// `experience[1].highlights`, `projects`) is on; without an index,
+/// `experience.location` is the first entry that has one. With `text`,
+/// the line within that value where the text starts, for an entry of a
+/// list written one per line. Falls back to the key's table when the
+/// key isn't written out, and gives up on anything but TOML.
+pub fn line_of(source: &str, path: &str, text: Option<&str>) -> Option<usize> {
+    let (table, key) = match path.rsplit_once('.') {
+        Some((table, key)) => (table, key),
+        None => ("", path),
+    };
+    let key = key.split('[').next().unwrap_or(key);
+    let snippet = text.and_then(snippet);
+
+    let mut current = (String::new(), "");
+    let mut entries = std::collections::BTreeMap::<String, usize>::new();
+    let mut table_line = None;
+    let mut key_line = None;
+    for (n, line) in source.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+        if let Some((name, array)) = header(line) {
+            if key_line.is_some() {
+                break;
+            }
+            if table.is_empty() && (name == key || name.starts_with(&format!("{}.", key))) {
+                return Some(n);
+            }
+            current = match array {
+                true => {
+                    let count = entries.entry(name.to_string()).or_default();
+                    *count += 1;
+                    (format!("{}[{}]", name, *count - 1), name)
+                }
+                false => (name.to_string(), name),
+            };
+            if (current.0 == table || current.1 == table) && table_line.is_none() {
+                table_line = Some(n);
+            }
+            continue;
+        }
+        if current.0 != table && current.1 != table {
+            continue;
+        }
+        if key_line.is_none() && key_of(line) == Some(key) {
+            key_line = Some(n);
+        }
+        if key_line.is_some() {
+            match &snippet {
+                Some(snippet) if line.contains(snippet.as_str()) => return Some(n),
+                Some(_) => {}
+                None => return key_line,
+            }
+        }
+    }
+    key_line.or(table_line)
+}
+
+/// The line of a JOBL file that a lint finding's or budget overrun's
+/// `location` (`experience "Analyst at Engine Co", highlight 2`,
+/// `projects.url`) is about.
+pub fn line_of_location(source: &str, doc: &jobl::JoblDocument, location: &str) -> Option<usize> {
+    match crate::lint::texts(doc).iter().find(|text| text.location == location) {
+        Some(text) => line_of(source, &text.path, Some(text.text)),
+        None if location == "summary" => line_of(source, "person.summary", None),
+        None => line_of(source, location, None),
+    }
+}
+
+/// The line a TOML parse error names ("TOML parse error at line 3").
+pub fn line_in_error(message: &str) -> Option<usize> {
+    let rest = &message[message.find("at line ")? + "at line ".len()..];
+    rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+}
+
+/// The start of `text` as it's written in a TOML string: up to the
+/// first character TOML would escape.
+fn snippet(text: &str) -> Option<String> {
+    let snippet: String = text.trim().chars().take_while(|c| !matches!(c, '"' | '\\' | '\n')).take(40).collect();
+    Some(snippet).filter(|s| !s.trim().is_empty())
+}
+
+/// A table header's name and whether it's an array of tables.
+fn header(line: &str) -> Option<(&str, bool)> {
+    let line = line.split('#').next()?.trim();
+    if let Some(name) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+        return Some((name.trim(), true));
+    }
+    let name = line.strip_prefix('[')?.strip_suffix(']')?;
+    Some((name.trim(), false))
+}
+
+/// The key a `key = value` line sets.
+fn key_of(line: &str) -> Option<&str> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let (key, _) = line.split_once('=')?;
+    Some(key.trim().trim_matches('"'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESUME: &str = r#"[person]
+name = "Ada Lovelace"
+summary = "Writes programs."
+
+[[experience]]
+title = "Analyst"
+company = "Engine Co"
+
+[[experience]]
+title = "Consultant"
+company = "Self"
+highlights = [
+  "Wrote the first algorithm",
+  "Translated Menabrea's \"Sketch\"",
+]
+
+[skills]
+Math = ["Calculus"]
+"#;
+
+    #[test]
+    fn finds_the_line_of_a_path() {
+        assert_eq!(line_of(RESUME, "person.summary", None), Some(3));
+        assert_eq!(line_of(RESUME, "experience[1].title", None), Some(10));
+        assert_eq!(line_of(RESUME, "experience[1].highlights", Some("Wrote the first algorithm")), Some(13));
+        assert_eq!(line_of(RESUME, "experience[1].highlights", Some("Translated Menabrea's \"Sketch\"")), Some(14));
+        assert_eq!(line_of(RESUME, "skills", None), Some(17));
+        // An unwritten key points at its table.
+        assert_eq!(line_of(RESUME, "experience[0].location", None), Some(5));
+        assert_eq!(line_of(RESUME, "projects", None), None);
+        let doc = jobl::parse_str(RESUME).unwrap();
+        let location = "experience \"Consultant at Self\", highlight 1";
+        assert_eq!(line_of_location(RESUME, &doc, location), Some(13));
+        assert_eq!(line_of_location(RESUME, &doc, "summary"), Some(3));
+        assert_eq!(line_of_location(RESUME, &doc, "experience.company"), Some(7));
+        assert_eq!(line_in_error("TOML parse error at line 3, column 7\n  |"), Some(3));
+    }
+
+    #[test]
+    fn writes_workflow_commands() {
+        let warning = Diagnostic::warning("no numbers; add 50%\nor more")
+            .at(Path::new("./cv/resume.jobl"), Some(12))
+            .titled("srg lint: quantify");
+        assert_eq!(
+            warning.annotation(),
+            "::warning file=cv/resume.jobl,line=12,title=srg lint%3A quantify::no numbers; add 50%25%0Aor more"
+        );
+        assert_eq!(Diagnostic::error("Failed").annotation(), "::error::Failed");
+    }
+}
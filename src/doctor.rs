@@ -0,0 +1,267 @@
+//! Environment checks (`srg doctor`).
+//!
+//! A failed PDF build usually surfaces as "Failed to launch Chrome
+//! browser" with nothing to go on. The doctor looks at each thing a
+//! build depends on (Chrome, the theme's fonts, the output directory,
+//! the bundled themes) and says what to do about whatever is wrong.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::themes::{self, ThemeStack};
+
+/// Families every renderer resolves itself; no need to look for
+/// them.
+const GENERIC_FAMILIES: &[&str] = &[
+    "serif", "sans-serif", "monospace", "cursive", "fantasy", "system-ui", "ui-serif",
+    "ui-sans-serif", "ui-monospace", "-apple-system", "BlinkMacSystemFont",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Check { name, status: Status::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check { name, status: Status::Warn, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check { name, status: Status::Fail, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    /// The check as `srg doctor` prints it.
+    pub fn report(&self) -> String {
+        let label = match self.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        let mut out = format!("  {:<5} {}: {}\n", label, self.name, self.detail);
+        if let Some(fix) = &self.fix {
+            out.push_str(&format!("        fix: {}\n", fix));
+        }
+        out
+    }
+}
+
+/// Every check, for building with `theme` into `out_dir`.
+pub fn run(theme: &str, out_dir: &Path) -> Vec<Check> {
+    let mut checks = chrome();
+    checks.push(themes_check());
+    checks.push(fonts(theme));
+    checks.push(output_dir(out_dir));
+    checks
+}
+
+/// Whether Chrome can be found and started.
+fn chrome() -> Vec<Check> {
+    let path = match headless_chrome::browser::default_executable() {
+        Ok(path) => path,
+        Err(_) => {
+            return vec![Check::fail(
+                "Chrome",
+                "no Chrome, Chromium, or Edge found",
+                "install Google Chrome or Chromium (e.g. `apt install chromium`), \
+                 or point the CHROME environment variable at the executable",
+            )]
+        }
+    };
+
+    let version = Command::new(&path)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|v| !v.is_empty());
+    let found = match &version {
+        Some(version) => format!("{} ({})", path.display(), version),
+        None => path.display().to_string(),
+    };
+
+    let launch = match crate::build::chrome::launch() {
+        Ok(_) => Check::ok("Chrome launch", "headless Chrome starts"),
+        Err(e) => Check::fail(
+            "Chrome launch",
+            format!("{} doesn't start: {}", path.display(), e),
+            "run it with --headless --version to see why; missing shared libraries \
+             (check with ldd) and running as root without a sandbox are the usual causes",
+        ),
+    };
+    vec![Check::ok("Chrome", found), launch]
+}
+
+/// Whether every bundled theme resolves, parses, and has valid
+/// settings.
+fn themes_check() -> Check {
+    let broken: Vec<String> = themes::THEMES
+        .iter()
+        .filter_map(|name| theme_problem(name).map(|e| format!("{}: {}", name, e)))
+        .collect();
+    if broken.is_empty() {
+        Check::ok("Themes", format!("{} bundled themes load", themes::THEMES.len()))
+    } else {
+        Check::fail(
+            "Themes",
+            broken.join("; "),
+            "fix the theme files under src/layouts/ and rebuild srg",
+        )
+    }
+}
+
+fn theme_problem(name: &str) -> Option<String> {
+    let check = || -> anyhow::Result<()> {
+        let stack = ThemeStack::resolve(name)?;
+        crate::layout::Layout::from_theme(name)?;
+        stack.css()?;
+        stack.pdf()?;
+        Ok(())
+    };
+    check().err().map(|e| format!("{:#}", e))
+}
+
+/// Whether the fonts `theme` asks for are installed. Themes that
+/// bundle their fonts need nothing installed.
+fn fonts(theme: &str) -> Check {
+    let stack = match ThemeStack::resolve(theme) {
+        Ok(stack) => stack,
+        Err(e) => {
+            return Check::fail(
+                "Theme",
+                e.to_string(),
+                format!("pick one of: {}", themes::THEMES.join(", ")),
+            )
+        }
+    };
+    if !stack.fonts().is_empty() {
+        return Check::ok("Fonts", format!("theme {} bundles its fonts", theme));
+    }
+    let typography = stack.typography().unwrap_or_default();
+    let Some(body) = typography.get("body-font") else {
+        return Check::ok("Fonts", format!("theme {} doesn't name any fonts", theme));
+    };
+
+    let families = font_families(body);
+    if families.is_empty() {
+        return Check::ok("Fonts", "the theme only uses generic families");
+    }
+    let Some(installed) = installed_families() else {
+        return Check::warn(
+            "Fonts",
+            "can't list installed fonts (fc-list not found)",
+            format!("make sure one of these is installed: {}", families.join(", ")),
+        );
+    };
+
+    match families.iter().find(|f| installed.iter().any(|i| i.eq_ignore_ascii_case(f))) {
+        Some(family) => Check::ok("Fonts", format!("{} is installed", family)),
+        None => Check::warn(
+            "Fonts",
+            format!("none of {} is installed; the PDF falls back to a generic font", families.join(", ")),
+            "install one of them, or pick another with [typography] body-font in srg.toml",
+        ),
+    }
+}
+
+/// Named families in a CSS font list, without quotes, generics, or
+/// `var()` references.
+pub fn font_families(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|f| f.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+        .filter(|f| !f.is_empty() && !f.starts_with("var("))
+        .filter(|f| !GENERIC_FAMILIES.iter().any(|g| g.eq_ignore_ascii_case(f)))
+        .collect()
+}
+
+/// Installed font family names, from fontconfig.
+fn installed_families() -> Option<Vec<String>> {
+    let output = Command::new("fc-list").args([":", "family"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .flat_map(|line| line.split(','))
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect(),
+    )
+}
+
+/// Whether outputs can be written to `out_dir`, or to the nearest
+/// existing directory above it when it doesn't exist yet.
+fn output_dir(out_dir: &Path) -> Check {
+    let existing = out_dir
+        .ancestors()
+        .map(|d| if d.as_os_str().is_empty() { Path::new(".") } else { d })
+        .find(|d| d.is_dir());
+    let Some(existing) = existing else {
+        return Check::fail(
+            "Output directory",
+            format!("{} has no existing parent directory", out_dir.display()),
+            "pass --out with a path on a mounted file system",
+        );
+    };
+
+    match tempfile::tempfile_in(existing) {
+        Ok(_) if existing == out_dir => Check::ok("Output directory", format!("{} is writable", out_dir.display())),
+        Ok(_) => Check::ok(
+            "Output directory",
+            format!("{} will be created in {}", out_dir.display(), existing.display()),
+        ),
+        Err(e) => Check::fail(
+            "Output directory",
+            format!("can't write to {}: {}", existing.display(), e),
+            "fix its permissions, or pass --out with a writable directory",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn font_families_skip_generics_and_variables() {
+        assert_eq!(
+            font_families(r#""Times New Roman", Times, "Liberation Serif", serif"#),
+            vec!["Times New Roman", "Times", "Liberation Serif"]
+        );
+        assert_eq!(font_families("var(--srg-body-font)"), Vec::<String>::new());
+        assert_eq!(font_families("-apple-system, 'Segoe UI', sans-serif"), vec!["Segoe UI"]);
+    }
+
+    #[test]
+    fn bundled_themes_are_intact() {
+        assert_eq!(themes_check().status, Status::Ok);
+    }
+
+    #[test]
+    fn output_dir_checks_nearest_existing_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = output_dir(&dir.path().join("dist/nested"));
+        assert_eq!(check.status, Status::Ok);
+        assert!(check.detail.contains("will be created"));
+        assert_eq!(output_dir(dir.path()).status, Status::Ok);
+    }
+}
@@ -0,0 +1,223 @@
+//! GitHub metadata for projects (`--github` / `github = true`).
+//!
+//! Projects whose `url` points at a GitHub repository get `stars`,
+//! `language`, and `description` layout fields from the GitHub API.
+//! Responses are cached in `.srg-cache/github.json` next to the
+//! resume for a day, so rebuilding while editing doesn't hit the
+//! API (or its unauthenticated rate limit) every time. Set
+//! `GITHUB_TOKEN` to raise that limit.
+//!
+//! Network problems never fail the build: a stale cache entry is
+//! used if there is one, otherwise the project just goes without.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a cached response is considered fresh.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const API_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The parts of a repository we keep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepoInfo {
+    pub stars: u64,
+    pub language: Option<String>,
+    pub description: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub fetched_at: u64,
+}
+
+impl RepoInfo {
+    /// The layout fields this repository provides.
+    pub fn fields(&self) -> BTreeMap<String, String> {
+        let mut fields = BTreeMap::new();
+        fields.insert("stars".to_string(), self.stars.to_string());
+        if let Some(language) = &self.language {
+            fields.insert("language".to_string(), language.clone());
+        }
+        if let Some(description) = self.description.as_ref().filter(|d| !d.trim().is_empty()) {
+            fields.insert("description".to_string(), description.trim().to_string());
+        }
+        fields
+    }
+}
+
+/// Shape of the `GET /repos/{owner}/{repo}` response we read.
+#[derive(Deserialize)]
+struct ApiRepo {
+    stargazers_count: u64,
+    language: Option<String>,
+    description: Option<String>,
+}
+
+/// `owner/repo` for a GitHub repository URL, or `None` for anything
+/// else (user pages, gists, other hosts).
+pub fn repo_slug(url: &str) -> Option<String> {
+    let rest = url
+        .trim()
+        .strip_prefix("https://")
+        .or_else(|| url.trim().strip_prefix("http://"))?;
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    let path = rest.strip_prefix("github.com/")?;
+
+    let mut parts = path.split(['/', '?', '#']).filter(|p| !p.is_empty());
+    let owner = parts.next()?;
+    let repo = parts.next()?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    Some(format!("{}/{}", owner, repo))
+}
+
+/// Cache file location for a resume at `input`.
+pub fn cache_path(input: &Path) -> PathBuf {
+    input
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(".srg-cache")
+        .join("github.json")
+}
+
+/// Layout fields for each of `doc`'s projects, in order. Projects
+/// without a GitHub URL (or whose lookup failed) get no fields.
+pub fn project_fields(doc: &jobl::JoblDocument, cache_file: &Path) -> Vec<BTreeMap<String, String>> {
+    let mut cache: BTreeMap<String, RepoInfo> = std::fs::read_to_string(cache_file)
+        .ok()
+        .and_then(|body| serde_json::from_str(&body).ok())
+        .unwrap_or_default();
+    let now = now();
+    let mut dirty = false;
+
+    let fields = doc
+        .projects
+        .iter()
+        .map(|proj| {
+            let Some(slug) = proj.url.as_deref().and_then(repo_slug) else {
+                return BTreeMap::new();
+            };
+
+            let fresh = cache
+                .get(&slug)
+                .is_some_and(|info| now.saturating_sub(info.fetched_at) < CACHE_TTL.as_secs());
+            if !fresh {
+                match fetch(&slug) {
+                    Ok(info) => {
+                        cache.insert(slug.clone(), info);
+                        dirty = true;
+                    }
+                    Err(err) => {
+                        let fallback = if cache.contains_key(&slug) { "using cached data" } else { "skipping" };
+                        crate::diagnostics::warn(format!("GitHub lookup for {} failed ({:#}); {}", slug, err, fallback));
+                    }
+                }
+            }
+
+            cache.get(&slug).map(RepoInfo::fields).unwrap_or_default()
+        })
+        .collect();
+
+    if dirty {
+        if let Err(err) = save_cache(cache_file, &cache) {
+            crate::diagnostics::warn(format!("could not write {}: {:#}", cache_file.display(), err));
+        }
+    }
+
+    fields
+}
+
+fn fetch(slug: &str) -> Result<RepoInfo> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(API_TIMEOUT))
+        .build()
+        .into();
+    let mut request = agent
+        .get(&format!("https://api.github.com/repos/{}", slug))
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", concat!("srg/", env!("CARGO_PKG_VERSION")));
+    let token = std::env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty());
+    if let Some(token) = &token {
+        request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    let body = request
+        .call()
+        .context("request failed")?
+        .body_mut()
+        .read_to_string()
+        .context("reading response")?;
+    let repo: ApiRepo = serde_json::from_str(&body).context("parsing response")?;
+
+    Ok(RepoInfo {
+        stars: repo.stargazers_count,
+        language: repo.language,
+        description: repo.description,
+        fetched_at: now(),
+    })
+}
+
+fn save_cache(path: &Path, cache: &BTreeMap<String, RepoInfo>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(cache)?)?;
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_slug_accepts_repository_urls_only() {
+        assert_eq!(repo_slug("https://github.com/ducks/srg").as_deref(), Some("ducks/srg"));
+        assert_eq!(
+            repo_slug("https://www.github.com/ducks/srg.git").as_deref(),
+            Some("ducks/srg")
+        );
+        assert_eq!(
+            repo_slug("https://github.com/ducks/srg/tree/main#readme").as_deref(),
+            Some("ducks/srg")
+        );
+        assert_eq!(repo_slug("https://github.com/ducks"), None);
+        assert_eq!(repo_slug("https://gitlab.com/ducks/srg"), None);
+    }
+
+    #[test]
+    fn fresh_cache_entries_are_used_without_fetching() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_file = dir.path().join(".srg-cache/github.json");
+        let mut cache = BTreeMap::new();
+        cache.insert(
+            "ducks/srg".to_string(),
+            RepoInfo {
+                stars: 42,
+                language: Some("Rust".into()),
+                description: Some("Static resumes".into()),
+                fetched_at: now(),
+            },
+        );
+        save_cache(&cache_file, &cache).unwrap();
+
+        let input = dir.path().join("resume.jobl");
+        std::fs::write(
+            &input,
+            "[person]\nname = \"Ada\"\n\n[[projects]]\nname = \"srg\"\nurl = \"https://github.com/ducks/srg\"\n\n[[projects]]\nname = \"site\"\n",
+        )
+        .unwrap();
+        let doc = jobl::parse_file(&input).unwrap();
+
+        let fields = project_fields(&doc, &cache_path(&input));
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0]["stars"], "42");
+        assert_eq!(fields[0]["language"], "Rust");
+        assert!(fields[1].is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! Gravatar for the person `avatar` field (`--gravatar` /
+//! `gravatar = true`).
+//!
+//! The image for `person.email` is fetched once, kept in
+//! `.srg-cache/` next to the resume, and inlined into the page as a
+//! `data:` URI so the HTML and the PDF don't depend on Gravatar
+//! being reachable later. Fetching is opt-in because it sends a hash
+//! of the email address to a third party.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a cached image is used before fetching it again.
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Requested size in pixels; big enough for print.
+const SIZE: u32 = 256;
+
+/// Gravatar's hash of an email address.
+pub fn email_hash(email: &str) -> String {
+    format!("{:x}", md5::compute(email.trim().to_lowercase()))
+}
+
+/// Cache file for `email`'s image, for a resume at `input`.
+pub fn cache_path(input: &Path, email: &str) -> PathBuf {
+    input
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(".srg-cache")
+        .join(format!("gravatar-{}", email_hash(email)))
+}
+
+/// The avatar for `email` as a `data:` URI, from the cache when it's
+/// fresh. Returns `None` (after a warning) when there is no Gravatar
+/// for the address or it can't be fetched and nothing is cached.
+pub fn avatar(email: &str, cache_file: &Path) -> Option<String> {
+    let cached = std::fs::read(cache_file).ok();
+    let fresh = std::fs::metadata(cache_file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < CACHE_TTL);
+
+    let bytes = match (cached, fresh) {
+        (Some(bytes), true) => bytes,
+        (cached, _) => match fetch(email) {
+            Ok(bytes) => {
+                if let Err(err) = save(cache_file, &bytes) {
+                    crate::diagnostics::warn(format!("could not write {}: {:#}", cache_file.display(), err));
+                }
+                bytes
+            }
+            Err(err) => {
+                let fallback = if cached.is_some() { "using cached image" } else { "skipping avatar" };
+                crate::diagnostics::warn(format!("Gravatar lookup failed ({:#}); {}", err, fallback));
+                cached?
+            }
+        },
+    };
+
+    Some(data_uri(&bytes))
+}
+
+fn fetch(email: &str) -> Result<Vec<u8>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(FETCH_TIMEOUT))
+        .build()
+        .into();
+    // d=404 makes a missing Gravatar an error instead of a stock image.
+    let url = format!(
+        "https://www.gravatar.com/avatar/{}?s={}&d=404",
+        email_hash(email),
+        SIZE
+    );
+    let mut bytes = Vec::new();
+    agent
+        .get(&url)
+        .header("User-Agent", concat!("srg/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .context("request failed")?
+        .body_mut()
+        .as_reader()
+        .read_to_end(&mut bytes)
+        .context("reading image")?;
+    Ok(bytes)
+}
+
+fn save(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Inline `bytes` as a `data:` URI, typed by its magic number.
+fn data_uri(bytes: &[u8]) -> String {
+    let mime = if bytes.starts_with(b"\x89PNG") {
+        "image/png"
+    } else if bytes.starts_with(b"GIF8") {
+        "image/gif"
+    } else {
+        "image/jpeg"
+    };
+    format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn email_hash_normalizes_address() {
+        // The example from Gravatar's documentation.
+        assert_eq!(
+            email_hash(" MyEmailAddress@example.com "),
+            "0bc83cb571cd1c50ba6f3e8a78ef1346"
+        );
+    }
+
+    #[test]
+    fn fresh_cache_is_inlined() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_file = cache_path(&dir.path().join("resume.jobl"), "ada@example.com");
+        save(&cache_file, b"\x89PNG\r\n").unwrap();
+
+        assert_eq!(
+            avatar("ada@example.com", &cache_file).as_deref(),
+            Some("data:image/png;base64,iVBORw0K")
+        );
+    }
+}
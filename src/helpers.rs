@@ -0,0 +1,136 @@
+//! Layout helpers: `short(website)`, `local(phone)`.
+//!
+//! A helper wraps a field word on a layout line and changes how its
+//! value reads, not what it is: links keep their full `href` and only
+//! the shown text changes. Values a helper doesn't recognize are shown
+//! as written.
+
+use std::fmt;
+
+/// A helper a layout can wrap a field in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Helper {
+    /// A URL without its scheme, `www.`, or trailing slash:
+    /// `https://www.example.com/` reads `example.com`.
+    Short,
+    /// An E.164 phone number (`+14155550123`) as it's written in its
+    /// country: `(415) 555-0123`.
+    Local,
+}
+
+impl Helper {
+    pub const NAMES: &'static [&'static str] = &["short", "local"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "short" => Some(Helper::Short),
+            "local" => Some(Helper::Local),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Helper::Short => "short",
+            Helper::Local => "local",
+        }
+    }
+
+    /// `value` as the helper shows it.
+    pub fn apply(self, value: &str) -> String {
+        match self {
+            Helper::Short => short_url(value),
+            Helper::Local => local_phone(value).unwrap_or_else(|| value.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Helper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Split `helper(field)` into the helper's name and the field.
+pub fn split_call(word: &str) -> Option<(&str, &str)> {
+    let (name, rest) = word.split_once('(')?;
+    let field = rest.strip_suffix(')')?;
+    Some((name, field)).filter(|(name, field)| !name.is_empty() && !field.is_empty())
+}
+
+fn short_url(url: &str) -> String {
+    let url = url.trim();
+    let rest = ["https://", "http://"]
+        .iter()
+        .find_map(|scheme| url.strip_prefix(scheme))
+        .unwrap_or(url);
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    rest.strip_suffix('/').unwrap_or(rest).to_string()
+}
+
+/// National formats by country calling code, for numbers of the
+/// given length: each `#` is a digit of the national number.
+const NATIONAL_FORMATS: &[(&str, usize, &str)] = &[
+    ("1", 10, "(###) ###-####"),
+    ("33", 9, "0# ## ## ## ##"),
+    ("44", 10, "0#### ######"),
+    ("61", 9, "0# #### ####"),
+    ("91", 10, "##### #####"),
+];
+
+/// The national format of an E.164 number, or `None` for anything
+/// else, including countries the table doesn't know. Spaces, dashes,
+/// dots, and parentheses around the digits are ignored.
+fn local_phone(phone: &str) -> Option<String> {
+    let digits = phone.trim().strip_prefix('+')?;
+    if !digits.chars().all(|c| c.is_ascii_digit() || " -.()".contains(c)) {
+        return None;
+    }
+    let digits: String = digits.chars().filter(char::is_ascii_digit).collect();
+    let (code, national, format) = NATIONAL_FORMATS.iter().find_map(|&(code, length, format)| {
+        let national = digits.strip_prefix(code)?;
+        (national.len() == length).then_some((code, national, format))
+    })?;
+    // London numbers and Australian mobiles group differently from
+    // the rest of their country.
+    let format = match code {
+        "44" if national.starts_with("20") => "0## #### ####",
+        "61" if national.starts_with('4') => "0### ### ###",
+        _ => format,
+    };
+    let mut digits = national.chars();
+    Some(format.chars().map(|c| if c == '#' { digits.next().unwrap_or_default() } else { c }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_lose_their_scheme() {
+        assert_eq!(Helper::Short.apply("https://www.example.com/"), "example.com");
+        assert_eq!(Helper::Short.apply("http://github.com/ada/"), "github.com/ada");
+        assert_eq!(Helper::Short.apply("example.com/cv"), "example.com/cv");
+    }
+
+    #[test]
+    fn phones_read_as_they_do_at_home() {
+        assert_eq!(Helper::Local.apply("+14155550123"), "(415) 555-0123");
+        assert_eq!(Helper::Local.apply("+1 415-555-0123"), "(415) 555-0123");
+        assert_eq!(Helper::Local.apply("+442079460958"), "020 7946 0958");
+        assert_eq!(Helper::Local.apply("+447911123456"), "07911 123456");
+        assert_eq!(Helper::Local.apply("+33612345678"), "06 12 34 56 78");
+        assert_eq!(Helper::Local.apply("+61412345678"), "0412 345 678");
+        // Not E.164, or a country the table doesn't know: as written.
+        assert_eq!(Helper::Local.apply("555-1234"), "555-1234");
+        assert_eq!(Helper::Local.apply("+4930123456"), "+4930123456");
+        assert_eq!(Helper::Local.apply("+1 415 CALL-NOW"), "+1 415 CALL-NOW");
+    }
+
+    #[test]
+    fn calls_split() {
+        assert_eq!(split_call("short(website)"), Some(("short", "website")));
+        assert_eq!(split_call("website"), None);
+        assert_eq!(split_call("short()"), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! `srg import`: a first JOBL file from a resume kept somewhere else.
+//!
+//! Each importer reads its format into a [`Draft`]: the document, the
+//! `[person.meta]` fields JOBL has no place for, and notes on what
+//! it left out or couldn't find. The draft is written as JOBL for the
+//! user to check and finish by hand.
+
+use anyhow::{Context, Result};
+use jobl::{JoblDocument, Person};
+use std::collections::BTreeMap;
+
+pub mod linkedin;
+pub mod markdown;
+pub mod text;
+
+/// What separates the role from the company (the degree from the
+/// school) in an entry's heading, tried in order.
+const HEADING_SEPARATORS: &[&str] = &[" — ", " – ", " | ", " @ ", " at ", ", "];
+
+/// What separates the start from the end of a date range.
+const DATE_SEPARATORS: &[&str] = &[" – ", " — ", " - ", "–", "—", " to "];
+
+/// A resume section, from its heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// Before the first heading.
+    Intro,
+    Summary,
+    Experience,
+    Education,
+    Projects,
+    Skills,
+    /// A section JOBL has no place for.
+    Other,
+}
+
+impl Section {
+    fn from_heading(heading: &str) -> Self {
+        match heading.trim().trim_end_matches(':').to_lowercase().as_str() {
+            "summary" | "professional summary" | "about" | "about me" | "profile" | "objective" => Section::Summary,
+            "experience" | "work experience" | "professional experience" | "work" | "work history"
+            | "employment" | "employment history" => Section::Experience,
+            "education" => Section::Education,
+            "projects" | "side projects" | "selected projects" => Section::Projects,
+            "skills" | "technical skills" | "core competencies" => Section::Skills,
+            _ => Section::Other,
+        }
+    }
+}
+
+/// An entry's heading split at the first of [`HEADING_SEPARATORS`]
+/// it has: `Engineer at Acme` is `("Engineer", "Acme")`. Without one
+/// the second half is empty.
+fn split_pair(heading: &str) -> (String, String) {
+    HEADING_SEPARATORS
+        .iter()
+        .find_map(|sep| heading.split_once(sep))
+        .map(|(a, b)| (a.trim().to_string(), b.trim().to_string()))
+        .unwrap_or((heading.trim().to_string(), String::new()))
+}
+
+/// A resume read from another format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draft {
+    pub doc: JoblDocument,
+    /// Person fields beyond JOBL's own, written to `[person.meta]`.
+    pub meta: BTreeMap<String, String>,
+    /// What to look at before building: things that were read but
+    /// have no place in the draft, and required fields that weren't
+    /// found.
+    pub notes: Vec<String>,
+}
+
+impl Default for Draft {
+    fn default() -> Self {
+        Self {
+            doc: JoblDocument {
+                person: Person {
+                    name: String::new(),
+                    headline: None,
+                    location: None,
+                    email: None,
+                    website: None,
+                    github: None,
+                    linkedin: None,
+                    phone: None,
+                    summary: None,
+                },
+                skills: None,
+                experience: Vec::new(),
+                projects: Vec::new(),
+                education: Vec::new(),
+            },
+            meta: BTreeMap::new(),
+            notes: Vec::new(),
+        }
+    }
+}
+
+impl Draft {
+    /// The draft as a JOBL file.
+    pub fn to_jobl(&self) -> Result<String> {
+        let mut table = toml::Table::try_from(&self.doc).context("Failed to write the draft as JOBL")?;
+        if !self.meta.is_empty() {
+            let meta = toml::Table::try_from(&self.meta).context("Failed to write [person.meta]")?;
+            if let Some(toml::Value::Table(person)) = table.get_mut("person") {
+                person.insert("meta".to_string(), toml::Value::Table(meta));
+            }
+        }
+        toml::to_string_pretty(&table).context("Failed to write the draft as JOBL")
+    }
+}
@@ -0,0 +1,257 @@
+//! LinkedIn data exports (`srg import linkedin archive.zip`).
+//!
+//! LinkedIn's "Get a copy of your data" sends a zip of CSV files. The
+//! importer reads the ones a resume is made of:
+//!
+//! - `Profile.csv`: the name, headline, summary, location, and
+//!   websites. Twitter handles go to `[person.meta]`.
+//! - `Positions.csv`: experience. A position's description becomes
+//!   its highlights where it's written as bullets, its summary
+//!   otherwise, and a position without an end date is current.
+//! - `Education.csv`: education, with notes and activities as
+//!   details.
+//!
+//! When the export has them, `Email Addresses.csv` gives the primary
+//! email, `Skills.csv` the skills (one `Skills` category, to sort by
+//! hand), and `Projects.csv` the projects. LinkedIn writes dates as
+//! `Mar 2021` or `2021`; they're converted to JOBL's `2021-03`.
+
+use anyhow::{bail, Result};
+use jobl::{EducationItem, ExperienceItem, ProjectItem};
+use std::collections::BTreeMap;
+
+use super::text::{bullet, link};
+use super::Draft;
+use crate::dates::from_written;
+
+/// One CSV row, by column name.
+type Row = BTreeMap<String, String>;
+
+/// Read a LinkedIn data export.
+pub fn convert(archive: &[u8]) -> Result<Draft> {
+    let files = crate::archive::read_zip(archive)?;
+    let table = |name: &str| -> Option<Vec<Row>> {
+        files
+            .iter()
+            .find(|(path, _)| path.rsplit('/').next().is_some_and(|file| file.eq_ignore_ascii_case(name)))
+            .map(|(_, bytes)| rows(&String::from_utf8_lossy(bytes)))
+    };
+
+    let profile = table("Profile.csv");
+    let positions = table("Positions.csv");
+    let education = table("Education.csv");
+    if profile.is_none() && positions.is_none() && education.is_none() {
+        bail!("no Profile.csv, Positions.csv, or Education.csv in the archive; is it LinkedIn's data export?");
+    }
+
+    let mut draft = Draft::default();
+    match profile.as_deref() {
+        Some([row, ..]) => read_profile(row, &mut draft),
+        _ => draft.notes.push("no Profile.csv: fill in the name by hand".to_string()),
+    }
+    if let Some(email) = table("Email Addresses.csv").and_then(primary_email) {
+        draft.doc.person.email = Some(email);
+    }
+    let doc = &mut draft.doc;
+    doc.experience = positions.unwrap_or_default().iter().map(position).collect();
+    doc.education = education.unwrap_or_default().iter().map(school).collect();
+    doc.projects = table("Projects.csv").unwrap_or_default().iter().map(project).collect();
+    let skills: Vec<String> = table("Skills.csv")
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|row| field(row, "Name"))
+        .collect();
+    if !skills.is_empty() {
+        doc.skills = Some(BTreeMap::from([("Skills".to_string(), skills)]));
+    }
+
+    let mut notes = Vec::new();
+    for exp in &draft.doc.experience {
+        if exp.start.is_none() {
+            notes.push(format!("experience \"{}\" at {} has no start date", exp.title, exp.company));
+        }
+    }
+    draft.notes.extend(notes);
+    Ok(draft)
+}
+
+fn read_profile(row: &Row, draft: &mut Draft) {
+    let person = &mut draft.doc.person;
+    let name = [field(row, "First Name"), field(row, "Last Name")];
+    person.name = name.into_iter().flatten().collect::<Vec<_>>().join(" ");
+    person.headline = field(row, "Headline");
+    person.summary = field(row, "Summary");
+    person.location = field(row, "Geo Location");
+    // `[PERSONAL:https://ada.dev,PORTFOLIO:github.com/ada]`
+    let websites = field(row, "Websites").unwrap_or_default();
+    for site in websites.trim_matches(['[', ']']).split(',') {
+        let url = site.split_once(':').filter(|(kind, _)| kind.chars().all(|c| c.is_ascii_uppercase()));
+        let Some(url) = link(url.map_or(site, |(_, url)| url).trim()) else { continue };
+        let slot = if url.contains("github.com") { &mut person.github } else { &mut person.website };
+        if slot.is_none() {
+            *slot = Some(url);
+        }
+    }
+    if let Some(twitter) = field(row, "Twitter Handles") {
+        draft.meta.insert("twitter".to_string(), twitter.trim_matches(['[', ']']).to_string());
+    }
+}
+
+/// The primary address, or the first one.
+fn primary_email(rows: Vec<Row>) -> Option<String> {
+    let primary = rows.iter().find(|row| field(row, "Primary").as_deref() == Some("Yes"));
+    primary.or(rows.first()).and_then(|row| field(row, "Email Address"))
+}
+
+fn position(row: &Row) -> ExperienceItem {
+    let (summary, highlights) = description(field(row, "Description"));
+    ExperienceItem {
+        title: field(row, "Title").unwrap_or_default(),
+        company: field(row, "Company Name").unwrap_or_default(),
+        location: field(row, "Location"),
+        start: date(row, "Started On"),
+        end: date(row, "Finished On").or_else(|| Some("Present".to_string())),
+        summary,
+        technologies: Vec::new(),
+        highlights,
+    }
+}
+
+fn school(row: &Row) -> EducationItem {
+    EducationItem {
+        institution: field(row, "School Name").unwrap_or_default(),
+        degree: field(row, "Degree Name").unwrap_or_default(),
+        location: None,
+        start: date(row, "Start Date"),
+        end: date(row, "End Date"),
+        details: ["Notes", "Activities"].into_iter().filter_map(|column| field(row, column)).collect(),
+    }
+}
+
+fn project(row: &Row) -> ProjectItem {
+    ProjectItem {
+        name: field(row, "Title").unwrap_or_default(),
+        url: field(row, "Url"),
+        summary: field(row, "Description"),
+        role: None,
+        start: date(row, "Started On"),
+        end: date(row, "Finished On"),
+        technologies: Vec::new(),
+    }
+}
+
+/// A column's value, if it's there and not blank.
+fn field(row: &Row, column: &str) -> Option<String> {
+    row.get(column).map(|value| value.trim()).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+/// A date column in JOBL's form, or as written when it can't be read.
+fn date(row: &Row, column: &str) -> Option<String> {
+    field(row, column).map(|value| from_written(&value).unwrap_or(value))
+}
+
+/// A description's prose as the summary and its bullets as
+/// highlights.
+fn description(text: Option<String>) -> (Option<String>, Vec<String>) {
+    let mut prose = Vec::new();
+    let mut highlights = Vec::new();
+    for line in text.iter().flat_map(|text| text.lines()).map(str::trim).filter(|line| !line.is_empty()) {
+        match bullet(line) {
+            Some(item) => highlights.push(item.to_string()),
+            None => prose.push(line),
+        }
+    }
+    (Some(prose.join("\n\n")).filter(|s| !s.is_empty()), highlights)
+}
+
+/// A CSV file's rows, keyed by the header row. Fields may be quoted,
+/// with `""` for a quote and line breaks inside.
+fn rows(csv: &str) -> Vec<Row> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    let mut records = records.into_iter().filter(|record| record.iter().any(|field| !field.is_empty()));
+    let Some(header) = records.next() else { return Vec::new() };
+    records.map(|record| header.iter().cloned().zip(record).collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::ZipWriter;
+
+    #[test]
+    fn reads_quoted_csv() {
+        let csv = "\u{feff}Title,Description\r\nEngineer,\"Built things, \"\"fast\"\"\nand well\"\r\n\r\nLead,\r\n";
+        let rows = rows(csv);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["Description"], "Built things, \"fast\"\nand well");
+        assert_eq!(rows[1]["Title"], "Lead");
+    }
+
+    #[test]
+    fn reads_an_export() {
+        let mut zip = ZipWriter::new(Vec::new());
+        let profile = "First Name,Last Name,Headline,Summary,Geo Location,Websites,Twitter Handles\n\
+                       Ada,Lovelace,Analyst,Writes programs.,\"London, UK\",\"[PERSONAL:ada.dev,PORTFOLIO:https://github.com/ada]\",[ada]\n";
+        let positions = "Company Name,Title,Description,Location,Started On,Finished On\n\
+                         Engine Co,Analyst,\"Notes on the engine.\n- Wrote the first algorithm\",London,Jan 1842,\n";
+        let education = "School Name,Start Date,End Date,Notes,Degree Name,Activities\n\
+                         University of London,1829,1835,,Mathematics,Chess club\n";
+        zip.add_file("Basic_LinkedInDataExport/Profile.csv", profile.as_bytes()).unwrap();
+        zip.add_file("Basic_LinkedInDataExport/Positions.csv", positions.as_bytes()).unwrap();
+        zip.add_file("Education.csv", education.as_bytes()).unwrap();
+        zip.add_file("Email Addresses.csv", b"Email Address,Confirmed,Primary\nold@example.com,Yes,No\nada@example.com,Yes,Yes\n").unwrap();
+        zip.add_file("Skills.csv", b"Name\nCalculus\nAlgebra\n").unwrap();
+        let draft = convert(&zip.finish().unwrap()).unwrap();
+
+        let person = &draft.doc.person;
+        assert_eq!(person.name, "Ada Lovelace");
+        assert_eq!(person.location.as_deref(), Some("London, UK"));
+        assert_eq!(person.email.as_deref(), Some("ada@example.com"));
+        assert_eq!(person.website.as_deref(), Some("https://ada.dev"));
+        assert_eq!(person.github.as_deref(), Some("https://github.com/ada"));
+        assert_eq!(draft.meta["twitter"], "ada");
+
+        let exp = &draft.doc.experience[0];
+        assert_eq!((exp.start.as_deref(), exp.end.as_deref()), (Some("1842-01"), Some("Present")));
+        assert_eq!(exp.summary.as_deref(), Some("Notes on the engine."));
+        assert_eq!(exp.highlights, ["Wrote the first algorithm"]);
+
+        let edu = &draft.doc.education[0];
+        assert_eq!((edu.institution.as_str(), edu.degree.as_str()), ("University of London", "Mathematics"));
+        assert_eq!(edu.details, ["Chess club"]);
+        assert_eq!(draft.doc.skills.as_ref().unwrap()["Skills"], ["Calculus", "Algebra"]);
+        assert!(draft.notes.is_empty(), "{:?}", draft.notes);
+    }
+
+    #[test]
+    fn rejects_other_archives() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("index.html", b"<h1>Hi</h1>").unwrap();
+        assert!(convert(&zip.finish().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,552 @@
+//! Markdown notes (`srg import markdown cv.md`).
+//!
+//! For a CV kept as a note in Obsidian or any other Markdown editor.
+//! The frontmatter holds the person:
+//!
+//! ```text
+//! ---
+//! name: Ada Lovelace
+//! email: ada@example.com
+//! pronouns: she/her
+//! tags: [cv]
+//! ---
+//! ```
+//!
+//! JOBL's person fields map onto `[person]`, other text properties go
+//! to `[person.meta]`, and the properties Obsidian keeps for itself
+//! (`tags`, `aliases`, ...) are left out. Without a `name` property, a
+//! `# Name` heading names the person.
+//!
+//! In the body, `##` headings pick the section (`## Experience`,
+//! `## Education`, `## Projects`, `## Skills`, `## Summary`) and `###`
+//! headings start an entry: `### Engineer at Acme` (or `Engineer —
+//! Acme`, `Engineer | Acme`), `### BSc — University`, `### Project`.
+//! Under an entry, `start: 2020-01` lines set fields (Dataview's
+//! `start:: 2020-01` too), bullets are highlights (an education's
+//! details), and other text is the summary. Under `## Skills`, each
+//! `Category: a, b, c` line or bullet is a category. Text before the
+//! first section is the person's summary.
+//!
+//! Wiki links, Markdown links, and emphasis are reduced to their text.
+
+use anyhow::Result;
+use jobl::{EducationItem, ExperienceItem, ProjectItem};
+use std::collections::BTreeMap;
+
+use super::{split_pair, Draft, Section, DATE_SEPARATORS};
+
+/// Properties Obsidian and its publishing plugins keep for
+/// themselves.
+const NOTE_PROPERTIES: &[&str] = &[
+    "tags", "tag", "aliases", "alias", "cssclasses", "cssclass", "publish", "permalink", "created", "updated",
+];
+
+/// Read a Markdown note.
+pub fn convert(note: &str) -> Result<Draft> {
+    let (properties, body) = frontmatter(note)?;
+    let mut reader = Reader {
+        draft: Draft::default(),
+        section: Section::Intro,
+        entry: false,
+        category: None,
+        paragraphs: Vec::new(),
+        lines: Vec::new(),
+    };
+    for (key, value) in properties {
+        reader.property(&key, value);
+    }
+    for line in body.lines() {
+        reader.line(line);
+    }
+    reader.end_entry();
+    Ok(reader.finish())
+}
+
+struct Reader {
+    draft: Draft,
+    section: Section,
+    /// Whether a `###` entry is open in the section.
+    entry: bool,
+    /// The `### Category` heading skills lines go under.
+    category: Option<String>,
+    /// Finished paragraphs of the open entry, or of the section when
+    /// no entry is open.
+    paragraphs: Vec<String>,
+    /// Lines of the paragraph being read.
+    lines: Vec<String>,
+}
+
+impl Reader {
+    fn property(&mut self, key: &str, value: Option<String>) {
+        let name = key.to_lowercase();
+        if NOTE_PROPERTIES.contains(&name.as_str()) {
+            return;
+        }
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            self.draft.notes.push(format!("property `{}` isn't text; left out", key));
+            return;
+        };
+        let person = &mut self.draft.doc.person;
+        let field = match name.as_str() {
+            "name" => {
+                person.name = value;
+                return;
+            }
+            "headline" => &mut person.headline,
+            "location" => &mut person.location,
+            "email" => &mut person.email,
+            "phone" => &mut person.phone,
+            "website" => &mut person.website,
+            "github" => &mut person.github,
+            "linkedin" => &mut person.linkedin,
+            "summary" => &mut person.summary,
+            _ => {
+                self.draft.meta.insert(key.to_string(), value);
+                return;
+            }
+        };
+        *field = Some(value);
+    }
+
+    fn line(&mut self, line: &str) {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            self.end_paragraph();
+            return;
+        }
+        // Obsidian comments.
+        if trimmed.starts_with("%%") {
+            return;
+        }
+        if let Some((level, heading)) = heading(trimmed) {
+            self.heading(level, &plain(heading));
+            return;
+        }
+        if self.section == Section::Skills {
+            self.skills(bullet(trimmed).unwrap_or(trimmed));
+            return;
+        }
+        if let Some(item) = bullet(trimmed) {
+            self.end_paragraph();
+            self.bullet(plain(item));
+            return;
+        }
+        if self.entry {
+            if let Some((key, value)) = trimmed.split_once("::").or_else(|| trimmed.split_once(':')) {
+                if self.set(&key.trim().to_lowercase(), &plain(value)) {
+                    return;
+                }
+            }
+        }
+        self.lines.push(plain(trimmed));
+    }
+
+    fn heading(&mut self, level: usize, text: &str) {
+        match level {
+            // The note's title: the person, unless a property named them.
+            1 => {
+                if self.draft.doc.person.name.is_empty() {
+                    self.draft.doc.person.name = text.to_string();
+                }
+            }
+            2 => {
+                self.end_entry();
+                self.section = Section::from_heading(text);
+                self.category = None;
+                if self.section == Section::Other {
+                    self.draft.notes.push(format!("section \"{}\" has no place in JOBL; left out", text));
+                }
+            }
+            3 => match self.section {
+                Section::Experience | Section::Education | Section::Projects => {
+                    self.end_entry();
+                    self.start_entry(text);
+                }
+                Section::Skills => self.category = Some(text.to_string()),
+                _ => self.lines.push(text.to_string()),
+            },
+            _ => self.lines.push(text.to_string()),
+        }
+    }
+
+    fn start_entry(&mut self, heading: &str) {
+        let doc = &mut self.draft.doc;
+        let (first, second) = split_pair(heading);
+        match self.section {
+            Section::Experience => doc.experience.push(ExperienceItem {
+                title: first,
+                company: second,
+                location: None,
+                start: None,
+                end: None,
+                summary: None,
+                technologies: Vec::new(),
+                highlights: Vec::new(),
+            }),
+            Section::Education => doc.education.push(EducationItem {
+                institution: second,
+                degree: first,
+                location: None,
+                start: None,
+                end: None,
+                details: Vec::new(),
+            }),
+            _ => doc.projects.push(ProjectItem {
+                name: heading.to_string(),
+                url: None,
+                summary: None,
+                role: None,
+                start: None,
+                end: None,
+                technologies: Vec::new(),
+            }),
+        }
+        self.entry = true;
+    }
+
+    /// Set field `key` of the open entry. `false` when the entry has
+    /// no such field, and the line is text after all.
+    fn set(&mut self, key: &str, value: &str) -> bool {
+        let value = value.to_string();
+        let list = || value.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+        let dates = || match DATE_SEPARATORS.iter().find_map(|sep| value.split_once(sep)) {
+            Some((start, end)) => (Some(start.trim().to_string()), Some(end.trim().to_string())),
+            None => (Some(value.clone()), None),
+        };
+        let doc = &mut self.draft.doc;
+        match self.section {
+            Section::Experience => {
+                let Some(exp) = doc.experience.last_mut() else { return false };
+                match key {
+                    "title" | "role" => exp.title = value,
+                    "company" => exp.company = value,
+                    "location" => exp.location = Some(value),
+                    "start" => exp.start = Some(value),
+                    "end" => exp.end = Some(value),
+                    "dates" => (exp.start, exp.end) = dates(),
+                    "technologies" | "tech" | "stack" => exp.technologies = list(),
+                    _ => return false,
+                }
+            }
+            Section::Education => {
+                let Some(edu) = doc.education.last_mut() else { return false };
+                match key {
+                    "degree" => edu.degree = value,
+                    "institution" | "school" => edu.institution = value,
+                    "location" => edu.location = Some(value),
+                    "start" => edu.start = Some(value),
+                    "end" => edu.end = Some(value),
+                    "dates" => (edu.start, edu.end) = dates(),
+                    _ => return false,
+                }
+            }
+            Section::Projects => {
+                let Some(proj) = doc.projects.last_mut() else { return false };
+                match key {
+                    "url" | "link" => proj.url = Some(value),
+                    "role" => proj.role = Some(value),
+                    "start" => proj.start = Some(value),
+                    "end" => proj.end = Some(value),
+                    "dates" => (proj.start, proj.end) = dates(),
+                    "technologies" | "tech" | "stack" => proj.technologies = list(),
+                    _ => return false,
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn bullet(&mut self, item: String) {
+        let doc = &mut self.draft.doc;
+        match (self.section, self.entry) {
+            (Section::Experience, true) => {
+                if let Some(exp) = doc.experience.last_mut() {
+                    exp.highlights.push(item);
+                }
+            }
+            (Section::Education, true) => {
+                if let Some(edu) = doc.education.last_mut() {
+                    edu.details.push(item);
+                }
+            }
+            (Section::Experience | Section::Education | Section::Projects, false) => {
+                self.draft.notes.push(format!("\"{}\" comes before any ### entry; left out", item));
+            }
+            (Section::Other, _) => {}
+            // A project's bullets and the summary's read as sentences.
+            _ => self.paragraphs.push(item),
+        }
+    }
+
+    /// A `Category: a, b, c` line, or a line of skills under a
+    /// `### Category` heading.
+    fn skills(&mut self, line: &str) {
+        let line = plain(line);
+        let (category, items) = match (line.split_once(':'), &self.category) {
+            (Some((category, items)), _) => (category.trim().to_string(), items),
+            (None, Some(category)) => (category.clone(), line.as_str()),
+            (None, None) => ("Skills".to_string(), line.as_str()),
+        };
+        let items = items.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let skills = self.draft.doc.skills.get_or_insert_with(BTreeMap::new);
+        skills.entry(category).or_default().extend(items);
+    }
+
+    fn end_paragraph(&mut self) {
+        if !self.lines.is_empty() {
+            self.paragraphs.push(self.lines.join(" "));
+            self.lines.clear();
+        }
+    }
+
+    /// Close the open entry, or the section's text when there's none,
+    /// giving it the paragraphs read as its summary (an education's
+    /// details).
+    fn end_entry(&mut self) {
+        self.end_paragraph();
+        let text = std::mem::take(&mut self.paragraphs).join("\n\n");
+        let entry = std::mem::take(&mut self.entry);
+        if text.is_empty() {
+            return;
+        }
+        let doc = &mut self.draft.doc;
+        if let (Section::Education, true) = (self.section, entry) {
+            if let Some(edu) = doc.education.last_mut() {
+                edu.details.extend(text.split("\n\n").map(str::to_string));
+            }
+            return;
+        }
+        let summary = match (self.section, entry) {
+            (Section::Intro | Section::Summary, _) => &mut doc.person.summary,
+            (Section::Experience, true) => match doc.experience.last_mut() {
+                Some(exp) => &mut exp.summary,
+                None => return,
+            },
+            (Section::Projects, true) => match doc.projects.last_mut() {
+                Some(proj) => &mut proj.summary,
+                None => return,
+            },
+            _ => return,
+        };
+        *summary = Some(match summary.take() {
+            Some(before) => format!("{}\n\n{}", before, text),
+            None => text,
+        });
+    }
+
+    fn finish(mut self) -> Draft {
+        let doc = &self.draft.doc;
+        let mut notes = Vec::new();
+        if doc.person.name.is_empty() {
+            notes.push("no name: add a `name` property or a `# Name` heading".to_string());
+        }
+        for exp in doc.experience.iter().filter(|exp| exp.company.is_empty()) {
+            notes.push(format!("experience \"{}\" has no company: write `### Title at Company`", exp.title));
+        }
+        for edu in doc.education.iter().filter(|edu| edu.institution.is_empty()) {
+            notes.push(format!("education \"{}\" has no institution: write `### Degree — Institution`", edu.degree));
+        }
+        self.draft.notes.extend(notes);
+        self.draft
+    }
+}
+
+/// Frontmatter properties, in order. A property whose value isn't a
+/// line of text (a list) is `None`.
+type Properties = Vec<(String, Option<String>)>;
+
+/// The note's frontmatter properties and the rest of the note.
+fn frontmatter(note: &str) -> Result<(Properties, &str)> {
+    let note = note.strip_prefix('\u{feff}').unwrap_or(note);
+    let Some(rest) = note.strip_prefix("---\n").or_else(|| note.strip_prefix("---\r\n")) else {
+        return Ok((Vec::new(), note));
+    };
+    let mut properties = Properties::new();
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Ok((properties, &rest[offset..]));
+        }
+        if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        // The items of a list, or the rest of a nested value.
+        if trimmed.starts_with([' ', '\t', '-']) {
+            if let Some((_, value)) = properties.last_mut() {
+                *value = None;
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else { continue };
+        let value = value.trim();
+        let value = if value.starts_with(['[', '{']) { None } else { Some(unquote(value).to_string()) };
+        properties.push((key.trim().to_string(), value));
+    }
+    anyhow::bail!("The note's frontmatter has no closing ---")
+}
+
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+        .unwrap_or(value)
+}
+
+/// `## Heading` as its level and text.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then(|| (level, text.trim().trim_end_matches('#').trim()))
+}
+
+/// The text of a `- item`, `* item`, `+ item`, or `1. item` line,
+/// and of a task (`- [x] item`).
+fn bullet(line: &str) -> Option<&str> {
+    let item = ["- ", "* ", "+ "].iter().find_map(|marker| line.strip_prefix(marker)).or_else(|| {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        line[digits..].strip_prefix(". ").filter(|_| digits > 0)
+    })?;
+    let item = ["[ ] ", "[x] ", "[X] "].iter().find_map(|task| item.strip_prefix(task)).unwrap_or(item);
+    Some(item.trim())
+}
+
+/// Markdown text as plain text: `[[page|alias]]` and `[[page]]` as
+/// the alias or page, `[text](url)` and `![text](url)` as the text,
+/// and no emphasis or code marks.
+fn plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.trim();
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        if let Some(inner) = after.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let link = &inner[..end];
+                match link.split_once('|') {
+                    Some((_, alias)) => out.push_str(alias),
+                    None => out.push_str(link.split('#').next().unwrap_or(link)),
+                }
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        match after.find("](").zip(after.find(')')) {
+            Some((middle, end)) if middle < end => {
+                // An image's `!` goes with it.
+                if out.ends_with('!') {
+                    out.pop();
+                }
+                out.push_str(&after[1..middle]);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push('[');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.replace("**", "").replace("__", "").replace(['*', '`'], "").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---
+name: Ada Lovelace
+headline: \"Analyst\"
+email: ada@example.com
+pronouns: she/her
+tags:
+  - cv
+aliases: [resume]
+---
+
+# Ada Lovelace
+
+Writes the first programs.
+
+## Experience
+
+### Analyst at [[Analytical Engine Co|the Engine]]
+dates:: 1842 – 1843
+location: London
+
+Translated and annotated Menabrea's paper.
+
+- Wrote the **first** algorithm
+- Saw beyond [calculation](https://example.com)
+
+## Education
+
+### Mathematics — Private tutoring
+- Taught by De Morgan
+
+## Projects
+
+### Notes on the Engine
+url: https://example.com/notes
+- Annotations three times the paper's length
+
+## Skills
+
+- Mathematics: Calculus, Algebra
+- **Languages**: English, French
+
+## Hobbies
+
+Poetry.
+";
+
+    #[test]
+    fn reads_a_note() {
+        let draft = convert(NOTE).unwrap();
+        let doc = &draft.doc;
+        assert_eq!(doc.person.name, "Ada Lovelace");
+        assert_eq!(doc.person.headline.as_deref(), Some("Analyst"));
+        assert_eq!(doc.person.summary.as_deref(), Some("Writes the first programs."));
+        assert_eq!(draft.meta.get("pronouns").map(String::as_str), Some("she/her"));
+
+        let exp = &doc.experience[0];
+        assert_eq!((exp.title.as_str(), exp.company.as_str()), ("Analyst", "the Engine"));
+        assert_eq!((exp.start.as_deref(), exp.end.as_deref()), (Some("1842"), Some("1843")));
+        assert_eq!(exp.location.as_deref(), Some("London"));
+        assert_eq!(exp.summary.as_deref(), Some("Translated and annotated Menabrea's paper."));
+        assert_eq!(exp.highlights, vec!["Wrote the first algorithm", "Saw beyond calculation"]);
+
+        assert_eq!(doc.education[0].institution, "Private tutoring");
+        assert_eq!(doc.education[0].details, vec!["Taught by De Morgan"]);
+        assert_eq!(doc.projects[0].url.as_deref(), Some("https://example.com/notes"));
+        assert_eq!(doc.projects[0].summary.as_deref(), Some("Annotations three times the paper's length"));
+
+        let skills = doc.skills.as_ref().unwrap();
+        assert_eq!(skills["Languages"], vec!["English", "French"]);
+        assert_eq!(skills["Mathematics"], vec!["Calculus", "Algebra"]);
+
+        // Obsidian's own properties are dropped quietly; the extra section isn't.
+        assert_eq!(draft.notes, vec!["section \"Hobbies\" has no place in JOBL; left out"]);
+    }
+
+    #[test]
+    fn notes_what_is_missing() {
+        let draft = convert("## Experience\n\n### Analyst\n").unwrap();
+        assert_eq!(
+            draft.notes,
+            vec![
+                "no name: add a `name` property or a `# Name` heading",
+                "experience \"Analyst\" has no company: write `### Title at Company`",
+            ]
+        );
+        assert!(convert("---\nname: Ada\n").is_err());
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(plain("**Led** [[Projects/Engine#Design|design]] of `x` ([docs](https://a.test))"), "Led design of x (docs)");
+        assert_eq!(plain("[draft] notes"), "[draft] notes");
+    }
+}
@@ -0,0 +1,550 @@
+//! Plain-text resumes (`srg import text old-resume.txt`).
+//!
+//! A resume saved as text from a word processor or a PDF has no markup
+//! to go by, so the importer guesses:
+//!
+//! - The first line is the name. Until the first section, lines with
+//!   an email address, phone number, or URL are contact details (a
+//!   piece of such a line that's none of these is the location), the
+//!   first other short line is the headline, and longer ones are the
+//!   summary.
+//! - A section starts at a line naming one (`EXPERIENCE`, `Work
+//!   History:`, ...) or at any line underlined with `---` or `===`.
+//! - In experience, education, and projects, an entry starts at a
+//!   line after a blank line or after bullets. Its first lines are
+//!   the heading (`Engineer at Acme`, or the title and company on two
+//!   lines), with the dates wherever they appear in them. Bullets are
+//!   highlights (an education's details), and sentences are the
+//!   summary.
+//! - Under skills, `Category: a, b, c` lines are categories.
+//!
+//! The result is a draft: it's meant to be read over, and `srg
+//! import` notes what it couldn't place.
+
+use anyhow::Result;
+use jobl::{EducationItem, ExperienceItem, Person, ProjectItem};
+use std::collections::BTreeMap;
+
+use super::{split_pair, Draft, Section, DATE_SEPARATORS};
+use crate::dates::from_written;
+
+/// Characters that start a bullet, followed by a space. (Word saves
+/// second-level bullets as `o`.)
+const BULLETS: &[char] = &['-', '*', '•', '·', '◦', '▪', '‣', '–', '>', 'o'];
+
+/// Headings of sections JOBL has no place for, recognized so their
+/// content isn't read into the section before them.
+const OTHER_SECTIONS: &[&str] = &[
+    "certifications", "certificates", "awards", "honors", "honours", "languages", "interests", "hobbies",
+    "publications", "references", "volunteering", "volunteer experience", "activities", "leadership",
+    "achievements", "courses", "training",
+];
+
+/// Words that mark a line of an education entry as the school.
+const SCHOOL_WORDS: &[&str] = &["universit", "college", "school", "institut", "academy", "polytechnic"];
+
+/// Read a plain-text resume.
+pub fn convert(text: &str) -> Result<Draft> {
+    let mut draft = Draft::default();
+    for (section, lines) in sections(text, &mut draft.notes) {
+        match section {
+            Section::Intro => intro(&lines, &mut draft),
+            Section::Summary => {
+                let summary = paragraphs(&lines).join("\n\n");
+                if !summary.is_empty() {
+                    draft.doc.person.summary = Some(summary);
+                }
+            }
+            Section::Experience => draft.doc.experience.extend(entries(&lines).into_iter().map(experience)),
+            Section::Education => draft.doc.education.extend(entries(&lines).into_iter().map(education)),
+            Section::Projects => draft.doc.projects.extend(entries(&lines).into_iter().map(project)),
+            Section::Skills => skills(&lines, draft.doc.skills.get_or_insert_with(BTreeMap::new)),
+            Section::Other => {}
+        }
+    }
+
+    let doc = &draft.doc;
+    let mut notes = Vec::new();
+    if doc.person.name.is_empty() {
+        notes.push("no name: the first line should be the name".to_string());
+    }
+    for exp in &doc.experience {
+        if exp.company.is_empty() {
+            notes.push(format!("experience \"{}\" has no company", exp.title));
+        }
+        if exp.start.is_none() {
+            notes.push(format!("experience \"{}\" has no dates", exp.title));
+        }
+    }
+    for edu in doc.education.iter().filter(|edu| edu.institution.is_empty()) {
+        notes.push(format!("education \"{}\" has no institution", edu.degree));
+    }
+    draft.notes.extend(notes);
+    Ok(draft)
+}
+
+/// The text's lines by section, headings and rules left out. Lines
+/// before the first heading are the intro.
+fn sections<'a>(text: &'a str, notes: &mut Vec<String>) -> Vec<(Section, Vec<&'a str>)> {
+    let mut sections = vec![(Section::Intro, Vec::new())];
+    let mut lines = text.lines().peekable();
+    let mut named = false;
+    while let Some(line) = lines.next() {
+        if is_rule(line) {
+            continue;
+        }
+        let trimmed = line.trim();
+        // The name, however it's written, isn't a heading.
+        if !named && !trimmed.is_empty() {
+            named = true;
+            sections[0].1.push(line);
+            continue;
+        }
+        let underlined = lines.peek().is_some_and(|next| is_rule(next));
+        match heading(trimmed, underlined) {
+            Some(section) => {
+                if section == Section::Other {
+                    notes.push(format!("section \"{}\" has no place in JOBL; left out", trimmed.trim_end_matches(':')));
+                }
+                sections.push((section, Vec::new()));
+            }
+            None => {
+                if let Some((_, lines)) = sections.last_mut() {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    sections
+}
+
+/// The section a line names, if it's a heading.
+fn heading(line: &str, underlined: bool) -> Option<Section> {
+    if line.is_empty() || line.chars().count() > 40 || bullet(line).is_some() {
+        return None;
+    }
+    let name = line.trim_end_matches(':').trim();
+    match Section::from_heading(name) {
+        Section::Other if underlined || OTHER_SECTIONS.contains(&name.to_lowercase().as_str()) => Some(Section::Other),
+        Section::Other => None,
+        section => Some(section),
+    }
+}
+
+/// A line that only draws a rule: `-----`, `=====`, `_____`.
+fn is_rule(line: &str) -> bool {
+    let line = line.trim();
+    line.chars().count() >= 3 && line.chars().all(|c| matches!(c, '-' | '=' | '_' | '─' | '━'))
+}
+
+/// The text of a bullet line.
+pub(super) fn bullet(line: &str) -> Option<&str> {
+    let mut chars = line.chars();
+    chars.next().filter(|c| BULLETS.contains(c))?;
+    chars.as_str().strip_prefix(' ').map(str::trim)
+}
+
+/// Whether a line reads as a sentence rather than a heading.
+fn is_prose(line: &str) -> bool {
+    let words = line.split_whitespace().count();
+    words > 12 || (words > 6 && line.ends_with('.'))
+}
+
+/// The intro: the name, contact details, headline, and summary.
+fn intro(lines: &[&str], draft: &mut Draft) {
+    let mut lines = lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty());
+    if let Some(name) = lines.next() {
+        draft.doc.person.name = name.to_string();
+    }
+    let mut summary = Vec::new();
+    for line in lines {
+        let pieces: Vec<&str> = line.split(['|', '•', '·']).map(str::trim).filter(|p| !p.is_empty()).collect();
+        let person = &mut draft.doc.person;
+        let rest: Vec<&str> = pieces.iter().copied().filter(|piece| !contact(piece, person)).collect();
+        if rest.len() < pieces.len() {
+            for piece in rest {
+                match &person.location {
+                    None if piece.split_whitespace().count() <= 5 => person.location = Some(piece.to_string()),
+                    _ => draft.notes.push(format!("\"{}\" in the contact details; left out", piece)),
+                }
+            }
+        } else if person.headline.is_none() && summary.is_empty() && !is_prose(line) {
+            person.headline = Some(line.to_string());
+        } else {
+            summary.push(line);
+        }
+    }
+    if !summary.is_empty() {
+        draft.doc.person.summary = Some(summary.join(" "));
+    }
+}
+
+/// Set the contact detail `piece` is, if it's one.
+fn contact(piece: &str, person: &mut Person) -> bool {
+    // "Email: ada@example.com", but not "https://...".
+    let value = match piece.split_once(": ") {
+        Some((label, value)) if label.chars().all(char::is_alphabetic) => value.trim(),
+        _ => piece,
+    };
+    if value.contains(' ') {
+        return phone(value, person);
+    }
+    let value = value.strip_prefix("mailto:").unwrap_or(value);
+    if value.split_once('@').is_some_and(|(user, host)| !user.is_empty() && host.contains('.')) {
+        person.email = Some(value.to_string());
+        return true;
+    }
+    if let Some(url) = link(value) {
+        let lower = url.to_lowercase();
+        let field = if lower.contains("linkedin.com") {
+            &mut person.linkedin
+        } else if lower.contains("github.com") {
+            &mut person.github
+        } else {
+            &mut person.website
+        };
+        *field = Some(url);
+        return true;
+    }
+    phone(value, person)
+}
+
+/// `value` as a URL, if it reads as one: `example.com` is
+/// `https://example.com`.
+pub(super) fn link(value: &str) -> Option<String> {
+    let lower = value.to_lowercase();
+    let looks_like_url = lower.starts_with("http")
+        || lower.starts_with("www.")
+        || lower.contains(".com/")
+        || [".com", ".dev", ".io", ".net", ".org", ".me", ".app", ".co", ".tech", ".xyz"].iter().any(|tld| lower.ends_with(tld));
+    if !looks_like_url || !value.contains('.') || value.contains(' ') {
+        return None;
+    }
+    Some(if lower.starts_with("http") { value.to_string() } else { format!("https://{}", value) })
+}
+
+fn phone(value: &str, person: &mut Person) -> bool {
+    let digits = value.chars().filter(char::is_ascii_digit).count();
+    let is_phone = digits >= 7 && value.chars().all(|c| c.is_ascii_digit() || " +-().".contains(c));
+    if is_phone {
+        person.phone = Some(value.to_string());
+    }
+    is_phone
+}
+
+/// Lines as paragraphs: runs of lines between blank ones, bullets
+/// each on their own.
+fn paragraphs(lines: &[&str]) -> Vec<String> {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut open = false;
+    for line in lines.iter().map(|line| line.trim()) {
+        match bullet(line) {
+            _ if line.is_empty() => open = false,
+            Some(item) => {
+                paragraphs.push(item.to_string());
+                open = false;
+            }
+            None if open => {
+                let last = paragraphs.last_mut().expect("an open paragraph");
+                last.push(' ');
+                last.push_str(line);
+            }
+            None => {
+                paragraphs.push(line.to_string());
+                open = true;
+            }
+        }
+    }
+    paragraphs
+}
+
+/// An entry's lines, sorted.
+#[derive(Debug, Default)]
+struct Entry {
+    heading: Vec<String>,
+    text: Vec<String>,
+    bullets: Vec<String>,
+}
+
+/// A section's lines cut into entries.
+fn entries(lines: &[&str]) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut gap = true;
+    let mut after_bullet = false;
+    for raw in lines {
+        let line = raw.trim();
+        if line.is_empty() {
+            gap = true;
+            continue;
+        }
+        if let Some(item) = bullet(line) {
+            if entries.is_empty() {
+                entries.push(Entry::default());
+            }
+            if let Some(entry) = entries.last_mut() {
+                entry.bullets.push(item.to_string());
+            }
+            (gap, after_bullet) = (false, true);
+            continue;
+        }
+        // A heading that runs long still has its dates.
+        let prose = is_prose(line) && date_range(line).is_none();
+        let wrapped = after_bullet && !gap && (raw.starts_with([' ', '\t']) || prose);
+        match entries.last_mut() {
+            // The rest of a bullet that didn't fit on its line.
+            Some(entry) if wrapped => {
+                if let Some(last) = entry.bullets.last_mut() {
+                    last.push(' ');
+                    last.push_str(line);
+                }
+            }
+            Some(entry) if prose => match entry.text.last_mut() {
+                Some(last) if !gap => {
+                    last.push(' ');
+                    last.push_str(line);
+                }
+                _ => entry.text.push(line.to_string()),
+            },
+            Some(entry) if !gap && !after_bullet && entry.text.is_empty() => entry.heading.push(line.to_string()),
+            _ => entries.push(Entry { heading: vec![line.to_string()], ..Default::default() }),
+        }
+        (gap, after_bullet) = (false, false);
+    }
+    entries
+}
+
+/// Take the date range out of an entry's heading lines: the start
+/// and end, or the one date a line is alone.
+fn dates(heading: &mut Vec<String>) -> (Option<String>, Option<String>) {
+    let mut found = (None, None);
+    for line in heading.iter_mut() {
+        if let Some((start, end, rest)) = date_range(line) {
+            *line = rest;
+            found = (Some(start), Some(end));
+            break;
+        }
+        if let Some(date) = from_written(line) {
+            line.clear();
+            found = (None, Some(date));
+            break;
+        }
+    }
+    heading.retain(|line| !line.is_empty());
+    found
+}
+
+/// A range like `Jan 2020 – Present` anywhere in `line`: its start,
+/// its end, and what's left of the line.
+fn date_range(line: &str) -> Option<(String, String, String)> {
+    for separator in DATE_SEPARATORS {
+        for (at, _) in line.match_indices(separator) {
+            let (before, after) = (&line[..at], &line[at + separator.len()..]);
+            if let (Some((start, before)), Some((end, after))) = (date_before(before), date_after(after)) {
+                let rest = format!("{} {}", before, after);
+                let punctuation: &[char] = &['|', ',', '·', '•', '(', ')', '-', '–', '—', ':'];
+                let rest = rest.trim_matches(|c: char| c.is_whitespace() || punctuation.contains(&c));
+                return Some((start, end, rest.to_string()));
+            }
+        }
+    }
+    None
+}
+
+/// The date `text` ends with (one or two words) and the text before
+/// it.
+fn date_before(text: &str) -> Option<(String, &str)> {
+    let text = text.trim_end();
+    let last = word_start(text);
+    let second = word_start(text[..last].trim_end());
+    [second, last].into_iter().find_map(|at| Some((from_written(&text[at..])?, &text[..at])))
+}
+
+/// The date `text` starts with (one or two words) and the text after
+/// it.
+fn date_after(text: &str) -> Option<(String, &str)> {
+    let text = text.trim_start();
+    let first = word_end(text);
+    let rest = text[first..].trim_start();
+    let second = text.len() - rest.len() + word_end(rest);
+    [second, first].into_iter().find_map(|at| Some((from_written(&text[..at])?, &text[at..])))
+}
+
+/// Where the last word of `text` starts. Whitespace may be more than
+/// a byte: exports are full of non-breaking spaces.
+fn word_start(text: &str) -> usize {
+    text.char_indices().rev().find(|(_, c)| c.is_whitespace()).map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+/// Where the first word of `text` ends.
+fn word_end(text: &str) -> usize {
+    text.char_indices().find(|(_, c)| c.is_whitespace()).map_or(text.len(), |(i, _)| i)
+}
+
+fn experience(mut entry: Entry) -> ExperienceItem {
+    let (start, end) = match dates(&mut entry.heading) {
+        // A lone date is a role that didn't span a year.
+        (None, Some(date)) => (Some(date.clone()), Some(date)),
+        range => range,
+    };
+    let (title, company, location) = match entry.heading.as_slice() {
+        [] => (String::new(), String::new(), None),
+        [line] => {
+            let (title, company) = split_pair(line);
+            (title, company, None)
+        }
+        [first, second, ..] => match split_pair(first) {
+            (title, company) if !company.is_empty() => (title, company, Some(second.clone())),
+            _ => (first.clone(), second.clone(), entry.heading.get(2).cloned()),
+        },
+    };
+    ExperienceItem {
+        title,
+        company,
+        location,
+        start,
+        end,
+        summary: Some(entry.text.join("\n\n")).filter(|s| !s.is_empty()),
+        technologies: Vec::new(),
+        highlights: entry.bullets,
+    }
+}
+
+fn education(mut entry: Entry) -> EducationItem {
+    let (start, end) = dates(&mut entry.heading);
+    let mut parts: Vec<String> = entry
+        .heading
+        .iter()
+        .flat_map(|line| {
+            let (a, b) = split_pair(line);
+            [a, b]
+        })
+        .filter(|part| !part.is_empty())
+        .collect();
+    let school = parts.iter().position(|part| {
+        let part = part.to_lowercase();
+        SCHOOL_WORDS.iter().any(|word| part.contains(word))
+    });
+    let institution = match school.or((parts.len() > 1).then_some(1)) {
+        Some(index) => parts.remove(index),
+        None => String::new(),
+    };
+    let mut parts = parts.into_iter();
+    EducationItem {
+        institution,
+        degree: parts.next().unwrap_or_default(),
+        location: parts.next(),
+        start,
+        end,
+        details: entry.bullets.into_iter().chain(entry.text).collect(),
+    }
+}
+
+fn project(mut entry: Entry) -> ProjectItem {
+    let (start, end) = dates(&mut entry.heading);
+    let url = entry.heading.iter().find_map(|line| link(line));
+    entry.heading.retain(|line| link(line).is_none());
+    let mut heading = entry.heading.into_iter();
+    let summary: Vec<String> = entry.text.into_iter().chain(entry.bullets).collect();
+    ProjectItem {
+        name: heading.next().unwrap_or_default(),
+        url,
+        summary: Some(summary.join(" ")).filter(|s| !s.is_empty()),
+        role: heading.next(),
+        start,
+        end,
+        technologies: Vec::new(),
+    }
+}
+
+/// Skills lines: `Category: a, b, c`, or a plain list under "Skills".
+fn skills(lines: &[&str], skills: &mut BTreeMap<String, Vec<String>>) {
+    for line in lines.iter().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        let line = bullet(line).unwrap_or(line);
+        let (category, items) = match line.split_once(':') {
+            Some((category, items)) => (category.trim(), items),
+            None => ("Skills", line),
+        };
+        let items = items.split([',', ';', '•', '|', '·']).map(str::trim).filter(|s| !s.is_empty());
+        skills.entry(category.to_string()).or_default().extend(items.map(str::to_string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESUME: &str = "ADA LOVELACE
+Analyst and Mathematician
+London, UK | ada@example.com | +44 20 7946 0958 | github.com/ada
+
+SUMMARY
+Writes programs for machines that don't exist yet, and explains
+them to the people who will build them.
+
+EXPERIENCE
+---------
+Analyst, Analytical Engine Co          Jan 1842 – Present
+• Wrote the first published algorithm for the Engine, computing
+  Bernoulli numbers
+• Annotated Menabrea's paper
+
+Translator
+Scientific Memoirs
+1843
+- Translated Menabrea's paper from French
+
+Education:
+Mathematics — University of London (1829 - 1835)
+
+Skills
+Mathematics: Calculus, Algebra
+Languages: English, French
+
+Awards
+Honored by the Royal Society
+";
+
+    #[test]
+    fn reads_a_resume() {
+        let draft = convert(RESUME).unwrap();
+        let person = &draft.doc.person;
+        assert_eq!(person.name, "ADA LOVELACE");
+        assert_eq!(person.headline.as_deref(), Some("Analyst and Mathematician"));
+        assert_eq!(person.location.as_deref(), Some("London, UK"));
+        assert_eq!(person.email.as_deref(), Some("ada@example.com"));
+        assert_eq!(person.phone.as_deref(), Some("+44 20 7946 0958"));
+        assert_eq!(person.github.as_deref(), Some("https://github.com/ada"));
+        assert!(person.summary.as_deref().unwrap().starts_with("Writes programs for machines"));
+
+        let [analyst, translator] = draft.doc.experience.as_slice() else { panic!("{:?}", draft.doc.experience) };
+        assert_eq!((analyst.title.as_str(), analyst.company.as_str()), ("Analyst", "Analytical Engine Co"));
+        assert_eq!((analyst.start.as_deref(), analyst.end.as_deref()), (Some("1842-01"), Some("Present")));
+        assert_eq!(
+            analyst.highlights,
+            vec![
+                "Wrote the first published algorithm for the Engine, computing Bernoulli numbers",
+                "Annotated Menabrea's paper"
+            ]
+        );
+        assert_eq!((translator.title.as_str(), translator.company.as_str()), ("Translator", "Scientific Memoirs"));
+        assert_eq!(translator.start.as_deref(), Some("1843"));
+
+        let edu = &draft.doc.education[0];
+        assert_eq!((edu.institution.as_str(), edu.degree.as_str()), ("University of London", "Mathematics"));
+        assert_eq!((edu.start.as_deref(), edu.end.as_deref()), (Some("1829"), Some("1835")));
+
+        let skills = draft.doc.skills.as_ref().unwrap();
+        assert_eq!(skills["Languages"], vec!["English", "French"]);
+        assert_eq!(draft.notes, vec!["section \"Awards\" has no place in JOBL; left out"]);
+    }
+
+    #[test]
+    fn finds_date_ranges_anywhere() {
+        let range = |line: &str| date_range(line).map(|(start, end, rest)| format!("{}|{}|{}", start, end, rest));
+        assert_eq!(range("Engineer (Mar 2020 - Jun 2021)").as_deref(), Some("2020-03|2021-06|Engineer"));
+        assert_eq!(range("2019 – current, Berlin").as_deref(), Some("2019|Present|Berlin"));
+        assert_eq!(range("Engineer - Acme"), None);
+        assert_eq!(range("Engineer, Jan\u{a0}2020 – Present").as_deref(), Some("2020-01|Present|Engineer"));
+        assert_eq!(range("Mar\u{a0}2019 – Jun\u{a0}2021\u{a0}Berlin").as_deref(), Some("2019-03|2021-06|Berlin"));
+    }
+}
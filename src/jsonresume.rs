@@ -0,0 +1,165 @@
+//! JSON Resume export (`srg export --format json-resume`).
+//!
+//! JSON Resume (<https://jsonresume.org/schema>) is the schema most
+//! resume tools outside srg read, and it has hundreds of themes. The
+//! export maps JOBL's sections onto its `basics`, `work`, `education`,
+//! `skills`, and `projects`. JOBL fields the schema has no place for
+//! (a role's technologies, education details) are left out, as are
+//! srg's own sections.
+
+use jobl::JoblDocument;
+use serde_json::{json, Map, Value};
+
+use crate::dates::is_iso;
+
+/// `doc` as a JSON Resume document.
+pub fn convert(doc: &JoblDocument) -> Value {
+    let mut resume = Map::new();
+    resume.insert(
+        "$schema".to_string(),
+        json!("https://raw.githubusercontent.com/jsonresume/resume-schema/v1.0.0/schema.json"),
+    );
+    resume.insert("basics".to_string(), basics(doc));
+
+    let work: Vec<Value> = doc
+        .experience
+        .iter()
+        .map(|exp| {
+            object([
+                ("name", Some(json!(exp.company))),
+                ("position", Some(json!(exp.title))),
+                ("location", exp.location.as_ref().map(|l| json!(l))),
+                ("startDate", date(&exp.start)),
+                ("endDate", date(&exp.end)),
+                ("summary", exp.summary.as_ref().map(|s| json!(s))),
+                ("highlights", list(&exp.highlights)),
+            ])
+        })
+        .collect();
+    let education: Vec<Value> = doc
+        .education
+        .iter()
+        .map(|edu| {
+            object([
+                ("institution", Some(json!(edu.institution))),
+                ("area", Some(json!(edu.degree))),
+                ("startDate", date(&edu.start)),
+                ("endDate", date(&edu.end)),
+            ])
+        })
+        .collect();
+    let skills: Vec<Value> = doc
+        .skills
+        .iter()
+        .flatten()
+        .map(|(category, names)| object([("name", Some(json!(category))), ("keywords", list(names))]))
+        .collect();
+    let projects: Vec<Value> = doc
+        .projects
+        .iter()
+        .map(|proj| {
+            object([
+                ("name", Some(json!(proj.name))),
+                ("description", proj.summary.as_ref().map(|s| json!(s))),
+                ("url", proj.url.as_ref().map(|u| json!(u))),
+                ("roles", proj.role.as_ref().map(|r| json!([r]))),
+                ("startDate", date(&proj.start)),
+                ("endDate", date(&proj.end)),
+                ("keywords", list(&proj.technologies)),
+            ])
+        })
+        .collect();
+
+    for (key, items) in [("work", work), ("education", education), ("skills", skills), ("projects", projects)] {
+        if !items.is_empty() {
+            resume.insert(key.to_string(), Value::Array(items));
+        }
+    }
+    Value::Object(resume)
+}
+
+fn basics(doc: &JoblDocument) -> Value {
+    let person = &doc.person;
+    let profiles: Vec<Value> = [("GitHub", &person.github), ("LinkedIn", &person.linkedin)]
+        .into_iter()
+        .filter_map(|(network, url)| Some(json!({ "network": network, "url": url.as_ref()? })))
+        .collect();
+    object([
+        ("name", Some(json!(person.name))),
+        ("label", person.headline.as_ref().map(|h| json!(h))),
+        ("email", person.email.as_ref().map(|e| json!(e))),
+        ("phone", person.phone.as_ref().map(|p| json!(p))),
+        ("url", person.website.as_ref().map(|w| json!(w))),
+        ("summary", person.summary.as_ref().map(|s| json!(s))),
+        // JOBL's location is one free-form line; `city` is what themes show.
+        ("location", person.location.as_ref().map(|l| json!({ "city": l }))),
+        ("profiles", (!profiles.is_empty()).then_some(Value::Array(profiles))),
+    ])
+}
+
+/// An object of the keys that have a value.
+fn object<const N: usize>(fields: [(&str, Option<Value>); N]) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?)))
+            .collect(),
+    )
+}
+
+fn list(items: &[String]) -> Option<Value> {
+    (!items.is_empty()).then(|| json!(items))
+}
+
+/// A JOBL date the schema accepts. Anything else ("Present",
+/// "Summer 2019") is left out; a missing `endDate` means ongoing.
+fn date(raw: &Option<String>) -> Option<Value> {
+    raw.as_deref().map(str::trim).filter(|d| is_iso(d)).map(|d| json!(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jobl::{ExperienceItem, Person};
+
+    #[test]
+    fn maps_sections_and_drops_empty_fields() {
+        let doc = JoblDocument {
+            person: Person {
+                name: "Ada".to_string(),
+                headline: Some("Analyst".to_string()),
+                location: Some("London".to_string()),
+                email: None,
+                website: None,
+                github: Some("https://github.com/ada".to_string()),
+                linkedin: None,
+                phone: None,
+                summary: None,
+            },
+            skills: Some([("Maths".to_string(), vec!["Analysis".to_string()])].into_iter().collect()),
+            experience: vec![ExperienceItem {
+                title: "Correspondent".to_string(),
+                company: "Royal Society".to_string(),
+                location: None,
+                start: Some("1840-03".to_string()),
+                end: Some("Present".to_string()),
+                summary: None,
+                highlights: vec!["Wrote Note G".to_string()],
+                technologies: vec!["Engine".to_string()],
+            }],
+            projects: Vec::new(),
+            education: Vec::new(),
+        };
+        let resume = convert(&doc);
+
+        assert_eq!(resume["basics"]["label"], "Analyst");
+        assert_eq!(resume["basics"]["location"]["city"], "London");
+        assert_eq!(resume["basics"]["profiles"][0]["network"], "GitHub");
+        assert!(resume["basics"].get("email").is_none());
+        assert_eq!(resume["work"][0]["name"], "Royal Society");
+        assert_eq!(resume["work"][0]["startDate"], "1840-03");
+        assert!(resume["work"][0].get("endDate").is_none());
+        assert_eq!(resume["skills"][0], json!({ "name": "Maths", "keywords": ["Analysis"] }));
+        assert!(resume.get("projects").is_none() && resume.get("education").is_none());
+    }
+}
@@ -1,7 +1,11 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
+use crate::helpers::{split_call, Helper};
+
 #[cfg(test)]
 #[path = "layout_tests.rs"]
 mod layout_tests;
@@ -9,20 +13,148 @@ mod layout_tests;
 #[derive(Debug, Clone)]
 pub struct Layout {
   pub sections: Vec<Section>,
+  /// Build settings from the layout's frontmatter.
+  pub settings: LayoutSettings,
+  /// Set by a `partial` line at the top level. A partial layout only
+  /// overrides the sections it mentions and is merged onto the
+  /// theme's layout with `merge_onto`.
+  pub partial: bool,
+  /// Sections a partial layout drops from the layout it's merged
+  /// onto, from `-name` lines at the top level.
+  pub removed: Vec<String>,
+}
+
+/// Build settings a layout carries in a `---` frontmatter block at
+/// its top, so a layout meant for one page of A4 brings its own print
+/// settings. CLI flags and srg.toml still win.
+///
+/// ```text
+/// ---
+/// paper = "a4"
+/// density = "compact"
+/// date_format = "%b %Y"
+/// ---
+/// person
+///   name
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutSettings {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub paper: Option<crate::build::Paper>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub density: Option<crate::build::Density>,
+  /// See `crate::dates` for the directives.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub date_format: Option<String>,
+  /// Language for month names and date ranges, e.g. `fr`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub locale: Option<crate::dates::Locale>,
+  /// Add a keyword filter box to the hosted page.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub search: Option<bool>,
+}
+
+impl LayoutSettings {
+  /// `self` with any setting it leaves unset taken from `base`.
+  fn or(&self, base: &LayoutSettings) -> LayoutSettings {
+    LayoutSettings {
+      paper: self.paper.or(base.paper),
+      density: self.density.or(base.density),
+      date_format: self.date_format.clone().or_else(|| base.date_format.clone()),
+      locale: self.locale.or(base.locale),
+      search: self.search.or(base.search),
+    }
+  }
+}
+
+/// Split a leading `---` frontmatter block off `content`.
+fn split_frontmatter(content: &str) -> Result<(LayoutSettings, &str)> {
+  let Some(rest) = content.trim_start().strip_prefix("---") else {
+    return Ok((LayoutSettings::default(), content));
+  };
+  let Some(rest) = rest.strip_prefix('\n').or_else(|| rest.strip_prefix("\r\n")) else {
+    return Ok((LayoutSettings::default(), content));
+  };
+  let mut offset = 0;
+  for line in rest.split_inclusive('\n') {
+    if line.trim_end() == "---" {
+      let settings: LayoutSettings =
+        toml::from_str(&rest[..offset]).context("Invalid layout frontmatter")?;
+      if let Some(format) = &settings.date_format {
+        crate::dates::check_format(format).map_err(|e| anyhow::anyhow!("Invalid layout frontmatter: {}", e))?;
+      }
+      return Ok((settings, &rest[offset + line.len()..]));
+    }
+    offset += line.len();
+  }
+  anyhow::bail!("Layout frontmatter starts with --- but never ends with ---")
 }
 
+/// Section names the renderers know. Other sections parse but render
+/// nothing.
+pub const SECTION_NAMES: &[&str] = &["person", "toc", "summary", "skills", "experience", "projects", "education", "talks", "patents"];
+
 #[derive(Debug, Clone)]
 pub struct Section {
   pub name: String,
+  /// Words after the section name on its header line
+  /// (`experience grouped`), switching on alternate renderings.
+  pub modifiers: Vec<String>,
   pub fields: Vec<FieldOrContainer>,
 }
 
+impl Section {
+  /// The section as its item `index` (0-based) sees it: fields
+  /// limited to other items (`[1] highlights`) are left out.
+  pub fn for_item(&self, index: usize) -> Section {
+    let applies = |field: &Field| field.items.is_none_or(|range| range.contains(index));
+    let fields = self
+      .fields
+      .iter()
+      .filter_map(|entry| match entry {
+        FieldOrContainer::Field(field) => applies(field).then(|| entry.clone()),
+        FieldOrContainer::Container(container) => Some(FieldOrContainer::Container(Container {
+          class_name: container.class_name.clone(),
+          fields: container.fields.iter().filter(|f| applies(f)).cloned().collect(),
+        })),
+      })
+      .collect();
+    Section { name: self.name.clone(), modifiers: self.modifiers.clone(), fields }
+  }
+
+  pub fn has_modifier(&self, modifier: &str) -> bool {
+    self.modifiers.iter().any(|m| m == modifier)
+  }
+
+  /// Value of a `key=value` modifier (`projects accent=teal`).
+  pub fn modifier_value(&self, key: &str) -> Option<&str> {
+    self.modifiers.iter().find_map(|m| m.strip_prefix(key)?.strip_prefix('='))
+  }
+
+  /// How many entries `collapse=N` keeps in view on the web page.
+  /// The layout parser has already checked the number.
+  pub fn collapse(&self) -> Option<usize> {
+    self.modifier_value("collapse")?.parse().ok()
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum FieldOrContainer {
   Field(Field),
   Container(Container),
 }
 
+impl FieldOrContainer {
+  /// The field, if this entry isn't a container.
+  pub fn as_field(&self) -> Option<&Field> {
+    match self {
+      FieldOrContainer::Field(field) => Some(field),
+      FieldOrContainer::Container(_) => None,
+    }
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct Container {
   pub class_name: String,
@@ -33,31 +165,220 @@ pub struct Container {
 pub enum FieldPart {
   Field(String),
   Literal(String),
+  /// `$name`: a value set for the build (`--set name=value`) rather
+  /// than taken from the resume. `value` is filled in by
+  /// `Layout::resolve_variables`; until then it renders as nothing.
+  Variable { name: String, value: Option<String> },
+}
+
+/// A bare word on a field line: `$name` is a variable, anything else
+/// a field.
+fn word_part(word: String) -> FieldPart {
+  match word.strip_prefix('$') {
+    Some(name) if is_variable_name(name) => FieldPart::Variable { name: name.to_string(), value: None },
+    _ => FieldPart::Field(word),
+  }
+}
+
+/// Variable names are letters, digits, `-`, and `_`.
+pub fn is_variable_name(name: &str) -> bool {
+  !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
 
 #[derive(Debug, Clone)]
 pub struct Field {
   pub parts: Vec<FieldPart>,
   pub class_name: Option<String>,
+  /// HTML element chosen by the layout (`h1: name`). When unset the
+  /// renderer picks its default element for the field.
+  pub element: Option<String>,
+  /// Set by `contactrow email phone location`: the parts are shown
+  /// in one row with a divider between those that have a value, and
+  /// missing ones are skipped along with their divider.
+  pub contact_row: bool,
+  /// Set by `split title | start " - " end`: parts from this index
+  /// on are the line's right-hand side, shown flush right on the
+  /// same row as the left-hand side.
+  pub split: Option<usize>,
+  /// Items of a list section the field shows for, from an `[1]`,
+  /// `[1-3]`, or `[2-]` prefix. `None` shows it for every item.
+  pub items: Option<ItemRange>,
+  /// Helpers wrapped around field words (`short(website)`), by field
+  /// name. The part itself is the bare field.
+  pub helpers: Vec<(String, Helper)>,
+}
+
+/// A 1-based, inclusive range of items (`[2-]` is the second item
+/// on).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemRange {
+  pub first: usize,
+  pub last: Option<usize>,
+}
+
+impl ItemRange {
+  /// Whether the range holds the item at 0-based `index`.
+  pub fn contains(&self, index: usize) -> bool {
+    index + 1 >= self.first && self.last.is_none_or(|last| index < last)
+  }
+
+  fn parse(spec: &str) -> Result<Self> {
+    let number = |n: &str| -> Result<usize> {
+      match n.trim().parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => anyhow::bail!("Invalid item range [{}]: items are numbered from 1", spec),
+      }
+    };
+    let range = match spec.split_once('-') {
+      None => ItemRange { first: number(spec)?, last: Some(number(spec)?) },
+      Some((first, "")) => ItemRange { first: number(first)?, last: None },
+      Some((first, last)) => ItemRange { first: number(first)?, last: Some(number(last)?) },
+    };
+    if range.last.is_some_and(|last| last < range.first) {
+      anyhow::bail!("Invalid item range [{}]: it ends before it starts", spec);
+    }
+    Ok(range)
+  }
+}
+
+impl std::fmt::Display for ItemRange {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self.last {
+      Some(last) if last == self.first => write!(f, "{}", self.first),
+      Some(last) => write!(f, "{}-{}", self.first, last),
+      None => write!(f, "{}-", self.first),
+    }
+  }
 }
 
 impl Field {
   pub fn new(parts: Vec<FieldPart>) -> Self {
-    Self { parts, class_name: None }
+    Self { parts, class_name: None, element: None, contact_row: false, split: None, items: None, helpers: Vec::new() }
   }
 
   pub fn with_class(parts: Vec<FieldPart>, class_name: String) -> Self {
-    Self { parts, class_name: Some(class_name) }
+    Self { class_name: Some(class_name), ..Self::new(parts) }
+  }
+
+  /// `value` of field `name` as the line shows it, through the
+  /// helper the line wraps the field in, if any.
+  pub fn shown(&self, name: &str, value: &str) -> String {
+    match self.helpers.iter().find(|(field, _)| field == name) {
+      Some((_, helper)) => helper.apply(value),
+      None => value.to_string(),
+    }
+  }
+
+  /// Text of a mixed line, with `lookup` giving field values.
+  /// Literals only show next to values that are there: a literal
+  /// before a missing value is dropped, as is one after a missing
+  /// value at the end of the line, and a separator needs a value on
+  /// both sides. So `location " | " phone` without a phone is just
+  /// the location, and `"(" location ")"` without one is nothing.
+  ///
+  /// `None` when the line has fields or variables and none of them
+  /// has a value.
+  pub fn mixed_text(&self, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let values: Vec<Option<Option<String>>> = self
+      .parts
+      .iter()
+      .map(|part| match part {
+        FieldPart::Literal(_) => None,
+        FieldPart::Field(name) => Some(lookup(name).map(|v| self.shown(name, &v)).filter(|v| !v.is_empty())),
+        FieldPart::Variable { value, .. } => Some(value.clone().filter(|v| !v.is_empty())),
+      })
+      .collect();
+    if values.iter().flatten().next().is_some() && values.iter().flatten().all(Option::is_none) {
+      return None;
+    }
+
+    let mut text = String::new();
+    let mut shown_value = false;
+    for (i, part) in self.parts.iter().enumerate() {
+      match (part, &values[i]) {
+        (FieldPart::Literal(literal), _) => {
+          let prev = values[..i].iter().rev().flatten().next();
+          let next = values[i + 1..].iter().flatten().next();
+          let show = match (prev, next) {
+            (_, Some(None)) => false,
+            (None, _) => true,
+            (Some(prev), None) => prev.is_some(),
+            (Some(_), Some(Some(_))) => shown_value,
+          };
+          if show {
+            text.push_str(literal);
+          }
+        }
+        (_, Some(Some(value))) => {
+          text.push_str(value);
+          shown_value = true;
+        }
+        _ => {}
+      }
+    }
+    Some(text)
+  }
+
+  /// Parse a field definition, after any prefix.
+  fn parse(definition: &str) -> Result<Self> {
+    Self::parse_parts(definition)?.take_helpers()
+  }
+
+  /// Unwrap `helper(field)` words into the bare field and an entry in
+  /// `helpers`.
+  fn take_helpers(mut self) -> Result<Self> {
+    for part in &mut self.parts {
+      let FieldPart::Field(word) = part else { continue };
+      let Some((name, field)) = split_call(word) else { continue };
+      let helper = Helper::from_name(name).ok_or_else(|| {
+        anyhow::anyhow!("Unknown helper `{}` in {}: the helpers are {}", name, word, Helper::NAMES.join(", "))
+      })?;
+      let field = field.to_string();
+      self.helpers.push((field.clone(), helper));
+      *part = FieldPart::Field(field);
+    }
+    Ok(self)
+  }
+
+  fn parse_parts(definition: &str) -> Result<Self> {
+    if let Some(items) = definition.strip_prefix("contactrow ") {
+      return Ok(Self { contact_row: true, ..Self::new(parse_field_parts(items.trim())) });
+    }
+    if let Some(sides) = definition.strip_prefix("split ") {
+      let bar = sides
+        .char_indices()
+        .scan(false, |quoted, (i, c)| {
+          if c == '"' {
+            *quoted = !*quoted;
+          }
+          Some((i, c, *quoted))
+        })
+        .find(|&(_, c, quoted)| c == '|' && !quoted)
+        .map(|(i, _, _)| i)
+        .ok_or_else(|| anyhow::anyhow!("`split {}` needs a | between its left and right sides", sides.trim()))?;
+      let mut parts = parse_field_parts(sides[..bar].trim());
+      let split = parts.len();
+      parts.extend(parse_field_parts(sides[bar + 1..].trim()));
+      return Ok(Self { split: Some(split), ..Self::new(parts) });
+    }
+    Ok(Self::new(parse_field_parts(definition)))
   }
 }
 
+/// Elements a layout may pick for a field with the `element: field`
+/// prefix. Anything else before the colon is a class name, which keeps
+/// older layouts working unchanged.
+const FIELD_ELEMENTS: &[&str] = &[
+  "h1", "h2", "h3", "h4", "h5", "h6", "p", "div", "span", "small", "strong", "em",
+  "b", "i", "address", "time", "cite", "code", "blockquote", "ul", "ol",
+];
+
 fn parse_field_parts(line: &str) -> Vec<FieldPart> {
   let mut parts = Vec::new();
-  let mut chars = line.chars().peekable();
   let mut current = String::new();
   let mut in_quote = false;
 
-  while let Some(ch) = chars.next() {
+  for ch in line.chars() {
     match ch {
       '"' => {
         if in_quote {
@@ -68,7 +389,7 @@ fn parse_field_parts(line: &str) -> Vec<FieldPart> {
         } else {
           // Start of quoted string
           if !current.is_empty() {
-            parts.push(FieldPart::Field(current.clone()));
+            parts.push(word_part(current.clone()));
             current.clear();
           }
           in_quote = true;
@@ -77,7 +398,7 @@ fn parse_field_parts(line: &str) -> Vec<FieldPart> {
       ' ' if !in_quote => {
         // Whitespace outside quotes - end current field
         if !current.is_empty() {
-          parts.push(FieldPart::Field(current.clone()));
+          parts.push(word_part(current.clone()));
           current.clear();
         }
       }
@@ -93,13 +414,59 @@ fn parse_field_parts(line: &str) -> Vec<FieldPart> {
       // Unclosed quote - treat as literal
       parts.push(FieldPart::Literal(current));
     } else {
-      parts.push(FieldPart::Field(current));
+      parts.push(word_part(current));
     }
   }
 
   parts
 }
 
+/// Parse one field line, honoring the prefix syntax wherever a field
+/// can appear:
+///
+/// - `[1] field line` shows it for the first item only (`[1-3]`,
+///   `[2-]` for ranges)
+/// - `class-name: field definition` adds a class
+/// - `h1: field definition` picks the element
+/// - `h2.class-a.class-b: field definition` does both
+fn parse_field_line(trimmed: &str) -> Result<Field> {
+  let Some(rest) = trimmed.strip_prefix('[') else {
+    return parse_field_definition(trimmed);
+  };
+  let (spec, rest) = rest
+    .split_once(']')
+    .ok_or_else(|| anyhow::anyhow!("Unclosed item range in {:?}", trimmed))?;
+  let mut field = parse_field_definition(rest.trim())?;
+  field.items = Some(ItemRange::parse(spec)?);
+  Ok(field)
+}
+
+fn parse_field_definition(trimmed: &str) -> Result<Field> {
+  if let Some(colon_pos) = trimmed.find(':') {
+    let before_colon = trimmed[..colon_pos].trim();
+    let after_colon = trimmed[colon_pos + 1..].trim();
+
+    // Check if before_colon looks like a class name (no quotes or special chars)
+    if !before_colon.is_empty() && !before_colon.contains('"') && !before_colon.contains(' ') && !after_colon.is_empty() {
+      let mut field = Field::parse(after_colon)?;
+      let mut segments = before_colon.split('.');
+      let first = segments.next().unwrap_or("");
+      if FIELD_ELEMENTS.contains(&first) {
+        field.element = Some(first.to_string());
+        let classes: Vec<&str> = segments.filter(|c| !c.is_empty()).collect();
+        if !classes.is_empty() {
+          field.class_name = Some(classes.join(" "));
+        }
+      } else {
+        field.class_name = Some(before_colon.to_string());
+      }
+      return Ok(field);
+    }
+  }
+
+  Field::parse(trimmed)
+}
+
 impl Layout {
   pub fn from_file(path: &Path) -> Result<Self> {
     let content =
@@ -107,14 +474,43 @@ impl Layout {
     Self::parse(&content)
   }
 
+  /// Load the layout for a theme spec. Layered specs
+  /// (`minimal+compact`) use the topmost layer that ships a layout.
   pub fn from_theme(theme: &str) -> Result<Self> {
-    let content = crate::themes::layout_for(theme)
-      .ok_or_else(|| anyhow::anyhow!("Unknown theme: {}", theme))?;
-    Self::parse(content)
+    Self::theme_layout(theme, &mut Vec::new())
+  }
+
+  fn theme_layout(theme: &str, chain: &mut Vec<String>) -> Result<Self> {
+    if chain.iter().any(|t| t == theme) {
+      anyhow::bail!("Layout extends itself: {} -> {}", chain.join(" -> "), theme);
+    }
+    let content = crate::themes::ThemeStack::resolve(theme)?
+      .layout()
+      .ok_or_else(|| anyhow::anyhow!("Theme {} has no layout", theme))?;
+    chain.push(theme.to_string());
+    let layout = Self::parse_in(content, chain);
+    chain.pop();
+    layout
   }
 
+  /// Parse layout text.
+  ///
+  /// An `extends <theme>` line at the top level makes the file an
+  /// extension of that theme's layout: it's parsed like a `partial`
+  /// layout and merged onto the theme's layout right here (see
+  /// [`Layout::merge_onto`] for the rules), so the result is a
+  /// complete layout whatever theme is used for CSS. A `-name` line
+  /// at the top level removes that section.
   pub fn parse(content: &str) -> Result<Self> {
+    Self::parse_in(content, &mut Vec::new())
+  }
+
+  fn parse_in(content: &str, chain: &mut Vec<String>) -> Result<Self> {
+    let (settings, content) = split_frontmatter(content)?;
     let mut sections = Vec::new();
+    let mut partial = false;
+    let mut extends: Option<String> = None;
+    let mut removed = Vec::new();
     let mut current_section: Option<Section> = None;
     let mut current_container: Option<Container> = None;
 
@@ -126,6 +522,25 @@ impl Layout {
       let indent_level = line.len() - line.trim_start().len();
       let trimmed = line.trim();
 
+      if indent_level == 0 && trimmed == "partial" {
+        partial = true;
+        continue;
+      }
+
+      if indent_level == 0 {
+        if let Some(theme) = trimmed.strip_prefix("extends ") {
+          if extends.is_some() || !sections.is_empty() || current_section.is_some() {
+            anyhow::bail!("`extends` must come once, before any section");
+          }
+          extends = Some(theme.trim().to_string());
+          continue;
+        }
+        if let Some(name) = trimmed.strip_prefix('-') {
+          removed.push(name.trim().to_string());
+          continue;
+        }
+      }
+
       if indent_level == 0 {
         // Close any open container
         if let (Some(container), Some(ref mut section)) = (current_container.take(), current_section.as_mut()) {
@@ -137,8 +552,10 @@ impl Layout {
           sections.push(section);
         }
 
+        let mut words = trimmed.split_whitespace();
         current_section = Some(Section {
-          name: trimmed.to_string(),
+          name: words.next().unwrap_or_default().to_string(),
+          modifiers: words.map(str::to_string).collect(),
           fields: Vec::new(),
         });
       } else if indent_level == 2 {
@@ -160,44 +577,15 @@ impl Layout {
             }
           }
 
-          // Check for custom class syntax: "class-name: field definition"
-          if let Some(colon_pos) = trimmed.find(':') {
-            let before_colon = &trimmed[..colon_pos].trim();
-            let after_colon = &trimmed[colon_pos + 1..].trim();
-
-            // Check if before_colon looks like a class name (no quotes or special chars)
-            if !before_colon.is_empty() && !before_colon.contains('"') && !before_colon.contains(' ') && !after_colon.is_empty() {
-              let parts = parse_field_parts(after_colon);
-              section.fields.push(FieldOrContainer::Field(Field::with_class(parts, before_colon.to_string())));
-              continue;
-            }
-          }
-
-          // Default: regular field
-          let parts = parse_field_parts(trimmed);
-          section.fields.push(FieldOrContainer::Field(Field::new(parts)));
+          section.fields.push(FieldOrContainer::Field(parse_field_line(trimmed)?));
         }
       } else if indent_level >= 4 {
         // Add to current container if one exists, otherwise to section
         if let Some(ref mut container) = current_container {
-          // Check for custom class syntax
-          if let Some(colon_pos) = trimmed.find(':') {
-            let before_colon = &trimmed[..colon_pos].trim();
-            let after_colon = &trimmed[colon_pos + 1..].trim();
-
-            if !before_colon.is_empty() && !before_colon.contains('"') && !before_colon.contains(' ') && !after_colon.is_empty() {
-              let parts = parse_field_parts(after_colon);
-              container.fields.push(Field::with_class(parts, before_colon.to_string()));
-              continue;
-            }
-          }
-
-          let parts = parse_field_parts(trimmed);
-          container.fields.push(Field::new(parts));
+          container.fields.push(parse_field_line(trimmed)?);
         } else if let Some(ref mut section) = current_section {
           // Treat as regular field if no container
-          let parts = parse_field_parts(trimmed);
-          section.fields.push(FieldOrContainer::Field(Field::new(parts)));
+          section.fields.push(FieldOrContainer::Field(parse_field_line(trimmed)?));
         }
       }
     }
@@ -212,10 +600,226 @@ impl Layout {
       sections.push(section);
     }
 
-    Ok(Layout { sections })
+    for section in &sections {
+      if let Some(value) = section.modifier_value("collapse") {
+        if !value.parse::<usize>().is_ok_and(|n| n > 0) {
+          anyhow::bail!("{} collapse={}: collapse takes a number of entries, 1 or more", section.name, value);
+        }
+      }
+    }
+
+    if partial && extends.is_some() {
+      anyhow::bail!("A layout can be `partial` or `extends` a theme, not both");
+    }
+    if !removed.is_empty() && !partial && extends.is_none() {
+      anyhow::bail!("Removing sections (-{}) needs `partial` or `extends`", removed[0]);
+    }
+
+    let layout = Layout { sections, settings, partial, removed };
+    match extends {
+      Some(theme) => {
+        let base = Self::theme_layout(&theme, chain)
+          .with_context(|| format!("Failed to load layout of theme {} to extend", theme))?;
+        Ok(layout.merge_onto(&base))
+      }
+      None => Ok(layout),
+    }
+  }
+
+  /// Every field line, including those inside containers.
+  fn fields_mut(&mut self) -> impl Iterator<Item = &mut Field> {
+    self.sections.iter_mut().flat_map(|section| {
+      section.fields.iter_mut().flat_map(|entry| match entry {
+        FieldOrContainer::Field(field) => std::slice::from_mut(field).iter_mut(),
+        FieldOrContainer::Container(container) => container.fields.iter_mut(),
+      })
+    })
+  }
+
+  /// Fill in every `$name` with its value from `vars`. Fails on the
+  /// first variable that has no value, so a typo doesn't silently
+  /// leave a gap in the resume.
+  pub fn resolve_variables(&mut self, vars: &BTreeMap<String, String>) -> Result<()> {
+    for field in self.fields_mut() {
+      for part in &mut field.parts {
+        if let FieldPart::Variable { name, value } = part {
+          match vars.get(name.as_str()) {
+            Some(v) => *value = Some(v.clone()),
+            None => anyhow::bail!(
+              "Layout uses ${name} but it isn't set; pass --set {name}=... or add it to [variables] in srg.toml"
+            ),
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Apply `--include-section` and `--exclude-section`. An included
+  /// section the layout lacks is taken from `base` (the theme's
+  /// layout) and placed after the sections that precede it there;
+  /// excluded sections are dropped.
+  pub fn toggle_sections(&mut self, include: &[String], exclude: &[String], base: &Layout) -> Result<()> {
+    for name in include.iter().chain(exclude) {
+      if !SECTION_NAMES.contains(&name.as_str()) {
+        anyhow::bail!("Unknown section {} (sections: {})", name, SECTION_NAMES.join(", "));
+      }
+    }
+    if let Some(name) = include.iter().find(|name| exclude.contains(name)) {
+      anyhow::bail!("Section {} is both included and excluded", name);
+    }
+
+    for name in include {
+      if self.sections.iter().any(|s| &s.name == name) {
+        continue;
+      }
+      let Some(at) = base.sections.iter().position(|s| &s.name == name) else {
+        anyhow::bail!("Can't include section {}: neither the layout nor the theme's layout has it", name);
+      };
+      // After the last section that comes before it in the theme.
+      let insert_at = base.sections[..at]
+        .iter()
+        .rev()
+        .find_map(|prev| self.sections.iter().position(|s| s.name == prev.name))
+        .map_or(0, |i| i + 1);
+      self.sections.insert(insert_at, base.sections[at].clone());
+    }
+
+    self.sections.retain(|s| !exclude.contains(&s.name));
+    Ok(())
+  }
+
+  /// Merge this (partial) layout onto `base`.
+  ///
+  /// - A section that exists in `base` replaces it. If the override
+  ///   lists no fields, the base section's fields are kept, so a
+  ///   partial can reorder sections without restating them.
+  /// - Overridden sections take the slots of the base sections they
+  ///   name, in the order the partial lists them. Listing
+  ///   `education` then `experience` swaps the two and leaves every
+  ///   other section where it was.
+  /// - Sections the base doesn't have are appended at the end.
+  /// - Sections listed as removed (`-name`) are dropped, whether the
+  ///   base or the partial has them.
+  /// - Frontmatter settings the partial sets win over the base's.
+  pub fn merge_onto(&self, base: &Layout) -> Layout {
+    let mut sections = base.sections.clone();
+
+    let slots: Vec<usize> = base
+      .sections
+      .iter()
+      .enumerate()
+      .filter(|(_, s)| self.sections.iter().any(|o| o.name == s.name))
+      .map(|(i, _)| i)
+      .collect();
+
+    let mut overrides = Vec::new();
+    let mut appended = Vec::new();
+    for section in &self.sections {
+      match base.sections.iter().find(|b| b.name == section.name) {
+        Some(base_section) => {
+          let mut merged = section.clone();
+          if merged.fields.is_empty() {
+            merged.fields = base_section.fields.clone();
+          }
+          overrides.push(merged);
+        }
+        None => appended.push(section.clone()),
+      }
+    }
+
+    for (slot, section) in slots.into_iter().zip(overrides) {
+      sections[slot] = section;
+    }
+    sections.extend(appended);
+    sections.retain(|s| !self.removed.contains(&s.name));
+
+    Layout { sections, settings: self.settings.or(&base.settings), partial: false, removed: Vec::new() }
+  }
+}
+
+/// The layout in canonical form: frontmatter, then each section with
+/// its fields, one line each and a blank line between sections. It
+/// parses back to the same layout, so `srg layout show` output can
+/// be saved and edited.
+impl std::fmt::Display for Layout {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let settings = toml::to_string(&self.settings).map_err(|_| std::fmt::Error)?;
+    if !settings.is_empty() {
+      write!(f, "---\n{}---\n", settings)?;
+    }
+    let mut blocks = Vec::new();
+    if self.partial || !self.removed.is_empty() {
+      let mut block = if self.partial { "partial\n".to_string() } else { String::new() };
+      for name in &self.removed {
+        block.push_str(&format!("-{}\n", name));
+      }
+      blocks.push(block);
+    }
+
+    for section in &self.sections {
+      let mut block = std::iter::once(section.name.as_str())
+        .chain(section.modifiers.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+      block.push('\n');
+      for entry in &section.fields {
+        match entry {
+          FieldOrContainer::Field(field) => block.push_str(&format!("  {}\n", field)),
+          FieldOrContainer::Container(container) => {
+            block.push_str(&format!("  {}:\n", container.class_name));
+            for field in &container.fields {
+              block.push_str(&format!("    {}\n", field));
+            }
+          }
+        }
+      }
+      blocks.push(block);
+    }
+
+    write!(f, "{}", blocks.join("\n"))
+  }
+}
+
+/// A field line as a layout file writes it, prefixes included.
+impl std::fmt::Display for Field {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    if let Some(items) = &self.items {
+      write!(f, "[{}] ", items)?;
+    }
+    match (&self.element, &self.class_name) {
+      (Some(element), Some(classes)) => write!(f, "{}.{}: ", element, classes.replace(' ', "."))?,
+      (Some(element), None) => write!(f, "{}: ", element)?,
+      (None, Some(class)) => write!(f, "{}: ", class)?,
+      (None, None) => {}
+    }
+    if self.contact_row {
+      write!(f, "contactrow ")?;
+    }
+    if self.split.is_some() {
+      write!(f, "split ")?;
+    }
+    let mut parts: Vec<String> = self
+      .parts
+      .iter()
+      .map(|part| match part {
+        FieldPart::Field(name) => match self.helpers.iter().find(|(field, _)| field == name) {
+          Some((_, helper)) => format!("{}({})", helper, name),
+          None => name.clone(),
+        },
+        FieldPart::Literal(text) => format!("\"{}\"", text),
+        FieldPart::Variable { name, .. } => format!("${}", name),
+      })
+      .collect();
+    if let Some(split) = self.split {
+      parts.insert(split, "|".to_string());
+    }
+    write!(f, "{}", parts.join(" "))
   }
+}
 
-  pub fn default() -> Self {
+impl Default for Layout {
+  fn default() -> Self {
     Self::from_theme("minimal").expect("Default layout should be valid")
   }
 }
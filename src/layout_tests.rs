@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-  use crate::layout::{FieldPart, Layout};
+  use crate::layout::{FieldOrContainer, FieldPart, ItemRange, Layout};
 
   #[test]
   fn test_parse_simple_layout() {
@@ -20,26 +20,39 @@ experience
     assert_eq!(layout.sections[0].name, "person");
     assert_eq!(layout.sections[0].fields.len(), 2);
     assert_eq!(
-      layout.sections[0].fields[0].parts,
+      layout.sections[0].fields[0].as_field().unwrap().parts,
       vec![FieldPart::Field("name".to_string())]
     );
     assert_eq!(
-      layout.sections[0].fields[1].parts,
+      layout.sections[0].fields[1].as_field().unwrap().parts,
       vec![FieldPart::Field("email".to_string())]
     );
 
     assert_eq!(layout.sections[1].name, "experience");
     assert_eq!(layout.sections[1].fields.len(), 2);
     assert_eq!(
-      layout.sections[1].fields[0].parts,
+      layout.sections[1].fields[0].as_field().unwrap().parts,
       vec![FieldPart::Field("title".to_string())]
     );
     assert_eq!(
-      layout.sections[1].fields[1].parts,
+      layout.sections[1].fields[1].as_field().unwrap().parts,
       vec![FieldPart::Field("company".to_string())]
     );
   }
 
+  #[test]
+  fn test_parse_section_modifiers() {
+    let layout = Layout::parse("experience grouped\n  title\n\neducation\n  degree\n").unwrap();
+    assert_eq!(layout.sections[0].name, "experience");
+    assert!(layout.sections[0].has_modifier("grouped"));
+    assert_eq!(layout.sections[0].fields.len(), 1);
+    assert!(layout.sections[1].modifiers.is_empty());
+
+    let layout = Layout::parse("projects accent=#0d9488\n  name\n").unwrap();
+    assert_eq!(layout.sections[0].modifier_value("accent"), Some("#0d9488"));
+    assert_eq!(layout.sections[0].modifier_value("other"), None);
+  }
+
   #[test]
   fn test_parse_multi_part_field() {
     let content = r#"
@@ -51,7 +64,7 @@ experience
     assert_eq!(layout.sections.len(), 1);
     assert_eq!(layout.sections[0].fields.len(), 1);
     assert_eq!(
-      layout.sections[0].fields[0].parts,
+      layout.sections[0].fields[0].as_field().unwrap().parts,
       vec![
         FieldPart::Field("start".to_string()),
         FieldPart::Field("-".to_string()),
@@ -124,7 +137,7 @@ person
     assert_eq!(layout.sections[0].name, "person");
     assert_eq!(layout.sections[0].fields.len(), 1);
     assert_eq!(
-      layout.sections[0].fields[0].parts,
+      layout.sections[0].fields[0].as_field().unwrap().parts,
       vec![FieldPart::Field("name".to_string())]
     );
   }
@@ -143,7 +156,7 @@ person
 
     // First field: name "at" email
     assert_eq!(
-      layout.sections[0].fields[0].parts,
+      layout.sections[0].fields[0].as_field().unwrap().parts,
       vec![
         FieldPart::Field("name".to_string()),
         FieldPart::Literal("at".to_string()),
@@ -153,7 +166,7 @@ person
 
     // Second field: "Location:" location
     assert_eq!(
-      layout.sections[0].fields[1].parts,
+      layout.sections[0].fields[1].as_field().unwrap().parts,
       vec![
         FieldPart::Literal("Location:".to_string()),
         FieldPart::Field("location".to_string())
@@ -170,7 +183,7 @@ experience
 
     let layout = Layout::parse(content).unwrap();
     assert_eq!(
-      layout.sections[0].fields[0].parts,
+      layout.sections[0].fields[0].as_field().unwrap().parts,
       vec![
         FieldPart::Field("start".to_string()),
         FieldPart::Literal(" - ".to_string()),
@@ -178,4 +191,362 @@ experience
       ]
     );
   }
+
+  #[test]
+  fn test_partial_directive() {
+    let layout = Layout::parse("partial\n\nskills\n").unwrap();
+    assert!(layout.partial);
+    assert_eq!(layout.sections.len(), 1);
+    assert_eq!(layout.sections[0].name, "skills");
+
+    let layout = Layout::parse("skills\n").unwrap();
+    assert!(!layout.partial);
+  }
+
+  #[test]
+  fn test_partial_merge_reorders_and_inherits_fields() {
+    let base = Layout::parse(
+      r#"
+person
+  name
+
+experience
+  title
+
+education
+  degree
+
+skills
+"#,
+    )
+    .unwrap();
+
+    let partial = Layout::parse(
+      r#"
+partial
+
+education
+
+experience
+"#,
+    )
+    .unwrap();
+
+    let merged = partial.merge_onto(&base);
+    let names: Vec<_> = merged.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["person", "education", "experience", "skills"]);
+    assert_eq!(merged.sections[1].fields.len(), 1);
+    assert_eq!(merged.sections[2].fields.len(), 1);
+  }
+
+  #[test]
+  fn test_partial_merge_overrides_fields_and_appends() {
+    let base = Layout::parse("person\n  name\n  email\n\nexperience\n  title\n").unwrap();
+    let partial = Layout::parse("partial\nperson\n  name\nprojects\n  name\n").unwrap();
+
+    let merged = partial.merge_onto(&base);
+    let names: Vec<_> = merged.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["person", "experience", "projects"]);
+    assert_eq!(merged.sections[0].fields.len(), 1);
+  }
+
+  #[test]
+  fn test_extends_merges_onto_theme_layout() {
+    let theme = Layout::from_theme("minimal").unwrap();
+    let layout = Layout::parse("extends minimal
+
+-projects
+
+experience
+  title
+
+awards
+  name
+").unwrap();
+    assert!(!layout.partial);
+
+    let names: Vec<_> = layout.sections.iter().map(|s| s.name.as_str()).collect();
+    let mut expected: Vec<_> = theme
+      .sections
+      .iter()
+      .map(|s| s.name.as_str())
+      .filter(|&name| name != "projects")
+      .collect();
+    expected.push("awards");
+    assert_eq!(names, expected);
+
+    let experience = layout.sections.iter().find(|s| s.name == "experience").unwrap();
+    assert_eq!(experience.fields.len(), 1);
+  }
+
+  #[test]
+  fn test_partial_can_remove_sections() {
+    let base = Layout::parse("person
+  name
+
+skills
+
+education
+  degree
+").unwrap();
+    let partial = Layout::parse("partial
+-skills
+").unwrap();
+    let merged = partial.merge_onto(&base);
+    let names: Vec<_> = merged.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["person", "education"]);
+  }
+
+  #[test]
+  fn test_toggle_sections() {
+    let base = Layout::parse("person\n  name\nsummary\nskills\nexperience\n  title\nprojects\n  name\n").unwrap();
+    let mut layout = Layout::parse("person\n  name\nexperience\n  title\nprojects\n  name\n").unwrap();
+
+    layout
+      .toggle_sections(&["skills".to_string()], &["projects".to_string()], &base)
+      .unwrap();
+    let names: Vec<_> = layout.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["person", "skills", "experience"]);
+
+    assert!(layout.toggle_sections(&["awards".to_string()], &[], &base).is_err());
+    assert!(layout.toggle_sections(&["skills".to_string()], &["skills".to_string()], &base).is_err());
+    assert!(layout.toggle_sections(&["education".to_string()], &[], &base).is_err());
+  }
+
+  #[test]
+  fn test_extends_errors() {
+    assert!(Layout::parse("extends nope
+person
+").is_err());
+    assert!(Layout::parse("partial
+extends minimal
+").is_err());
+    assert!(Layout::parse("person
+  name
+extends minimal
+").is_err());
+    assert!(Layout::parse("-skills
+person
+").is_err());
+  }
+
+  #[test]
+  fn test_element_and_class_prefixes() {
+    let content = r#"
+person
+  h2: name
+  small.muted.right: location
+  contact-line: email
+"#;
+
+    let layout = Layout::parse(content).unwrap();
+    let fields: Vec<_> = layout.sections[0]
+      .fields
+      .iter()
+      .map(|f| f.as_field().unwrap())
+      .collect();
+
+    assert_eq!(fields[0].element.as_deref(), Some("h2"));
+    assert_eq!(fields[0].class_name, None);
+
+    assert_eq!(fields[1].element.as_deref(), Some("small"));
+    assert_eq!(fields[1].class_name.as_deref(), Some("muted right"));
+
+    assert_eq!(fields[2].element, None);
+    assert_eq!(fields[2].class_name.as_deref(), Some("contact-line"));
+  }
+
+  #[test]
+  fn test_variables_parse_and_resolve() {
+    let content = r#"
+person
+  name
+  target: "Applying for " $role
+  at: "@" $company
+"#;
+
+    let mut layout = Layout::parse(content).unwrap();
+    let parts = &layout.sections[0].fields[1].as_field().unwrap().parts;
+    assert_eq!(parts[0], FieldPart::Literal("Applying for ".to_string()));
+    assert_eq!(parts[1], FieldPart::Variable { name: "role".to_string(), value: None });
+
+    let mut vars = std::collections::BTreeMap::new();
+    vars.insert("role".to_string(), "Platform Engineer".to_string());
+    let err = layout.clone().resolve_variables(&vars).unwrap_err();
+    assert!(err.to_string().contains("$company"));
+
+    vars.insert("company".to_string(), "Initech".to_string());
+    layout.resolve_variables(&vars).unwrap();
+    let parts = &layout.sections[0].fields[1].as_field().unwrap().parts;
+    assert_eq!(
+      parts[1],
+      FieldPart::Variable { name: "role".to_string(), value: Some("Platform Engineer".to_string()) }
+    );
+  }
+
+  #[test]
+  fn test_frontmatter_settings() {
+    let content = "---\npaper = \"a4\"\ndate_format = \"%b %Y\"\n---\npartial\nperson\n  name\n";
+    let layout = Layout::parse(content).unwrap();
+    assert_eq!(layout.settings.paper, Some(crate::build::Paper::A4));
+    assert_eq!(layout.sections.len(), 1);
+    assert!(layout.partial);
+
+    let base = Layout::parse("---\ndensity = \"compact\"\npaper = \"legal\"\n---\nperson\n  name\n").unwrap();
+    let merged = layout.merge_onto(&base);
+    assert_eq!(merged.settings.paper, Some(crate::build::Paper::A4));
+    assert_eq!(merged.settings.density, Some(crate::build::Density::Compact));
+    assert_eq!(merged.settings.search, None);
+    let searchable = Layout::parse("---\nsearch = true\n---\nperson\n  name\n").unwrap();
+    assert_eq!(searchable.settings.search, Some(true));
+    assert!(searchable.to_string().starts_with("---\nsearch = true\n---\n"));
+
+    assert!(Layout::parse("---\npaper = \"tabloid\"\n---\n").is_err());
+    assert!(Layout::parse("---\ndate_format = \"%Q\"\n---\n").is_err());
+    let french = Layout::parse("---\nlocale = \"fr-CA\"\n---\nperson\n  name\n").unwrap();
+    assert_eq!(french.settings.locale, Some(crate::dates::Locale::Fr));
+    assert!(Layout::parse("---\nlocale = \"tlh\"\n---\n").is_err());
+    assert!(Layout::parse("---\npaper = \"a4\"\n").is_err());
+  }
+
+  #[test]
+  fn test_contact_row() {
+    let layout = Layout::parse("person
+  contactrow email phone location
+  small: contactrow website
+").unwrap();
+    let row = layout.sections[0].fields[0].as_field().unwrap();
+    assert!(row.contact_row);
+    assert_eq!(row.parts.len(), 3);
+    assert_eq!(row.parts[2], FieldPart::Field("location".to_string()));
+
+    let small = layout.sections[0].fields[1].as_field().unwrap();
+    assert!(small.contact_row);
+    assert_eq!(small.element.as_deref(), Some("small"));
+    assert!(!Layout::parse("person
+  email
+").unwrap().sections[0].fields[0].as_field().unwrap().contact_row);
+  }
+
+  #[test]
+  fn test_mixed_text_drops_separators_next_to_missing_values() {
+    let text = |line: &str, present: &[&str]| {
+      let layout = Layout::parse(&format!("person\n  {}\n", line)).unwrap();
+      let field = layout.sections[0].fields[0].as_field().unwrap().clone();
+      field.mixed_text(|name| present.contains(&name).then(|| name.to_uppercase()))
+    };
+
+    assert_eq!(text(r#"location " | " phone"#, &["location", "phone"]).as_deref(), Some("LOCATION | PHONE"));
+    assert_eq!(text(r#"location " | " phone"#, &["location"]).as_deref(), Some("LOCATION"));
+    assert_eq!(text(r#"location " | " phone"#, &["phone"]).as_deref(), Some("PHONE"));
+    assert_eq!(text(r#"location " | " phone"#, &[]), None);
+    assert_eq!(text(r#"a ", " b ", " c"#, &["a", "c"]).as_deref(), Some("A, C"));
+    assert_eq!(text(r#"name " (" location ")""#, &["name"]).as_deref(), Some("NAME"));
+    assert_eq!(text(r#""Based in " location"#, &["location"]).as_deref(), Some("Based in LOCATION"));
+    assert_eq!(text(r#""Based in " location"#, &[]), None);
+    assert_eq!(text(r#""References on request""#, &[]).as_deref(), Some("References on request"));
+  }
+
+  #[test]
+  fn test_item_ranges() {
+    let content = r#"
+experience
+  title
+  [1] highlights
+  [2-] summary
+  [1-2] em: company
+  meta:
+    [3] location
+"#;
+    let layout = Layout::parse(content).unwrap();
+    let section = &layout.sections[0];
+    let company = section.fields[3].as_field().unwrap();
+    assert_eq!(company.items, Some(ItemRange { first: 1, last: Some(2) }));
+    assert_eq!(company.element.as_deref(), Some("em"));
+
+    let names = |index: usize| -> Vec<String> {
+      section
+        .for_item(index)
+        .fields
+        .iter()
+        .flat_map(|entry| match entry {
+          FieldOrContainer::Field(field) => vec![field.clone()],
+          FieldOrContainer::Container(container) => container.fields.clone(),
+        })
+        .map(|field| match &field.parts[0] {
+          FieldPart::Field(name) => name.clone(),
+          other => panic!("unexpected part {:?}", other),
+        })
+        .collect()
+    };
+    assert_eq!(names(0), vec!["title", "highlights", "company"]);
+    assert_eq!(names(1), vec!["title", "summary", "company"]);
+    assert_eq!(names(2), vec!["title", "summary", "location"]);
+
+    assert!(Layout::parse("experience\n  [0] title\n").is_err());
+    assert!(Layout::parse("experience\n  [3-1] title\n").is_err());
+    assert!(Layout::parse("experience\n  [1 title\n").is_err());
+  }
+
+  #[test]
+  fn test_split_lines() {
+    let layout = Layout::parse("experience\n  split title \" | \" company | start \" - \" end\n  small: split | location\n").unwrap();
+    let row = layout.sections[0].fields[0].as_field().unwrap();
+    assert_eq!(row.split, Some(3));
+    assert_eq!(row.parts[2], FieldPart::Field("company".to_string()));
+    let right_only = layout.sections[0].fields[1].as_field().unwrap();
+    assert_eq!((right_only.split, right_only.element.as_deref()), (Some(0), Some("small")));
+
+    assert!(Layout::parse("experience\n  split title start\n").is_err());
+  }
+
+  #[test]
+  fn test_helpers() {
+    let layout = Layout::parse("person\n  contactrow email local(phone) short(website)\n  \"Call \" local(phone)\n").unwrap();
+    let row = layout.sections[0].fields[0].as_field().unwrap();
+    assert_eq!(row.parts[1], FieldPart::Field("phone".to_string()));
+    assert_eq!(row.shown("website", "https://www.ada.dev/"), "ada.dev");
+    assert_eq!(row.shown("email", "https://www.ada.dev/"), "https://www.ada.dev/");
+
+    let line = layout.sections[0].fields[1].as_field().unwrap();
+    let text = line.mixed_text(|name| (name == "phone").then(|| "+14155550123".to_string()));
+    assert_eq!(text.as_deref(), Some("Call (415) 555-0123"));
+
+    let error = Layout::parse("person\n  pretty(phone)\n").unwrap_err().to_string();
+    assert!(error.contains("Unknown helper `pretty`"), "{}", error);
+  }
+
+  #[test]
+  fn test_display_round_trips() {
+    let content = r#"---
+paper = "a4"
+date_format = "%b %Y"
+---
+person
+  name
+  small.muted: contactrow email local(phone) $role
+  "Hi " name
+  short(website)
+
+experience grouped accent=navy
+  h3: title
+  [2-] company
+  split title | "(" location ")"
+  row:
+    [1-3] start " - " end
+    em.a.b: location
+
+skills
+  "Languages"
+"#;
+    let layout = Layout::parse(content).unwrap();
+    let shown = layout.to_string();
+    assert_eq!(shown, content);
+    assert_eq!(Layout::parse(&shown).unwrap().to_string(), shown);
+
+    for theme in crate::themes::THEMES {
+      let shown = Layout::from_theme(theme).unwrap().to_string();
+      assert_eq!(Layout::parse(&shown).unwrap().to_string(), shown, "{}", theme);
+    }
+  }
 }
@@ -1,3 +1,18 @@
+pub mod archive;
+pub mod budget;
 pub mod build;
+pub mod compare;
+pub mod contract;
+pub mod css;
+pub mod dates;
+pub mod diagnostics;
+pub mod helpers;
+pub mod import;
+pub mod jsonresume;
 pub mod layout;
+pub mod lint;
+pub mod markup;
+pub mod merge;
+pub mod sections;
+pub mod stats;
 pub mod themes;
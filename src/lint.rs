@@ -0,0 +1,605 @@
+//! Content lint for resume writing (`srg lint`).
+//!
+//! The JOBL parser checks that a resume is well-formed; these rules
+//! check that it reads well. Each rule looks at the summaries and
+//! highlights and reports findings with where they are and what to
+//! do about them. Nothing here changes the resume.
+//!
+//! One rule, [`unused`], looks at the layout instead: content the
+//! resume has that the layout never shows. Builds print its findings
+//! as warnings too.
+
+use jobl::JoblDocument;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+use crate::layout::{FieldOrContainer, FieldPart, Layout, Section};
+
+/// Sentences longer than this are hard to take in at a glance.
+const MAX_SENTENCE_WORDS: usize = 30;
+/// Flesch-Kincaid grade above which a sentence is flagged as
+/// convoluted. Resume writing is dense with jargon, so this is
+/// looser than the usual advice for prose.
+const MAX_GRADE: f64 = 14.0;
+/// Sentences shorter than this aren't graded; the formula is
+/// meaningless for a handful of words.
+const MIN_GRADED_WORDS: usize = 8;
+/// Share of words two highlights must have in common to count as
+/// near-duplicates.
+const NEAR_DUPLICATE: f64 = 0.8;
+/// Highlights with fewer distinct words than this are only compared
+/// for exact repeats; short bullets overlap by accident.
+const MIN_COMPARED_WORDS: usize = 4;
+
+/// Sections the `quantify` rule can check.
+pub const QUANTIFIABLE_SECTIONS: &[&str] = &["summary", "experience", "projects"];
+
+/// Words that state an amount without a numeral.
+const NUMBER_WORDS: &[&str] = &[
+    "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "twelve", "dozen",
+    "dozens", "hundred", "hundreds", "thousand", "thousands", "million", "millions", "billion",
+    "billions", "twice", "doubled", "tripled", "quadrupled", "halved", "half",
+];
+
+/// The `[lint]` table in srg.toml.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    /// Sections whose entries should state measurable impact:
+    /// "experience" (highlights), "projects" (summaries), and
+    /// "summary". Defaults to experience only; `[]` turns the rule
+    /// off.
+    #[serde(default = "default_quantify")]
+    pub quantify: Vec<String>,
+}
+
+fn default_quantify() -> Vec<String> {
+    vec!["experience".to_string()]
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            quantify: default_quantify(),
+        }
+    }
+}
+
+impl LintConfig {
+    /// Refuse section names the rules don't know.
+    pub fn check(&self) -> Result<(), String> {
+        match self.quantify.iter().find(|s| !QUANTIFIABLE_SECTIONS.contains(&s.as_str())) {
+            Some(section) => Err(format!(
+                "lint.quantify: unknown section {:?} (expected one of {})",
+                section,
+                QUANTIFIABLE_SECTIONS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// What kind of text a finding is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextKind {
+    Summary,
+    Highlight,
+}
+
+/// One summary or highlight, with a human-readable location.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text<'a> {
+    pub location: String,
+    /// Where the text is in the JOBL file, e.g.
+    /// `experience[0].highlights` (see [`crate::diagnostics::line_of`]).
+    pub path: String,
+    /// Layout section the text belongs to, e.g. `experience`.
+    pub section: &'static str,
+    pub kind: TextKind,
+    pub text: &'a str,
+}
+
+/// A problem one rule found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Short rule name, e.g. `readability`.
+    pub rule: &'static str,
+    pub location: String,
+    pub message: String,
+}
+
+/// Run every rule over `doc`.
+pub fn lint(doc: &JoblDocument, config: &LintConfig) -> Vec<Finding> {
+    let texts = texts(doc);
+    let mut findings = Vec::new();
+    findings.extend(readability(&texts));
+    findings.extend(duplicates(&texts));
+    findings.extend(quantify(&texts, &config.quantify));
+    findings
+}
+
+/// Every summary and highlight in `doc`, in document order.
+pub fn texts(doc: &JoblDocument) -> Vec<Text<'_>> {
+    let mut texts = Vec::new();
+    if let Some(summary) = &doc.person.summary {
+        texts.push(Text {
+            location: "summary".to_string(),
+            path: "person.summary".to_string(),
+            section: "summary",
+            kind: TextKind::Summary,
+            text: summary,
+        });
+    }
+    for (i, exp) in doc.experience.iter().enumerate() {
+        let role = format!("experience \"{} at {}\"", exp.title, exp.company);
+        if let Some(summary) = &exp.summary {
+            texts.push(Text {
+                location: format!("{}, summary", role),
+                path: format!("experience[{}].summary", i),
+                section: "experience",
+                kind: TextKind::Summary,
+                text: summary,
+            });
+        }
+        for (j, highlight) in exp.highlights.iter().enumerate() {
+            texts.push(Text {
+                location: format!("{}, highlight {}", role, j + 1),
+                path: format!("experience[{}].highlights", i),
+                section: "experience",
+                kind: TextKind::Highlight,
+                text: highlight,
+            });
+        }
+    }
+    for (i, proj) in doc.projects.iter().enumerate() {
+        if let Some(summary) = &proj.summary {
+            texts.push(Text {
+                location: format!("project \"{}\", summary", proj.name),
+                path: format!("projects[{}].summary", i),
+                section: "projects",
+                kind: TextKind::Summary,
+                text: summary,
+            });
+        }
+    }
+    texts
+}
+
+/// Flag sentences that run long or score as hard to read.
+fn readability(texts: &[Text]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for text in texts {
+        for sentence in sentences(text.text) {
+            let words = words(sentence);
+            let message = if words.len() > MAX_SENTENCE_WORDS {
+                format!(
+                    "sentence runs {} words (\"{}\"); split it or cut it to {} or fewer",
+                    words.len(),
+                    opening(&words),
+                    MAX_SENTENCE_WORDS
+                )
+            } else if words.len() >= MIN_GRADED_WORDS && grade(&words) > MAX_GRADE {
+                format!(
+                    "reads at grade {:.0} (\"{}\"); try shorter words and a plainer structure",
+                    grade(&words),
+                    opening(&words)
+                )
+            } else {
+                continue;
+            };
+            findings.push(Finding {
+                rule: "readability",
+                location: text.location.clone(),
+                message,
+            });
+        }
+    }
+    findings
+}
+
+/// Flag highlights that repeat, word for word or nearly, an earlier
+/// highlight. Tailoring a resume by copy and paste leaves these
+/// behind, and a reviewer reads them as careless.
+fn duplicates(texts: &[Text]) -> Vec<Finding> {
+    let highlights: Vec<(&Text, BTreeSet<String>)> = texts
+        .iter()
+        .filter(|t| t.kind == TextKind::Highlight)
+        .map(|t| (t, words(t.text).iter().map(|w| w.to_lowercase()).collect()))
+        .collect();
+
+    let mut findings = Vec::new();
+    for (i, (text, set)) in highlights.iter().enumerate() {
+        let earlier = highlights[..i].iter().find_map(|(other, other_set)| {
+            if set == other_set {
+                return Some((other, 1.0));
+            }
+            if set.len() < MIN_COMPARED_WORDS || other_set.len() < MIN_COMPARED_WORDS {
+                return None;
+            }
+            let shared = set.intersection(other_set).count() as f64;
+            let similarity = shared / set.union(other_set).count() as f64;
+            (similarity >= NEAR_DUPLICATE).then_some((other, similarity))
+        });
+
+        if let Some((other, similarity)) = earlier {
+            let message = if similarity >= 1.0 {
+                format!("repeats {}", other.location)
+            } else {
+                format!(
+                    "nearly repeats {} ({:.0}% the same words); reword one or drop it",
+                    other.location,
+                    similarity * 100.0
+                )
+            };
+            findings.push(Finding {
+                rule: "duplicates",
+                location: text.location.clone(),
+                message,
+            });
+        }
+    }
+    findings
+}
+
+/// Flag entries in `sections` that state no amount: no numeral, no
+/// percentage, no "doubled" or "thousands". A role's own summary
+/// describes the job rather than an achievement, so it's skipped.
+fn quantify(texts: &[Text], sections: &[String]) -> Vec<Finding> {
+    texts
+        .iter()
+        .filter(|t| sections.iter().any(|s| s == t.section))
+        .filter(|t| !(t.section == "experience" && t.kind == TextKind::Summary))
+        .filter(|t| !states_amount(t.text))
+        .map(|t| Finding {
+            rule: "quantify",
+            location: t.location.clone(),
+            message: "no numbers; add measurable impact (how much, how many, how fast) if you can"
+                .to_string(),
+        })
+        .collect()
+}
+
+fn states_amount(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit() || c == '%')
+        || words(text)
+            .iter()
+            .any(|w| NUMBER_WORDS.contains(&w.to_lowercase().as_str()))
+}
+
+/// Flag content `layout` has no place for: a section with entries
+/// but no section in the layout, or a field some entry fills in that
+/// the layout's section never names. Only fields the layout could
+/// show count. Sections in `excluded` (`--exclude-section`) were left
+/// out on purpose and aren't reported.
+pub fn unused(doc: &JoblDocument, layout: &Layout, excluded: &[String]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let person = &doc.person;
+    let summary_shown = layout.sections.iter().any(|s| s.name == "summary")
+        || layout.sections.iter().any(|s| s.name == "person" && names_any(s, &["summary"]));
+    if person.summary.is_some() && !summary_shown && !excluded.iter().any(|s| s == "summary") {
+        findings.push(Finding {
+            rule: "unused",
+            location: "summary".to_string(),
+            message: "the resume has a summary, but the layout has no summary section and its person section doesn't show it".to_string(),
+        });
+    }
+    let mut check = |section: &'static str, entries: usize, noun: (&str, &str), fields: &[(&str, usize, &[&str])]| {
+        if entries == 0 || excluded.iter().any(|s| s == section) {
+            return;
+        }
+        let what = |n: usize| if section == "person" { "a name and contact details".to_string() } else { format!("{} {}", n, if n == 1 { noun.0 } else { noun.1 }) };
+        let shown: Vec<&Section> = layout.sections.iter().filter(|s| s.name == section).collect();
+        if shown.is_empty() {
+            findings.push(Finding {
+                rule: "unused",
+                location: section.to_string(),
+                message: format!("the resume has {}, but the layout has no {} section", what(entries), section),
+            });
+            return;
+        }
+        for &(field, filled, names) in fields {
+            if filled > 0 && !shown.iter().any(|s| names_any(s, names)) {
+                findings.push(Finding {
+                    rule: "unused",
+                    location: format!("{}.{}", section, field),
+                    message: format!(
+                        "{} {} {}, but the layout's {} section doesn't show it",
+                        if section == "person" { "the resume".to_string() } else { format!("{} of {}", filled, what(entries)) },
+                        if filled == 1 { "has" } else { "have" },
+                        article(field),
+                        section
+                    ),
+                });
+            }
+        }
+    };
+
+    let count = |values: &[&Option<String>]| values.iter().filter(|v| v.is_some()).count();
+    check(
+        "person",
+        1,
+        ("person", "people"),
+        &[
+            ("headline", count(&[&person.headline]), &["headline"]),
+            ("email", count(&[&person.email]), &["email"]),
+            ("phone", count(&[&person.phone]), &["phone"]),
+            ("location", count(&[&person.location]), &["location"]),
+            ("website", count(&[&person.website]), &["website"]),
+            ("github", count(&[&person.github]), &["github"]),
+            ("linkedin", count(&[&person.linkedin]), &["linkedin"]),
+        ],
+    );
+    check("skills", doc.skills.as_ref().map_or(0, |s| s.len()), ("skill category", "skill categories"), &[]);
+
+    let exp = &doc.experience;
+    let filled = |f: fn(&jobl::ExperienceItem) -> bool| exp.iter().filter(|e| f(e)).count();
+    check(
+        "experience",
+        exp.len(),
+        ("role", "roles"),
+        &[
+            ("title", exp.len(), &["title"]),
+            ("company", exp.len(), &["company"]),
+            ("location", filled(|e| e.location.is_some()), &["location"]),
+            ("start", filled(|e| e.start.is_some()), &["start", "dates"]),
+            ("end", filled(|e| e.end.is_some()), &["end", "dates"]),
+            ("summary", filled(|e| e.summary.is_some()), &["summary"]),
+            ("highlights", filled(|e| !e.highlights.is_empty()), &["highlights"]),
+        ],
+    );
+
+    let projects = &doc.projects;
+    check(
+        "projects",
+        projects.len(),
+        ("project", "projects"),
+        &[
+            ("name", projects.len(), &["name"]),
+            ("url", projects.iter().filter(|p| p.url.is_some()).count(), &["url"]),
+            ("summary", projects.iter().filter(|p| p.summary.is_some()).count(), &["summary"]),
+        ],
+    );
+
+    let edu = &doc.education;
+    let filled = |f: fn(&jobl::EducationItem) -> bool| edu.iter().filter(|e| f(e)).count();
+    check(
+        "education",
+        edu.len(),
+        ("degree", "degrees"),
+        &[
+            ("degree", edu.len(), &["degree"]),
+            ("institution", edu.len(), &["institution"]),
+            ("location", filled(|e| e.location.is_some()), &["location"]),
+            ("start", filled(|e| e.start.is_some()), &["start", "dates"]),
+            ("end", filled(|e| e.end.is_some()), &["end", "dates"]),
+            ("details", filled(|e| !e.details.is_empty()), &["details"]),
+        ],
+    );
+    findings
+}
+
+/// "a location", "an email", "highlights".
+fn article(field: &str) -> String {
+    if field.ends_with('s') {
+        field.to_string()
+    } else if field.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        format!("an {}", field)
+    } else {
+        format!("a {}", field)
+    }
+}
+
+/// Whether any field line in `section` names one of `names`.
+fn names_any(section: &Section, names: &[&str]) -> bool {
+    section
+        .fields
+        .iter()
+        .flat_map(|entry| match entry {
+            FieldOrContainer::Field(field) => std::slice::from_ref(field),
+            FieldOrContainer::Container(container) => container.fields.as_slice(),
+        })
+        .flat_map(|field| &field.parts)
+        .any(|part| matches!(part, FieldPart::Field(name) if names.contains(&name.as_str())))
+}
+
+/// Flesch-Kincaid grade level of one sentence.
+pub fn grade(words: &[&str]) -> f64 {
+    if words.is_empty() {
+        return 0.0;
+    }
+    let syllables: usize = words.iter().map(|w| syllables(w)).sum();
+    0.39 * words.len() as f64 + 11.8 * syllables as f64 / words.len() as f64 - 15.59
+}
+
+/// Split text into sentences on `.`, `!`, `?`, and `;` followed by
+/// whitespace, so version numbers and decimals like "3.5M" stay put.
+pub fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_break = matches!(c, '.' | '!' | '?' | ';')
+            && chars.peek().is_none_or(|&(_, next)| next.is_whitespace());
+        if at_break {
+            let end = i + c.len_utf8();
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Words in a sentence, without surrounding punctuation.
+pub fn words(sentence: &str) -> Vec<&str> {
+    sentence
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Estimated syllables in an English word: vowel groups, less a
+/// silent final "e" or "-ed", at least one.
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    if word.chars().any(|c| c.is_ascii_digit()) {
+        return 1;
+    }
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    let silent_e = word.ends_with('e') && !word.ends_with("le");
+    let silent_ed = word.ends_with("ed") && !word.ends_with("ted") && !word.ends_with("ded");
+    if (silent_e || silent_ed) && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// The first few words, to point at the sentence in a finding.
+fn opening(words: &[&str]) -> String {
+    let mut opening = words.iter().take(6).copied().collect::<Vec<_>>().join(" ");
+    if words.len() > 6 {
+        opening.push_str(" ...");
+    }
+    opening
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sentences_split_on_terminal_punctuation_only() {
+        assert_eq!(
+            sentences("Shipped v1.2 to 3.5M users. Cut costs; kept SLAs!"),
+            vec!["Shipped v1.2 to 3.5M users.", "Cut costs;", "kept SLAs!"]
+        );
+    }
+
+    #[test]
+    fn syllables_are_estimated() {
+        assert_eq!(syllables("cut"), 1);
+        assert_eq!(syllables("shipped"), 1);
+        assert_eq!(syllables("table"), 2);
+        assert_eq!(syllables("infrastructure"), 4);
+        assert_eq!(syllables("k8s"), 1);
+    }
+
+    #[test]
+    fn repeated_highlights_are_flagged_once() {
+        let text = |location: &str, text: &'static str| Text {
+            location: location.into(),
+            path: "experience[0].highlights".into(),
+            section: "experience",
+            kind: TextKind::Highlight,
+            text,
+        };
+        let findings = duplicates(&[
+            text("a", "Cut p95 latency by 40% with a new cache layer."),
+            text("b", "Cut P95 latency by 40% with a new cache layer"),
+            text("c", "Cut p95 latency by 40% with a new caching layer."),
+            text("d", "Mentored four engineers."),
+        ]);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].location, "b");
+        assert_eq!(findings[0].message, "repeats a");
+        assert_eq!(findings[1].location, "c");
+        assert!(findings[1].message.starts_with("nearly repeats a (82%"));
+    }
+
+    #[test]
+    fn quantify_checks_configured_sections_only() {
+        let text = |section: &'static str, kind: TextKind, text: &'static str| Text {
+            location: format!("{} {}", section, text),
+            path: format!("{}[0].summary", section),
+            section,
+            kind,
+            text,
+        };
+        let texts = [
+            text("experience", TextKind::Highlight, "Cut p95 latency by 40%."),
+            text("experience", TextKind::Highlight, "Doubled test coverage."),
+            text("experience", TextKind::Highlight, "Improved the deploy pipeline."),
+            text("experience", TextKind::Summary, "Platform team."),
+            text("projects", TextKind::Summary, "A static resume generator."),
+        ];
+
+        let findings = quantify(&texts, &default_quantify());
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location, "experience Improved the deploy pipeline.");
+
+        let sections = vec!["projects".to_string()];
+        assert_eq!(quantify(&texts, &sections).len(), 1);
+        assert!(quantify(&texts, &[]).is_empty());
+    }
+
+    #[test]
+    fn plain_bullets_pass_and_convoluted_ones_are_flagged() {
+        let plain = Text {
+            location: "x".into(),
+            path: "experience[0].highlights".into(),
+            section: "experience",
+            kind: TextKind::Highlight,
+            text: "Cut build times in half by caching test fixtures.",
+        };
+        let dense = Text {
+            location: "y".into(),
+            path: "experience[0].highlights".into(),
+            section: "experience",
+            kind: TextKind::Highlight,
+            text: "Orchestrated comprehensive organizational modernization initiatives \
+                   facilitating interdepartmental operational interoperability.",
+        };
+        let findings = readability(&[plain, dense]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location, "y");
+        assert!(findings[0].message.starts_with("reads at grade"));
+    }
+
+    #[test]
+    fn content_the_layout_never_shows_is_flagged() {
+        let doc = jobl::parse_str(
+            r#"
+[person]
+name = "Ada"
+email = "ada@example.com"
+phone = "555"
+
+[[experience]]
+title = "Engineer"
+company = "Acme"
+location = "Berlin"
+start = "2020"
+highlights = ["Built billing"]
+
+[[experience]]
+title = "Intern"
+company = "Initech"
+
+[[projects]]
+name = "srg"
+"#,
+        )
+        .unwrap();
+        let layout = Layout::parse("person\n  name\n  email\n\nexperience\n  title\n  company\n  dates\n  highlights\n").unwrap();
+
+        let findings = unused(&doc, &layout, &[]);
+        let locations: Vec<&str> = findings.iter().map(|f| f.location.as_str()).collect();
+        assert_eq!(locations, ["person.phone", "experience.location", "projects"]);
+        assert_eq!(findings[0].message, "the resume has a phone, but the layout's person section doesn't show it");
+        assert_eq!(findings[1].message, "1 of 2 roles has a location, but the layout's experience section doesn't show it");
+        assert_eq!(findings[2].message, "the resume has 1 project, but the layout has no projects section");
+
+        assert_eq!(unused(&doc, &layout, &["projects".to_string()]).len(), 2);
+    }
+}
@@ -1,29 +1,89 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
 
+pub mod archive;
+pub mod budget;
 pub mod build;
+pub mod ci;
+pub mod clipboard;
+pub mod compare;
 pub mod config;
+pub mod contract;
+pub mod css;
+pub mod dates;
+pub mod diagnostics;
+pub mod doctor;
+pub mod github;
+pub mod gravatar;
+pub mod helpers;
+pub mod import;
+pub mod jsonresume;
 pub mod layout;
+pub mod lint;
+pub mod manifest;
+pub mod markup;
+pub mod merge;
+pub mod pack;
+pub mod pdfdiff;
+pub mod pdfsign;
+pub mod revisions;
+pub mod sections;
+pub mod send;
+pub mod stats;
 pub mod themes;
+pub mod versioned;
 
 /// Static Resume Generator - Build HTML and PDF resumes from JOBL files
 #[derive(Parser, Debug)]
 #[command(name = "srg")]
 #[command(about = "Static Resume Generator", long_about = None)]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Input JOBL file
-    #[arg(short, long, value_name = "FILE")]
-    input: PathBuf,
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[command(flatten)]
+    build: BuildArgs,
+}
+
+/// Options for building a resume, shared by the default command and
+/// subcommands that build before doing something with the output.
+#[derive(clap::Args, Debug, Clone)]
+struct BuildArgs {
+    /// Input JOBL (or TOML, YAML, or Markdown) file. Repeat to merge
+    /// several in order, e.g. a shared base and a variant: tables
+    /// merge, lists append, and later values win. srg.toml is read
+    /// next to the first.
+    #[arg(short, long, value_name = "FILE", required = true)]
+    input: Vec<PathBuf>,
+
+    /// How to read the input file: toml (JOBL is TOML), yaml, or
+    /// markdown. Defaults to the one its extension names, and toml
+    /// otherwise.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    input_format: Option<InputFormat>,
 
     /// Output directory. Overrides `out` in srg.toml. Defaults to "dist".
     #[arg(short, long, value_name = "DIR")]
     out: Option<PathBuf>,
 
-    /// Theme name. Overrides `theme` in srg.toml.
+    /// Theme name. Overrides `theme` in srg.toml. Stack themes with
+    /// `+` to layer an overlay on a base, e.g. `minimal+compact`.
     #[arg(short, long)]
     theme: Option<String>,
 
+    /// Theme for the web page (and preview image) only. Overrides
+    /// `theme_html` in srg.toml and the theme otherwise chosen.
+    #[arg(long, value_name = "NAME")]
+    theme_html: Option<String>,
+
+    /// Theme for the PDF only, e.g. a plain one for applicant
+    /// tracking systems. Overrides `theme_pdf` in srg.toml and the
+    /// theme otherwise chosen.
+    #[arg(long, value_name = "NAME")]
+    theme_pdf: Option<String>,
+
     /// Custom layout file. Overrides `layout` in srg.toml.
     #[arg(short, long, value_name = "FILE")]
     layout: Option<PathBuf>,
@@ -31,23 +91,710 @@ struct Args {
     /// Custom CSS file. Overrides `css` in srg.toml.
     #[arg(short, long, value_name = "FILE")]
     css: Option<PathBuf>,
+
+    /// How CSS is delivered: inline, external, or critical. Overrides
+    /// `css_mode` in srg.toml. Defaults to inline.
+    #[arg(long, value_enum, value_name = "MODE")]
+    css_mode: Option<build::CssMode>,
+
+    /// Spacing: compact, normal, or relaxed. Overrides `density` in
+    /// srg.toml. Defaults to normal.
+    #[arg(long, value_enum, value_name = "DENSITY")]
+    density: Option<build::Density>,
+
+    /// List marker for highlights and education details: disc,
+    /// dash, or none. Overrides `bullets` in srg.toml and the theme.
+    #[arg(long, value_enum, value_name = "STYLE")]
+    bullets: Option<build::Bullets>,
+
+    /// What to do with emoji in every output: keep, strip, or
+    /// replace. Overrides `[emoji]` in srg.toml. By default plain
+    /// text strips them and everything else keeps them.
+    #[arg(long, value_enum, value_name = "POLICY")]
+    emoji: Option<build::emoji::EmojiPolicy>,
+
+    /// Fold the plain-text export to ASCII (José -> Jose, curly
+    /// quotes -> straight) and list what changed.
+    #[arg(long)]
+    ascii: bool,
+
+    /// Column the plain-text export wraps at; 0 doesn't wrap.
+    /// Overrides `txt_width` in srg.toml. Defaults to 80.
+    #[arg(long, value_name = "COLUMNS")]
+    txt_width: Option<usize>,
+
+    /// Print the PDF in shades of gray for black-and-white printers.
+    /// The HTML keeps its colors.
+    #[arg(long)]
+    grayscale: bool,
+
+    /// Stamp TEXT (e.g. DRAFT) diagonally across every printed page
+    /// and the PDF. Overrides `watermark` in srg.toml.
+    #[arg(long, value_name = "TEXT")]
+    watermark: Option<String>,
+
+    /// A line printed at the bottom of every PDF page, e.g.
+    /// "Confidential — do not distribute". Overrides `footer` in
+    /// srg.toml.
+    #[arg(long, value_name = "TEXT")]
+    footer: Option<String>,
+
+    /// Stamp every PDF page with URL (the hosted, canonical resume), a
+    /// QR code for it, and a hash of the content, so a printed copy can
+    /// be checked against the current version. Overrides `verify_url`
+    /// in srg.toml.
+    #[arg(long, value_name = "URL")]
+    verify_url: Option<String>,
+
+    /// What prints the PDF: chrome (the themed page, the default) or
+    /// typst (a plain typeset document, no browser needed).
+    /// Overrides `pdf_backend` in srg.toml.
+    #[arg(long, value_enum, value_name = "BACKEND")]
+    pdf_backend: Option<build::PdfBackend>,
+
+    /// What the PDF conforms to: standard (the default) or pdfa
+    /// (PDF/A-2b, for portals that only take archival PDFs; the
+    /// Chrome backend needs Ghostscript). Overrides `pdf_profile` in
+    /// srg.toml.
+    #[arg(long, value_enum, value_name = "PROFILE")]
+    pdf_profile: Option<build::PdfProfile>,
+
+    /// Tag the PDF's headings, lists, and reading order so screen
+    /// readers can navigate it (Chrome backend).
+    #[arg(long)]
+    tagged_pdf: bool,
+
+    /// Add an outline of the page's headings to the PDF, shown as
+    /// bookmarks by PDF viewers; implies --tagged-pdf (Chrome backend).
+    #[arg(long)]
+    pdf_outline: bool,
+
+    /// PDF paper size: letter, a4, or legal. Overrides `[pdf]` in
+    /// srg.toml, the layout, and the theme. Defaults to letter.
+    #[arg(long, value_enum, value_name = "SIZE")]
+    paper: Option<build::Paper>,
+
+    /// PDF page margins, CSS style: one to four lengths such as
+    /// 0.5in or "12mm 15mm". Overrides `[pdf]` in srg.toml and the
+    /// theme.
+    #[arg(long, value_name = "LENGTHS", value_parser = build::Margins::parse)]
+    margin: Option<build::Margins>,
+
+    /// PDF scale factor, 0.1 to 2. Overrides `[pdf]` in srg.toml and
+    /// the theme. Defaults to 1.
+    #[arg(long, value_name = "FACTOR", value_parser = |v: &str| {
+        v.parse::<f64>().map_err(|_| format!("expected a number, got {:?}", v)).and_then(build::check_scale)
+    })]
+    scale: Option<f64>,
+
+    /// Print background colors and images in the PDF (true or false).
+    /// Overrides `[pdf]` in srg.toml and the theme. Defaults to true.
+    #[arg(long, value_name = "BOOL")]
+    background: Option<bool>,
+
+    /// How links print in the PDF: underline, plain, url (address in
+    /// parentheses), or strip. Overrides `[pdf]` in srg.toml and the
+    /// theme. Defaults to the theme's styling.
+    #[arg(long, value_enum, value_name = "POLICY")]
+    links: Option<build::PrintLinks>,
+
+    /// Sign the PDF with the key and certificate in --cert, for
+    /// portals that check signatures. Needs the openssl CLI.
+    #[arg(long, requires = "cert")]
+    sign_pdf: bool,
+
+    /// PKCS#12 file (.p12 or .pfx) to sign the PDF with. Its password
+    /// is read from SRG_CERT_PASSWORD.
+    #[arg(long, value_name = "FILE", requires = "sign_pdf")]
+    cert: Option<PathBuf>,
+
+    /// Repeat a section's title, marked "(continued)", at the top of
+    /// each PDF page the section runs onto (true or false). Overrides
+    /// `[pdf]` in srg.toml and the theme. Defaults to false.
+    #[arg(long, value_name = "BOOL")]
+    continued: Option<bool>,
+
+    /// Pages the PDF should fit on. The Chrome build measures the
+    /// laid-out page before printing and warns (fails with --strict)
+    /// when it runs over, naming the sections past the limit.
+    /// Overrides `[pdf]` in srg.toml and the theme.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_pages: Option<u64>,
+
+    /// Cut summaries and highlights that overrun their `[budget]` in
+    /// srg.toml to fit, instead of only warning. Overrides
+    /// `budget.truncate` in srg.toml.
+    #[arg(long)]
+    truncate: bool,
+
+    /// Base font size for the theme, e.g. 10.5pt. Overrides
+    /// `font_size` in srg.toml.
+    #[arg(long, value_name = "SIZE", value_parser = |v: &str| css::parse_length(v, false))]
+    font_size: Option<String>,
+
+    /// Line height for body text, e.g. 1.3. Overrides `line_height`
+    /// in srg.toml.
+    #[arg(long, value_name = "HEIGHT", value_parser = |v: &str| css::parse_length(v, true))]
+    line_height: Option<String>,
+
+    /// List skills categories in the order the JOBL file has them
+    /// instead of alphabetically. Overrides `keep_skill_order` in
+    /// srg.toml.
+    #[arg(long)]
+    keep_skill_order: bool,
+
+    /// Leave a section out of this build, e.g. --exclude-section
+    /// projects. Repeat or separate with commas.
+    #[arg(long = "exclude-section", value_delimiter = ',', value_name = "SECTION")]
+    exclude_sections: Vec<String>,
+
+    /// Add a section the layout leaves out, taking its fields from
+    /// the theme's layout. Repeat or separate with commas.
+    #[arg(long = "include-section", value_delimiter = ',', value_name = "SECTION")]
+    include_sections: Vec<String>,
+
+    /// Set a layout variable, e.g. --set role="Platform Engineer"
+    /// for a layout line like `"Applying for " $role`. Repeatable.
+    /// Overrides `[variables]` in srg.toml.
+    #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    set: Vec<(String, String)>,
+
+    /// Outputs to produce: html, pdf, email-html, gemtext, org, rst,
+    /// md, odt, rtf, epub, txt, og-image. Repeat the flag or separate with commas. Overrides
+    /// `formats` in srg.toml. Defaults to html and pdf.
+    #[arg(short, long = "format", value_enum, value_delimiter = ',', value_name = "FORMAT")]
+    format: Vec<build::OutputFormat>,
+
+    /// Produce only the PDF, printed straight from memory: no
+    /// index.html, other formats, or post-build steps.
+    #[arg(long, conflicts_with_all = ["format", "archive", "versioned", "history", "copy"])]
+    pdf_only: bool,
+
+    /// With --pdf-only, write the PDF to standard output instead of
+    /// the output directory, e.g. `srg -i resume.jobl --pdf-only
+    /// --stdout > resume.pdf`.
+    #[arg(long, requires = "pdf_only")]
+    stdout: bool,
+
+    /// Also copy the resume to the system clipboard as plain text
+    /// or Markdown.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    copy: Option<CopyFormat>,
+
+    /// Also zip the outputs and the source JOBL file into
+    /// resume-bundle.zip in the output directory.
+    #[arg(long)]
+    archive: bool,
+
+    /// Write each build to its own <out>/<date>_<id>/ directory and
+    /// point <out>/latest at it instead of overwriting <out>.
+    #[arg(long)]
+    versioned: bool,
+
+    /// Also write history.html, listing each git commit that changed
+    /// the JOBL file and what it changed.
+    #[arg(long)]
+    history: bool,
+
+    /// Fail the build when a generated page isn't well-formed HTML
+    /// instead of warning. Overrides `strict` in srg.toml.
+    #[arg(long)]
+    strict: bool,
+
+    /// Write index.html as a fragment to embed in another site's
+    /// layout (Hugo, Zola, Jekyll): the resume's sections without
+    /// <html>, <head>, or <body>. The PDF still prints the full page.
+    /// Overrides `fragment` in srg.toml.
+    #[arg(long)]
+    fragment: bool,
+
+    /// Name style.css and the theme's fonts after their content
+    /// (style.3f2a9c1e0b.css) and write _headers and
+    /// asset-manifest.json, so hosts can cache them for good.
+    /// Overrides `fingerprint` in srg.toml.
+    #[arg(long)]
+    fingerprint: bool,
+
+    /// Look up GitHub stars, language, and description for projects
+    /// with a GitHub URL, for use as layout fields.
+    #[arg(long)]
+    github: bool,
+
+    /// Fetch the Gravatar for person.email for the `avatar` layout
+    /// field.
+    #[arg(long)]
+    gravatar: bool,
+}
+
+/// Formats `--copy` accepts: the ones that paste sensibly as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum CopyFormat {
+    Txt,
+    Md,
+}
+
+impl CopyFormat {
+    fn output_format(self) -> build::OutputFormat {
+        match self {
+            CopyFormat::Txt => build::OutputFormat::Txt,
+            CopyFormat::Md => build::OutputFormat::Markdown,
+        }
+    }
+}
+
+/// Formats the resume is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum InputFormat {
+    /// TOML: a `.jobl` or `.toml` file.
+    #[value(alias = "jobl")]
+    Toml,
+    /// YAML, in the same shape: a `.yaml` or `.yml` file.
+    Yaml,
+    /// A Markdown resume with the person in its frontmatter, read as
+    /// `srg import markdown` reads it: a `.md` or `.markdown` file.
+    #[value(alias = "md")]
+    Markdown,
+}
+
+impl InputFormat {
+    /// The format `input`'s extension names.
+    fn of(input: &Path) -> Self {
+        match input.extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("yaml" | "yml") => InputFormat::Yaml,
+            Some("md" | "markdown") => InputFormat::Markdown,
+            _ => InputFormat::Toml,
+        }
+    }
+}
+
+/// Formats `srg export` converts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ExportFormat {
+    /// JSON Resume (jsonresume.org), for its themes and tools.
+    JsonResume,
+}
+
+/// Formats `srg import` reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ImportFormat {
+    /// A Markdown note with the person in its frontmatter, as
+    /// Obsidian keeps them.
+    Markdown,
+    /// A plain-text resume, as a word processor or PDF reader saves it
+    Text,
+    /// LinkedIn's data export: the zip from "Get a copy of your data"
+    Linkedin,
+}
+
+/// Tools besides the plain build.
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Compare resume variants side by side (length, sections, and
+    /// keyword coverage) in an HTML report
+    Compare {
+        /// The JOBL files to compare
+        #[arg(required = true, num_args = 2..)]
+        variants: Vec<PathBuf>,
+
+        /// Keyword to check each variant for, e.g. from the job
+        /// posting. Repeat or separate with commas. Defaults to every
+        /// skill the variants list.
+        #[arg(short, long = "keyword", value_delimiter = ',', value_name = "KEYWORD")]
+        keywords: Vec<String>,
+
+        /// Where to write the report
+        #[arg(short, long, value_name = "FILE", default_value = "variants.html")]
+        output: PathBuf,
+    },
+
+    /// Check summaries and highlights for writing problems, and the
+    /// resume for content the layout never shows. Exits with an error
+    /// when anything is found
+    Lint {
+        /// Input JOBL file
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+
+    /// Print counts, the most used terms in highlights, and which
+    /// listed skills no highlight backs up
+    Stats {
+        /// Input JOBL file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// How many of the most frequent terms to list
+        #[arg(long, default_value_t = 15)]
+        top: usize,
+    },
+
+    /// Rasterize two PDFs and write an image highlighting what changed
+    DiffPdf {
+        /// The previous PDF
+        old: PathBuf,
+
+        /// The updated PDF
+        new: PathBuf,
+
+        /// Where to write the highlighted diff image
+        #[arg(short, long, value_name = "FILE", default_value = "pdf-diff.png")]
+        output: PathBuf,
+
+        /// Rasterization resolution
+        #[arg(long, default_value_t = 72)]
+        dpi: u32,
+    },
+
+    /// Check that Chrome, the theme's fonts, and the output directory
+    /// are ready for a build, and say how to fix what isn't
+    Doctor {
+        /// JOBL file whose srg.toml supplies the theme and output
+        /// directory
+        #[arg(short, long, value_name = "FILE")]
+        input: Option<PathBuf>,
+
+        /// Theme to check fonts for. Overrides `theme` in srg.toml.
+        #[arg(short, long)]
+        theme: Option<String>,
+
+        /// Output directory to check. Overrides `out` in srg.toml.
+        #[arg(short, long, value_name = "DIR")]
+        out: Option<PathBuf>,
+
+        /// Remove the Chrome profiles srg keeps between builds before
+        /// checking
+        #[arg(long)]
+        clean: bool,
+    },
+
+    /// Convert the resume to another tool's format
+    Export {
+        /// Input JOBL file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Format to convert to
+        #[arg(short, long, value_enum)]
+        format: ExportFormat,
+
+        /// Where to write the result. Defaults to standard output.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Draft a JOBL file from a resume kept in another format
+    Import {
+        /// Format to read
+        #[arg(value_enum)]
+        format: ImportFormat,
+
+        /// The file to read
+        input: PathBuf,
+
+        /// Where to write the JOBL file. Defaults to standard output;
+        /// an existing file is never overwritten.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Inspect layouts
+    Layout {
+        #[command(subcommand)]
+        command: LayoutCommand,
+    },
+
+    /// Build the resume and email the PDF. SMTP settings come from
+    /// the [send] table in srg.toml and SRG_SMTP_* variables.
+    Send {
+        #[command(flatten)]
+        build: Box<BuildArgs>,
+
+        /// Recipient address. Repeat for several recipients.
+        #[arg(long, required = true, value_name = "ADDRESS")]
+        to: Vec<String>,
+
+        /// Subject template. Overrides `subject` in srg.toml.
+        #[arg(long, value_name = "TEMPLATE")]
+        subject: Option<String>,
+
+        /// File holding the body template. Overrides `body` in srg.toml.
+        #[arg(long, value_name = "FILE")]
+        body: Option<PathBuf>,
+
+        /// Write the message to resume.eml in the output directory
+        /// instead of sending it.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print the resume to the terminal, styled, as the layout
+    /// shows it. Nothing is written to the output directory
+    View {
+        #[command(flatten)]
+        build: Box<BuildArgs>,
+
+        /// Column to wrap at. Defaults to the terminal's width
+        /// (COLUMNS), or 80.
+        #[arg(long, value_name = "COLUMNS")]
+        width: Option<usize>,
+
+        /// Leave out the bold and underlining. Also off when NO_COLOR
+        /// is set or the output isn't a terminal.
+        #[arg(long)]
+        no_color: bool,
+    },
+
+    /// Build everything for one application into a dated folder
+    /// under the output directory: the PDF, the plain text, a cover
+    /// letter, and a report on how the resume matches the posting
+    Pack {
+        #[command(flatten)]
+        build: Box<BuildArgs>,
+
+        /// The job posting, as a text file
+        #[arg(long, value_name = "FILE")]
+        jd: PathBuf,
+
+        /// A [profiles.<NAME>] table in srg.toml to tailor the build
+        /// with: its theme, layout, sections, variables, and cover
+        /// letter template
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+    },
+
+    /// Build for CI, with every setting and the resume taken from
+    /// srg.toml: warnings and errors print as GitHub Actions
+    /// annotations, and a JSON manifest lists what was built. Never
+    /// prompts
+    Ci {
+        /// The directory with srg.toml and the resume
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+
+        /// Where to write the manifest. Defaults to
+        /// build-manifest.json in the output directory.
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum LayoutCommand {
+    /// Print the layout a build would use, after the theme's layout,
+    /// `extends`, partial overrides, and section flags, as a layout
+    /// file to save and edit
+    Show {
+        /// JOBL file whose srg.toml supplies the theme and layout
+        #[arg(short, long, value_name = "FILE")]
+        input: Option<PathBuf>,
+
+        /// Theme name. Overrides `theme` in srg.toml.
+        #[arg(short, long)]
+        theme: Option<String>,
+
+        /// Layout file. Overrides `layout` in srg.toml.
+        #[arg(short, long, value_name = "FILE")]
+        layout: Option<PathBuf>,
+
+        /// Leave a section out. Repeat or separate with commas.
+        #[arg(long = "exclude-section", value_delimiter = ',', value_name = "SECTION")]
+        exclude_sections: Vec<String>,
+
+        /// Add a section the layout leaves out, taking its fields
+        /// from the theme's layout. Repeat or separate with commas.
+        #[arg(long = "include-section", value_delimiter = ',', value_name = "SECTION")]
+        include_sections: Vec<String>,
+    },
+}
+
+/// What a build produced, for subcommands that act on it.
+struct Built {
+    doc: jobl::JoblDocument,
+    loaded: Option<config::LoadedConfig>,
+    theme: Option<String>,
+    out_dir: PathBuf,
+    written: Vec<(build::OutputFormat, PathBuf)>,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Parse and validate JOBL file.
-    let doc = jobl::parse_file(&args.input).map_err(|errors| {
-        eprintln!("Validation errors in {}:", args.input.display());
-        for err in &errors {
-            eprintln!("  - {}", err);
+    match &args.command {
+        Some(command) => run_command(command),
+        None => run_build(&args.build, &[]).map(|_| ()),
+    }
+}
+
+/// The resume's source as JOBL. A YAML file is deserialized and
+/// written back out as TOML, and a Markdown file is converted as
+/// `srg import markdown` would, so the rest of srg reads either just
+/// as it reads a `.jobl` file, srg's own sections included.
+fn read_source(input: &Path, format: InputFormat) -> Result<String> {
+    convert_source(input, format).map(|(source, _)| source)
+}
+
+/// [`read_source`], with notes on what a Markdown file's conversion
+/// left out.
+fn convert_source(input: &Path, format: InputFormat) -> Result<(String, Vec<String>)> {
+    let source = std::fs::read_to_string(input).with_context(|| format!("reading {}", input.display()))?;
+    match format {
+        InputFormat::Toml => Ok((source, Vec::new())),
+        InputFormat::Yaml => {
+            let table: toml::Table = serde_yaml::from_str(&source)
+                .with_context(|| format!("Failed to parse {} as YAML", input.display()))?;
+            let jobl = toml::to_string(&table).with_context(|| format!("Failed to read {} as JOBL", input.display()))?;
+            Ok((jobl, Vec::new()))
+        }
+        InputFormat::Markdown => {
+            let draft = import::markdown::convert(&source).with_context(|| format!("reading {}", input.display()))?;
+            Ok((draft.to_jobl()?, draft.notes))
+        }
+    }
+}
+
+/// Parse and validate a JOBL (or YAML, or Markdown) file, read as
+/// `format`, listing every validation error. Alias sections (see
+/// `sections`) are taken out first; JOBL then validates the rest from
+/// a scratch copy next to the file. A converted file always goes
+/// through the scratch copy, as TOML.
+fn parse_input(input: &Path, format: InputFormat) -> Result<jobl::JoblDocument> {
+    let converted = format != InputFormat::Toml;
+    let source = if converted {
+        let (source, notes) = convert_source(input, format)?;
+        for note in notes {
+            diagnostics::Diagnostic::warning(format!("{}: {}", input.display(), note)).at(input, None).emit();
+        }
+        Some(source)
+    } else {
+        std::fs::read_to_string(input).ok()
+    };
+    let stripped = source.as_deref().and_then(|source| sections::strip(source).ok().flatten());
+    validate(input, stripped.or(source.filter(|_| converted)), &input.display().to_string(), true)
+}
+
+/// Validate `input` as JOBL, or `source` in its place, from a scratch
+/// copy next to it. Errors are listed under `name`; under `srg ci`
+/// they're annotations on `input`'s lines when `located`, that is
+/// when they can only be about `input`.
+fn validate(input: &Path, source: Option<String>, name: &str, located: bool) -> Result<jobl::JoblDocument> {
+    let rewritten = source.is_some();
+    let parsed = match source {
+        None => jobl::parse_file(input),
+        Some(source) => {
+            let dir = input.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let mut scratch = tempfile::Builder::new()
+                .prefix(".srg-")
+                .suffix(".jobl")
+                .tempfile_in(dir)
+                .context("Failed to create a scratch copy of the JOBL file")?;
+            std::io::Write::write_all(&mut scratch, source.as_bytes())
+                .context("Failed to write a scratch copy of the JOBL file")?;
+            jobl::parse_file(scratch.path())
+        }
+    };
+    parsed.map_err(|errors| {
+        if diagnostics::annotating() {
+            let text = std::fs::read_to_string(input).ok().filter(|_| located);
+            for err in &errors {
+                let mut error = diagnostics::Diagnostic::error(err.to_string());
+                if let Some(text) = &text {
+                    // A parse error's line is in the source JOBL read,
+                    // which is only the file when nothing rewrote it.
+                    let line = match err.path.as_str() {
+                        "document" if rewritten => None,
+                        "document" => diagnostics::line_in_error(&err.message),
+                        path => diagnostics::line_of(text, path, None),
+                    };
+                    error = error.at(input, line);
+                }
+                error.titled(format!("Validation error in {}", name)).emit();
+            }
+        } else {
+            eprintln!("Validation errors in {}:", name);
+            for err in &errors {
+                eprintln!("  - {}", err);
+            }
         }
         anyhow::anyhow!("Failed to parse JOBL file")
-    })?;
+    })
+}
+
+/// The layout a build uses: the layout file, or the theme's layout
+/// when there's none. A partial layout file is merged onto the
+/// theme's layout instead of replacing it. `include` and `exclude`
+/// are `--include-section` and `--exclude-section`.
+fn load_layout(
+    theme: Option<&str>,
+    layout_path: Option<&Path>,
+    include: &[String],
+    exclude: &[String],
+) -> Result<layout::Layout> {
+    let theme_layout = || -> Result<layout::Layout> {
+        match theme {
+            Some(theme_name) => layout::Layout::from_theme(theme_name)
+                .context("Failed to load theme layout"),
+            None => Ok(layout::Layout::default()),
+        }
+    };
+    let mut layout = match layout_path {
+        Some(path) => {
+            let custom =
+                layout::Layout::from_file(path).context("Failed to load layout file")?;
+            if custom.partial {
+                custom.merge_onto(&theme_layout()?)
+            } else {
+                custom
+            }
+        }
+        None => theme_layout()?,
+    };
+
+    if !include.is_empty() || !exclude.is_empty() {
+        layout.toggle_sections(include, exclude, &theme_layout()?)?;
+    }
+    Ok(layout)
+}
+
+/// Everything a build decides before it writes anything: the parsed
+/// resume, and the theme, layout, and options the flags and srg.toml
+/// ask for.
+struct Plan {
+    input: PathBuf,
+    doc: jobl::JoblDocument,
+    loaded: Option<config::LoadedConfig>,
+    theme: Option<String>,
+    layout: layout::Layout,
+    css_path: Option<PathBuf>,
+    out_dir: PathBuf,
+    /// The output directory and build name of a `--versioned` build.
+    out_base: Option<(PathBuf, String)>,
+    options: build::BuildOptions,
+}
+
+/// Work out the build the flags and srg.toml ask for.
+fn plan_build(args: &BuildArgs) -> Result<Plan> {
+    // clap enforces --input whenever there's no subcommand.
+    let (input, layers) = args.input.split_first().context("--input is required")?;
+    let input = input.clone();
+
+    let format_of = |path: &Path| args.input_format.unwrap_or_else(|| InputFormat::of(path));
+    let (doc, source) = if layers.is_empty() {
+        (parse_input(&input, format_of(&input))?, read_source(&input, format_of(&input))?)
+    } else {
+        let sources = args
+            .input
+            .iter()
+            .map(|path| Ok((path.display().to_string(), read_source(path, format_of(path))?)))
+            .collect::<Result<Vec<_>>>()?;
+        let merged = merge::merge_sources(sources.iter().map(|(name, source)| (name.as_str(), source.as_str())))?;
+        let stripped = sections::strip(&merged)?.unwrap_or_else(|| merged.clone());
+        let names: Vec<&str> = sources.iter().map(|(name, _)| name.as_str()).collect();
+        (validate(&input, Some(stripped), &names.join(" + "), false)?, merged)
+    };
 
     // Load srg.toml from the JOBL file's directory if present. Missing
     // is OK; malformed is fatal.
-    let loaded = config::Config::load_for(&args.input)?;
+    let loaded = config::Config::load_for(&input)?;
 
     // Resolve each setting with the precedence:
     //   CLI flag  >  srg.toml  >  built-in default
@@ -61,10 +808,18 @@ fn main() -> Result<()> {
         }
     };
 
+    // --theme wins over everything in srg.toml, per-output themes
+    // included; --theme-html and --theme-pdf win over --theme.
+    let config = |key: fn(&config::Config) -> &Option<String>| {
+        loaded.as_ref().and_then(|l| key(&l.config).clone()).filter(|_| args.theme.is_none())
+    };
     let theme = args
-        .theme
+        .theme_html
         .clone()
-        .or_else(|| loaded.as_ref().and_then(|l| l.config.theme.clone()));
+        .or_else(|| config(|c| &c.theme_html))
+        .or_else(|| args.theme.clone())
+        .or_else(|| config(|c| &c.theme));
+    let pdf_theme = args.theme_pdf.clone().or_else(|| config(|c| &c.theme_pdf));
 
     let layout_path = args
         .layout
@@ -87,27 +842,710 @@ fn main() -> Result<()> {
         })
         .unwrap_or_else(|| PathBuf::from("dist"));
 
+    let versioned = args.versioned || loaded.as_ref().and_then(|l| l.config.versioned).unwrap_or(false);
+    let (out_base, out_dir) = if versioned {
+        let name = versioned::dir_name(&args.input)?;
+        (Some((out_dir.clone(), name.clone())), out_dir.join(name))
+    } else {
+        (None, out_dir)
+    };
+
     // Default to the "minimal" theme only when nothing else was
     // chosen. A custom CSS by itself implies "no theme, just this CSS,"
     // which matches the original behavior.
     let theme = theme.or_else(|| if css_path.is_none() { Some("minimal".into()) } else { None });
+    let pdf_theme = pdf_theme.filter(|pdf_theme| Some(pdf_theme) != theme.as_ref());
 
-    // Load layout — either from a custom file or from the theme.
-    let layout = match layout_path.as_deref() {
-        Some(path) => layout::Layout::from_file(path).context("Failed to load layout file")?,
-        None => match theme.as_deref() {
-            Some(theme_name) => layout::Layout::from_theme(theme_name)
-                .context("Failed to load theme layout")?,
-            None => layout::Layout::default(),
+    let mut layout = load_layout(
+        theme.as_deref(),
+        layout_path.as_deref(),
+        &args.include_sections,
+        &args.exclude_sections,
+    )?;
+
+    // Layout variables: srg.toml's [variables], then --set on top.
+    let mut variables = loaded
+        .as_ref()
+        .and_then(|l| l.config.variables.clone())
+        .unwrap_or_default();
+    variables.extend(args.set.iter().cloned());
+    layout.resolve_variables(&variables)?;
+
+    let mut formats = if !args.format.is_empty() {
+        args.format.clone()
+    } else {
+        loaded
+            .as_ref()
+            .and_then(|l| l.config.formats.clone())
+            .unwrap_or_else(|| build::BuildOptions::default().formats)
+    };
+    formats.sort();
+    formats.dedup();
+
+    // PDF settings: srg.toml's [pdf], then the layout's paper, then
+    // the PDF's theme's [pdf]. CLI flags win over all of them below.
+    let (theme_pdf, theme_bullets) = match theme.as_deref() {
+        Some(theme) => {
+            let stack = themes::ThemeStack::resolve(theme)?;
+            (stack.pdf()?, stack.bullets()?)
+        }
+        None => Default::default(),
+    };
+    let theme_pdf = match pdf_theme.as_deref() {
+        Some(pdf_theme) => themes::ThemeStack::resolve(pdf_theme)?.pdf()?,
+        None => theme_pdf,
+    };
+    let config_pdf = loaded.as_ref().and_then(|l| l.config.pdf.clone()).unwrap_or_default();
+    config_pdf.check("srg.toml")?;
+    let layout_pdf = themes::PrintSettings { paper: layout.settings.paper, ..Default::default() };
+    let pdf = config_pdf.or(layout_pdf).or(theme_pdf);
+
+    let mut options = build::BuildOptions {
+        css_mode: args
+            .css_mode
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.css_mode))
+            .unwrap_or_default(),
+        formats,
+        extras: build::Extras::default(),
+        density: args
+            .density
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.density))
+            .or(layout.settings.density)
+            .unwrap_or_default(),
+        bullets: args
+            .bullets
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.bullets))
+            .or(theme_bullets),
+        pdf_theme,
+        pdf_backend: args
+            .pdf_backend
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.pdf_backend))
+            .unwrap_or_default(),
+        pdf_profile: args
+            .pdf_profile
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.pdf_profile))
+            .unwrap_or_default(),
+        tagged_pdf: args.tagged_pdf || loaded.as_ref().and_then(|l| l.config.tagged_pdf).unwrap_or(false),
+        pdf_outline: args.pdf_outline || loaded.as_ref().and_then(|l| l.config.pdf_outline).unwrap_or(false),
+        paper: args.paper.or(pdf.paper).unwrap_or_default(),
+        margins: match args.margin {
+            Some(margins) => margins,
+            None => match pdf.margin.as_deref() {
+                Some(margin) => build::Margins::parse(margin).map_err(anyhow::Error::msg)?,
+                None => build::Margins::default(),
+            },
+        },
+        scale: args.scale.or(pdf.scale).unwrap_or(1.0),
+        print_background: args.background.or(pdf.background).unwrap_or(true),
+        links: args.links.or(pdf.links),
+        continued: args.continued.or(pdf.continued).unwrap_or(false),
+        max_pages: args.max_pages.map(|pages| pages as usize).or(pdf.max_pages),
+        date_format: layout.settings.date_format.clone(),
+        locale: layout.settings.locale,
+        font_size: pick_length(
+            &args.font_size,
+            loaded.as_ref().and_then(|l| l.config.font_size.as_deref()),
+            "font_size",
+            false,
+        )?,
+        line_height: pick_length(
+            &args.line_height,
+            loaded.as_ref().and_then(|l| l.config.line_height.as_deref()),
+            "line_height",
+            true,
+        )?,
+        emoji: match args.emoji {
+            Some(policy) => <build::OutputFormat as clap::ValueEnum>::value_variants()
+                .iter()
+                .map(|&format| (format, policy))
+                .collect(),
+            None => loaded.as_ref().and_then(|l| l.config.emoji.clone()).unwrap_or_default(),
+        },
+        accents: loaded.as_ref().and_then(|l| l.config.accents.clone()).unwrap_or_default(),
+        ascii: args.ascii || loaded.as_ref().and_then(|l| l.config.ascii).unwrap_or(false),
+        txt_width: args
+            .txt_width
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.txt_width))
+            .unwrap_or(build::TXT_WIDTH),
+        grayscale: args.grayscale || loaded.as_ref().and_then(|l| l.config.grayscale).unwrap_or(false),
+        strict: args.strict || loaded.as_ref().and_then(|l| l.config.strict).unwrap_or(false),
+        fragment: args.fragment || loaded.as_ref().and_then(|l| l.config.fragment).unwrap_or(false),
+        fingerprint: args.fingerprint || loaded.as_ref().and_then(|l| l.config.fingerprint).unwrap_or(false),
+        watermark: args
+            .watermark
+            .clone()
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.watermark.clone()))
+            .filter(|text| !text.trim().is_empty()),
+        footer: args
+            .footer
+            .clone()
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.footer.clone()))
+            .filter(|text| !text.trim().is_empty()),
+        verify: args
+            .verify_url
+            .clone()
+            .or_else(|| loaded.as_ref().and_then(|l| l.config.verify_url.clone()))
+            .map(|url| build::Verification::new(&url, &doc))
+            .transpose()?,
+        budgets: {
+            let mut budgets = loaded.as_ref().and_then(|l| l.config.budget.clone()).unwrap_or_default();
+            budgets.truncate |= args.truncate;
+            budgets
+        },
+        typography: match loaded.as_ref().and_then(|l| l.config.typography.clone()) {
+            Some(overrides) => check_typography(overrides, theme.as_deref())?,
+            None => Default::default(),
         },
     };
 
-    build::build_resume(&doc, &out_dir, theme.as_deref(), &layout, css_path.as_deref())
-        .context("Failed to build resume")?;
+    // Warnings about the resume's content point at the file, and the
+    // line when the file is the JOBL source as read.
+    let about = |warning: diagnostics::Diagnostic, location: &str| match layers.is_empty() {
+        true => {
+            let text = Some(source.as_str()).filter(|_| format_of(&input) == InputFormat::Toml);
+            warning.at(&input, text.and_then(|text| diagnostics::line_of_location(text, &doc, location)))
+        }
+        false => warning,
+    };
+    for overrun in budget::overruns(&doc, &options.budgets) {
+        let action = if options.budgets.truncate { "truncated" } else { "pass --truncate to cut it" };
+        let warning = diagnostics::Diagnostic::warning(format!("{}; {}", overrun.message(), action));
+        about(warning.titled("Over budget"), &overrun.location).emit();
+    }
+    for finding in lint::unused(&doc, &layout, &args.exclude_sections) {
+        let warning = diagnostics::Diagnostic::warning(format!("{}: {}", finding.location, finding.message));
+        about(warning.titled(format!("srg lint: {}", finding.rule)), &finding.location).emit();
+    }
+    if args.sign_pdf {
+        pdfsign::check_options(&options)?;
+    }
+    let chrome_pdfa = options.pdf_backend == build::PdfBackend::Chrome && options.pdf_profile == build::PdfProfile::Pdfa;
+    if chrome_pdfa && (options.tagged_pdf || options.pdf_outline) {
+        diagnostics::warn("Ghostscript drops a PDF's tags when it converts to PDF/A, so the PDF won't be tagged");
+    }
+    if options.pdf_backend == build::PdfBackend::Typst && options.max_pages.is_some() {
+        diagnostics::warn("only the Chrome PDF is measured, so the Typst PDF's length isn't checked against max_pages");
+    }
+
+    let keep_skill_order =
+        args.keep_skill_order || loaded.as_ref().and_then(|l| l.config.keep_skill_order).unwrap_or(false);
+    if keep_skill_order {
+        options.extras.skill_order = build::skill_categories_in(&source)?;
+    }
+    options.extras.sections = sections::aliases(&source)?;
+    options.extras.talks = sections::talks(&source)?;
+    options.extras.patents = sections::patents(&source)?;
+    options.extras.person = sections::person_fields(&source)?;
+
+    let github = args.github || loaded.as_ref().and_then(|l| l.config.github).unwrap_or(false);
+    if github {
+        options.extras.projects = github::project_fields(&doc, &github::cache_path(&input));
+    }
+
+    let gravatar = args.gravatar || loaded.as_ref().and_then(|l| l.config.gravatar).unwrap_or(false);
+    if gravatar {
+        match &doc.person.email {
+            Some(email) => {
+                options.extras.avatar = gravatar::avatar(email, &gravatar::cache_path(&input, email));
+            }
+            None => diagnostics::warn("--gravatar needs person.email; skipping avatar"),
+        }
+    }
+
+    if let Some(booking) = loaded.as_ref().and_then(|l| l.config.booking.clone()) {
+        booking.check().context("Invalid [booking] in srg.toml")?;
+        options.extras.booking = Some(booking);
+    }
+
+    Ok(Plan { input, doc, loaded, theme, layout, css_path, out_dir, out_base, options })
+}
+
+/// Build the resume as the flags and srg.toml ask, plus any
+/// `required` formats the caller needs on disk.
+fn run_build(args: &BuildArgs, required: &[build::OutputFormat]) -> Result<Built> {
+    let Plan { input, doc, loaded, theme, layout, css_path, out_dir, out_base, mut options } = plan_build(args)?;
+    options.formats.extend_from_slice(required);
+    options.formats.sort();
+    options.formats.dedup();
+
+    if args.pdf_only {
+        let mut pdf = build::render_pdf(&doc, theme.as_deref(), &layout, css_path.as_deref(), &options)
+            .context("Failed to build PDF")?;
+        if let Some(cert) = &args.cert {
+            pdf = pdfsign::sign(&pdf, cert).context("Failed to sign the PDF")?;
+        }
+        let mut written = Vec::new();
+        if args.stdout {
+            use std::io::Write;
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&pdf).and_then(|_| stdout.flush()).context("Failed to write PDF to stdout")?;
+        } else {
+            std::fs::create_dir_all(&out_dir).context("Failed to create output directory")?;
+            let path = out_dir.join(build::OutputFormat::Pdf.file_name());
+            build::write_atomic(&path, pdf).context("Failed to write PDF file")?;
+            println!("Resume built successfully:");
+            println!("  {}: {}", build::OutputFormat::Pdf.label(), path.display());
+            written.push((build::OutputFormat::Pdf, path));
+        }
+        return Ok(Built { doc, loaded, theme, out_dir, written });
+    }
+
+    let written = build::build_resume(
+        &doc,
+        &out_dir,
+        theme.as_deref(),
+        &layout,
+        css_path.as_deref(),
+        &options,
+    )
+    .context("Failed to build resume")?;
+
+    if let Some(cert) = &args.cert {
+        let (_, path) = written
+            .iter()
+            .find(|(format, _)| *format == build::OutputFormat::Pdf)
+            .context("--sign-pdf needs the pdf output; add it with --format")?;
+        let pdf = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        let signed = pdfsign::sign(&pdf, cert).context("Failed to sign the PDF")?;
+        build::write_atomic(path, signed).context("Failed to write the signed PDF")?;
+    }
 
     println!("Resume built successfully:");
-    println!("  HTML: {}/index.html", out_dir.display());
-    println!("  PDF:  {}/resume.pdf", out_dir.display());
+    for (format, path) in &written {
+        println!("  {}: {}", format.label(), path.display());
+    }
+    let html_written = written.iter().any(|(format, _)| *format == build::OutputFormat::Html);
+    if html_written && options.extras.booking.as_ref().is_some_and(|b| b.hours.is_some()) {
+        println!("  Calendar: {}", out_dir.join(build::booking::ICS_FILE).display());
+    }
+    let history = args.history || loaded.as_ref().and_then(|l| l.config.history).unwrap_or(false);
+    if history {
+        let revisions = revisions::revisions(&input).context("Failed to read the resume's history")?;
+        let page = build::history::write_history_page(
+            &out_dir,
+            &doc,
+            &revisions,
+            theme.as_deref(),
+            &layout,
+            css_path.as_deref(),
+            &options,
+        )?;
+        println!("  History: {}", page.display());
+    }
+
+    if let Some((base, name)) = &out_base {
+        let latest = versioned::update_latest(base, name).context("Failed to update latest build")?;
+        println!("  Latest: {} -> {}", latest.display(), name);
+    }
+
+    let text_written = written.iter().any(|(format, _)| *format == build::OutputFormat::Txt);
+    let text_copied = args.copy == Some(CopyFormat::Txt);
+    if options.ascii && (text_written || text_copied) {
+        let changes = build::ascii_changes(&doc, &layout, &options);
+        if !changes.is_empty() {
+            println!("  Transliterated to ASCII:");
+            for (from, to) in changes {
+                println!("    {} -> {}", from, to);
+            }
+        }
+    }
+
+    let archive = args.archive || loaded.as_ref().and_then(|l| l.config.archive).unwrap_or(false);
+    if archive {
+        let bundle = archive::bundle(&out_dir, &args.input).context("Failed to write archive")?;
+        println!("  Bundle: {}", bundle.display());
+    }
+
+    if let Some(copy) = args.copy {
+        let format = copy.output_format();
+        let text = build::render_export(format, &doc, &layout, &options)
+            .context("Copy format has no text rendering")?;
+        clipboard::copy(&text).context("Failed to copy resume to clipboard")?;
+        println!("  {} copied to clipboard", format.label());
+    }
+
+    if let Some(url) = loaded.as_ref().and_then(|l| l.config.webhook.as_deref()) {
+        manifest::Manifest::new(&doc.person.name, &input, theme.as_deref(), &out_dir, &written)
+            .post(url)
+            .context("Failed to notify webhook")?;
+        println!("  Webhook notified: {}", url);
+    }
+
+    Ok(Built {
+        doc,
+        loaded,
+        theme,
+        out_dir,
+        written,
+    })
+}
+
+/// Check srg.toml's `[typography]` overrides against the knobs the
+/// theme declares, so a typo doesn't silently do nothing.
+fn check_typography(
+    overrides: std::collections::BTreeMap<String, String>,
+    theme: Option<&str>,
+) -> Result<std::collections::BTreeMap<String, String>> {
+    let known = match theme {
+        Some(theme) => themes::ThemeStack::resolve(theme)?.typography()?,
+        None => Default::default(),
+    };
+    for (key, value) in &overrides {
+        if !known.contains_key(key) {
+            let available: Vec<&str> = known.keys().map(String::as_str).collect();
+            anyhow::bail!(
+                "typography.{} in srg.toml: theme {} has no such setting (available: {})",
+                key,
+                theme.unwrap_or("(none)"),
+                if available.is_empty() { "none".to_string() } else { available.join(", ") }
+            );
+        }
+        css::check_value(value).map_err(|e| anyhow::anyhow!("typography.{} in srg.toml: {}", key, e))?;
+    }
+    Ok(overrides)
+}
+
+/// `name=value` for `--set`.
+fn parse_variable(arg: &str) -> Result<(String, String), String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got {:?}", arg))?;
+    let name = name.trim().trim_start_matches('$');
+    if !layout::is_variable_name(name) {
+        return Err(format!("{:?} is not a valid variable name", name));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// A CSS length from the CLI (already checked by clap) or srg.toml
+/// (checked here, naming the offending key).
+fn pick_length(cli: &Option<String>, config: Option<&str>, key: &str, unitless: bool) -> Result<Option<String>> {
+    match (cli, config) {
+        (Some(value), _) => Ok(Some(value.clone())),
+        (None, Some(value)) => css::parse_length(value, unitless)
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("{} in srg.toml: {}", key, e)),
+        (None, None) => Ok(None),
+    }
+}
+
+fn run_command(command: &Commands) -> Result<()> {
+    match command {
+        Commands::Compare { variants, keywords, output } => {
+            let docs = variants
+                .iter()
+                .map(|path| parse_input(path, InputFormat::of(path)))
+                .collect::<Result<Vec<_>>>()?;
+            let keywords: Vec<String> = if keywords.is_empty() {
+                compare::default_keywords(&docs.iter().collect::<Vec<_>>())
+            } else {
+                keywords.iter().map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect()
+            };
+            let rows: Vec<compare::Variant> = variants
+                .iter()
+                .zip(&docs)
+                .map(|(path, doc)| compare::Variant::new(&compare::label(path), doc, &keywords))
+                .collect();
+
+            std::fs::write(output, compare::render_html(&rows, &keywords))
+                .with_context(|| format!("writing {}", output.display()))?;
+            for row in &rows {
+                println!(
+                    "  {}: {} words, {} highlights, {}% keyword coverage",
+                    row.name,
+                    row.words,
+                    row.highlights,
+                    row.coverage()
+                );
+            }
+            println!("Report: {}", output.display());
+            Ok(())
+        }
+        Commands::Lint { input } => {
+            let doc = parse_input(input, InputFormat::of(input))?;
+            let loaded = config::Config::load_for(input)?;
+            let lint_config = loaded.as_ref().and_then(|l| l.config.lint.clone()).unwrap_or_default();
+            lint_config.check().map_err(|e| anyhow::anyhow!("srg.toml: {}", e))?;
+            let theme = loaded.as_ref().and_then(|l| l.config.theme.clone()).unwrap_or_else(|| "minimal".to_string());
+            let layout_path = loaded.as_ref().and_then(|l| l.config.layout.as_ref().map(|p| l.resolve(p)));
+            let layout = load_layout(Some(&theme), layout_path.as_deref(), &[], &[])?;
+            let mut findings = lint::lint(&doc, &lint_config);
+            findings.extend(lint::unused(&doc, &layout, &[]));
+            for finding in &findings {
+                println!("{}: {} [{}]", finding.location, finding.message, finding.rule);
+            }
+            if !findings.is_empty() {
+                anyhow::bail!("{} lint finding(s) in {}", findings.len(), input.display());
+            }
+            println!("No lint findings in {}.", input.display());
+            Ok(())
+        }
+        Commands::Stats { input, top } => {
+            let doc = parse_input(input, InputFormat::of(input))?;
+            print!("{}", stats::Stats::new(&doc).report(*top));
+            Ok(())
+        }
+        Commands::Export { input, format, output } => {
+            let doc = parse_input(input, InputFormat::of(input))?;
+            let converted = match format {
+                ExportFormat::JsonResume => jsonresume::convert(&doc),
+            };
+            let json = serde_json::to_string_pretty(&converted).context("Failed to write JSON")? + "\n";
+            match output {
+                Some(path) => {
+                    build::write_atomic(path, json).with_context(|| format!("writing {}", path.display()))?;
+                    println!("Exported {}", path.display());
+                }
+                None => print!("{}", json),
+            }
+            Ok(())
+        }
+        Commands::Import { format, input, output } => {
+            let source = std::fs::read(input).with_context(|| format!("reading {}", input.display()))?;
+            let text = || std::str::from_utf8(&source).with_context(|| format!("{} isn't UTF-8 text", input.display()));
+            let draft = match format {
+                ImportFormat::Markdown => import::markdown::convert(text()?),
+                ImportFormat::Text => import::text::convert(text()?),
+                ImportFormat::Linkedin => import::linkedin::convert(&source),
+            }
+            .with_context(|| format!("reading {}", input.display()))?;
+            let jobl = draft.to_jobl()?;
+            match output {
+                Some(path) if path.exists() => {
+                    anyhow::bail!("{} already exists; pick another --output", path.display())
+                }
+                Some(path) => {
+                    build::write_atomic(path, jobl).with_context(|| format!("writing {}", path.display()))?;
+                    println!("Imported {} into {}", input.display(), path.display());
+                }
+                None => print!("{}", jobl),
+            }
+            for note in &draft.notes {
+                diagnostics::warn(format!("{}: {}", input.display(), note));
+            }
+            Ok(())
+        }
+        Commands::DiffPdf { old, new, output, dpi } => {
+            let pages = pdfdiff::run(old, new, output, *dpi).context("Failed to diff PDFs")?;
+
+            let mut changed = 0;
+            for page in &pages {
+                let note = match &page.status {
+                    pdfdiff::PageStatus::Unchanged => continue,
+                    pdfdiff::PageStatus::Changed { changed_pixels, total_pixels } => format!(
+                        "changed ({:.2}% of pixels)",
+                        *changed_pixels as f64 * 100.0 / *total_pixels as f64
+                    ),
+                    pdfdiff::PageStatus::Added => format!("only in {}", new.display()),
+                    pdfdiff::PageStatus::Removed => format!("only in {}", old.display()),
+                };
+                println!("  Page {}: {}", page.page, note);
+                changed += 1;
+            }
+
+            if changed == 0 {
+                println!("No visual changes across {} page(s).", pages.len());
+            } else {
+                println!("{} of {} page(s) differ.", changed, pages.len());
+            }
+            println!("Diff image: {}", output.display());
+            Ok(())
+        }
+        Commands::Doctor { input, theme, out, clean } => {
+            if *clean {
+                match build::chrome::clean()? {
+                    Some(dir) => println!("Removed the cached Chrome profiles in {}", dir.display()),
+                    None => println!("No cached Chrome profiles to remove"),
+                }
+            }
+            let loaded = match input {
+                Some(input) => config::Config::load_for(input)?,
+                None => None,
+            };
+            let theme = theme
+                .clone()
+                .or_else(|| loaded.as_ref().and_then(|l| l.config.theme.clone()))
+                .unwrap_or_else(|| "minimal".to_string());
+            let out = out
+                .clone()
+                .or_else(|| loaded.as_ref().and_then(|l| l.config.out.as_ref().map(|p| l.resolve(p))))
+                .unwrap_or_else(|| PathBuf::from("dist"));
+
+            let checks = doctor::run(&theme, &out);
+            for check in &checks {
+                print!("{}", check.report());
+            }
+            let failed = checks.iter().filter(|c| c.status == doctor::Status::Fail).count();
+            if failed > 0 {
+                anyhow::bail!("{} check(s) failed", failed);
+            }
+            println!("Ready to build.");
+            Ok(())
+        }
+        Commands::Layout {
+            command: LayoutCommand::Show { input, theme, layout, exclude_sections, include_sections },
+        } => {
+            let loaded = match input {
+                Some(input) => config::Config::load_for(input)?,
+                None => None,
+            };
+            let theme = theme.clone().or_else(|| loaded.as_ref().and_then(|l| l.config.theme.clone()));
+            let layout_path = layout
+                .clone()
+                .or_else(|| loaded.as_ref().and_then(|l| l.config.layout.as_ref().map(|p| l.resolve(p))));
+            let layout = load_layout(theme.as_deref(), layout_path.as_deref(), include_sections, exclude_sections)?;
+            print!("{}", layout);
+            Ok(())
+        }
+        Commands::Send {
+            build: build_args,
+            to,
+            subject,
+            body,
+            dry_run,
+        } => {
+            let built = run_build(build_args, &[build::OutputFormat::Pdf])?;
+            let pdf_path = built
+                .written
+                .iter()
+                .find(|(format, _)| *format == build::OutputFormat::Pdf)
+                .map(|(_, path)| path)
+                .context("Build did not produce a PDF")?;
+            let pdf = std::fs::read(pdf_path)
+                .with_context(|| format!("reading {}", pdf_path.display()))?;
+
+            let mut settings = send::Settings::resolve(
+                built.loaded.as_ref().and_then(|l| l.config.send.as_ref()),
+                &built.doc,
+            )?;
+            if let Some(subject) = subject {
+                settings.subject = subject.clone();
+            }
+            if let Some(path) = body {
+                settings.body = std::fs::read_to_string(path)
+                    .with_context(|| format!("reading {}", path.display()))?;
+            }
+
+            let message = send::compose(&settings, to, &built.doc, &pdf);
+            if *dry_run {
+                let eml = built.out_dir.join("resume.eml");
+                std::fs::write(&eml, &message)
+                    .with_context(|| format!("writing {}", eml.display()))?;
+                println!("Dry run, message written to {}", eml.display());
+            } else {
+                send::deliver(&settings, to, &message).context("Failed to send email")?;
+                println!("Sent {} to {}", pdf_path.display(), to.join(", "));
+            }
+            Ok(())
+        }
+        Commands::View { build: build_args, width, no_color } => {
+            use std::io::IsTerminal;
+            let plan = plan_build(build_args)?;
+            let width = width
+                .or_else(|| std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()))
+                .unwrap_or(build::TXT_WIDTH);
+            let color = !no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+            print!("{}", build::render_terminal(&plan.doc, &plan.layout, &plan.options, width, color));
+            Ok(())
+        }
+        Commands::Pack { build: build_args, jd, profile } => run_pack(build_args, jd, profile.as_deref()),
+        Commands::Ci { dir, manifest } => {
+            diagnostics::use_annotations();
+            run_ci(dir, manifest.as_deref())
+                .inspect_err(|err| diagnostics::Diagnostic::error(format!("{:#}", err)).titled("srg ci").emit())
+        }
+    }
+}
+
+/// `srg ci`: a build as `srg -i <resume>` with nothing but srg.toml,
+/// the lint rules over the result as warnings, and the manifest.
+fn run_ci(dir: &Path, manifest_path: Option<&Path>) -> Result<()> {
+    let input = ci::find_input(dir)?;
+    let args = Args::try_parse_from([std::ffi::OsStr::new("srg"), "--input".as_ref(), input.as_os_str()])?.build;
+    let built = run_build(&args, &[])?;
+
+    let lint_config = built.loaded.as_ref().and_then(|l| l.config.lint.clone()).unwrap_or_default();
+    lint_config.check().map_err(|e| anyhow::anyhow!("srg.toml: {}", e))?;
+    let source = std::fs::read_to_string(&input).ok().filter(|_| InputFormat::of(&input) == InputFormat::Toml);
+    for finding in lint::lint(&built.doc, &lint_config) {
+        let line = source.as_deref().and_then(|source| diagnostics::line_of_location(source, &built.doc, &finding.location));
+        diagnostics::Diagnostic::warning(format!("{}: {}", finding.location, finding.message))
+            .at(&input, line)
+            .titled(format!("srg lint: {}", finding.rule))
+            .emit();
+    }
+
+    let manifest = manifest::Manifest::new(&built.doc.person.name, &input, built.theme.as_deref(), &built.out_dir, &built.written);
+    let path = manifest_path.map_or_else(|| built.out_dir.join(ci::MANIFEST_FILE), Path::to_path_buf);
+    build::write_atomic(&path, manifest.to_json()? + "\n").with_context(|| format!("writing {}", path.display()))?;
+    println!("  Manifest: {}", path.display());
+    Ok(())
+}
+
+/// `srg pack`: the profile's settings under the CLI's, a build into
+/// `<out>/<date>_<posting>-<profile>/`, then the cover letter and the
+/// match report next to the outputs.
+fn run_pack(args: &BuildArgs, jd: &Path, profile_name: Option<&str>) -> Result<()> {
+    let input = args.input.first().cloned().context("--input is required")?;
+    let posting = std::fs::read_to_string(jd).with_context(|| format!("reading {}", jd.display()))?;
+    let loaded = config::Config::load_for(&input)?;
+    let profile = match profile_name {
+        Some(name) => {
+            let profiles = loaded.as_ref().and_then(|l| l.config.profiles.as_ref());
+            let profile = profiles.and_then(|p| p.get(name)).with_context(|| {
+                let known: Vec<&str> = profiles.iter().flat_map(|p| p.keys()).map(String::as_str).collect();
+                if known.is_empty() {
+                    format!("No profile {:?}: srg.toml has no [profiles] tables", name)
+                } else {
+                    format!("No profile {:?} in srg.toml; it has {}", name, known.join(", "))
+                }
+            })?;
+            profile.clone()
+        }
+        None => config::Profile::default(),
+    };
+    let resolve = |p: &PathBuf| loaded.as_ref().map_or_else(|| p.clone(), |l| l.resolve(p));
+
+    let mut args = args.clone();
+    args.theme = args.theme.or(profile.theme.clone());
+    args.layout = args.layout.or_else(|| profile.layout.as_ref().map(resolve));
+    args.exclude_sections.extend(profile.exclude_sections.iter().cloned());
+    args.include_sections.extend(profile.include_sections.iter().cloned());
+    let mut set: Vec<(String, String)> = profile.variables.clone().unwrap_or_default().into_iter().collect();
+    set.append(&mut args.set);
+    args.set = set;
+
+    let out = args
+        .out
+        .clone()
+        .or_else(|| loaded.as_ref().and_then(|l| l.config.out.as_ref()).map(resolve))
+        .unwrap_or_else(|| PathBuf::from("dist"));
+    args.out = Some(out.join(pack::folder_name(&dates::today(), jd, profile_name)));
+
+    let built = run_build(&args, &[build::OutputFormat::Pdf, build::OutputFormat::Txt])?;
+
+    let matched = pack::Match::new(&built.doc, &posting);
+    let report = built.out_dir.join("match.txt");
+    std::fs::write(&report, matched.report(&jd.display().to_string(), 15))
+        .with_context(|| format!("writing {}", report.display()))?;
+
+    let template = match &profile.cover_letter {
+        Some(path) => {
+            let path = resolve(path);
+            Some(std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?)
+        }
+        None => None,
+    };
+    let mut variables = loaded.as_ref().and_then(|l| l.config.variables.clone()).unwrap_or_default();
+    variables.extend(args.set.iter().cloned());
+    let letter = built.out_dir.join("cover-letter.txt");
+    std::fs::write(&letter, pack::cover_letter(template.as_deref(), &built.doc, &matched.matched, &variables))
+        .with_context(|| format!("writing {}", letter.display()))?;
 
+    println!(
+        "Packed into {}: {}% of the resume's skills appear in the posting (see match.txt)",
+        built.out_dir.display(),
+        matched.coverage()
+    );
     Ok(())
 }
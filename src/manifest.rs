@@ -0,0 +1,116 @@
+//! Build manifest: a JSON summary of what a build produced.
+//!
+//! After a successful build srg can POST the manifest to a webhook
+//! (`webhook = "https://..."` in srg.toml), so a personal site or
+//! automation can pick up the new version without polling.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::build::OutputFormat;
+
+/// How long a webhook gets to answer before the build reports an error.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    /// The person's name from the resume.
+    pub name: String,
+    /// The JOBL file the build read.
+    pub source: PathBuf,
+    /// Theme spec used, if any.
+    pub theme: Option<String>,
+    pub out_dir: PathBuf,
+    /// Seconds since the Unix epoch.
+    pub built_at: u64,
+    pub srg_version: &'static str,
+    pub outputs: Vec<ManifestOutput>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestOutput {
+    pub format: OutputFormat,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+impl Manifest {
+    pub fn new(
+        name: &str,
+        source: &Path,
+        theme: Option<&str>,
+        out_dir: &Path,
+        written: &[(OutputFormat, PathBuf)],
+    ) -> Self {
+        let outputs = written
+            .iter()
+            .map(|(format, path)| ManifestOutput {
+                format: *format,
+                path: path.clone(),
+                bytes: std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            source: source.to_path_buf(),
+            theme: theme.map(str::to_string),
+            out_dir: out_dir.to_path_buf(),
+            built_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            srg_version: env!("CARGO_PKG_VERSION"),
+            outputs,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("serializing build manifest")
+    }
+
+    /// POST the manifest as JSON to `url`. Any non-2xx answer is an
+    /// error.
+    pub fn post(&self, url: &str) -> Result<()> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(WEBHOOK_TIMEOUT))
+            .build()
+            .into();
+        agent
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", concat!("srg/", env!("CARGO_PKG_VERSION")))
+            .send(self.to_json()?)
+            .with_context(|| format!("POST {}", url))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_serializes_outputs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let html = dir.path().join("index.html");
+        std::fs::write(&html, "<html></html>").unwrap();
+
+        let manifest = Manifest::new(
+            "Ada",
+            Path::new("resume.jobl"),
+            Some("minimal"),
+            dir.path(),
+            &[(OutputFormat::Html, html), (OutputFormat::EmailHtml, dir.path().join("missing"))],
+        );
+        let json: serde_json::Value = serde_json::from_str(&manifest.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["name"], "Ada");
+        assert_eq!(json["theme"], "minimal");
+        assert_eq!(json["outputs"][0]["format"], "html");
+        assert_eq!(json["outputs"][0]["bytes"], 13);
+        assert_eq!(json["outputs"][1]["format"], "email-html");
+    }
+}
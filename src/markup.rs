@@ -0,0 +1,222 @@
+//! Well-formedness check for the HTML srg writes.
+//!
+//! Not a full HTML5 validator: it tokenizes the page and checks what
+//! a renderer bug would break. Every element is closed, in order;
+//! void elements aren't closed; attributes are quoted and not
+//! repeated; ids are unique; and a bare `<` never starts something
+//! that isn't a tag. The renderers close every element they open,
+//! so the check is strict where HTML itself would be forgiving
+//! (`<p>` and `<li>` must be closed too).
+
+/// Elements that never have content or an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// Elements whose content is text up to their end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "title", "textarea"];
+
+/// Something wrong with the markup, with the 1-based line it's on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Every problem in `html`, in document order.
+pub fn check(html: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut open: Vec<(String, usize)> = Vec::new();
+    let mut ids: Vec<String> = Vec::new();
+    let line_at = |offset: usize| html[..offset].matches('\n').count() + 1;
+    let mut report = |offset: usize, message: String| problems.push(Problem { line: line_at(offset), message });
+
+    let mut pos = 0;
+    while let Some(found) = html[pos..].find('<') {
+        let start = pos + found;
+        let rest = &html[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            match comment.find("-->") {
+                Some(end) => pos = start + 4 + end + 3,
+                None => {
+                    report(start, "comment never ends".to_string());
+                    break;
+                }
+            }
+            continue;
+        }
+        if rest.starts_with("<!") {
+            pos = start + rest.find('>').map_or(rest.len(), |end| end + 1);
+            continue;
+        }
+
+        if !rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+            report(start, "`<` that doesn't start a tag (unescaped text?)".to_string());
+            pos = start + 1;
+            continue;
+        }
+        let Some(end) = tag_end(rest) else {
+            report(start, "tag never ends".to_string());
+            break;
+        };
+        let tag = &rest[1..end];
+        pos = start + end + 1;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_ascii_lowercase();
+            if VOID_ELEMENTS.contains(&name.as_str()) {
+                report(start, format!("</{}> closes a void element", name));
+                continue;
+            }
+            match open.iter().rposition(|(open_name, _)| *open_name == name) {
+                Some(i) => {
+                    for (unclosed, at) in open.drain(i + 1..).rev() {
+                        report(at, format!("<{}> is never closed (</{}> comes first)", unclosed, name));
+                    }
+                    open.pop();
+                }
+                None => report(start, format!("</{}> has no matching <{}>", name, name)),
+            }
+            continue;
+        }
+
+        let name: String = tag
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        let (attributes, attribute_problems) = parse_attributes(&tag[name.len()..]);
+        for message in attribute_problems {
+            report(start, format!("<{}>: {}", name, message));
+        }
+        for (i, (attribute, value)) in attributes.iter().enumerate() {
+            if attributes[..i].iter().any(|(a, _)| a == attribute) {
+                report(start, format!("<{}> repeats the {} attribute", name, attribute));
+            }
+            if attribute == "id" {
+                if ids.contains(value) {
+                    report(start, format!("id {:?} is used more than once", value));
+                } else {
+                    ids.push(value.clone());
+                }
+            }
+        }
+
+        if VOID_ELEMENTS.contains(&name.as_str()) || tag.trim_end().ends_with('/') {
+            continue;
+        }
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let close = format!("</{}", name);
+            match html[pos..].to_ascii_lowercase().find(&close) {
+                Some(end) => pos += end,
+                None => {
+                    report(start, format!("<{}> is never closed", name));
+                    break;
+                }
+            }
+        }
+        open.push((name, start));
+    }
+
+    for (name, at) in open {
+        report(at, format!("<{}> is never closed", name));
+    }
+    problems.sort_by_key(|p| p.line);
+    problems
+}
+
+/// Offset of the `>` ending the tag at the start of `rest`, skipping
+/// any inside quoted attribute values.
+fn tag_end(rest: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in rest.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            (None, '<') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Attributes of a start tag (after its name), with any problems.
+fn parse_attributes(text: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let mut attributes = Vec::new();
+    let mut problems = Vec::new();
+    let mut rest = text.trim_start().trim_end_matches('/').trim_end();
+
+    while !rest.is_empty() {
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let name = rest[..name_len].to_ascii_lowercase();
+        if name.is_empty() || name.contains(['"', '\'', '<']) {
+            problems.push(format!("malformed attribute near {:?}", rest.chars().take(20).collect::<String>()));
+            break;
+        }
+        rest = rest[name_len..].trim_start();
+
+        let Some(after_eq) = rest.strip_prefix('=') else {
+            attributes.push((name, String::new()));
+            continue;
+        };
+        let after_eq = after_eq.trim_start();
+        match after_eq.chars().next() {
+            Some(q @ ('"' | '\'')) => match after_eq[1..].find(q) {
+                Some(end) => {
+                    attributes.push((name, after_eq[1..1 + end].to_string()));
+                    rest = after_eq[end + 2..].trim_start();
+                }
+                None => {
+                    problems.push(format!("{} has an unclosed quote", name));
+                    break;
+                }
+            },
+            _ => {
+                problems.push(format!("{} isn't quoted", name));
+                let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                attributes.push((name, after_eq[..end].to_string()));
+                rest = after_eq[end..].trim_start();
+            }
+        }
+    }
+    (attributes, problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(html: &str) -> Vec<String> {
+        check(html).into_iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn accepts_well_formed_pages() {
+        let html = "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"UTF-8\">\
+                    <style>a > b { color: red; }</style></head>\n\
+                    <body><!-- note --><p id=\"a\" class='x'>1 &lt; 2<br></p><img src=\"a.png\" alt=\"\"></body>\n</html>\n";
+        assert_eq!(messages(html), Vec::<String>::new());
+    }
+
+    #[test]
+    fn finds_renderer_bugs() {
+        assert_eq!(messages("<div>\n<p>text</div>"), vec!["line 2: <p> is never closed (</div> comes first)"]);
+        assert_eq!(messages("<ul><li>x</li>"), vec!["line 1: <ul> is never closed"]);
+        assert_eq!(messages("<p>a</p></p>"), vec!["line 1: </p> has no matching <p>"]);
+        assert_eq!(messages("<p id=\"a\"></p><p id=\"a\"></p>"), vec!["line 1: id \"a\" is used more than once"]);
+        assert_eq!(messages("<a href=x>y</a>"), vec!["line 1: <a>: href isn't quoted"]);
+        assert_eq!(messages("<p>1 < 2</p>"), vec!["line 1: `<` that doesn't start a tag (unescaped text?)"]);
+        assert_eq!(messages("<br></br>"), vec!["line 1: </br> closes a void element"]);
+    }
+}
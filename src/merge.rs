@@ -0,0 +1,116 @@
+//! Layered resumes (`-i base.jobl -i freelance.jobl`).
+//!
+//! A build can read several files and merge them in the order given,
+//! so one shared file holds what every version has and smaller ones
+//! add or change what differs. The files are merged as TOML, before
+//! JOBL sees them, so srg's own sections and `[person.meta]` merge
+//! like everything else. The rules, for each key of a later file:
+//!
+//! - A table merges into the earlier file's table key by key, by
+//!   these same rules: `[person]` in both files is one person.
+//! - An array is appended to the earlier file's array, leaving out
+//!   items it already has: experience, projects, and education
+//!   entries, highlights, and a skills category's skills add up. Only
+//!   an exact repeat is left out. An entry that differs in any field,
+//!   say one more highlight, is a second entry, so a later file adds
+//!   new entries rather than editing the earlier file's.
+//! - Anything else (a string, number, boolean, or date) replaces the
+//!   earlier value, as does a value of another kind than the one it
+//!   meets.
+//! - A key only one file has is kept as it is.
+//!
+//! Warnings about a merged resume name no line, as the merged source's
+//! lines aren't any one file's.
+
+use anyhow::{Context, Result};
+use toml::{Table, Value};
+
+/// Merge `overlay` into `base` (see the module docs).
+pub fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge(base, overlay),
+            (Some(Value::Array(base)), Value::Array(overlay)) => {
+                for item in overlay {
+                    if !base.contains(&item) {
+                        base.push(item);
+                    }
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Merge JOBL sources in order, each given with a name for errors.
+/// Returns the merged source.
+pub fn merge_sources<'a>(sources: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<String> {
+    let mut merged = Table::new();
+    for (name, source) in sources {
+        let table: Table = toml::from_str(source).with_context(|| format!("Failed to parse {}", name))?;
+        merge(&mut merged, table);
+    }
+    toml::to_string(&merged).context("Failed to write the merged resume")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(base: &str, overlay: &str) -> Table {
+        toml::from_str(&merge_sources([("base.jobl", base), ("overlay.jobl", overlay)]).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn scalars_are_overridden_and_tables_merged() {
+        let doc = merged(
+            "[person]\nname = \"Ada\"\nheadline = \"Analyst\"\n\n[person.meta]\npronouns = \"she/her\"\n",
+            "[person]\nheadline = \"Freelance analyst\"\n\n[person.meta]\nrate = \"day\"\n",
+        );
+        let person = doc["person"].as_table().unwrap();
+        assert_eq!(person["name"].as_str(), Some("Ada"));
+        assert_eq!(person["headline"].as_str(), Some("Freelance analyst"));
+        let meta = person["meta"].as_table().unwrap();
+        assert_eq!((meta["pronouns"].as_str(), meta["rate"].as_str()), (Some("she/her"), Some("day")));
+    }
+
+    #[test]
+    fn arrays_are_appended_without_repeats() {
+        let doc = merged(
+            "[skills]\nMath = [\"Calculus\", \"Algebra\"]\n\n[[experience]]\ntitle = \"Analyst\"\ncompany = \"Engine Co\"\n",
+            "[skills]\nMath = [\"Algebra\", \"Logic\"]\nWriting = [\"Notes\"]\n\n[[experience]]\ntitle = \"Consultant\"\ncompany = \"Self\"\n\n[[experience]]\ntitle = \"Analyst\"\ncompany = \"Engine Co\"\n",
+        );
+        let skills = doc["skills"].as_table().unwrap();
+        let math: Vec<&str> = skills["Math"].as_array().unwrap().iter().filter_map(Value::as_str).collect();
+        assert_eq!(math, ["Calculus", "Algebra", "Logic"]);
+        assert!(skills.contains_key("Writing"));
+        let titles: Vec<&str> =
+            doc["experience"].as_array().unwrap().iter().filter_map(|e| e["title"].as_str()).collect();
+        assert_eq!(titles, ["Analyst", "Consultant"]);
+    }
+
+    #[test]
+    fn a_changed_entry_is_another_entry() {
+        let doc = merged(
+            "[[experience]]\ntitle = \"Analyst\"\ncompany = \"Engine Co\"\nhighlights = [\"Wrote the first algorithm\"]\n",
+            "[[experience]]\ntitle = \"Analyst\"\ncompany = \"Engine Co\"\nhighlights = [\"Wrote the first algorithm\", \"Translated the Sketch\"]\n",
+        );
+        let highlights: Vec<usize> =
+            doc["experience"].as_array().unwrap().iter().map(|e| e["highlights"].as_array().unwrap().len()).collect();
+        assert_eq!(highlights, [1, 2]);
+    }
+
+    #[test]
+    fn another_kind_replaces() {
+        let doc = merged("[person]\nname = \"Ada\"\nsummary = [\"a\"]\n", "[person]\nsummary = \"Plain\"\n");
+        assert_eq!(doc["person"]["summary"].as_str(), Some("Plain"));
+    }
+
+    #[test]
+    fn names_the_file_that_fails() {
+        let err = merge_sources([("base.jobl", "[person]\nname = \"Ada\"\n"), ("bad.jobl", "[person")]).unwrap_err();
+        assert!(err.to_string().contains("bad.jobl"), "{}", err);
+    }
+}
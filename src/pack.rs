@@ -0,0 +1,228 @@
+//! `srg pack`: everything for one application in one folder.
+//!
+//! Applying for a job means the same handful of steps each time:
+//! build the resume with the right layout for the kind of role, keep
+//! a plain-text copy for forms that want one, write a cover letter,
+//! and check the resume against the posting. `srg pack --jd job.txt
+//! --profile backend` does them in one go into
+//! `<out>/<date>_<posting>-<profile>/`.
+//!
+//! A profile is a `[profiles.<name>]` table in srg.toml: the theme,
+//! layout, sections, and variables that tailor the build to a kind
+//! of role, and an optional cover letter template.
+
+use jobl::JoblDocument;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::build::{mentions_word, slugify};
+use crate::stats;
+
+/// Words every job posting uses that say nothing about the job.
+const POSTING_STOPWORDS: &[&str] = &[
+    "able", "ability", "apply", "candidate", "candidates", "company", "etc", "experience",
+    "including", "join", "looking", "must", "nice", "our", "plus", "requirements", "role",
+    "should", "skills", "strong", "team", "us", "what", "will", "work", "working", "would",
+    "year", "years", "you", "your",
+];
+
+/// How the resume lines up with a job posting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    /// Skills and technologies from the resume the posting mentions,
+    /// in the resume's order.
+    pub matched: Vec<String>,
+    /// Skills and technologies from the resume it doesn't.
+    pub unmatched: Vec<String>,
+    /// Terms the posting uses more than once that the resume never
+    /// does, most frequent first.
+    pub missing: Vec<(String, usize)>,
+}
+
+impl Match {
+    pub fn new(doc: &JoblDocument, posting: &str) -> Self {
+        let mut keywords: Vec<&String> = Vec::new();
+        let listed = doc.skills.iter().flatten().flat_map(|(_, skills)| skills);
+        for keyword in listed.chain(doc.experience.iter().flat_map(|e| &e.technologies)) {
+            if !keywords.iter().any(|k| k.eq_ignore_ascii_case(keyword)) {
+                keywords.push(keyword);
+            }
+        }
+        let (matched, unmatched): (Vec<&String>, Vec<&String>) =
+            keywords.into_iter().partition(|k| mentions_word(posting, k));
+
+        let texts = crate::compare::text_of(doc);
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for term in posting.split_whitespace().filter_map(stats::term) {
+            if !POSTING_STOPWORDS.contains(&term.as_str()) {
+                *counts.entry(term).or_default() += 1;
+            }
+        }
+        let mut missing: Vec<(String, usize)> = counts
+            .into_iter()
+            .filter(|(term, count)| *count > 1 && !texts.iter().any(|t| mentions_word(t, term)))
+            .collect();
+        missing.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Match {
+            matched: matched.into_iter().cloned().collect(),
+            unmatched: unmatched.into_iter().cloned().collect(),
+            missing,
+        }
+    }
+
+    /// Share of the resume's keywords the posting mentions, 0–100.
+    pub fn coverage(&self) -> usize {
+        let total = self.matched.len() + self.unmatched.len();
+        if total == 0 {
+            return 0;
+        }
+        self.matched.len() * 100 / total
+    }
+
+    /// The match report written to match.txt, listing at most `top`
+    /// missing terms.
+    pub fn report(&self, posting: &str, top: usize) -> String {
+        let mut out = format!("Match against {}\n\n", posting);
+        out.push_str(&format!(
+            "{} of {} skills and technologies on the resume appear in the posting ({}%).\n",
+            self.matched.len(),
+            self.matched.len() + self.unmatched.len(),
+            self.coverage()
+        ));
+        if !self.matched.is_empty() {
+            out.push_str(&format!("\nIn the posting:\n  {}\n", self.matched.join(", ")));
+        }
+        if !self.unmatched.is_empty() {
+            out.push_str(&format!("\nNot in the posting:\n  {}\n", self.unmatched.join(", ")));
+        }
+        if !self.missing.is_empty() {
+            out.push_str("\nUsed in the posting but nowhere on the resume:\n");
+            for (term, count) in self.missing.iter().take(top) {
+                out.push_str(&format!("  {:<24} {}\n", term, count));
+            }
+        }
+        out
+    }
+}
+
+/// The cover letter when the profile has no template of its own,
+/// with a sentence on the matched skills when there are any.
+fn default_letter(has_skills: bool) -> String {
+    let mut letter = String::from("Dear hiring team,\n\nI'm writing to apply for this position.");
+    if has_skills {
+        letter.push_str(" My work has centered on {skills}, which the role calls for.");
+    }
+    letter.push_str(
+        "\n\nI've attached my resume and would welcome the chance to talk.\n\nBest regards,\n{name}\n{email}\n",
+    );
+    letter
+}
+
+/// The cover letter: `template` (or the default) with person fields,
+/// layout `variables`, and `{skills}`, the resume's skills the
+/// posting asks for, filled in.
+pub fn cover_letter(
+    template: Option<&str>,
+    doc: &JoblDocument,
+    matched: &[String],
+    variables: &BTreeMap<String, String>,
+) -> String {
+    let skills = join_and(matched);
+    let extra = |name: &str| match name {
+        "skills" => Some(skills.clone()),
+        _ => variables.get(name).cloned(),
+    };
+    let template = template.map(str::to_string).unwrap_or_else(|| default_letter(!matched.is_empty()));
+    crate::send::interpolate_with(&template, doc, &extra)
+}
+
+/// "a, b and c".
+fn join_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// The pack's folder name: the date, then the posting's file name and
+/// the profile, e.g. `2026-10-16_acme-backend`.
+pub fn folder_name(date: &str, posting: &Path, profile: Option<&str>) -> String {
+    let stem = posting.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut name = format!("{}_{}", date, slugify(&stem));
+    if let Some(profile) = profile {
+        name.push('-');
+        name.push_str(&slugify(profile));
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jobl::{ExperienceItem, Person};
+
+    fn doc() -> JoblDocument {
+        JoblDocument {
+            person: Person {
+                name: "Ada".to_string(),
+                headline: None,
+                location: None,
+                email: Some("ada@example.com".to_string()),
+                website: None,
+                github: None,
+                linkedin: None,
+                phone: None,
+                summary: None,
+            },
+            skills: Some(BTreeMap::from([(
+                "Languages".to_string(),
+                vec!["Rust".to_string(), "Go".to_string(), "Python".to_string()],
+            )])),
+            experience: vec![ExperienceItem {
+                title: "Engineer".to_string(),
+                company: "Acme".to_string(),
+                location: None,
+                start: None,
+                end: None,
+                summary: None,
+                technologies: vec!["Postgres".to_string(), "rust".to_string()],
+                highlights: vec!["Built the billing service".to_string()],
+            }],
+            projects: Vec::new(),
+            education: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn matches_keywords_against_the_posting() {
+        let posting = "We use Rust and Postgres. You'll own Kafka pipelines; Kafka experience is a must. \
+                       Billing knowledge helps, billing matters.";
+        let matched = Match::new(&doc(), posting);
+
+        assert_eq!(matched.matched, ["Rust", "Postgres"]);
+        assert_eq!(matched.unmatched, ["Go", "Python"]);
+        assert_eq!(matched.coverage(), 50);
+        // "billing" is on the resume; "experience" is posting boilerplate.
+        assert_eq!(matched.missing, [("kafka".to_string(), 2)]);
+        assert!(matched.report("job.txt", 10).contains("2 of 4 skills and technologies"));
+    }
+
+    #[test]
+    fn fills_in_the_cover_letter() {
+        let skills = ["Rust".to_string(), "Go".to_string(), "Postgres".to_string()];
+        let variables = BTreeMap::from([("company".to_string(), "Initech".to_string())]);
+        let letter = cover_letter(Some("Dear {company}: {skills}. {name}, {unknown}"), &doc(), &skills, &variables);
+        assert_eq!(letter, "Dear Initech: Rust, Go and Postgres. Ada, {unknown}");
+
+        let letter = cover_letter(None, &doc(), &[], &variables);
+        assert!(!letter.contains("centered on") && letter.ends_with("Ada\nada@example.com\n"));
+    }
+
+    #[test]
+    fn folder_names() {
+        assert_eq!(folder_name("2026-10-16", Path::new("jobs/Acme Corp.txt"), Some("backend")), "2026-10-16_acme-corp-backend");
+        assert_eq!(folder_name("2026-10-16", Path::new("job.txt"), None), "2026-10-16_job");
+    }
+}
@@ -0,0 +1,351 @@
+//! Visual diff of two PDFs (`srg diff-pdf old.pdf new.pdf`).
+//!
+//! Both files are rasterized with poppler's `pdftoppm`, compared
+//! pixel by pixel, and stacked page by page into a single PNG:
+//! unchanged content is faded, changed pixels are red, pages that
+//! only exist in the new file are green and pages that only exist in
+//! the old one are red. A per-page summary goes alongside it.
+//!
+//! `pdftoppm` writes binary PPM, which is simple enough to read
+//! without an image crate; the PNG is written by hand with flate2.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Per-channel difference below which two pixels count as equal, so
+/// anti-aliasing noise doesn't light up the whole page.
+const TOLERANCE: u8 = 24;
+
+/// Gap drawn between stacked pages in the diff image.
+const PAGE_GAP: usize = 12;
+
+const CHANGED: [u8; 3] = [220, 30, 30];
+const ADDED: [u8; 3] = [30, 150, 60];
+const REMOVED: [u8; 3] = [220, 30, 30];
+const GAP: [u8; 3] = [128, 128, 128];
+
+/// An RGB image, 3 bytes per pixel, row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    fn blank(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: color.repeat(width * height),
+        }
+    }
+
+    /// Pixel at (x, y), or white outside the image. Comparing pages
+    /// of different sizes treats the missing area as blank paper.
+    fn get(&self, x: usize, y: usize) -> [u8; 3] {
+        if x >= self.width || y >= self.height {
+            return [255, 255, 255];
+        }
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: [u8; 3]) {
+        let i = (y * self.width + x) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&color);
+    }
+}
+
+/// What happened to one page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageStatus {
+    Unchanged,
+    Changed { changed_pixels: usize, total_pixels: usize },
+    Added,
+    Removed,
+}
+
+/// Summary line for one page, 1-based.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageDiff {
+    pub page: usize,
+    pub status: PageStatus,
+}
+
+/// Diff `old` against `new`, write the highlighted image to `output`,
+/// and return a summary per page.
+pub fn run(old: &Path, new: &Path, output: &Path, dpi: u32) -> Result<Vec<PageDiff>> {
+    let work = tempfile::TempDir::new().context("Failed to create temp directory")?;
+    let old_pages = rasterize(old, dpi, work.path(), "old")?;
+    let new_pages = rasterize(new, dpi, work.path(), "new")?;
+
+    let page_count = old_pages.len().max(new_pages.len());
+    let mut summary = Vec::with_capacity(page_count);
+    let mut images = Vec::with_capacity(page_count);
+    for i in 0..page_count {
+        let (status, image) = diff_page(old_pages.get(i), new_pages.get(i));
+        summary.push(PageDiff { page: i + 1, status });
+        images.push(image);
+    }
+
+    let png = encode_png(&stack(&images))?;
+    fs::write(output, png)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    Ok(summary)
+}
+
+/// Render every page of `pdf` to a raster via `pdftoppm`.
+fn rasterize(pdf: &Path, dpi: u32, dir: &Path, prefix: &str) -> Result<Vec<Raster>> {
+    let status = Command::new("pdftoppm")
+        .arg("-r")
+        .arg(dpi.to_string())
+        .arg(pdf)
+        .arg(dir.join(prefix))
+        .status()
+        .context("Failed to run pdftoppm (install poppler-utils)")?;
+    if !status.success() {
+        anyhow::bail!("pdftoppm failed on {} ({})", pdf.display(), status);
+    }
+
+    // pdftoppm names pages prefix-1.ppm or prefix-01.ppm depending on
+    // the page count, so sort by the parsed number, not the name.
+    let mut pages: Vec<(usize, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if let Some(number) = stem
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .and_then(|n| n.parse().ok())
+        {
+            pages.push((number, path));
+        }
+    }
+    pages.sort();
+
+    pages
+        .iter()
+        .map(|(_, path)| {
+            let bytes = fs::read(path)?;
+            parse_ppm(&bytes).with_context(|| format!("Failed to read {}", path.display()))
+        })
+        .collect()
+}
+
+/// Parse a binary (P6) PPM with 8-bit channels.
+pub fn parse_ppm(bytes: &[u8]) -> Result<Raster> {
+    let mut pos = 0;
+    let mut next_token = || -> Result<String> {
+        // Skip whitespace and `#` comments between header tokens.
+        loop {
+            match bytes.get(pos) {
+                Some(b) if b.is_ascii_whitespace() => pos += 1,
+                Some(b'#') => {
+                    while bytes.get(pos).is_some_and(|&b| b != b'\n') {
+                        pos += 1;
+                    }
+                }
+                Some(_) => break,
+                None => anyhow::bail!("truncated PPM header"),
+            }
+        }
+        let start = pos;
+        while bytes.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        Ok(String::from_utf8_lossy(&bytes[start..pos]).into_owned())
+    };
+
+    if next_token()? != "P6" {
+        anyhow::bail!("not a binary PPM");
+    }
+    let width: usize = next_token()?.parse().context("bad PPM width")?;
+    let height: usize = next_token()?.parse().context("bad PPM height")?;
+    let max: usize = next_token()?.parse().context("bad PPM maxval")?;
+    if max != 255 {
+        anyhow::bail!("unsupported PPM maxval {}", max);
+    }
+
+    // Exactly one whitespace byte separates the header from the data.
+    let data = &bytes[pos + 1..];
+    let len = width * height * 3;
+    if data.len() < len {
+        anyhow::bail!("truncated PPM data");
+    }
+
+    Ok(Raster {
+        width,
+        height,
+        pixels: data[..len].to_vec(),
+    })
+}
+
+/// Compare one page and build its highlighted image.
+pub fn diff_page(old: Option<&Raster>, new: Option<&Raster>) -> (PageStatus, Raster) {
+    match (old, new) {
+        (Some(old), Some(new)) => {
+            let width = old.width.max(new.width);
+            let height = old.height.max(new.height);
+            let mut image = Raster::blank(width, height, [255, 255, 255]);
+            let mut changed = 0;
+            for y in 0..height {
+                for x in 0..width {
+                    let (a, b) = (old.get(x, y), new.get(x, y));
+                    let differs = a.iter().zip(&b).any(|(p, q)| p.abs_diff(*q) > TOLERANCE);
+                    if differs {
+                        changed += 1;
+                        image.set(x, y, CHANGED);
+                    } else {
+                        image.set(x, y, fade(b));
+                    }
+                }
+            }
+            let status = if changed == 0 {
+                PageStatus::Unchanged
+            } else {
+                PageStatus::Changed {
+                    changed_pixels: changed,
+                    total_pixels: width * height,
+                }
+            };
+            (status, image)
+        }
+        (None, Some(new)) => (PageStatus::Added, tint(new, ADDED)),
+        (Some(old), None) => (PageStatus::Removed, tint(old, REMOVED)),
+        (None, None) => (PageStatus::Unchanged, Raster::blank(0, 0, GAP)),
+    }
+}
+
+/// Wash a pixel out towards white so highlights stand out.
+fn fade(pixel: [u8; 3]) -> [u8; 3] {
+    pixel.map(|c| 255 - (255 - c) / 3)
+}
+
+/// Paint every non-white pixel of a page in one color.
+fn tint(page: &Raster, color: [u8; 3]) -> Raster {
+    let mut image = page.clone();
+    for y in 0..page.height {
+        for x in 0..page.width {
+            if page.get(x, y).iter().any(|&c| c < 255 - TOLERANCE) {
+                image.set(x, y, color);
+            }
+        }
+    }
+    image
+}
+
+/// Stack pages top to bottom with a gray gap between them.
+fn stack(pages: &[Raster]) -> Raster {
+    let width = pages.iter().map(|p| p.width).max().unwrap_or(0).max(1);
+    let height = pages.iter().map(|p| p.height).sum::<usize>()
+        + PAGE_GAP * pages.len().saturating_sub(1);
+    let mut image = Raster::blank(width, height.max(1), GAP);
+
+    let mut top = 0;
+    for page in pages {
+        for y in 0..page.height {
+            let row = &page.pixels[y * page.width * 3..(y + 1) * page.width * 3];
+            let start = ((top + y) * width) * 3;
+            image.pixels[start..start + row.len()].copy_from_slice(row);
+        }
+        top += page.height + PAGE_GAP;
+    }
+    image
+}
+
+/// Encode an RGB raster as a PNG: one IHDR, one zlib IDAT with no
+/// row filtering, and IEND.
+pub fn encode_png(image: &Raster) -> Result<Vec<u8>> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // 8-bit depth, truecolor, deflate, adaptive filtering, no interlace.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    push_chunk(&mut png, b"IHDR", &header);
+
+    let mut encoder =
+        flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    for row in image.pixels.chunks(image.width * 3) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    push_chunk(&mut png, b"IDAT", &encoder.finish()?);
+    push_chunk(&mut png, b"IEND", &[]);
+
+    Ok(png)
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: usize, height: usize, color: [u8; 3]) -> Raster {
+        Raster::blank(width, height, color)
+    }
+
+    #[test]
+    fn parses_ppm_with_comments() {
+        let mut bytes = b"P6\n# made by pdftoppm\n2 1\n255\n".to_vec();
+        bytes.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+        let raster = parse_ppm(&bytes).unwrap();
+        assert_eq!((raster.width, raster.height), (2, 1));
+        assert_eq!(raster.pixels, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn identical_pages_are_unchanged() {
+        let page = solid(4, 4, [10, 10, 10]);
+        let (status, _) = diff_page(Some(&page), Some(&page));
+        assert_eq!(status, PageStatus::Unchanged);
+    }
+
+    #[test]
+    fn changed_pixels_are_counted_and_highlighted() {
+        let old = solid(2, 2, [255, 255, 255]);
+        let mut new = old.clone();
+        new.set(1, 0, [0, 0, 0]);
+
+        let (status, image) = diff_page(Some(&old), Some(&new));
+        assert_eq!(
+            status,
+            PageStatus::Changed { changed_pixels: 1, total_pixels: 4 }
+        );
+        assert_eq!(image.get(1, 0), CHANGED);
+        assert_eq!(image.get(0, 0), [255, 255, 255]);
+    }
+
+    #[test]
+    fn missing_pages_are_added_or_removed() {
+        let page = solid(1, 1, [0, 0, 0]);
+        assert_eq!(diff_page(None, Some(&page)).0, PageStatus::Added);
+        assert_eq!(diff_page(Some(&page), None).0, PageStatus::Removed);
+    }
+
+    #[test]
+    fn png_has_signature_and_chunks() {
+        let png = encode_png(&solid(3, 2, [1, 2, 3])).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..20], &3u32.to_be_bytes());
+        assert!(png.ends_with(&[0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82]));
+    }
+}
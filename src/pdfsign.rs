@@ -0,0 +1,627 @@
+//! Digital signatures for the PDF (`--sign-pdf --cert me.p12`).
+//!
+//! The signature is added the way Acrobat adds one: an incremental
+//! update appended to the file, holding a signature dictionary, an
+//! invisible signature field on the first page, and new versions of
+//! the catalog and that page pointing at it. The original bytes are
+//! untouched, so the update can be checked against them.
+//!
+//! The signature itself is a detached CMS (`adbe.pkcs7.detached`)
+//! over every byte of the file except the hex placeholder it goes in.
+//! It's made by the `openssl` CLI from the PKCS#12 file, the way
+//! `diff-pdf` leaves rasterizing to poppler: srg does the PDF side and
+//! nothing cryptographic. The certificate's password is read from
+//! `SRG_CERT_PASSWORD`.
+//!
+//! Only PDFs with classic cross-reference tables are handled, which
+//! covers everything Chrome and Typst write. Ghostscript's PDF/A
+//! rewrite of the Chrome PDF uses cross-reference streams, so that
+//! combination is refused before the build (see [`check_options`]).
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Room reserved for the CMS signature, in bytes. A signature with a
+/// certificate chain of two or three certificates needs 3 to 6 KB.
+const SIGNATURE_SPACE: usize = 16_384;
+
+/// Environment variable holding the PKCS#12 file's password.
+pub const PASSWORD_VAR: &str = "SRG_CERT_PASSWORD";
+
+/// Fail unless the PDF `options` build is one srg can sign.
+pub fn check_options(options: &crate::build::BuildOptions) -> Result<()> {
+    use crate::build::{PdfBackend, PdfProfile};
+    if options.pdf_backend == PdfBackend::Chrome && options.pdf_profile == PdfProfile::Pdfa {
+        anyhow::bail!(
+            "--sign-pdf can't sign a Chrome PDF/A: Ghostscript writes it with compressed cross-references, \
+             which srg can't sign yet. Use --pdf-backend typst for a signed PDF/A, or drop --pdf-profile pdfa"
+        );
+    }
+    Ok(())
+}
+
+/// Sign `pdf` with the key and certificate in the PKCS#12 file
+/// `cert`.
+pub fn sign(pdf: &[u8], cert: &Path) -> Result<Vec<u8>> {
+    let dir = tempfile::TempDir::new().context("Failed to create a scratch directory for signing")?;
+    let (signer, chain) = unpack_p12(cert, dir.path())?;
+
+    let mut prepared = prepare(pdf, &signing_time())?;
+    let content = dir.path().join("content.bin");
+    fs::write(&content, prepared.signed_bytes()).context("Failed to write the bytes to sign")?;
+    let signature = dir.path().join("signature.der");
+
+    let mut openssl = Command::new("openssl");
+    openssl.args(["cms", "-sign", "-binary", "-nosmimecap", "-md", "sha256", "-outform", "DER"]);
+    openssl.arg("-in").arg(&content).arg("-signer").arg(&signer).arg("-out").arg(&signature);
+    if let Some(chain) = &chain {
+        openssl.arg("-certfile").arg(chain);
+    }
+    run(&mut openssl, "sign the PDF")?;
+
+    let der = fs::read(&signature).context("Failed to read the signature openssl wrote")?;
+    prepared.embed(&der)?;
+    Ok(prepared.pdf)
+}
+
+/// Split the PKCS#12 file into a PEM with the key and certificate
+/// and, when it has any, a PEM of the other certificates in its
+/// chain.
+fn unpack_p12(cert: &Path, dir: &Path) -> Result<(std::path::PathBuf, Option<std::path::PathBuf>)> {
+    let pass = match std::env::var(PASSWORD_VAR) {
+        Ok(_) => format!("env:{}", PASSWORD_VAR),
+        Err(_) => "pass:".to_string(),
+    };
+    let signer = dir.join("signer.pem");
+    let chain = dir.join("chain.pem");
+    let export = |out: &Path, extra: &[&str]| {
+        let mut openssl = Command::new("openssl");
+        openssl.args(["pkcs12", "-nodes", "-passin", &pass]).args(extra);
+        openssl.arg("-in").arg(cert).arg("-out").arg(out);
+        openssl
+    };
+
+    let description = format!("read {}", cert.display());
+    // Certificates exported by older tools use ciphers OpenSSL 3 only
+    // reads in legacy mode.
+    if run(&mut export(&signer, &[]), &description).is_err() {
+        run(&mut export(&signer, &["-legacy"]), &description).with_context(|| {
+            format!("Is {} the right password for {}?", PASSWORD_VAR, cert.display())
+        })?;
+        run(&mut export(&chain, &["-legacy", "-cacerts", "-nokeys"]), &description)?;
+    } else {
+        run(&mut export(&chain, &["-cacerts", "-nokeys"]), &description)?;
+    }
+
+    let has_chain = fs::read_to_string(&chain).is_ok_and(|pem| pem.contains("BEGIN CERTIFICATE"));
+    Ok((signer, has_chain.then_some(chain)))
+}
+
+fn run(command: &mut Command, what: &str) -> Result<()> {
+    let output = command
+        .output()
+        .context("Failed to run openssl, which signing needs. Install it (e.g. `apt install openssl`)")?;
+    if !output.status.success() {
+        anyhow::bail!("openssl couldn't {}:\n{}", what, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// The current time as a PDF date in UTC, `D:20250102030405Z`.
+fn signing_time() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (year, month, day) = crate::dates::civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!(
+        "D:{:04}{:02}{:02}{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// A PDF with its signature update appended and the signature still
+/// to be filled in.
+#[derive(Debug)]
+pub struct Prepared {
+    pub pdf: Vec<u8>,
+    /// The hex digits of `/Contents`, between its `<` and `>`.
+    pub contents: Range<usize>,
+}
+
+impl Prepared {
+    /// Everything the signature covers: the file minus the
+    /// `/Contents` string, delimiters included.
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.pdf[..self.contents.start - 1].to_vec();
+        bytes.extend_from_slice(&self.pdf[self.contents.end + 1..]);
+        bytes
+    }
+
+    /// Write the DER signature into the placeholder, zero-padded.
+    pub fn embed(&mut self, der: &[u8]) -> Result<()> {
+        let hex: String = der.iter().map(|b| format!("{:02X}", b)).collect();
+        if hex.len() > self.contents.len() {
+            anyhow::bail!(
+                "The signature is {} bytes, more than the {} bytes srg reserves for it; is the certificate chain unusually long?",
+                der.len(),
+                SIGNATURE_SPACE
+            );
+        }
+        self.pdf[self.contents.start..self.contents.start + hex.len()].copy_from_slice(hex.as_bytes());
+        Ok(())
+    }
+}
+
+/// Append the signature update to `pdf`, with `/ByteRange` filled in
+/// and `/Contents` left as zeros. `time` is the PDF date for `/M`.
+pub fn prepare(pdf: &[u8], time: &str) -> Result<Prepared> {
+    let file = Xref::read(pdf)?;
+    let root = file.trailer_ref("Root")?;
+    let catalog = file.object(root)?;
+    if dict_get(pdf, catalog.clone(), "AcroForm")?.is_some() {
+        anyhow::bail!("The PDF already has a form; srg only signs PDFs without one");
+    }
+    let page = file.first_page(root)?;
+    let page_dict = file.object(page)?;
+
+    let sig = file.size;
+    let field = file.size + 1;
+    let mut objects: BTreeMap<u32, (u16, Vec<u8>)> = BTreeMap::new();
+
+    // The catalog and page, with the form and the field added.
+    let mut new_catalog = pdf[catalog.start..catalog.end - 2].trim_ascii_end().to_vec();
+    new_catalog.extend_from_slice(format!(" /AcroForm << /Fields [{} 0 R] /SigFlags 3 >> >>", field).as_bytes());
+    objects.insert(root.0, (root.1, new_catalog));
+
+    let field_ref = format!("{} 0 R", field);
+    let new_page = match dict_get(pdf, page_dict.clone(), "Annots")? {
+        Some(annots) if pdf[annots.start] == b'[' => {
+            let at = annots.end - 1;
+            splice(pdf, page_dict.clone(), at..at, &format!(" {}", field_ref))
+        }
+        Some(annots) => {
+            // An indirect array: update the array object instead.
+            let array_ref = parse_ref(&pdf[annots])?;
+            let array = file.object(array_ref)?;
+            if pdf.get(array.start) != Some(&b'[') {
+                anyhow::bail!("The first page's /Annots isn't an array");
+            }
+            let mut new_array = pdf[array.start..array.end - 1].to_vec();
+            new_array.extend_from_slice(format!(" {}]", field_ref).as_bytes());
+            objects.insert(array_ref.0, (array_ref.1, new_array));
+            pdf[page_dict.clone()].to_vec()
+        }
+        None => {
+            let mut new_page = pdf[page_dict.start..page_dict.end - 2].trim_ascii_end().to_vec();
+            new_page.extend_from_slice(format!(" /Annots [{}] >>", field_ref).as_bytes());
+            new_page
+        }
+    };
+    objects.insert(page.0, (page.1, new_page));
+
+    objects.insert(
+        field,
+        (
+            0,
+            format!(
+                "<< /Type /Annot /Subtype /Widget /FT /Sig /T (Signature) /V {} 0 R /F 132 /Rect [0 0 0 0] /P {} {} R >>",
+                sig, page.0, page.1
+            )
+            .into_bytes(),
+        ),
+    );
+    let placeholder = "0".repeat(SIGNATURE_SPACE * 2);
+    objects.insert(
+        sig,
+        (
+            0,
+            format!(
+                "<< /Type /Sig /Filter /Adobe.PPKLite /SubFilter /adbe.pkcs7.detached /ByteRange [{}] /Contents <{}> /M ({}) >>",
+                BYTE_RANGE_PLACEHOLDER, placeholder, time
+            )
+            .into_bytes(),
+        ),
+    );
+
+    let mut out = pdf.to_vec();
+    if !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    let mut offsets = Vec::new();
+    let mut contents = 0..0;
+    let mut byte_range = 0;
+    for (&number, (generation, body)) in &objects {
+        offsets.push((number, *generation, out.len()));
+        out.extend_from_slice(format!("{} {} obj\n", number, generation).as_bytes());
+        if number == sig {
+            let body_start = out.len();
+            let text = std::str::from_utf8(body).unwrap_or_default();
+            byte_range = body_start + text.find(BYTE_RANGE_PLACEHOLDER).unwrap_or_default();
+            let start = body_start + text.find(&placeholder).unwrap_or_default();
+            contents = start..start + placeholder.len();
+        }
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref = out.len();
+    out.extend_from_slice(b"xref\n");
+    for (number, generation, offset) in &offsets {
+        out.extend_from_slice(format!("{} 1\n{:010} {:05} n\r\n", number, offset, generation).as_bytes());
+    }
+    let mut trailer = format!("<< /Size {} /Root {} {} R /Prev {}", file.size + 2, root.0, root.1, file.start);
+    for key in ["Info", "ID"] {
+        if let Some(value) = &file.trailer_value(key) {
+            trailer.push_str(&format!(" /{} {}", key, value));
+        }
+    }
+    out.extend_from_slice(format!("trailer\n{} >>\nstartxref\n{}\n%%EOF\n", trailer, xref).as_bytes());
+
+    // Everything but the `<...>` of /Contents.
+    let ranges = format!(
+        "0 {} {} {}",
+        contents.start - 1,
+        contents.end + 1,
+        out.len() - (contents.end + 1)
+    );
+    let ranges = format!("{:<width$}", ranges, width = BYTE_RANGE_PLACEHOLDER.len());
+    out[byte_range..byte_range + ranges.len()].copy_from_slice(ranges.as_bytes());
+
+    Ok(Prepared { pdf: out, contents })
+}
+
+/// Fixed-width stand-in for the `/ByteRange` numbers, overwritten
+/// (and space-padded) once the offsets are known.
+const BYTE_RANGE_PLACEHOLDER: &str = "0 0000000000 0000000000 0000000000";
+
+/// `pdf[range]` with `at` replaced by `insert`.
+fn splice(pdf: &[u8], range: Range<usize>, at: Range<usize>, insert: &str) -> Vec<u8> {
+    let mut out = pdf[range.start..at.start].to_vec();
+    out.extend_from_slice(insert.as_bytes());
+    out.extend_from_slice(&pdf[at.end..range.end]);
+    out
+}
+
+/// The file's cross-reference table and trailer.
+struct Xref<'a> {
+    pdf: &'a [u8],
+    /// Offset of the newest `xref` section.
+    start: usize,
+    /// Byte offset and generation of each object in use.
+    offsets: BTreeMap<u32, (usize, u16)>,
+    /// The newest trailer dictionary.
+    trailer: Range<usize>,
+    size: u32,
+}
+
+impl<'a> Xref<'a> {
+    fn read(pdf: &'a [u8]) -> Result<Self> {
+        let marker = rfind(pdf, b"startxref").context("Not a PDF: no startxref")?;
+        let (start, _) = token(pdf, skip_ws(pdf, marker + 9));
+        let start: usize = std::str::from_utf8(start).ok().and_then(|s| s.parse().ok()).context("Malformed startxref")?;
+
+        let mut offsets = BTreeMap::new();
+        let mut trailer = None;
+        let mut section = Some(start);
+        let mut seen = 0;
+        while let Some(at) = section {
+            seen += 1;
+            if seen > 64 {
+                anyhow::bail!("Malformed PDF: cross-reference sections loop");
+            }
+            let dict = read_section(pdf, at, &mut offsets)?;
+            section = dict_get(pdf, dict.clone(), "Prev")?
+                .map(|prev| std::str::from_utf8(&pdf[prev]).ok().and_then(|s| s.trim().parse().ok()).context("Malformed /Prev"))
+                .transpose()?;
+            trailer.get_or_insert(dict);
+        }
+        let trailer = trailer.context("Malformed PDF: no trailer")?;
+        let size = dict_get(pdf, trailer.clone(), "Size")?
+            .and_then(|size| std::str::from_utf8(&pdf[size]).ok()?.trim().parse().ok())
+            .context("Malformed PDF: the trailer has no /Size")?;
+        Ok(Self { pdf, start, offsets, trailer, size })
+    }
+
+    fn trailer_value(&self, key: &str) -> Option<String> {
+        let range = dict_get(self.pdf, self.trailer.clone(), key).ok()??;
+        Some(String::from_utf8_lossy(&self.pdf[range]).into_owned())
+    }
+
+    fn trailer_ref(&self, key: &str) -> Result<(u32, u16)> {
+        let value = self.trailer_value(key).with_context(|| format!("Malformed PDF: the trailer has no /{}", key))?;
+        parse_ref(value.as_bytes())
+    }
+
+    /// The byte range of object `reference`'s value.
+    fn object(&self, reference: (u32, u16)) -> Result<Range<usize>> {
+        let &(offset, _) = self
+            .offsets
+            .get(&reference.0)
+            .with_context(|| format!("Object {} isn't in the cross-reference table", reference.0))?;
+        let mut at = offset;
+        for expected in [reference.0.to_string(), reference.1.to_string(), "obj".to_string()] {
+            let (word, next) = token(self.pdf, skip_ws(self.pdf, at));
+            if word != expected.as_bytes() {
+                anyhow::bail!("Object {} isn't where the cross-reference table says", reference.0);
+            }
+            at = next;
+        }
+        let start = skip_ws(self.pdf, at);
+        Ok(start..object_end(self.pdf, start)?)
+    }
+
+    /// The first page, walking down `/Kids` from the catalog's page
+    /// tree.
+    fn first_page(&self, root: (u32, u16)) -> Result<(u32, u16)> {
+        let catalog = self.object(root)?;
+        let pages = dict_get(self.pdf, catalog, "Pages")?.context("The catalog has no /Pages")?;
+        let mut node = parse_ref(&self.pdf[pages])?;
+        for _ in 0..64 {
+            let dict = self.object(node)?;
+            let Some(kids) = dict_get(self.pdf, dict, "Kids")? else {
+                return Ok(node);
+            };
+            let first = skip_ws(self.pdf, kids.start + 1);
+            node = parse_ref(&self.pdf[first..object_end(self.pdf, first)?])?;
+        }
+        anyhow::bail!("Malformed PDF: the page tree is too deep")
+    }
+}
+
+/// Read the `xref` section at `at` into `offsets` (entries already
+/// there are newer and win) and return its trailer dictionary.
+fn read_section(pdf: &[u8], at: usize, offsets: &mut BTreeMap<u32, (usize, u16)>) -> Result<Range<usize>> {
+    let (word, mut at) = token(pdf, skip_ws(pdf, at));
+    if word != b"xref" {
+        anyhow::bail!("This PDF uses compressed cross-references, which srg can't sign yet");
+    }
+    let number = |word: &[u8]| -> Result<usize> {
+        std::str::from_utf8(word).ok().and_then(|s| s.parse().ok()).context("Malformed cross-reference table")
+    };
+    loop {
+        let (word, next) = token(pdf, skip_ws(pdf, at));
+        if word == b"trailer" {
+            let start = skip_ws(pdf, next);
+            return Ok(start..object_end(pdf, start)?);
+        }
+        let first = number(word)?;
+        let (count, next) = token(pdf, skip_ws(pdf, next));
+        at = next;
+        for index in 0..number(count)? {
+            let (offset, next) = token(pdf, skip_ws(pdf, at));
+            let (generation, next) = token(pdf, skip_ws(pdf, next));
+            let (kind, next) = token(pdf, skip_ws(pdf, next));
+            at = next;
+            if kind == b"n" {
+                offsets
+                    .entry((first + index) as u32)
+                    .or_insert((number(offset)?, number(generation)? as u16));
+            }
+        }
+    }
+}
+
+/// The value of `key` in the dictionary at `dict`, if it has one.
+fn dict_get(pdf: &[u8], dict: Range<usize>, key: &str) -> Result<Option<Range<usize>>> {
+    if !pdf[dict.clone()].starts_with(b"<<") {
+        anyhow::bail!("Malformed PDF: expected a dictionary");
+    }
+    let mut at = dict.start + 2;
+    loop {
+        at = skip_ws(pdf, at);
+        if pdf[at..].starts_with(b">>") || at >= dict.end {
+            return Ok(None);
+        }
+        let (name, next) = token(pdf, at);
+        let value = skip_ws(pdf, next);
+        let end = object_end(pdf, value)?;
+        if name.strip_prefix(b"/") == Some(key.as_bytes()) {
+            return Ok(Some(value..end));
+        }
+        at = end;
+    }
+}
+
+/// Parse `12 0 R`.
+fn parse_ref(text: &[u8]) -> Result<(u32, u16)> {
+    let text = std::str::from_utf8(text).unwrap_or_default();
+    let mut parts = text.split_whitespace();
+    match (parts.next().map(str::parse), parts.next().map(str::parse), parts.next()) {
+        (Some(Ok(number)), Some(Ok(generation)), Some("R")) => Ok((number, generation)),
+        _ => anyhow::bail!("Malformed PDF: expected an object reference, got {:?}", text),
+    }
+}
+
+fn is_ws(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(byte, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+/// Skip whitespace and comments.
+fn skip_ws(pdf: &[u8], mut at: usize) -> usize {
+    while at < pdf.len() {
+        if is_ws(pdf[at]) {
+            at += 1;
+        } else if pdf[at] == b'%' {
+            while at < pdf.len() && pdf[at] != b'\n' && pdf[at] != b'\r' {
+                at += 1;
+            }
+        } else {
+            break;
+        }
+    }
+    at
+}
+
+/// The regular token (or name) at `at`, and where it ends.
+fn token(pdf: &[u8], at: usize) -> (&[u8], usize) {
+    let mut end = at + usize::from(pdf.get(at) == Some(&b'/'));
+    while end < pdf.len() && !is_ws(pdf[end]) && !is_delimiter(pdf[end]) {
+        end += 1;
+    }
+    (&pdf[at..end], end)
+}
+
+/// Where the object starting at `at` ends: a whole dictionary,
+/// array, string, name, or number, and `n g R` as one reference.
+fn object_end(pdf: &[u8], at: usize) -> Result<usize> {
+    let truncated = || anyhow::anyhow!("Malformed PDF: an object runs past the end of the file");
+    match pdf.get(at).ok_or_else(truncated)? {
+        b'<' if pdf.get(at + 1) == Some(&b'<') => {
+            let mut at = at + 2;
+            loop {
+                at = skip_ws(pdf, at);
+                if pdf[at..].starts_with(b">>") {
+                    return Ok(at + 2);
+                }
+                if at >= pdf.len() {
+                    return Err(truncated());
+                }
+                at = object_end(pdf, at)?;
+            }
+        }
+        b'[' => {
+            let mut at = at + 1;
+            loop {
+                at = skip_ws(pdf, at);
+                match pdf.get(at) {
+                    Some(b']') => return Ok(at + 1),
+                    Some(_) => at = object_end(pdf, at)?,
+                    None => return Err(truncated()),
+                }
+            }
+        }
+        b'(' => {
+            let (mut depth, mut at) = (0, at);
+            while let Some(&byte) = pdf.get(at) {
+                match byte {
+                    b'\\' => at += 1,
+                    b'(' => depth += 1,
+                    b')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(at + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                at += 1;
+            }
+            Err(truncated())
+        }
+        b'<' => pdf[at..].iter().position(|&b| b == b'>').map(|i| at + i + 1).ok_or_else(truncated),
+        _ => {
+            let (word, end) = token(pdf, at);
+            if word.is_empty() {
+                anyhow::bail!("Malformed PDF: unexpected {:?}", pdf[at] as char);
+            }
+            // `12 0 R` is one value.
+            if word.iter().all(u8::is_ascii_digit) {
+                let (generation, after) = token(pdf, skip_ws(pdf, end));
+                let (r, after) = token(pdf, skip_ws(pdf, after));
+                if !generation.is_empty() && generation.iter().all(u8::is_ascii_digit) && r == b"R" {
+                    return Ok(after);
+                }
+            }
+            Ok(end)
+        }
+    }
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one-page PDF with a classic xref table, offsets computed.
+    fn sample_pdf(page_extra: &str) -> Vec<u8> {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Title (a \\) b) {}>>", page_extra),
+            "[]".to_string(),
+        ];
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, body).as_bytes());
+        }
+        let xref = pdf.len();
+        pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f\r\n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend_from_slice(format!("{:010} 00000 n\r\n", offset).as_bytes());
+        }
+        pdf.extend_from_slice(format!("trailer\n<< /Size 5 /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", xref).as_bytes());
+        pdf
+    }
+
+    #[test]
+    fn appends_a_signature_update() {
+        let pdf = sample_pdf("");
+        let prepared = prepare(&pdf, "D:20260101000000Z").unwrap();
+        let out = &prepared.pdf;
+        let text = String::from_utf8_lossy(out);
+
+        assert!(out.starts_with(&pdf), "the original bytes are kept");
+        assert!(text.contains("1 0 obj\n<< /Type /Catalog /Pages 2 0 R /AcroForm << /Fields [6 0 R] /SigFlags 3 >> >>"));
+        assert!(text.contains("/Annots [6 0 R] >>"));
+        assert!(text.contains("/FT /Sig /T (Signature) /V 5 0 R"));
+        let prev = String::from_utf8_lossy(&pdf).find("xref\n").unwrap();
+        assert!(text.contains(&format!("trailer\n<< /Size 7 /Root 1 0 R /Prev {} >>", prev)));
+
+        // The byte range covers everything but the placeholder.
+        let start = prepared.contents.start - 1;
+        let end = prepared.contents.end + 1;
+        assert!(text.contains(&format!("/ByteRange [0 {} {} {}", start, end, out.len() - end)));
+        assert_eq!(prepared.signed_bytes().len(), out.len() - (end - start));
+
+        // The update reads back: the new catalog replaces the old.
+        let file = Xref::read(out).unwrap();
+        assert_eq!(file.size, 7);
+        let catalog = file.object((1, 0)).unwrap();
+        assert!(dict_get(out, catalog, "AcroForm").unwrap().is_some());
+    }
+
+    #[test]
+    fn adds_to_existing_annotations() {
+        let prepared = prepare(&sample_pdf("/Annots [9 0 R] "), "D:20260101000000Z").unwrap();
+        assert!(String::from_utf8_lossy(&prepared.pdf).contains("/Annots [9 0 R 6 0 R]"));
+
+        let prepared = prepare(&sample_pdf("/Annots 4 0 R "), "D:20260101000000Z").unwrap();
+        assert!(String::from_utf8_lossy(&prepared.pdf).contains("4 0 obj\n[ 6 0 R]\nendobj"));
+    }
+
+    #[test]
+    fn embeds_the_signature_as_hex() {
+        let mut prepared = prepare(&sample_pdf(""), "D:20260101000000Z").unwrap();
+        prepared.embed(&[0xAB, 0x01]).unwrap();
+        assert!(prepared.pdf[prepared.contents.clone()].starts_with(b"AB0100"));
+        let err = prepared.embed(&vec![0; SIGNATURE_SPACE + 1]).unwrap_err().to_string();
+        assert!(err.contains("16384 bytes srg reserves"), "{}", err);
+    }
+
+    #[test]
+    fn refuses_ghostscript_pdfa() {
+        use crate::build::{BuildOptions, PdfBackend, PdfProfile};
+        assert!(check_options(&BuildOptions::default()).is_ok());
+        let pdfa = BuildOptions { pdf_profile: PdfProfile::Pdfa, ..Default::default() };
+        assert!(check_options(&pdfa).unwrap_err().to_string().contains("can't sign a Chrome PDF/A"));
+        assert!(check_options(&BuildOptions { pdf_backend: PdfBackend::Typst, ..pdfa }).is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+//! Past versions of the JOBL file from git, for the history page
+//! (`--history`).
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use crate::build::history::Revision;
+
+/// Every commit that touched `input`, oldest first, following
+/// renames, each with the resume as it was at that commit.
+pub fn revisions(input: &Path) -> Result<Vec<Revision>> {
+    let dir = input.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let file = input.file_name().context("input has no file name")?;
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "--follow", "--date=short", "--name-only", "--format=%x00%H%x09%ad%x09%s", "--"])
+        .arg(file)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let log = String::from_utf8_lossy(&output.stdout);
+    let mut revisions = Vec::new();
+    for record in log.split('\0').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines().filter(|l| !l.trim().is_empty());
+        let Some(header) = lines.next() else { continue };
+        // With --name-only the path (from the repository root) follows
+        // the header; it changes across renames.
+        let Some(path) = lines.next_back() else { continue };
+        let mut fields = header.splitn(3, '\t');
+        let (Some(commit), Some(date)) = (fields.next(), fields.next()) else { continue };
+
+        revisions.push(Revision {
+            commit: commit.to_string(),
+            date: date.to_string(),
+            subject: fields.next().unwrap_or_default().to_string(),
+            doc: document_at(dir, commit, path),
+        });
+    }
+
+    if revisions.is_empty() {
+        bail!("{} has no git history", input.display());
+    }
+    revisions.reverse();
+    Ok(revisions)
+}
+
+/// The resume at `path` as of `commit`, if it parses.
+fn document_at(dir: &Path, commit: &str, path: &str) -> Option<jobl::JoblDocument> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("{}:{}", commit, path))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // jobl only parses files.
+    let file = tempfile::Builder::new().suffix(".jobl").tempfile().ok()?;
+    std::fs::write(file.path(), &output.stdout).ok()?;
+    jobl::parse_file(file.path()).ok()
+}
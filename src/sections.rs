@@ -0,0 +1,581 @@
+//! Alias sections: lists in the JOBL file under a name of their own
+//! that reuse a known section's schema and renderer.
+//!
+//! ```toml
+//! [sections.research]
+//! as = "experience"
+//! title = "Research Experience"
+//!
+//! [[research]]
+//! title = "Graduate Researcher"
+//! company = "Vision Lab"
+//! ```
+//!
+//! JOBL only knows its own sections, so srg takes these tables out
+//! of the source before JOBL validates it (see [`strip`]). A layout
+//! shows an alias section by naming it (`research`) and lists its
+//! fields as it would for the section it's an alias of.
+//!
+//! The same goes for the sections srg adds to JOBL's ([`OWN_SECTIONS`]),
+//! `[[talks]]` and `[[patents]]`, which have schemas of their own, and
+//! for the fields srg adds to `[person]` ([`PERSON_FIELDS`]), including
+//! whatever the author lists under `[person.meta]`.
+
+use anyhow::{Context, Result};
+use jobl::{EducationItem, ExperienceItem, JoblDocument, ProjectItem};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::build::{slugify, Anchors};
+
+/// Lists srg reads from the JOBL file itself, not JOBL.
+pub const OWN_SECTIONS: &[&str] = &["talks", "patents"];
+
+/// Keys srg reads from the JOBL file's `[person]`, not JOBL.
+pub const PERSON_FIELDS: &[&str] = &["work_authorization", "clearance", "meta"];
+
+/// Person field names a `[person.meta]` entry can't take: JOBL's,
+/// srg's own, and the ones the layout computes.
+const RESERVED_PERSON_FIELDS: &[&str] = &[
+    "name",
+    "headline",
+    "email",
+    "phone",
+    "location",
+    "website",
+    "github",
+    "linkedin",
+    "summary",
+    "avatar",
+    "booking",
+    "work_authorization",
+    "clearance",
+];
+
+/// srg's own `[person]` fields. A layout shows them by name in its
+/// `person` section, like JOBL's.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PersonFields {
+    /// Right to work, e.g. "US citizen" or "EU work permit, no
+    /// sponsorship needed".
+    pub work_authorization: Option<String>,
+    /// Security clearance, e.g. "Active TS/SCI".
+    pub clearance: Option<String>,
+    /// `[person.meta]`: any other fields (pronouns, nationality, ...)
+    /// by the name the layout uses.
+    #[serde(default, deserialize_with = "meta")]
+    pub meta: BTreeMap<String, String>,
+}
+
+impl PersonFields {
+    /// Value of a field by its layout name.
+    pub fn get(&self, field: &str) -> Option<String> {
+        match field {
+            "work_authorization" => self.work_authorization.clone(),
+            "clearance" => self.clearance.clone(),
+            _ => self.meta.get(field).cloned(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.work_authorization.is_none() && self.clearance.is_none() && self.meta.is_empty()
+    }
+}
+
+/// `[person.meta]` values as text. Numbers, booleans, and dates
+/// (`date_of_birth = 1815-12-10`) read as written; lists and tables
+/// aren't fields.
+fn meta<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error> {
+    use serde::de::Error;
+    BTreeMap::<String, toml::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, value)| {
+            let text = match value {
+                toml::Value::String(text) => text,
+                toml::Value::Array(_) | toml::Value::Table(_) => {
+                    return Err(D::Error::custom(format!("meta field {} must be a single value", name)));
+                }
+                value => value.to_string(),
+            };
+            Ok((name, text))
+        })
+        .collect()
+}
+
+/// One `[[talks]]` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TalkItem {
+    pub title: String,
+    /// Conference, meetup, or podcast.
+    pub event: String,
+    /// When it was given, written like JOBL's dates (`2024-05`).
+    pub date: Option<String>,
+    pub location: Option<String>,
+    /// Link to the slides.
+    pub slides: Option<String>,
+    /// Link to the recording.
+    pub video: Option<String>,
+}
+
+/// One `[[patents]]` entry.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatentItem {
+    pub title: String,
+    /// Patent or application number, e.g. "US 10,123,456 B2".
+    pub number: Option<String>,
+    /// "Granted", "Pending", ...
+    pub status: Option<String>,
+    /// Year filed or granted. Written as a number or a string.
+    #[serde(default, deserialize_with = "year")]
+    pub year: Option<String>,
+}
+
+fn year<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Year {
+        Number(i64),
+        Text(String),
+    }
+    Ok(match Year::deserialize(deserializer)? {
+        Year::Number(year) => Some(year.to_string()),
+        Year::Text(year) => Some(year),
+    })
+}
+
+/// Sections an alias can stand in for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Experience,
+    Projects,
+    Education,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Experience => "experience",
+            Kind::Projects => "projects",
+            Kind::Education => "education",
+        }
+    }
+}
+
+/// One `[sections.<name>]` entry.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Spec {
+    #[serde(rename = "as")]
+    kind: Kind,
+    title: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Items {
+    Experience(Vec<ExperienceItem>),
+    Projects(Vec<ProjectItem>),
+    Education(Vec<EducationItem>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AliasSection {
+    /// Name the JOBL file and the layout use (`research`). Also the
+    /// section's HTML id.
+    pub name: String,
+    /// Heading shown above the section.
+    pub title: String,
+    pub items: Items,
+}
+
+impl AliasSection {
+    pub fn kind(&self) -> Kind {
+        match self.items {
+            Items::Experience(_) => Kind::Experience,
+            Items::Projects(_) => Kind::Projects,
+            Items::Education(_) => Kind::Education,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match &self.items {
+            Items::Experience(items) => items.is_empty(),
+            Items::Projects(items) => items.is_empty(),
+            Items::Education(items) => items.is_empty(),
+        }
+    }
+
+    /// `doc` with this section's items as the only list, in the place
+    /// of the section it's an alias of, so that section's renderer
+    /// can draw it.
+    pub fn view(&self, doc: &JoblDocument) -> JoblDocument {
+        let mut view = doc.clone();
+        view.experience = Vec::new();
+        view.projects = Vec::new();
+        view.education = Vec::new();
+        match &self.items {
+            Items::Experience(items) => view.experience = items.clone(),
+            Items::Projects(items) => view.projects = items.clone(),
+            Items::Education(items) => view.education = items.clone(),
+        }
+        view
+    }
+
+    /// The section after a document-wide pass (emoji, budgets,
+    /// dates), run on its view of `doc`.
+    pub fn map(&self, doc: &JoblDocument, pass: impl Fn(&JoblDocument) -> JoblDocument) -> AliasSection {
+        let view = pass(&self.view(doc));
+        let items = match self.kind() {
+            Kind::Experience => Items::Experience(view.experience),
+            Kind::Projects => Items::Projects(view.projects),
+            Kind::Education => Items::Education(view.education),
+        };
+        AliasSection { name: self.name.clone(), title: self.title.clone(), items }
+    }
+
+    /// Item ids for `view`, prefixed with the section name so they
+    /// can't collide with the section it's an alias of.
+    pub fn anchors(&self, view: &JoblDocument) -> Vec<String> {
+        let anchors = Anchors::for_document(view);
+        let ids = match self.kind() {
+            Kind::Experience => anchors.experience,
+            Kind::Projects => anchors.projects,
+            Kind::Education => anchors.education,
+        };
+        ids.into_iter().map(|id| format!("{}-{}", self.name, id)).collect()
+    }
+
+    /// Item labels for a table of contents: company, project name,
+    /// or institution.
+    pub fn labels(&self) -> Vec<&str> {
+        match &self.items {
+            Items::Experience(items) => items.iter().map(|e| e.company.as_str()).collect(),
+            Items::Projects(items) => items.iter().map(|p| p.name.as_str()).collect(),
+            Items::Education(items) => items.iter().map(|e| e.institution.as_str()).collect(),
+        }
+    }
+}
+
+/// The alias sections a JOBL source declares, in the order its
+/// `[sections]` table lists them.
+pub fn aliases(source: &str) -> Result<Vec<AliasSection>> {
+    let mut table: toml::Table = toml::from_str(source).context("Failed to read alias sections")?;
+    let Some(specs) = table.remove("sections") else {
+        return Ok(Vec::new());
+    };
+    let specs: toml::Table = specs.try_into().context("[sections] must be a table")?;
+
+    let mut sections = Vec::new();
+    for (name, spec) in specs {
+        if crate::layout::SECTION_NAMES.contains(&name.as_str()) {
+            anyhow::bail!("[sections.{}]: {} is already a section", name, name);
+        }
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("[sections.{}]: section names are letters, digits, -, and _", name);
+        }
+        let spec: Spec = spec.try_into().with_context(|| format!("Invalid [sections.{}]", name))?;
+        let list = table.remove(&name).unwrap_or_else(|| toml::Value::Array(Vec::new()));
+        let context = || format!("Invalid [[{}]] entries for a {} section", name, spec.kind.name());
+        let items = match spec.kind {
+            Kind::Experience => Items::Experience(list.try_into().with_context(context)?),
+            Kind::Projects => Items::Projects(list.try_into().with_context(context)?),
+            Kind::Education => Items::Education(list.try_into().with_context(context)?),
+        };
+        let title = spec.title.unwrap_or_else(|| default_title(&name));
+        sections.push(AliasSection { name, title, items });
+    }
+    Ok(sections)
+}
+
+/// The `[[talks]]` a JOBL source lists.
+pub fn talks(source: &str) -> Result<Vec<TalkItem>> {
+    own_section(source, "talks")
+}
+
+/// The `[[patents]]` a JOBL source lists.
+pub fn patents(source: &str) -> Result<Vec<PatentItem>> {
+    own_section(source, "patents")
+}
+
+/// srg's own fields in a JOBL source's `[person]`.
+pub fn person_fields(source: &str) -> Result<PersonFields> {
+    let mut table: toml::Table = toml::from_str(source).context("Failed to read person fields")?;
+    let Some(toml::Value::Table(mut person)) = table.remove("person") else {
+        return Ok(PersonFields::default());
+    };
+    person.retain(|name, _| PERSON_FIELDS.contains(&name));
+    let fields: PersonFields = toml::Value::Table(person).try_into().context("Invalid [person] fields")?;
+    for name in fields.meta.keys() {
+        if RESERVED_PERSON_FIELDS.contains(&name.as_str()) {
+            anyhow::bail!("[person.meta]: {} is already a person field", name);
+        }
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("[person.meta]: {:?} isn't a field name (letters, digits, -, and _)", name);
+        }
+    }
+    Ok(fields)
+}
+
+fn own_section<T: DeserializeOwned>(source: &str, name: &str) -> Result<Vec<T>> {
+    let mut table: toml::Table = toml::from_str(source).with_context(|| format!("Failed to read {}", name))?;
+    match table.remove(name) {
+        Some(list) => list.try_into().with_context(|| format!("Invalid [[{}]] entries", name)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// `talks` after a document-wide pass (emoji, budgets, dates). The
+/// pass sees each talk as an experience entry: title as title,
+/// event as company, location as location, date as start.
+pub fn map_talks(talks: &[TalkItem], doc: &JoblDocument, pass: impl Fn(&JoblDocument) -> JoblDocument) -> Vec<TalkItem> {
+    let mut view = doc.clone();
+    view.projects = Vec::new();
+    view.education = Vec::new();
+    view.experience = talks
+        .iter()
+        .map(|talk| ExperienceItem {
+            title: talk.title.clone(),
+            company: talk.event.clone(),
+            location: talk.location.clone(),
+            start: talk.date.clone(),
+            end: None,
+            summary: None,
+            highlights: Vec::new(),
+            technologies: Vec::new(),
+        })
+        .collect();
+    pass(&view)
+        .experience
+        .into_iter()
+        .zip(talks)
+        .map(|(entry, talk)| TalkItem {
+            title: entry.title,
+            event: entry.company,
+            location: entry.location,
+            date: entry.start,
+            ..talk.clone()
+        })
+        .collect()
+}
+
+/// `patents` after a document-wide pass (emoji, budgets, dates). The
+/// pass sees each patent as an experience entry: title as title,
+/// status as company, number as location.
+pub fn map_patents(patents: &[PatentItem], doc: &JoblDocument, pass: impl Fn(&JoblDocument) -> JoblDocument) -> Vec<PatentItem> {
+    let mut view = doc.clone();
+    view.projects = Vec::new();
+    view.education = Vec::new();
+    view.experience = patents
+        .iter()
+        .map(|patent| ExperienceItem {
+            title: patent.title.clone(),
+            company: patent.status.clone().unwrap_or_default(),
+            location: patent.number.clone(),
+            start: None,
+            end: None,
+            summary: None,
+            highlights: Vec::new(),
+            technologies: Vec::new(),
+        })
+        .collect();
+    pass(&view)
+        .experience
+        .into_iter()
+        .zip(patents)
+        .map(|(entry, patent)| PatentItem {
+            title: entry.title,
+            status: patent.status.as_ref().map(|_| entry.company),
+            number: entry.location,
+            ..patent.clone()
+        })
+        .collect()
+}
+
+/// `fields` after a document-wide pass (emoji, budgets, dates). The
+/// pass sees each value as the title of an experience entry.
+pub fn map_person(fields: &PersonFields, doc: &JoblDocument, pass: impl Fn(&JoblDocument) -> JoblDocument) -> PersonFields {
+    let mut mapped = fields.clone();
+    let mut values: Vec<&mut String> = mapped.work_authorization.iter_mut().chain(mapped.clearance.iter_mut()).collect();
+    values.extend(mapped.meta.values_mut());
+
+    let mut view = doc.clone();
+    view.projects = Vec::new();
+    view.education = Vec::new();
+    view.experience = values
+        .iter()
+        .map(|value| ExperienceItem {
+            title: value.to_string(),
+            company: String::new(),
+            location: None,
+            start: None,
+            end: None,
+            summary: None,
+            highlights: Vec::new(),
+            technologies: Vec::new(),
+        })
+        .collect();
+    for (value, entry) in values.into_iter().zip(pass(&view).experience) {
+        *value = entry.title;
+    }
+    mapped
+}
+
+/// Item ids for one of srg's own sections: the section name and each
+/// item's title, numbered when two items share one.
+pub fn own_anchors<'a>(section: &str, titles: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut used: Vec<String> = Vec::new();
+    titles
+        .map(|title| {
+            let base = format!("{}-{}", section, slugify(title));
+            let mut candidate = base.clone();
+            let mut n = 2;
+            while used.contains(&candidate) {
+                candidate = format!("{}-{}", base, n);
+                n += 1;
+            }
+            used.push(candidate.clone());
+            candidate
+        })
+        .collect()
+}
+
+/// `source` without the alias sections' tables and srg's own
+/// sections and person fields, for JOBL to validate. `None` when there's nothing to
+/// take out.
+pub fn strip(source: &str) -> Result<Option<String>> {
+    let mut table: toml::Table = toml::from_str(source).context("Failed to read alias sections")?;
+    let mut stripped = false;
+    if let Some(specs) = table.remove("sections") {
+        stripped = true;
+        if let toml::Value::Table(specs) = specs {
+            for name in specs.keys() {
+                table.remove(name);
+            }
+        }
+    }
+    for name in OWN_SECTIONS {
+        stripped |= table.remove(*name).is_some();
+    }
+    if let Some(toml::Value::Table(person)) = table.get_mut("person") {
+        for name in PERSON_FIELDS {
+            stripped |= person.remove(*name).is_some();
+        }
+    }
+    if !stripped {
+        return Ok(None);
+    }
+    Ok(Some(toml::to_string(&table).context("Failed to write JOBL without alias sections")?))
+}
+
+/// "research-experience" -> "Research experience".
+fn default_title(name: &str) -> String {
+    let words = name.replace(['-', '_'], " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+[person]
+name = "Ada"
+
+[sections.research]
+as = "experience"
+title = "Research Experience"
+
+[sections.teaching-experience]
+as = "education"
+
+[[research]]
+title = "Graduate Researcher"
+company = "Vision Lab"
+highlights = ["Published at CVPR"]
+"#;
+
+    #[test]
+    fn reads_aliases_in_order() {
+        let sections = aliases(SOURCE).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].title, "Research Experience");
+        assert_eq!(sections[0].kind(), Kind::Experience);
+        assert_eq!(sections[0].labels(), vec!["Vision Lab"]);
+        assert_eq!(sections[1].title, "Teaching experience");
+        assert!(sections[1].is_empty());
+    }
+
+    #[test]
+    fn strips_alias_tables() {
+        let stripped = strip(SOURCE).unwrap().unwrap();
+        assert!(stripped.contains("[person]"));
+        assert!(!stripped.contains("research") && !stripped.contains("sections"));
+        assert_eq!(strip("[person]\nname = \"Ada\"\n").unwrap(), None);
+    }
+
+    #[test]
+    fn reads_and_strips_talks() {
+        let source = "[person]\nname = \"Ada\"\n\n[[talks]]\ntitle = \"Notes on Note G\"\nevent = \"RustConf\"\ndate = \"2024-09\"\n\n[[talks]]\ntitle = \"Notes on Note G\"\nevent = \"FOSDEM\"\n";
+        let talks = talks(source).unwrap();
+        assert_eq!(talks.len(), 2);
+        assert_eq!(talks[0].date.as_deref(), Some("2024-09"));
+        let anchors = own_anchors("talks", talks.iter().map(|t| t.title.as_str()));
+        assert_eq!(anchors, vec!["talks-notes-on-note-g", "talks-notes-on-note-g-2"]);
+
+        let stripped = strip(source).unwrap().unwrap();
+        assert!(stripped.contains("[person]") && !stripped.contains("talks"));
+        assert!(super::talks("[[talks]]\ntitle = \"No event\"\n").is_err());
+    }
+
+    #[test]
+    fn patent_years_may_be_numbers() {
+        let source = "[[patents]]\ntitle = \"Engine\"\nyear = 1843\n\n[[patents]]\ntitle = \"Loom\"\nyear = \"pending\"\n";
+        let patents = patents(source).unwrap();
+        assert_eq!(patents[0].year.as_deref(), Some("1843"));
+        assert_eq!(patents[1].year.as_deref(), Some("pending"));
+        assert_eq!(patents[1].number, None);
+        assert!(strip(source).unwrap().is_some());
+    }
+
+    #[test]
+    fn reads_and_strips_person_fields() {
+        let source = "[person]\nname = \"Ada\"\nclearance = \"Active TS/SCI\"\n";
+        let fields = person_fields(source).unwrap();
+        assert_eq!(fields.get("clearance").as_deref(), Some("Active TS/SCI"));
+        assert_eq!(fields.work_authorization, None);
+
+        let stripped = strip(source).unwrap().unwrap();
+        assert!(stripped.contains("name = \"Ada\"") && !stripped.contains("clearance"));
+        assert!(person_fields("[person]\nname = \"Ada\"\nclearance = 1\n").is_err());
+        assert!(person_fields("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn reads_person_meta() {
+        let source = "[person]\nname = \"Ada\"\n\n[person.meta]\npronouns = \"she/her\"\ndate_of_birth = 1815-12-10\nchildren = 3\n";
+        let fields = person_fields(source).unwrap();
+        assert_eq!(fields.get("pronouns").as_deref(), Some("she/her"));
+        assert_eq!(fields.get("date_of_birth").as_deref(), Some("1815-12-10"));
+        assert_eq!(fields.get("children").as_deref(), Some("3"));
+        assert!(!strip(source).unwrap().unwrap().contains("meta"));
+
+        assert!(person_fields("[person.meta]\nemail = \"a@b.c\"\n").is_err());
+        assert!(person_fields("[person.meta]\n\"date of birth\" = \"1815\"\n").is_err());
+        assert!(person_fields("[person.meta]\nlanguages = [\"en\"]\n").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_aliases() {
+        assert!(aliases("[sections.experience]\nas = \"projects\"\n").is_err());
+        assert!(aliases("[sections.talks]\nas = \"skills\"\n").is_err());
+        assert!(aliases("[sections.talks]\nas = \"projects\"\n[[talks]]\ntitle = 1\n").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! `srg send`: email the freshly built PDF.
+//!
+//! The message is composed here as a plain MIME document (text body
+//! plus the PDF attachment) and handed to `curl`, which already
+//! speaks SMTP with TLS and authentication on every platform srg
+//! targets. Credentials are passed to curl on stdin, never on its
+//! command line where other users could read them.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use jobl::JoblDocument;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::build::{get_person_field_value, slugify};
+use crate::config::SendConfig;
+
+const DEFAULT_SUBJECT: &str = "Resume - {name}";
+const DEFAULT_BODY: &str = "Hello,
+
+Please find my resume attached.
+
+Best regards,
+{name}
+{email}
+";
+
+/// Resolved settings for one send.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub smtp_url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Settings {
+    /// Combine the environment, the `[send]` table, and defaults,
+    /// in that order of precedence.
+    pub fn resolve(config: Option<&SendConfig>, doc: &JoblDocument) -> Result<Self> {
+        let pick = |env: &str, value: Option<&Option<String>>| {
+            std::env::var(env)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .or_else(|| value.cloned().flatten())
+        };
+
+        let smtp_url = pick("SRG_SMTP_URL", config.map(|c| &c.smtp_url)).context(
+            "No SMTP server configured. Set smtp_url under [send] in srg.toml or SRG_SMTP_URL",
+        )?;
+
+        let from = match pick("SRG_SMTP_FROM", config.map(|c| &c.from)) {
+            Some(from) => from,
+            None => match &doc.person.email {
+                Some(email) => format!("{} <{}>", doc.person.name, email),
+                None => anyhow::bail!(
+                    "No sender address. Set from under [send] in srg.toml, \
+                     SRG_SMTP_FROM, or person.email in the resume"
+                ),
+            },
+        };
+
+        Ok(Self {
+            smtp_url,
+            username: pick("SRG_SMTP_USERNAME", config.map(|c| &c.username)),
+            password: pick("SRG_SMTP_PASSWORD", config.map(|c| &c.password)),
+            from,
+            subject: config
+                .and_then(|c| c.subject.clone())
+                .unwrap_or_else(|| DEFAULT_SUBJECT.to_string()),
+            body: config
+                .and_then(|c| c.body.clone())
+                .unwrap_or_else(|| DEFAULT_BODY.to_string()),
+        })
+    }
+}
+
+/// Replace `{field}` placeholders with person fields from the resume.
+/// Fields without a value become empty; `{{` and `}}` are literal
+/// braces; anything else in braces is left as written.
+pub fn interpolate(template: &str, doc: &JoblDocument) -> String {
+    interpolate_with(template, doc, &|_| None)
+}
+
+/// [`interpolate`], with `extra` supplying values for placeholders
+/// that aren't person fields.
+pub fn interpolate_with(template: &str, doc: &JoblDocument, extra: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        if let (true, Some(end)) = (rest.starts_with('{'), rest.find('}')) {
+            let name = &rest[1..end];
+            let value = if is_person_field(name) {
+                Some(get_person_field_value(doc, name).unwrap_or_default())
+            } else {
+                extra(name)
+            };
+            if let Some(value) = value {
+                out.push_str(&value);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        out.push_str(&rest[..1]);
+        rest = &rest[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_person_field(name: &str) -> bool {
+    matches!(
+        name,
+        "name" | "headline" | "email" | "phone" | "location" | "website" | "github"
+            | "linkedin" | "summary"
+    )
+}
+
+/// Compose the full MIME message: headers, the text body, and the
+/// PDF as an attachment. Lines end in CRLF as SMTP requires. Leading
+/// dots aren't doubled here: curl does that as it sends.
+pub fn compose(settings: &Settings, to: &[String], doc: &JoblDocument, pdf: &[u8]) -> String {
+    let subject = interpolate(&settings.subject, doc);
+    let body = interpolate(&settings.body, doc);
+    let boundary = format!("srg-{:08x}", crc32fast::hash(pdf));
+    let filename = format!("{}-resume.pdf", slugify(&doc.person.name));
+
+    let mut lines: Vec<String> = vec![
+        format!("From: {}", one_line(&settings.from)),
+        format!("To: {}", to.iter().map(|to| one_line(to)).collect::<Vec<_>>().join(", ")),
+        format!("Subject: {}", encode_header(&one_line(&subject))),
+        "MIME-Version: 1.0".to_string(),
+        format!("Content-Type: multipart/mixed; boundary=\"{}\"", boundary),
+        String::new(),
+        format!("--{}", boundary),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+        "Content-Transfer-Encoding: 8bit".to_string(),
+        String::new(),
+    ];
+    lines.extend(body.lines().map(str::to_string));
+    lines.extend([
+        format!("--{}", boundary),
+        format!("Content-Type: application/pdf; name=\"{}\"", filename),
+        format!("Content-Disposition: attachment; filename=\"{}\"", filename),
+        "Content-Transfer-Encoding: base64".to_string(),
+        String::new(),
+    ]);
+    let encoded = base64::engine::general_purpose::STANDARD.encode(pdf);
+    lines.extend(
+        encoded
+            .as_bytes()
+            .chunks(76)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned()),
+    );
+    lines.push(format!("--{}--", boundary));
+
+    let mut message = lines.join("\r\n");
+    message.push_str("\r\n");
+    message
+}
+
+/// RFC 2047-encode a header value that isn't plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!(
+            "=?UTF-8?B?{}?=",
+            base64::engine::general_purpose::STANDARD.encode(value)
+        )
+    }
+}
+
+/// A header value on one line. A line break in a subject template or
+/// a resume field would otherwise start a header of its own.
+fn one_line(value: &str) -> String {
+    value.split(['\r', '\n']).map(str::trim).filter(|part| !part.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// The bare address from "Name <addr>" or "addr".
+fn address(mailbox: &str) -> &str {
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    }
+}
+
+/// Quote a value for a curl config file. Line breaks are escaped, as
+/// a raw one would end the option.
+fn curl_quote(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\r', "\\r").replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// The curl config (read from stdin via `-K -`) for one send.
+fn curl_config(settings: &Settings, to: &[String]) -> String {
+    let mut config = String::new();
+    config.push_str(&format!("url = {}\n", curl_quote(&settings.smtp_url)));
+    if let Some(username) = &settings.username {
+        let password = settings.password.as_deref().unwrap_or_default();
+        config.push_str(&format!(
+            "user = {}\n",
+            curl_quote(&format!("{}:{}", username, password))
+        ));
+    }
+    config.push_str(&format!("mail-from = {}\n", curl_quote(address(&settings.from))));
+    for rcpt in to {
+        config.push_str(&format!("mail-rcpt = {}\n", curl_quote(address(rcpt))));
+    }
+    config.push_str("ssl-reqd\nsilent\nshow-error\n");
+    config
+}
+
+/// Hand `message` to the SMTP server via curl.
+pub fn deliver(settings: &Settings, to: &[String], message: &str) -> Result<()> {
+    // The message goes through a temp file because stdin carries the
+    // config (and with it the password).
+    let mut file = tempfile::NamedTempFile::new().context("creating message file")?;
+    file.write_all(message.as_bytes())?;
+    file.flush()?;
+
+    let mut child = Command::new("curl")
+        .arg("-K")
+        .arg("-")
+        .arg("--upload-file")
+        .arg(file.path())
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl")?;
+    child
+        .stdin
+        .take()
+        .context("curl has no stdin")?
+        .write_all(curl_config(settings, to).as_bytes())?;
+
+    let status = child.wait().context("waiting for curl")?;
+    if !status.success() {
+        anyhow::bail!("curl exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> JoblDocument {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("resume.jobl");
+        std::fs::write(&path, "[person]\nname = \"Ada Lovelace\"\nemail = \"ada@example.com\"\n")
+            .unwrap();
+        jobl::parse_file(&path).unwrap()
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            smtp_url: "smtps://smtp.example.com:465".into(),
+            username: Some("ada".into()),
+            password: Some("p\"w".into()),
+            from: "Ada Lovelace <ada@example.com>".into(),
+            subject: DEFAULT_SUBJECT.into(),
+            body: DEFAULT_BODY.into(),
+        }
+    }
+
+    #[test]
+    fn interpolate_replaces_person_fields() {
+        let doc = doc();
+        assert_eq!(interpolate("Resume - {name}", &doc), "Resume - Ada Lovelace");
+        assert_eq!(interpolate("{headline}|{email}", &doc), "|ada@example.com");
+        assert_eq!(interpolate("{{name}} {other}", &doc), "{name} {other}");
+    }
+
+    #[test]
+    fn compose_attaches_pdf() {
+        let message = compose(&settings(), &["hr@example.com".to_string()], &doc(), b"%PDF-1.4");
+        assert!(message.contains("To: hr@example.com\r\n"));
+        assert!(message.contains("Subject: Resume - Ada Lovelace\r\n"));
+        assert!(message.contains("filename=\"ada-lovelace-resume.pdf\""));
+        assert!(message.contains("JVBERi0xLjQ=\r\n"));
+        assert!(message.ends_with("--\r\n"));
+    }
+
+    #[test]
+    fn compose_keeps_headers_to_one_line_each() {
+        let settings = Settings {
+            subject: "Resume\r\nBcc: everyone@example.com".into(),
+            body: "Hi,\n.\n.hidden".into(),
+            ..settings()
+        };
+        let message = compose(&settings, &["hr@example.com\nCc: x@example.com".to_string()], &doc(), b"%PDF-1.4");
+        assert!(message.contains("Subject: Resume Bcc: everyone@example.com\r\n"));
+        assert!(message.contains("To: hr@example.com Cc: x@example.com\r\n"));
+        assert!(!message.contains("\r\nBcc:") && !message.contains("\r\nCc:"));
+        // curl doubles leading dots itself.
+        assert!(message.contains("\r\nHi,\r\n.\r\n.hidden\r\n"));
+    }
+
+    #[test]
+    fn curl_config_quotes_credentials() {
+        let config = curl_config(&settings(), &["HR <hr@example.com>".to_string()]);
+        assert!(config.contains("user = \"ada:p\\\"w\"\n"));
+        assert!(config.contains("mail-from = \"ada@example.com\"\n"));
+        assert!(config.contains("mail-rcpt = \"hr@example.com\"\n"));
+    }
+}
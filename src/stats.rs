@@ -0,0 +1,144 @@
+//! Content statistics for a resume (`srg stats`).
+//!
+//! The interesting part is how the skills list lines up with the
+//! highlights: a skill that no bullet ever mentions is a claim
+//! without evidence, and a term that keeps coming up in bullets but
+//! isn't listed may be a skill worth declaring.
+
+use jobl::JoblDocument;
+use std::collections::BTreeMap;
+
+use crate::build::mentions_word;
+
+/// Words too common to say anything about the work.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "across", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be",
+    "been", "before", "between", "both", "but", "by", "can", "for", "from", "has", "have", "how",
+    "in", "into", "is", "it", "its", "more", "most", "new", "not", "of", "on", "one", "or",
+    "our", "out", "over", "per", "so", "such", "than", "that", "the", "their", "them", "then",
+    "these", "they", "this", "those", "through", "to", "two", "up", "using", "via", "was",
+    "we", "were", "which", "while", "who", "with", "within", "without",
+];
+
+/// `word` as a term worth counting: lowercased, without surrounding
+/// punctuation (`C++` and `C#` keep theirs). `None` for stopwords,
+/// single letters, and numbers.
+pub fn term(word: &str) -> Option<String> {
+    let term = word
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != '+' && c != '#')
+        .to_lowercase();
+    if term.chars().count() < 2 || STOPWORDS.contains(&term.as_str()) || term.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(term)
+}
+
+/// Counts and term usage for one resume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub roles: usize,
+    pub highlights: usize,
+    pub projects: usize,
+    pub skills: usize,
+    /// Words across highlights.
+    pub highlight_words: usize,
+    /// Terms used in highlights, most frequent first.
+    pub terms: Vec<(String, usize)>,
+    /// Every declared skill with the number of highlights naming it,
+    /// in the order the skills are listed.
+    pub coverage: Vec<SkillCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillCoverage {
+    pub category: String,
+    pub skill: String,
+    pub highlights: usize,
+}
+
+impl Stats {
+    pub fn new(doc: &JoblDocument) -> Self {
+        let highlights: Vec<&String> = doc.experience.iter().flat_map(|e| &e.highlights).collect();
+
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut highlight_words = 0;
+        for highlight in &highlights {
+            for word in highlight.split_whitespace() {
+                highlight_words += 1;
+                if let Some(term) = term(word) {
+                    *counts.entry(term).or_default() += 1;
+                }
+            }
+        }
+        let mut terms: Vec<(String, usize)> = counts.into_iter().collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let coverage: Vec<SkillCoverage> = doc
+            .skills
+            .iter()
+            .flatten()
+            .flat_map(|(category, skills)| {
+                skills.iter().map(|skill| SkillCoverage {
+                    category: category.clone(),
+                    skill: skill.clone(),
+                    highlights: highlights.iter().filter(|h| mentions_word(h, skill)).count(),
+                })
+            })
+            .collect();
+
+        Stats {
+            roles: doc.experience.len(),
+            highlights: highlights.len(),
+            projects: doc.projects.len(),
+            skills: coverage.len(),
+            highlight_words,
+            terms,
+            coverage,
+        }
+    }
+
+    /// Declared skills no highlight mentions.
+    pub fn unevidenced(&self) -> impl Iterator<Item = &SkillCoverage> {
+        self.coverage.iter().filter(|c| c.highlights == 0)
+    }
+
+    /// Whether `term` (as counted in [`Stats::terms`]) is a declared
+    /// skill.
+    pub fn is_skill(&self, term: &str) -> bool {
+        self.coverage.iter().any(|c| c.skill.eq_ignore_ascii_case(term))
+    }
+
+    /// The report `srg stats` prints, with the `top` most frequent
+    /// terms.
+    pub fn report(&self, top: usize) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{} roles, {} highlights ({} words), {} projects, {} skills\n",
+            self.roles, self.highlights, self.highlight_words, self.projects, self.skills
+        ));
+
+        if !self.terms.is_empty() {
+            out.push_str("\nMost frequent terms in highlights (* = listed skill):\n");
+            for (term, count) in self.terms.iter().take(top) {
+                let mark = if self.is_skill(term) { "*" } else { " " };
+                out.push_str(&format!("  {} {:<24} {}\n", mark, term, count));
+            }
+        }
+
+        if !self.coverage.is_empty() {
+            out.push_str("\nSkills coverage (highlights mentioning each skill):\n");
+            for c in &self.coverage {
+                let note = if c.highlights == 0 { "  <- never mentioned in a highlight" } else { "" };
+                out.push_str(&format!("  {:<24} {:<16} {}{}\n", c.skill, c.category, c.highlights, note));
+            }
+            let missing = self.unevidenced().count();
+            if missing > 0 {
+                out.push_str(&format!(
+                    "\n{} of {} skills have no highlight backing them up.\n",
+                    missing, self.skills
+                ));
+            }
+        }
+        out
+    }
+}
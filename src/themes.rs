@@ -6,5 +6,320 @@
 //! and exposed via `fonts_for`. Adding a new theme is a matter
 //! of dropping the directory into `src/layouts/` and rebuilding.
 //! No registration code to edit.
+//!
+//! Themes can also be layered. A theme's optional `theme.toml` may
+//! declare `extends = "minimal"`, and a theme spec on the command
+//! line may stack themes with `+` (`minimal+compact`). Layers apply
+//! base first: the topmost layer with a layout wins, CSS is
+//! concatenated so overlays override the base, and fonts are merged.
+//! Only the first theme of a stack brings its `extends` chain; the
+//! rest are overlays on it, so `classic+compact` is classic made
+//! compact, not classic under compact's base.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
 include!(concat!(env!("OUT_DIR"), "/themes.rs"));
+
+/// On-disk shape of a theme's `theme.toml`. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeManifest {
+    /// Theme this one builds on. Its layout, CSS, and fonts are
+    /// inherited; this theme's files are applied on top.
+    pub extends: Option<String>,
+
+    /// Typography knobs (`body-font = "..."`), exposed to the CSS as
+    /// `--srg-<name>` and overridable from srg.toml.
+    #[serde(default)]
+    pub typography: BTreeMap<String, String>,
+
+    /// PDF print defaults for the theme (`[pdf]`).
+    #[serde(default)]
+    pub pdf: PrintSettings,
+
+    /// Marker for highlights and education details (`bullets =
+    /// "dash"`). Unset keeps whatever the CSS does.
+    pub bullets: Option<crate::build::Bullets>,
+}
+
+/// PDF print settings from a theme's `[pdf]` table or srg.toml's.
+/// Unset fields fall through to the next source: CLI flags, then
+/// srg.toml, then the layout, then the theme.
+///
+/// ```toml
+/// [pdf]
+/// paper = "a4"
+/// margin = "0.4in 0.5in"
+/// scale = 0.95
+/// background = false
+/// links = "url"
+/// continued = true
+/// max_pages = 2
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrintSettings {
+    pub paper: Option<crate::build::Paper>,
+    /// CSS-style shorthand, one to four lengths (see
+    /// [`crate::build::Margins::parse`]).
+    pub margin: Option<String>,
+    /// Scale factor, 0.1 to 2.
+    pub scale: Option<f64>,
+    /// Whether background colors and images are printed.
+    pub background: Option<bool>,
+    /// How links print (see [`crate::build::PrintLinks`]).
+    pub links: Option<crate::build::PrintLinks>,
+    /// Whether a section's title repeats, marked "(continued)", on
+    /// each page the section runs onto.
+    pub continued: Option<bool>,
+    /// Pages the PDF should fit on, checked against the laid-out page.
+    pub max_pages: Option<usize>,
+}
+
+impl PrintSettings {
+    /// These settings, with `fallback` filling in the unset ones.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            paper: self.paper.or(fallback.paper),
+            margin: self.margin.or(fallback.margin),
+            scale: self.scale.or(fallback.scale),
+            background: self.background.or(fallback.background),
+            links: self.links.or(fallback.links),
+            continued: self.continued.or(fallback.continued),
+            max_pages: self.max_pages.or(fallback.max_pages),
+        }
+    }
+
+    /// Check the margin and scale, naming `source` in the error.
+    pub fn check(&self, source: &str) -> Result<()> {
+        if let Some(margin) = &self.margin {
+            crate::build::Margins::parse(margin).map_err(|e| anyhow::anyhow!("pdf.margin in {}: {}", source, e))?;
+        }
+        if let Some(scale) = self.scale {
+            crate::build::check_scale(scale).map_err(|e| anyhow::anyhow!("pdf.scale in {}: {}", source, e))?;
+        }
+        if self.max_pages == Some(0) {
+            anyhow::bail!("pdf.max_pages in {}: must be at least 1", source);
+        }
+        Ok(())
+    }
+}
+
+impl ThemeManifest {
+    /// Parse the bundled manifest for `theme`, or the default if the
+    /// theme doesn't ship one.
+    pub fn for_theme(theme: &str) -> Result<Self> {
+        match manifest_for(theme) {
+            Some(body) => toml::from_str(body)
+                .with_context(|| format!("parsing theme.toml for theme {}", theme)),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// A theme spec resolved into its layers, base first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeStack {
+    pub layers: Vec<String>,
+}
+
+impl ThemeStack {
+    /// Resolve a spec like `minimal+compact` into layers, expanding
+    /// the first theme's `extends` chain. The themes after it are
+    /// overlays and go on as they are. A layer that's already present
+    /// is only applied once.
+    pub fn resolve(spec: &str) -> Result<Self> {
+        let mut layers = Vec::new();
+        for (i, name) in spec.split('+').map(str::trim).filter(|s| !s.is_empty()).enumerate() {
+            if i == 0 {
+                push_with_bases(name, &mut layers, &mut Vec::new())?;
+            } else if !THEMES.contains(&name) {
+                anyhow::bail!("Unknown theme: {}", name);
+            } else if !layers.iter().any(|l| l == name) {
+                layers.push(name.to_string());
+            }
+        }
+        if layers.is_empty() {
+            anyhow::bail!("Unknown theme: {}", spec);
+        }
+        Ok(Self { layers })
+    }
+
+    /// Layout text from the topmost layer that has one.
+    pub fn layout(&self) -> Option<&'static str> {
+        self.layers.iter().rev().find_map(|name| layout_for(name))
+    }
+
+    /// Concatenated CSS of every layer, base first, followed by the
+    /// stack's typography defaults.
+    pub fn css(&self) -> Result<String> {
+        let mut css = String::new();
+        for name in &self.layers {
+            if let Some(layer_css) = css_for(name) {
+                if !css.is_empty() {
+                    css.push_str(&format!("\n\n/* Theme: {} */\n", name));
+                }
+                css.push_str(layer_css);
+            }
+        }
+        let vars: Vec<_> = self
+            .typography()?
+            .into_iter()
+            .map(|(name, value)| (format!("--srg-{}", name), value))
+            .collect();
+        css.push_str(&crate::css::root_rule("Typography defaults", &vars));
+        Ok(css)
+    }
+
+    /// Typography knobs of every layer; a later layer's value wins.
+    pub fn typography(&self) -> Result<BTreeMap<String, String>> {
+        let mut typography = BTreeMap::new();
+        for name in &self.layers {
+            for (key, value) in ThemeManifest::for_theme(name)?.typography {
+                crate::css::check_value(&value)
+                    .map_err(|e| anyhow::anyhow!("typography.{} in theme {}: {}", key, name, e))?;
+                typography.insert(key, value);
+            }
+        }
+        Ok(typography)
+    }
+
+    /// PDF print settings of every layer; a later layer's value wins.
+    pub fn pdf(&self) -> Result<PrintSettings> {
+        let mut pdf = PrintSettings::default();
+        for name in &self.layers {
+            let layer = ThemeManifest::for_theme(name)?.pdf;
+            layer.check(&format!("theme {}", name))?;
+            pdf = layer.or(pdf);
+        }
+        Ok(pdf)
+    }
+
+    /// The topmost layer's bullet style, if any layer sets one.
+    pub fn bullets(&self) -> Result<Option<crate::build::Bullets>> {
+        let mut bullets = None;
+        for name in &self.layers {
+            bullets = ThemeManifest::for_theme(name)?.bullets.or(bullets);
+        }
+        Ok(bullets)
+    }
+
+    /// Fonts from every layer. A later layer's file replaces an
+    /// earlier layer's file at the same relative path.
+    pub fn fonts(&self) -> Vec<(&'static str, &'static [u8])> {
+        let mut fonts: Vec<(&'static str, &'static [u8])> = Vec::new();
+        for name in &self.layers {
+            for &(rel, bytes) in fonts_for(name) {
+                fonts.retain(|(existing, _)| *existing != rel);
+                fonts.push((rel, bytes));
+            }
+        }
+        fonts
+    }
+}
+
+fn push_with_bases(name: &str, layers: &mut Vec<String>, visiting: &mut Vec<String>) -> Result<()> {
+    if !THEMES.contains(&name) {
+        anyhow::bail!("Unknown theme: {}", name);
+    }
+    if visiting.iter().any(|v| v == name) {
+        anyhow::bail!("Theme {} extends itself (via {})", name, visiting.join(" -> "));
+    }
+    if layers.iter().any(|l| l == name) {
+        return Ok(());
+    }
+
+    visiting.push(name.to_string());
+    if let Some(base) = ThemeManifest::for_theme(name)?.extends {
+        push_with_bases(&base, layers, visiting)?;
+    }
+    visiting.pop();
+
+    layers.push(name.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_single_theme() {
+        let stack = ThemeStack::resolve("minimal").unwrap();
+        assert_eq!(stack.layers, vec!["minimal"]);
+        assert!(stack.layout().is_some());
+    }
+
+    #[test]
+    fn resolve_expands_extends_once() {
+        let stack = ThemeStack::resolve("minimal+compact").unwrap();
+        assert_eq!(stack.layers, vec!["minimal", "compact"]);
+
+        let stack = ThemeStack::resolve("compact").unwrap();
+        assert_eq!(stack.layers, vec!["minimal", "compact"]);
+    }
+
+    #[test]
+    fn later_themes_are_overlays() {
+        let stack = ThemeStack::resolve("classic+compact").unwrap();
+        assert_eq!(stack.layers, vec!["classic", "compact"]);
+        assert_eq!(stack.layout(), layout_for("classic"));
+        assert!(!stack.css().unwrap().contains("/* Theme: minimal */"));
+    }
+
+    #[test]
+    fn overlay_inherits_layout_and_appends_css() {
+        let stack = ThemeStack::resolve("compact").unwrap();
+        assert_eq!(stack.layout(), layout_for("minimal"));
+
+        let css = stack.css().unwrap();
+        let base = css.find(css_for("minimal").unwrap()).unwrap();
+        let overlay = css.find("/* Theme: compact */").unwrap();
+        assert!(base < overlay);
+    }
+
+    #[test]
+    fn typography_defaults_follow_the_stack() {
+        let stack = ThemeStack::resolve("compact").unwrap();
+        let typography = stack.typography().unwrap();
+        assert_eq!(typography["accent-rule"], "1px solid #ccc");
+
+        let stack = ThemeStack::resolve("minimal+classic").unwrap();
+        assert_eq!(stack.typography().unwrap()["accent-rule"], "1px solid #000");
+        assert!(stack.css().unwrap().contains("  --srg-accent-rule: 1px solid #000;\n"));
+    }
+
+    #[test]
+    fn pdf_settings_follow_the_stack() {
+        let stack = ThemeStack::resolve("minimal").unwrap();
+        assert_eq!(stack.pdf().unwrap(), PrintSettings::default());
+
+        let stack = ThemeStack::resolve("jakes-resume+compact").unwrap();
+        let pdf = stack.pdf().unwrap();
+        assert_eq!(pdf.margin.as_deref(), Some("0.5in"));
+        assert_eq!(pdf.paper, Some(crate::build::Paper::Letter));
+
+        let config: PrintSettings = toml::from_str("continued = true").unwrap();
+        assert_eq!(config.or(pdf).continued, Some(true));
+
+        let config: PrintSettings = toml::from_str("max_pages = 0").unwrap();
+        assert!(config.check("srg.toml").is_err());
+    }
+
+    #[test]
+    fn bullets_follow_the_stack() {
+        let stack = ThemeStack::resolve("minimal").unwrap();
+        assert_eq!(stack.bullets().unwrap(), None);
+
+        let stack = ThemeStack::resolve("jakes-resume+compact").unwrap();
+        assert_eq!(stack.bullets().unwrap(), Some(crate::build::Bullets::Disc));
+    }
+
+    #[test]
+    fn resolve_rejects_unknown_theme() {
+        let err = ThemeStack::resolve("minimal+nope").unwrap_err();
+        assert!(err.to_string().contains("Unknown theme: nope"));
+    }
+}
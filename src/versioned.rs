@@ -0,0 +1,131 @@
+//! Versioned output directories (`--versioned`).
+//!
+//! Instead of overwriting `dist/` on every build, each build goes to
+//! its own `dist/<date>_<id>/` directory and `dist/latest` is pointed
+//! at it: a symlink where the platform allows one, otherwise a copy.
+//! The id is the short git commit when the resume's directory is
+//! committed and clean, and a hash of the JOBL file otherwise, so a
+//! rebuild of the same content lands in the same place while any
+//! change gets a new directory. A layered build (several `--input`
+//! files) counts as clean only when every file's directory is, at
+//! the same commit, and otherwise hashes every file in order.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Name of the entry pointing at the newest build.
+pub const LATEST: &str = "latest";
+
+/// Directory name for a build of `inputs` today, e.g.
+/// `2025-06-12_abc1234`.
+pub fn dir_name(inputs: &[PathBuf]) -> Result<String> {
+    let commits: Option<Vec<String>> = inputs.iter().map(|input| git_commit(input)).collect();
+    let id = match commits {
+        Some(commits) if !commits.is_empty() && commits.iter().all(|sha| *sha == commits[0]) => commits[0].clone(),
+        _ => content_hash(inputs)?,
+    };
+    Ok(format!("{}_{}", crate::dates::today(), id))
+}
+
+/// A short hash of every input's content, in order. One file hashes
+/// as its bytes alone, as it always has.
+fn content_hash(inputs: &[PathBuf]) -> Result<String> {
+    let mut hash = md5::Context::new();
+    for (i, input) in inputs.iter().enumerate() {
+        if i > 0 {
+            hash.consume(b"\0");
+        }
+        hash.consume(fs::read(input).with_context(|| format!("reading {}", input.display()))?);
+    }
+    Ok(format!("{:x}", hash.compute())[..7].to_string())
+}
+
+/// The short commit of the repository holding `input`, if its
+/// directory has no uncommitted changes. Missing git counts as no
+/// repository.
+fn git_commit(input: &Path) -> Option<String> {
+    let dir = input.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let git = |args: &[&str]| -> Option<String> {
+        let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let status = git(&["status", "--porcelain", "--", "."])?;
+    if !status.is_empty() {
+        return None;
+    }
+    git(&["rev-parse", "--short", "HEAD"]).filter(|sha| !sha.is_empty())
+}
+
+/// Point `base/latest` at `base/name`, replacing whatever `latest`
+/// was before. Returns the path of `latest`.
+pub fn update_latest(base: &Path, name: &str) -> Result<PathBuf> {
+    let latest = base.join(LATEST);
+    match fs::symlink_metadata(&latest) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(&latest),
+        Ok(_) => fs::remove_file(&latest),
+        Err(_) => Ok(()),
+    }
+    .with_context(|| format!("removing {}", latest.display()))?;
+
+    #[cfg(unix)]
+    if std::os::unix::fs::symlink(name, &latest).is_ok() {
+        return Ok(latest);
+    }
+
+    copy_dir(&base.join(name), &latest)
+        .with_context(|| format!("copying build to {}", latest.display()))?;
+    Ok(latest)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let dest = to.join(path.file_name().unwrap_or_default());
+        if path.is_dir() {
+            copy_dir(&path, &dest)?;
+        } else {
+            fs::copy(&path, &dest)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_follows_the_newest_build() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in ["2025-06-11_aaaaaaa", "2025-06-12_bbbbbbb"] {
+            fs::create_dir_all(dir.path().join(name)).unwrap();
+            fs::write(dir.path().join(name).join("index.html"), name).unwrap();
+            update_latest(dir.path(), name).unwrap();
+        }
+
+        let latest = fs::read_to_string(dir.path().join(LATEST).join("index.html")).unwrap();
+        assert_eq!(latest, "2025-06-12_bbbbbbb");
+        assert!(dir.path().join("2025-06-11_aaaaaaa/index.html").exists());
+    }
+
+    #[test]
+    fn every_layer_changes_the_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let layers = [dir.path().join("base.jobl"), dir.path().join("freelance.jobl")];
+        fs::write(&layers[0], "[person]\nname = \"Ada\"\n").unwrap();
+        fs::write(&layers[1], "[person]\nheadline = \"Analyst\"\n").unwrap();
+        let before = content_hash(&layers).unwrap();
+        assert_ne!(before, content_hash(&layers[..1]).unwrap());
+
+        fs::write(&layers[1], "[person]\nheadline = \"Consultant\"\n").unwrap();
+        assert_ne!(content_hash(&layers).unwrap(), before);
+        assert_eq!(content_hash(&layers[..1]).unwrap(), format!("{:x}", md5::compute(fs::read(&layers[0]).unwrap()))[..7]);
+    }
+}
@@ -10,6 +10,8 @@ fn create_test_document() -> JoblDocument {
       phone: Some("555-1234".to_string()),
       location: Some("Test City".to_string()),
       website: Some("https://example.com".to_string()),
+      github: None,
+      linkedin: None,
       summary: Some("Test summary".to_string()),
     },
     skills: Some({
@@ -50,7 +52,7 @@ person
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   assert!(html.contains("Test User"));
   assert!(!html.contains("test@example.com"));
@@ -58,6 +60,68 @@ person
   assert!(!html.contains("Software Engineer"));
 }
 
+#[test]
+fn test_unused_content() {
+  let doc = create_test_document();
+
+  let theme = srg::layout::Layout::from_theme("minimal").unwrap();
+  assert!(srg::lint::unused(&doc, &theme, &[]).is_empty());
+
+  let layout = srg::layout::Layout::parse("person\n  name\n").unwrap();
+  let findings = srg::lint::unused(&doc, &layout, &["education".to_string()]);
+  let locations: Vec<&str> = findings.iter().map(|f| f.location.as_str()).collect();
+  assert_eq!(
+    locations,
+    [
+      "summary",
+      "person.headline",
+      "person.email",
+      "person.phone",
+      "person.location",
+      "person.website",
+      "skills",
+      "experience"
+    ]
+  );
+}
+
+#[test]
+fn test_structured_data() {
+  let layout = srg::layout::Layout::parse("person\n  name\n  email\nexperience\n  title\n").unwrap();
+  let mut doc = create_test_document();
+  doc.experience[0].end = None;
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  let start = html.find("<script type=\"application/ld+json\">").unwrap();
+  let json = &html[start..].split_once('>').unwrap().1;
+  let json: serde_json::Value = serde_json::from_str(json.split_once("</script>").unwrap().0).unwrap();
+  assert_eq!(json["@type"], "Person");
+  assert_eq!(json["name"], "Test User");
+  assert_eq!(json["email"], "test@example.com");
+  assert_eq!(json["worksFor"][0]["name"], "Test Co");
+  // Not on the page, so not in the data.
+  assert!(json.get("telephone").is_none() && json.get("jobTitle").unwrap() == "Engineer");
+}
+
+#[test]
+fn test_microformats() {
+  let layout = srg::layout::Layout::parse(
+    "person\n  name\n  email\nexperience\n  title\n  company\n  highlights\nskills\n",
+  )
+  .unwrap();
+  let mut doc = create_test_document();
+  doc.skills = Some([("Languages".to_string(), vec!["Rust".to_string(), "Go".to_string()])].into());
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains("<main class=\"h-resume\">"));
+  assert!(html.contains("p-contact h-card"));
+  assert!(html.contains("class=\"person-name p-name\""));
+  assert!(html.contains("class=\"person-email u-email\""));
+  assert!(html.contains("class=\"experience-item p-experience h-event\">"));
+  assert!(html.contains("class=\"experience-company p-org\""));
+  assert!(html.contains("<span class=\"p-skill\">Rust</span>, <span class=\"p-skill\">Go</span>"));
+}
+
 #[test]
 fn test_full_person_section() {
   let layout_content = r#"
@@ -74,7 +138,7 @@ person
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   assert!(html.contains("Test User"));
   assert!(html.contains("Software Engineer"));
@@ -98,7 +162,7 @@ experience
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   let education_pos = html.find("Education").unwrap();
   let experience_pos = html.find("Experience").unwrap();
@@ -118,7 +182,7 @@ experience
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   assert!(html.contains("2020 - 2024"));
 }
@@ -134,7 +198,7 @@ experience
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   assert!(html.contains("<ul"));
   assert!(html.contains("Built stuff"));
@@ -151,7 +215,7 @@ summary
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   assert!(html.contains("Summary"));
   assert!(html.contains("Test summary"));
@@ -167,9 +231,1671 @@ skills
   let doc = create_test_document();
 
   let html =
-    srg::build::generate_test_html(&doc, "minimal", &layout).unwrap();
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
 
   assert!(html.contains("Skills"));
   assert!(html.contains("Languages"));
   assert!(html.contains("Rust"));
 }
+
+#[test]
+fn test_field_classes_on_builtin_fields() {
+  let layout_content = r#"
+person
+  name
+  accent: headline
+
+experience
+  lead: title
+  meta:
+    dates: start " - " end
+"#;
+
+  let layout = srg::layout::Layout::parse(layout_content).unwrap();
+  let doc = create_test_document();
+
+  let html =
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
+
+  assert!(html.contains("class=\"person-name p-name\""));
+  assert!(html.contains("class=\"person-headline accent p-job-title\""));
+  assert!(html.contains("class=\"experience-title lead p-name\""));
+  assert!(html.contains("<div class=\"meta\">"));
+  assert!(html.contains("class=\"dates\""));
+}
+
+#[test]
+fn test_element_overrides() {
+  let layout_content = r#"
+person
+  h2: name
+  small: location
+
+experience
+  h4.role: title
+  ol: highlights
+"#;
+
+  let layout = srg::layout::Layout::parse(layout_content).unwrap();
+  let doc = create_test_document();
+
+  let html =
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
+
+  assert!(html.contains("<h2 class=\"person-name p-name\">Test User</h2>"));
+  assert!(html.contains("<small class=\"person-location p-locality\">Test City</small>"));
+  assert!(html.contains("<h4 class=\"experience-title role p-name\">Engineer</h4>"));
+  assert!(html.contains("<ol class=\"experience-highlights e-description\">"));
+}
+
+#[test]
+fn test_item_anchors_and_toc() {
+  let layout_content = r#"
+toc
+
+experience
+  title
+
+education
+  degree
+"#;
+
+  let layout = srg::layout::Layout::parse(layout_content).unwrap();
+  let mut doc = create_test_document();
+  let mut second = doc.experience[0].clone();
+  second.title = "Senior Engineer".to_string();
+  doc.experience.push(second);
+
+  let html =
+    srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
+
+  assert!(html.contains("<div id=\"test-co\" class=\"experience-item p-experience h-event\">"));
+  assert!(html.contains("<div id=\"test-co-2\" class=\"experience-item p-experience h-event\">"));
+  assert!(html.contains("<div id=\"test-u\" class=\"education-item p-education h-event\">"));
+
+  assert!(html.contains("<nav id=\"toc\""));
+  assert!(html.contains("<a href=\"#experience\">Experience</a>"));
+  assert!(html.contains("<a href=\"#test-co-2\">Test Co</a>"));
+  assert!(html.contains("<a href=\"#education\">Education</a>"));
+  assert!(!html.contains("href=\"#skills\""));
+}
+
+#[test]
+fn test_slugify() {
+  assert_eq!(srg::build::slugify("Acme Corp."), "acme-corp");
+  assert_eq!(srg::build::slugify("  R&D -- Labs "), "r-d-labs");
+  assert_eq!(srg::build::slugify("???"), "item");
+}
+
+#[test]
+fn test_outline_follows_layout() {
+  use srg::build::outline::{Block, Outline};
+
+  let layout = srg::layout::Layout::parse(
+    r#"
+experience
+  title
+  start " - " end
+  highlights
+
+person
+  name
+  email
+"#,
+  )
+  .unwrap();
+  let outline = Outline::build(&create_test_document(), &layout, &Default::default());
+
+  assert_eq!(outline.sections.len(), 2);
+  assert_eq!(outline.sections[0].name, "experience");
+  let job = &outline.sections[0].items[0];
+  assert_eq!(job.heading.as_deref(), Some("Engineer"));
+  assert_eq!(job.anchor.as_deref(), Some("test-co"));
+  assert_eq!(
+    job.blocks,
+    vec![
+      Block::Text("2020 - 2024".to_string()),
+      Block::List(vec!["Built stuff".to_string()]),
+    ]
+  );
+  assert_eq!(outline.name(), Some("Test User"));
+}
+
+#[test]
+fn test_resolved_document_drives_html_and_outline() {
+  use srg::build::outline::{Block, Outline};
+  use srg::build::resolved::{Resolved, Role};
+
+  let layout = srg::layout::Layout::parse("experience\n  small.when: start \" - \" end\n  highlights\n").unwrap();
+  let doc = create_test_document();
+  let resolved = Resolved::build(&doc, &layout, &Default::default());
+
+  let section = &resolved.sections[0];
+  assert_eq!((section.name.as_str(), section.title.as_deref()), ("experience", Some("Experience")));
+  let job = &section.nodes[0];
+  assert_eq!(job.id.as_deref(), Some("test-co"));
+  assert!(matches!(&job.role, Role::Item { start: Some(start), .. } if start == "2020"));
+  let dates = &job.children()[0];
+  assert_eq!((dates.element.as_str(), dates.classes.clone()), ("small", vec!["when".to_string()]));
+  assert_eq!(dates.text_content(), "2020 - 2024");
+
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(html.contains("<small class=\"when\">2020 - 2024</small>"));
+  let outline = Outline::from_resolved(&resolved);
+  assert_eq!(outline.sections[0].items[0].blocks[0], Block::Text("2020 - 2024".to_string()));
+}
+
+#[test]
+fn test_email_html_is_inline_styled() {
+  use srg::build::OutputFormat;
+
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let html = srg::build::render_export(OutputFormat::EmailHtml, &create_test_document(), &layout, &Default::default())
+    .unwrap();
+
+  assert!(!html.contains("<style"));
+  assert!(!html.contains("<table"));
+  assert!(!html.contains("class="));
+  let styled_tags = ["<h1 ", "<h2 ", "<h3 ", "<p ", "<ul ", "<li ", "<a "];
+  for line in html.lines().filter(|l| styled_tags.iter().any(|t| l.contains(t))) {
+    assert!(line.contains("style=\""), "unstyled: {}", line);
+  }
+  assert!(html.contains("Test User</h1>"));
+  assert!(html.contains("<strong>Languages:</strong> Rust"));
+  assert!(html.contains("Built stuff</li>"));
+  assert!(srg::build::render_export(OutputFormat::Pdf, &create_test_document(), &layout, &Default::default()).is_none());
+}
+
+#[test]
+fn test_gemtext_export() {
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let gmi = srg::build::render_export(
+    srg::build::OutputFormat::Gemtext,
+    &create_test_document(),
+    &layout,
+    &Default::default(),
+  )
+  .unwrap();
+
+  assert!(gmi.starts_with("# Test User\nSoftware Engineer\n"));
+  assert!(gmi.contains("=> https://example.com\n"));
+  assert!(gmi.contains("## Experience\n\n### Engineer\nTest Co\n2020 - 2024\n"));
+  assert!(gmi.contains("* Built stuff\n"));
+  assert!(gmi.contains("Languages: Rust\n"));
+}
+
+#[test]
+fn test_org_export() {
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let org = srg::build::render_export(
+    srg::build::OutputFormat::Org,
+    &create_test_document(),
+    &layout,
+    &Default::default(),
+  )
+  .unwrap();
+
+  assert!(org.starts_with("#+TITLE: Test User\n"));
+  assert!(org.contains("[[https://example.com]]\n"));
+  assert!(org.contains(
+    "* Experience\n** Engineer\n:PROPERTIES:\n:CUSTOM_ID: test-co\n:START_DATE: 2020\n:END_DATE: 2024\n:END:\n"
+  ));
+  assert!(org.contains("- Built stuff\n"));
+  assert!(org.contains("- Languages :: Rust\n"));
+}
+
+#[test]
+fn test_rst_export() {
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let rst = srg::build::render_export(
+    srg::build::OutputFormat::Rst,
+    &create_test_document(),
+    &layout,
+    &Default::default(),
+  )
+  .unwrap();
+
+  assert!(rst.starts_with("=========\nTest User\n=========\n\nSoftware Engineer\n\n| test@example.com\n"));
+  assert!(rst.contains("| https://example.com\n"));
+  assert!(rst.contains("Experience\n==========\n\n.. _test-co:\n\nEngineer\n--------\n\nTest Co\n\n2020 - 2024\n"));
+  assert!(rst.contains("- Built stuff\n"));
+  assert!(rst.contains(":Languages: Rust\n"));
+}
+
+#[test]
+fn test_markdown_export() {
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let md = srg::build::render_export(
+    srg::build::OutputFormat::Markdown,
+    &create_test_document(),
+    &layout,
+    &Default::default(),
+  )
+  .unwrap();
+
+  assert!(md.starts_with("# Test User\n\nSoftware Engineer\n\n"));
+  assert!(md.contains("test@example.com · 555-1234 · Test City · <https://example.com>\n"));
+  assert!(md.contains("## Experience\n\n### Engineer\n\nTest Co\n\n2020 - 2024\n\nDid things\n\n- Built stuff\n"));
+  assert!(md.contains("- **Languages:** Rust\n"));
+}
+
+#[test]
+fn test_rtf_export() {
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let rtf = srg::build::render_export(
+    srg::build::OutputFormat::Rtf,
+    &create_test_document(),
+    &layout,
+    &Default::default(),
+  )
+  .unwrap();
+
+  assert!(rtf.starts_with("{\\rtf1\\ansi"));
+  assert!(rtf.contains("{\\info{\\title Test User}{\\author Test User}}"));
+  assert!(rtf.contains("\\paperw12240\\paperh15840"));
+  assert!(rtf.contains("\\b\\fs42 Test User\\par}"));
+  assert!(rtf.contains("{\\*\\bkmkstart test-co}Engineer{\\*\\bkmkend test-co}\\par}"));
+  assert!(rtf.contains("\\bullet\\tab Built stuff\\par}"));
+  assert!(rtf.contains("{\\cs7\\b Languages:} Rust\\par}"));
+  assert!(rtf.is_ascii());
+}
+
+#[test]
+fn test_txt_export() {
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let txt = srg::build::render_export(
+    srg::build::OutputFormat::Txt,
+    &create_test_document(),
+    &layout,
+    &Default::default(),
+  )
+  .unwrap();
+
+  assert!(txt.starts_with("TEST USER\nSoftware Engineer\ntest@example.com\n"));
+  assert!(txt.contains("EXPERIENCE\n----------\nEngineer\nTest Co\n2020 - 2024\nDid things\n- Built stuff\n"));
+  assert!(txt.contains("Languages: Rust\n"));
+  assert!(!txt.contains('<'));
+}
+
+#[test]
+fn test_project_extras_as_layout_fields() {
+  let mut doc = create_test_document();
+  doc.projects.push(jobl::ProjectItem {
+    name: "srg".to_string(),
+    url: Some("https://github.com/ducks/srg".to_string()),
+    summary: None,
+    role: None,
+    start: None,
+    end: None,
+    technologies: Vec::new(),
+  });
+  let layout = srg::layout::Layout::parse(
+    r#"
+projects
+  name
+  stars
+  language " · " stars " stars"
+"#,
+  )
+  .unwrap();
+  let mut options = srg::build::BuildOptions::default();
+  options.extras.projects = vec![[
+    ("stars".to_string(), "42".to_string()),
+    ("language".to_string(), "Rust".to_string()),
+  ]
+  .into_iter()
+  .collect()];
+
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("<span class=\"projects-stars\">42</span>"));
+  assert!(html.contains("Rust · 42 stars"));
+
+  let txt =
+    srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(txt.contains("srg\n42\nRust · 42 stars\n"));
+}
+
+#[test]
+fn test_avatar_field() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse(
+    r#"
+person
+  avatar
+  name
+"#,
+  )
+  .unwrap();
+
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("person-avatar"));
+
+  let mut options = srg::build::BuildOptions::default();
+  options.extras.avatar = Some("data:image/png;base64,AAAA".to_string());
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains(
+    "<img class=\"person-avatar u-photo\" src=\"data:image/png;base64,AAAA\" alt=\"Test User\">"
+  ));
+}
+
+#[test]
+fn test_grouped_experience() {
+  let mut doc = create_test_document();
+  let mut promotion = doc.experience[0].clone();
+  promotion.title = "Senior Engineer".to_string();
+  doc.experience.insert(0, promotion);
+  let mut other = doc.experience[0].clone();
+  other.company = "Other Co".to_string();
+  doc.experience.push(other);
+
+  let layout = srg::layout::Layout::parse(
+    r#"
+experience grouped
+  h3: company
+  h4: title
+"#,
+  )
+  .unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert_eq!(html.matches("class=\"experience-group\"").count(), 2);
+  assert_eq!(html.matches("<h3 class=\"experience-company\">Test Co</h3>").count(), 1);
+  assert!(html.contains("<div id=\"test-co\" class=\"experience-item experience-role p-experience h-event\">"));
+  assert!(html.contains("<div id=\"test-co-2\" class=\"experience-item experience-role p-experience h-event\">"));
+  assert_eq!(html.matches("<h4 class=\"experience-title p-name\">").count(), 3);
+}
+
+#[test]
+fn test_functional_experience() {
+  let mut doc = create_test_document();
+  doc.experience[0].technologies = vec!["Go".to_string(), "Rust".to_string()];
+  doc.experience[0].highlights = vec![
+    "Rewrote the parser in Rust".to_string(),
+    "Made good dashboards".to_string(),
+  ];
+  let mut other = doc.experience[0].clone();
+  other.company = "Other Co".to_string();
+  other.technologies = vec![];
+  other.highlights = vec!["Mentored".to_string()];
+  doc.experience.push(other);
+
+  let grouped: Vec<(String, Vec<String>)> = srg::build::highlights_by_skill(&doc)
+    .into_iter()
+    .map(|(skill, items)| (skill, items.into_iter().map(|(_, h)| h.clone()).collect()))
+    .collect();
+  assert_eq!(
+    grouped,
+    vec![
+      ("Rust".to_string(), vec!["Rewrote the parser in Rust".to_string()]),
+      ("Go".to_string(), vec!["Made good dashboards".to_string()]),
+      ("Other".to_string(), vec!["Mentored".to_string()]),
+    ]
+  );
+
+  let layout = srg::layout::Layout::parse(
+    r#"
+experience functional
+  title
+  company
+  highlights
+"#,
+  )
+  .unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(html.contains("<h3 class=\"experience-skill-name\">Rust</h3>"));
+  assert!(html.contains(
+    "<li>Mentored <span class=\"experience-source\">(Engineer, Other Co)</span></li>"
+  ));
+  assert!(html.contains("<div class=\"experience-history\">"));
+  // Highlights appear only under their skills, not again in the history.
+  assert_eq!(html.matches("class=\"experience-highlights\"").count(), 3);
+}
+
+#[test]
+fn test_jakes_resume_theme() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("jakes-resume").unwrap();
+  let html = srg::build::generate_test_html(&doc, Some("jakes-resume"), &layout).unwrap();
+
+  assert!(html.contains("font-variant: small-caps"));
+  let education = html.find("id=\"education\"").unwrap();
+  let experience = html.find("id=\"experience\"").unwrap();
+  assert!(education < experience);
+  assert!(html.contains("<div class=\"row\">\n          <strong class=\"experience-title p-name\">Engineer</strong>"));
+  assert!(html.contains("2020 – 2024</span>"));
+}
+
+#[test]
+fn test_density_sets_css_variable() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+
+  let html = srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
+  assert!(html.contains("var(--srg-density, 1)"));
+  assert!(!html.contains("srg settings"));
+
+  let options = srg::build::BuildOptions {
+    density: srg::build::Density::Compact,
+    ..Default::default()
+  };
+  let html =
+    srg::build::generate_test_html_with_options(&doc, Some("minimal"), &layout, &options).unwrap();
+  let settings = html.find("--srg-density: 0.6;").unwrap();
+  assert!(settings > html.find("var(--srg-density, 1)").unwrap());
+}
+
+#[test]
+fn test_font_size_and_line_height_variables() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("classic").unwrap();
+  let options = srg::build::BuildOptions {
+    font_size: Some("10.5pt".to_string()),
+    line_height: Some("1.25".to_string()),
+    ..Default::default()
+  };
+  let html =
+    srg::build::generate_test_html_with_options(&doc, Some("classic"), &layout, &options).unwrap();
+
+  assert!(html.contains("font-size: var(--srg-font-size, 11pt);"));
+  assert!(html.contains("  --srg-font-size: 10.5pt;\n  --srg-line-height: 1.25;\n"));
+  assert!(!html.contains("--srg-density:"));
+}
+
+#[test]
+fn test_typography_overrides_follow_theme_defaults() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let options = srg::build::BuildOptions {
+    typography: [("heading-font".to_string(), "Georgia, serif".to_string())].into_iter().collect(),
+    ..Default::default()
+  };
+  let html =
+    srg::build::generate_test_html_with_options(&doc, Some("minimal"), &layout, &options).unwrap();
+
+  let default = html.find("  --srg-heading-font: var(--srg-body-font);").unwrap();
+  let overridden = html.find("  --srg-heading-font: Georgia, serif;").unwrap();
+  assert!(default < overridden);
+}
+
+#[test]
+fn test_emoji_policy_per_format() {
+  use srg::build::emoji::{apply_text, EmojiPolicy};
+  use srg::build::OutputFormat;
+
+  assert_eq!(apply_text("🚀 Shipped v2 🎉", EmojiPolicy::Strip), "Shipped v2");
+  assert_eq!(apply_text("📧 me@example.com", EmojiPolicy::Replace), "Email: me@example.com");
+  assert_eq!(apply_text("Built 👩‍💻 tools", EmojiPolicy::Strip), "Built tools");
+  assert_eq!(apply_text("José ✓ ©", EmojiPolicy::Strip), "José ©");
+
+  let mut doc = create_test_document();
+  doc.experience[0].highlights = vec!["🚀 Built stuff".to_string()];
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let mut options = srg::build::BuildOptions::default();
+
+  let txt = srg::build::render_export(OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(txt.contains("Built stuff") && !txt.contains('🚀'));
+  let md = srg::build::render_export(OutputFormat::Markdown, &doc, &layout, &options).unwrap();
+  assert!(md.contains("🚀 Built stuff"));
+
+  options.emoji.insert(OutputFormat::Txt, EmojiPolicy::Keep);
+  let txt = srg::build::render_export(OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(txt.contains("🚀 Built stuff"));
+}
+
+#[test]
+fn test_ascii_text_export() {
+  use srg::build::OutputFormat;
+
+  let mut doc = create_test_document();
+  doc.person.name = "José Müller".to_string();
+  doc.experience[0].highlights = vec!["Built “fast” tools — twice".to_string()];
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let options = srg::build::BuildOptions { ascii: true, ..Default::default() };
+
+  let txt = srg::build::render_export(OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(txt.is_ascii());
+  assert!(txt.contains("JOSE MULLER"));
+  assert!(txt.contains("Built \"fast\" tools -- twice"));
+
+  // Other exports are left alone.
+  let md = srg::build::render_export(OutputFormat::Markdown, &doc, &layout, &options).unwrap();
+  assert!(md.contains("José Müller"));
+
+  let changes = srg::build::ascii_changes(&doc, &layout, &options);
+  assert!(changes.contains(&("JOSÉ".to_string(), "JOSE".to_string())));
+  assert!(changes.contains(&("“fast”".to_string(), "\"fast\"".to_string())));
+}
+
+#[test]
+fn test_script_font_fallbacks() {
+  let mut doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let html = srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
+  assert!(!html.contains("Script fallbacks"));
+
+  doc.person.name = "山田 太郎".to_string();
+  doc.person.location = Some("दिल्ली".to_string());
+  let scripts: Vec<&str> = srg::build::scripts::detect(&doc).iter().map(|s| s.name).collect();
+  assert_eq!(scripts, vec!["CJK", "Devanagari"]);
+
+  let html = srg::build::generate_test_html(&doc, Some("minimal"), &layout).unwrap();
+  let fallbacks = &html[html.find("/* Script fallbacks */").unwrap()..];
+  assert!(fallbacks.contains("--srg-body-font: -apple-system,"));
+  assert!(fallbacks.contains("\"Noto Sans CJK SC\""));
+  assert!(fallbacks.contains("\"Noto Sans Devanagari\""));
+  // heading-font follows body-font through var(), so it isn't repeated.
+  assert!(!fallbacks.contains("--srg-heading-font"));
+}
+
+#[test]
+fn test_section_accents() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse(
+    r#"
+experience accent=navy
+  title
+
+education accent=#0d9488
+  degree
+"#,
+  )
+  .unwrap();
+  let options = srg::build::BuildOptions {
+    accents: [("education".to_string(), "teal".to_string())].into_iter().collect(),
+    ..Default::default()
+  };
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains(".section-experience {\n  --srg-accent: navy;\n}"));
+  assert!(html.contains(".section-education {\n  --srg-accent: teal;\n}"));
+  assert!(!html.contains("#0d9488;"));
+
+  let options = srg::build::BuildOptions {
+    accents: [("projects".to_string(), "red; } body { x: y".to_string())].into_iter().collect(),
+    ..Default::default()
+  };
+  assert!(srg::build::generate_test_html_with_options(&doc, None, &layout, &options).is_err());
+}
+
+#[test]
+fn test_grayscale_print_page() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("experience accent=navy\n  title\n").unwrap();
+  let options = srg::build::BuildOptions {
+    grayscale: true,
+    css_mode: srg::build::CssMode::External,
+    ..Default::default()
+  };
+
+  let print = srg::build::generate_test_print_html(&doc, Some("minimal"), &layout, &options).unwrap();
+  assert!(print.contains("<style>"));
+  assert!(print.contains("--srg-accent: #090909;"));
+  assert!(print.contains("img { filter: grayscale(1); }"));
+
+  let options = srg::build::BuildOptions {
+    css_mode: srg::build::CssMode::Inline,
+    ..options
+  };
+  let web = srg::build::generate_test_html_with_options(&doc, Some("minimal"), &layout, &options).unwrap();
+  assert!(web.contains("--srg-accent: navy;"));
+  assert!(!web.contains("filter: grayscale"));
+}
+
+#[test]
+fn test_bullets() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("jake").unwrap();
+  let html = srg::build::generate_test_html(&doc, Some("jake"), &layout).unwrap();
+  assert!(!html.contains("/* Bullets"));
+
+  let options = srg::build::BuildOptions {
+    bullets: Some(srg::build::Bullets::None),
+    ..Default::default()
+  };
+  let html = srg::build::generate_test_html_with_options(&doc, Some("jake"), &layout, &options).unwrap();
+  let rules = html.find("/* Bullets: none */").unwrap();
+  assert!(rules > html.find(".experience-highlights {").unwrap());
+  assert!(html.contains("  list-style: none;\n  padding-left: 0;"));
+  assert!(html.contains(".education-details > li {\n  padding-left: 1em;\n  text-indent: -1em;"));
+
+  let print = srg::build::generate_test_print_html(&doc, Some("jake"), &layout, &options).unwrap();
+  assert!(print.contains("/* Bullets: none */"));
+
+  let options = srg::build::BuildOptions {
+    bullets: Some(srg::build::Bullets::Dash),
+    ..Default::default()
+  };
+  let html = srg::build::generate_test_html_with_options(&doc, Some("jake"), &layout, &options).unwrap();
+  assert!(html.contains("list-style-type: \"\\2013\\00a0\";"));
+}
+
+#[test]
+fn test_print_links() {
+  let doc = srg::contract::sample_document();
+  let layout = srg::layout::Layout::parse("toc\nperson\n  website\n  github\nprojects\n  name\n  url\n").unwrap();
+  let print = |links| {
+    let options = srg::build::BuildOptions { links, ..Default::default() };
+    srg::build::generate_test_print_html(&doc, None, &layout, &options).unwrap()
+  };
+
+  // A build prints the PDF from this page, not index.html, whenever
+  // links are set.
+  let differs = |links| srg::build::BuildOptions { links, ..Default::default() }.pdf_page_differs();
+  assert!(!differs(None));
+  assert!(differs(Some(srg::build::PrintLinks::Strip)));
+
+  let page = print(None);
+  assert!(page.contains("<a class=\"person-github u-url\" href=\"https://github.com/ada\">"));
+  assert!(!page.contains("/* Links"));
+
+  let page = print(Some(srg::build::PrintLinks::Strip));
+  assert!(!page.contains("href="));
+  assert!(page.contains("<span class=\"person-github u-url\">https://github.com/ada</span>"));
+
+  // Links that already show their address, and links within the
+  // page, get nothing added.
+  let page = print(Some(srg::build::PrintLinks::Url));
+  assert!(page.contains("href=\"#projects\""));
+  assert!(!page.contains("srg-link-url"));
+
+  let page = print(Some(srg::build::PrintLinks::Plain));
+  assert!(page.contains("/* Links: plain */\na {\n  color: inherit;\n  text-decoration: none;"));
+  let page = print(Some(srg::build::PrintLinks::Underline));
+  assert!(page.contains("/* Links: underline */\na {\n  text-decoration: underline;"));
+
+  // The page itself keeps its links as they are.
+  let options = srg::build::BuildOptions { links: Some(srg::build::PrintLinks::Strip), ..Default::default() };
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("href="));
+}
+
+#[test]
+fn test_watermark() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("person\n  name\n").unwrap();
+  let options = srg::build::BuildOptions {
+    watermark: Some("DRAFT <v2>".to_string()),
+    ..Default::default()
+  };
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains("<div class=\"srg-watermark\" aria-hidden=\"true\">DRAFT &lt;v2&gt;</div>"));
+  assert!(html.contains("@media print {\n  .srg-watermark {"));
+
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("srg-watermark"));
+}
+
+#[test]
+fn test_footer_template_escapes_text() {
+  let footer = srg::build::footer_template("Confidential — <internal>");
+  assert!(footer.contains("Confidential — &lt;internal&gt;"));
+  assert!(footer.contains("font-size: 8px"));
+}
+
+#[test]
+fn test_verification_stamp() {
+  let doc = create_test_document();
+  let verify = srg::build::Verification::new("https://example.com/resume/", &doc).unwrap();
+  assert_eq!(verify.hash.len(), 8);
+  assert_eq!(verify, srg::build::Verification::new(" https://example.com/resume/ ", &doc).unwrap());
+  assert!(srg::build::Verification::new("example.com", &doc).is_err());
+
+  let mut changed = create_test_document();
+  changed.person.name.push('!');
+  assert_ne!(srg::build::Verification::new("https://example.com/resume/", &changed).unwrap().hash, verify.hash);
+
+  let footer = srg::build::verify_footer_template(Some("Confidential"), &verify);
+  assert!(footer.contains("Current version: https://example.com/resume/"));
+  assert!(footer.contains(&verify.hash));
+  assert!(footer.contains("<svg"));
+  assert!(footer.contains("<span>Confidential</span>"));
+
+  let layout = srg::layout::Layout::parse("person\n  name\n").unwrap();
+  let options = srg::build::BuildOptions { verify: Some(verify.clone()), ..Default::default() };
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains(&format!("<footer class=\"srg-verify\">Version {}</footer>", verify.hash)));
+}
+
+#[test]
+fn test_history_changes() {
+  use srg::build::history::{changes, generate_history_html, Revision};
+
+  let old = create_test_document();
+  let mut new = old.clone();
+  new.experience[0].highlights.push("Cut build times in half".to_string());
+  new.experience.push(ExperienceItem {
+    title: "Staff Engineer".to_string(),
+    company: "Initech".to_string(),
+    location: None,
+    start: Some("2024-01".to_string()),
+    end: None,
+    summary: None,
+    highlights: vec![],
+    technologies: vec![],
+  });
+  new.education.clear();
+
+  assert_eq!(changes(None, &old), vec!["First version"]);
+  assert_eq!(changes(Some(&old), &old), vec!["No content changes"]);
+
+  let lines = changes(Some(&old), &new);
+  let role = format!("{} at {}", old.experience[0].title, old.experience[0].company);
+  assert!(lines.contains(&format!("{}: 1 highlight added", role)));
+  assert!(lines.contains(&"Added role: Staff Engineer at Initech".to_string()));
+  assert!(lines.iter().any(|l| l.starts_with("Removed education: ")));
+
+  let revisions = vec![
+    Revision { commit: "a1".into(), date: "2025-01-02".into(), subject: "Start".into(), doc: Some(old) },
+    Revision { commit: "b2".into(), date: "2025-06-12".into(), subject: "New <job>".into(), doc: Some(new.clone()) },
+  ];
+  let layout = srg::layout::Layout::parse("person\n  name\n").unwrap();
+  let html =
+    generate_history_html(&new, &revisions, None, &layout, None, &srg::build::BuildOptions::default()).unwrap();
+  assert!(html.contains("New &lt;job&gt;"));
+  assert!(html.find("2025-06-12").unwrap() < html.find("2025-01-02").unwrap());
+  assert!(html.contains("<li>First version</li>"));
+}
+
+#[test]
+fn test_stats_skill_coverage() {
+  let mut doc = create_test_document();
+  doc.experience[0].highlights = vec![
+    "Rewrote the billing service in Rust, cutting latency 40%".to_string(),
+    "Moved deploys to Kubernetes; latency alerts dropped".to_string(),
+  ];
+  doc.skills = Some(
+    [("Languages".to_string(), vec!["Rust".to_string(), "Go".to_string()])]
+      .into_iter()
+      .collect(),
+  );
+
+  let stats = srg::stats::Stats::new(&doc);
+  assert_eq!(stats.highlights, 2);
+  assert_eq!(stats.terms[0], ("latency".to_string(), 2));
+  assert!(stats.is_skill("rust"));
+  let missing: Vec<&str> = stats.unevidenced().map(|c| c.skill.as_str()).collect();
+  assert_eq!(missing, vec!["Go"]);
+  assert!(stats.report(5).contains("1 of 2 skills have no highlight backing them up."));
+}
+
+#[test]
+fn test_compare_variants() {
+  use srg::compare::{default_keywords, render_html, Variant};
+
+  let long = create_test_document();
+  let mut short = long.clone();
+  short.education.clear();
+  short.experience[0].highlights.push("Ran Kubernetes clusters".to_string());
+
+  let keywords = vec!["Kubernetes".to_string(), "stuff".to_string()];
+  let a = Variant::new("long", &long, &keywords);
+  let b = Variant::new("short", &short, &keywords);
+  assert!(a.sections.contains(&"education"));
+  assert!(!b.sections.contains(&"education"));
+  assert_eq!(a.keywords, vec![false, true]);
+  assert_eq!(b.coverage(), 100);
+  assert!(b.words > a.words - 10);
+
+  let html = render_html(&[a, b], &keywords);
+  assert!(html.contains("<th>long</th><th>short</th>"));
+  assert!(html.contains("<tr class=\"coverage\"><th>Keyword coverage</th><td>50%</td><td>100%</td></tr>"));
+
+  let mut skilled = long.clone();
+  skilled.skills = Some([("Ops".to_string(), vec!["Terraform".to_string()])].into_iter().collect());
+  assert_eq!(default_keywords(&[&long, &skilled]), vec!["Rust", "Terraform"]);
+}
+
+#[test]
+fn test_layout_variables_render() {
+  let doc = create_test_document();
+  let mut layout = srg::layout::Layout::parse("person\n  name\n  target: \"Applying for \" $role\n").unwrap();
+  let vars = [("role".to_string(), "Platform <Engineer>".to_string())].into_iter().collect();
+  layout.resolve_variables(&vars).unwrap();
+
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(html.contains("Applying for Platform &lt;Engineer&gt;"));
+
+  let options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Txt],
+    ..Default::default()
+  };
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(text.contains("Applying for Platform <Engineer>"));
+}
+
+#[test]
+fn test_date_format() {
+  let mut doc = create_test_document();
+  doc.experience[0].start = Some("2020-03".to_string());
+  doc.experience[0].end = Some("Present".to_string());
+  let layout = srg::layout::Layout::parse("---\ndate_format = \"%b %Y\"\n---\nexperience\n  start \" - \" end\n").unwrap();
+  let options = srg::build::BuildOptions {
+    date_format: layout.settings.date_format.clone(),
+    ..Default::default()
+  };
+
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("Mar 2020 - Present"));
+  let text = srg::build::render_export(srg::build::OutputFormat::Markdown, &doc, &layout, &options).unwrap();
+  assert!(text.contains("Mar 2020 - Present"));
+}
+
+#[test]
+fn test_localized_dates() {
+  let mut doc = create_test_document();
+  doc.experience[0].start = Some("2020-03".to_string());
+  doc.experience[0].end = Some("Present".to_string());
+  let layout = srg::layout::Layout::parse("---
+locale = \"fr\"\n---\nexperience\n  dates\n").unwrap();
+  let options = srg::build::BuildOptions { locale: layout.settings.locale, ..Default::default() };
+
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("<html lang=\"fr\">"));
+  assert!(html.contains("mars 2020 – aujourd&#39;hui"));
+  let text = srg::build::render_export(srg::build::OutputFormat::Markdown, &doc, &layout, &options).unwrap();
+  assert!(text.contains("mars 2020 – aujourd'hui"));
+}
+
+#[test]
+fn test_pdf_margins_shorthand() {
+  use srg::build::Margins;
+  let all = Margins::parse("0.5in").unwrap();
+  assert_eq!(all, Margins { top: 0.5, right: 0.5, bottom: 0.5, left: 0.5 });
+
+  let pair = Margins::parse("72pt 25.4mm").unwrap();
+  assert_eq!(pair, Margins { top: 1.0, right: 1.0, bottom: 1.0, left: 1.0 });
+
+  let three = Margins::parse("0 1in 48px").unwrap();
+  assert_eq!(three, Margins { top: 0.0, right: 1.0, bottom: 0.5, left: 1.0 });
+
+  assert!(Margins::parse("1em").is_err());
+  assert!(Margins::parse("1in 1in 1in 1in 1in").is_err());
+  assert!(Margins::parse("").is_err());
+}
+
+#[test]
+fn test_write_atomic_replaces_without_leftovers() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("resume.pdf");
+  std::fs::write(&path, "old").unwrap();
+
+  srg::build::write_atomic(&path, "new").unwrap();
+  assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+
+  let names: Vec<_> = std::fs::read_dir(dir.path())
+    .unwrap()
+    .map(|e| e.unwrap().file_name().into_string().unwrap())
+    .collect();
+  assert_eq!(names, vec!["resume.pdf"]);
+
+  // A missing directory fails before anything is touched.
+  assert!(srg::build::write_atomic(&dir.path().join("nope/resume.pdf"), "x").is_err());
+}
+
+#[test]
+fn test_file_url() {
+  use srg::build::file_url;
+  assert_eq!(file_url("/home/ada/My Resume/index.html"), "file:///home/ada/My%20Resume/index.html");
+  assert_eq!(file_url("/tmp/résumé#1.html"), "file:///tmp/r%C3%A9sum%C3%A9%231.html");
+  assert_eq!(
+    file_url(r"\\?\C:\Users\Ada Lovelace\dist\index.html"),
+    "file:///C:/Users/Ada%20Lovelace/dist/index.html"
+  );
+  assert_eq!(file_url(r"C:\dist\index.html"), "file:///C:/dist/index.html");
+  assert_eq!(file_url(r"\\?\UNC\server\share\cv\index.html"), "file://server/share/cv/index.html");
+  assert_eq!(file_url(r"\\server\share\index.html"), "file://server/share/index.html");
+}
+
+#[test]
+fn test_standalone_print_page_inlines_fonts() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("jake").unwrap();
+  let options = srg::build::BuildOptions::default();
+
+  let page = srg::build::standalone_print_page(&doc, Some("jake"), &layout, None, &options).unwrap();
+  assert!(page.contains("url(\"data:font/woff2;base64,"));
+  assert!(!page.contains("url(\"fonts/"));
+  assert!(!page.contains("<link rel=\"stylesheet\""));
+}
+
+#[test]
+fn test_budget_truncates_at_render_time() {
+  let mut doc = create_test_document();
+  doc.experience[0].highlights = vec!["Cut deploy time from forty minutes to six by caching builds".to_string()];
+  let layout = srg::layout::Layout::parse("experience\n  highlights\n").unwrap();
+  let budgets = srg::budget::Budgets {
+    highlights: Some(srg::budget::Budget { words: Some(5), chars: None }),
+    ..Default::default()
+  };
+  assert_eq!(srg::budget::overruns(&doc, &budgets).len(), 1);
+
+  let options = srg::build::BuildOptions { budgets: budgets.clone(), ..Default::default() };
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("forty minutes to six"));
+
+  let budgets = srg::budget::Budgets { truncate: true, ..budgets };
+  let options = srg::build::BuildOptions { budgets, ..Default::default() };
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("Cut deploy time from forty…"));
+  assert!(!html.contains("minutes to six"));
+}
+
+#[test]
+fn test_skill_category_order() {
+  let mut doc = create_test_document();
+  let skills: BTreeMap<String, Vec<String>> = [
+    ("Tools".to_string(), vec!["Git".to_string()]),
+    ("Languages".to_string(), vec!["Rust".to_string()]),
+    ("Cloud".to_string(), vec!["AWS".to_string()]),
+  ]
+  .into_iter()
+  .collect();
+  doc.skills = Some(skills);
+  let order = |html: &str| -> Vec<usize> {
+    ["Cloud:", "Languages:", "Tools:"].iter().map(|c| html.find(c).unwrap()).collect()
+  };
+
+  let layout = srg::layout::Layout::parse("skills\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  let at = order(&html);
+  assert!(at[0] < at[1] && at[1] < at[2]);
+
+  let source = "[skills]\nTools = [\"Git\"]\nLanguages = [\"Rust\"]\nCloud = [\"AWS\"]\n";
+  let mut options = srg::build::BuildOptions::default();
+  options.extras.skill_order = srg::build::skill_categories_in(source).unwrap();
+  assert_eq!(options.extras.skill_order, vec!["Tools", "Languages", "Cloud"]);
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  let at = order(&html);
+  assert!(at[2] < at[1] && at[1] < at[0]);
+
+  let layout = srg::layout::Layout::parse("skills\n  \"Languages\"\n").unwrap();
+  let text = srg::build::render_export(srg::build::OutputFormat::Markdown, &doc, &layout, &options).unwrap();
+  let at = order(&text.replace("**", ""));
+  assert!(at[1] < at[2] && at[2] < at[0]);
+}
+
+#[test]
+fn test_contact_row() {
+  let mut doc = create_test_document();
+  doc.person.phone = None;
+  let layout = srg::layout::Layout::parse("person\n  contactrow email phone location website\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  let divider = "<span class=\"contact-divider\" aria-hidden=\"true\"></span>";
+  assert!(html.contains("<p class=\"contact-row\">"));
+  assert_eq!(html.matches(divider).count(), 2);
+  assert!(html.contains("<a class=\"person-website u-url\" href=\"https://example.com\">"));
+  assert!(html.contains("content: var(--srg-contact-divider, \"·\");"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &Default::default()).unwrap();
+  assert!(text.contains("test@example.com · Test City · https://example.com"));
+
+  doc.person.email = None;
+  doc.person.location = None;
+  doc.person.website = None;
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("contact-row"));
+}
+
+#[test]
+fn test_split_rows() {
+  let layout = srg::layout::Layout::parse(
+    "experience\n  split title | start \" - \" end\n  split company | location\n",
+  )
+  .unwrap();
+  let doc = create_test_document();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains(
+    "<div class=\"split-row\">\n          <span class=\"split-left\">Engineer</span>\n          <span class=\"split-right\">2020 - 2024</span>"
+  ));
+  // No location: the company row has only its left side.
+  assert!(html.contains("<div class=\"split-row\">\n          <span class=\"split-left\">Test Co</span>\n        </div>"));
+  assert!(html.contains(".split-right {\n  margin-left: auto;"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &Default::default()).unwrap();
+  assert!(text.contains("Engineer · 2020 - 2024\nTest Co\n"));
+}
+
+#[test]
+fn test_layout_helpers() {
+  let mut doc = create_test_document();
+  doc.person.phone = Some("+44 20 7946 0958".to_string());
+  doc.person.website = Some("https://www.example.com/".to_string());
+  let layout = srg::layout::Layout::parse("person\n  contactrow local(phone) short(website)\n  short(website)\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains("<span class=\"person-phone p-tel\">020 7946 0958</span>"));
+  // The link still goes to the full address.
+  assert!(html.contains("href=\"https://www.example.com/\">example.com</a>"));
+  assert!(!html.contains(">https://www.example.com/</a>"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &Default::default()).unwrap();
+  assert!(text.contains("020 7946 0958 · example.com"));
+}
+
+#[test]
+fn test_mixed_fields_drop_dangling_separators() {
+  let mut doc = create_test_document();
+  doc.person.phone = None;
+  doc.experience[0].end = None;
+  let layout = srg::layout::Layout::parse(
+    "person\n  contact: location \" | \" phone\n  email \" | \" github\n\nexperience\n  start \" - \" end\n",
+  )
+  .unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains("<p class=\"contact\">Test City</p>"));
+  assert!(html.contains("<p>test@example.com</p>"));
+  assert!(html.contains("<p>2020</p>"));
+  assert!(!html.contains(" | "));
+  assert!(!html.contains(" - "));
+}
+
+#[test]
+fn test_item_ranges_compress_older_roles() {
+  let mut doc = create_test_document();
+  let mut older = doc.experience[0].clone();
+  older.title = "Junior Engineer".to_string();
+  older.summary = Some("Older role".to_string());
+  older.highlights = vec!["Old highlight".to_string()];
+  doc.experience.push(older);
+  let layout = srg::layout::Layout::parse("experience\n  title\n  [1] highlights\n  [2-] summary\n").unwrap();
+
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(html.contains("Built stuff"));
+  assert!(!html.contains("Old highlight"));
+  assert!(html.contains("Older role"));
+  assert!(!html.contains("Did things"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Markdown, &doc, &layout, &Default::default()).unwrap();
+  assert!(text.contains("Built stuff") && text.contains("Older role"));
+  assert!(!text.contains("Old highlight") && !text.contains("Did things"));
+}
+
+#[test]
+fn test_alias_sections() {
+  let doc = create_test_document();
+  let source = r#"
+[sections.research]
+as = "experience"
+title = "Research Experience"
+
+[[research]]
+title = "Graduate Researcher"
+company = "Test Co"
+start = "2019-09"
+highlights = ["Published <papers>"]
+"#;
+  let options = srg::build::BuildOptions {
+    date_format: Some("%b %Y".to_string()),
+    extras: srg::build::Extras {
+      sections: srg::sections::aliases(source).unwrap(),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let layout = srg::layout::Layout::parse("toc\n\nexperience\n  title\n\nresearch\n  title\n  start\n  highlights\n").unwrap();
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains("<section id=\"research\" class=\"section section-experience section-research\">"));
+  assert!(html.contains("<h2>Research Experience</h2>"));
+  assert!(html.contains("<div id=\"research-test-co\" class=\"experience-item p-experience h-event\">"));
+  assert!(html.contains("<div id=\"test-co\" class=\"experience-item p-experience h-event\">"));
+  assert!(html.contains("<a href=\"#research\">Research Experience</a>"));
+  assert!(html.contains("Sep 2019"));
+  assert!(html.contains("Published &lt;papers&gt;"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Markdown, &doc, &layout, &options).unwrap();
+  assert!(text.contains("Research Experience"));
+  assert!(text.contains("Graduate Researcher"));
+  assert!(text.contains("Sep 2019"));
+}
+
+#[test]
+fn test_work_authorization_and_clearance() {
+  let doc = create_test_document();
+  let source = "[person]\nname = \"Test\"\nwork_authorization = \"US citizen\"\nclearance = \"Active TS/SCI\"\n";
+  let options = srg::build::BuildOptions {
+    extras: srg::build::Extras {
+      person: srg::sections::person_fields(source).unwrap(),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let layout = srg::layout::Layout::parse("person\n  name\n  work_authorization\n  clearance\n  contactrow email clearance\n").unwrap();
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains("<p class=\"person-work_authorization\">US citizen</p>"));
+  assert!(html.contains("<p class=\"person-clearance\">Active TS/SCI</p>"));
+  assert!(html.contains("<span class=\"person-clearance\">Active TS/SCI</span>"));
+
+  // A layout that leaves them out shows neither.
+  let layout = srg::layout::Layout::parse("person\n  name\n  email\n").unwrap();
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(!html.contains("TS/SCI"));
+}
+
+#[test]
+fn test_person_meta_fields() {
+  let doc = create_test_document();
+  let source = "[person]\nname = \"Test\"\n\n[person.meta]\npronouns = \"they/them\"\nnationality = \"German\"\n";
+  let options = srg::build::BuildOptions {
+    extras: srg::build::Extras {
+      person: srg::sections::person_fields(source).unwrap(),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let layout = srg::layout::Layout::parse("person\n  name \" (\" pronouns \")\"\n  nationality\n  missing\n").unwrap();
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains("<p>Test User (they/them)</p>"));
+  assert!(html.contains("<p class=\"person-nationality\">German</p>"));
+  assert!(!html.contains("person-missing"));
+}
+
+#[test]
+fn test_linked_skills() {
+  let mut doc = create_test_document();
+  doc.skills.as_mut().unwrap().get_mut("Languages").unwrap().push("Haskell".to_string());
+  doc.experience[0].technologies = vec!["rust".to_string(), "Tokio".to_string()];
+  let layout = srg::layout::Layout::parse("skills linked\n\nexperience\n  title\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains("<a class=\"skills-link p-skill\" href=\"#test-co\" data-skill=\"rust\">Rust</a>, <span class=\"p-skill\">Haskell</span>"));
+  assert!(html.contains("<div id=\"test-co\" class=\"experience-item p-experience h-event\" data-skills=\"rust tokio\">"));
+  assert!(html.contains("<script>") && html.contains(".skill-match"));
+  assert!(srg::markup::check(&html).is_empty());
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &Default::default()).unwrap();
+  assert!(text.contains("Languages: Rust, Haskell"));
+
+  // Plain `skills` stays plain text, without the script.
+  let layout = srg::layout::Layout::parse("skills\n\nexperience\n  title\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("skills-link") && !html.contains("data-skills") && !html.contains("<script>"));
+}
+
+#[test]
+fn test_collapsed_entries() {
+  let mut doc = create_test_document();
+  for company in ["Second Co", "Third Co"] {
+    let mut exp = doc.experience[0].clone();
+    exp.company = company.to_string();
+    doc.experience.push(exp);
+  }
+  let layout = srg::layout::Layout::parse("experience collapse=1\n  company\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains("<div id=\"test-co\" class=\"experience-item p-experience h-event\">"));
+  assert!(html.contains("<div id=\"second-co\" class=\"experience-item srg-more p-experience h-event\">"));
+  assert!(html.contains("<button class=\"srg-toggle\" type=\"button\" aria-expanded=\"false\">Show 2 more</button>"));
+  assert!(html.contains("<noscript><style>") && html.contains("@media print"));
+  assert!(srg::markup::check(&html).is_empty());
+
+  // Exports list every entry and no toggle.
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &Default::default()).unwrap();
+  assert!(text.contains("Third Co") && !text.contains("Show 2 more"));
+
+  // Nothing to hide, no toggle.
+  let layout = srg::layout::Layout::parse("experience collapse=3\n  company\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("srg-more\"") && !html.contains("<button"));
+
+  assert!(srg::layout::Layout::parse("experience collapse=0\n  company\n").is_err());
+  assert!(srg::layout::Layout::parse("experience collapse=some\n  company\n").is_err());
+}
+
+#[test]
+fn test_search_box() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("---\nsearch = true\n---\nexperience collapse=1\n  company\n  highlights\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+
+  assert!(html.contains("/* Search */"));
+  assert!(html.contains("box.className = \"srg-search\";"));
+  assert!(html.contains(".srg-searching .section > .srg-more"));
+  assert!(srg::markup::check(&html).is_empty());
+
+  // Off unless the frontmatter asks for it.
+  let layout = srg::layout::Layout::parse("experience\n  company\n").unwrap();
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("srg-search"));
+}
+
+#[test]
+fn test_talks_section() {
+  let doc = create_test_document();
+  let source = r#"
+[[talks]]
+title = "Parsing <resumes>"
+event = "RustConf"
+date = "2024-09"
+location = "Montreal"
+slides = "https://example.com/slides"
+video = "https://example.com/video"
+
+[[talks]]
+title = "Lightning talk"
+event = "Local meetup"
+"#;
+  let options = srg::build::BuildOptions {
+    date_format: Some("%b %Y".to_string()),
+    extras: srg::build::Extras {
+      talks: srg::sections::talks(source).unwrap(),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let layout = srg::layout::Layout::parse("toc\n\ntalks\n  title\n  event \" · \" location\n  date\n  slides\n  video\n").unwrap();
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains("<section id=\"talks\" class=\"section section-talks\">"));
+  assert!(html.contains("<div id=\"talks-parsing-resumes\" class=\"talks-item\">"));
+  assert!(html.contains("<h3 class=\"talks-title\">Parsing &lt;resumes&gt;</h3>"));
+  assert!(html.contains("<p>RustConf · Montreal</p>"));
+  assert!(html.contains("<p>Local meetup</p>"));
+  assert!(html.contains("<span class=\"talks-date\">Sep 2024</span>"));
+  assert!(html.contains("<a class=\"talks-slides\" href=\"https://example.com/slides\">Slides</a>"));
+  assert!(html.contains("<a href=\"#talks-lightning-talk\">Lightning talk</a>"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(text.contains("TALKS\n-----\nParsing <resumes>\nRustConf · Montreal\nSep 2024\nSlides: https://example.com/slides\n"));
+
+  // Nothing to show without talks.
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("id=\"talks\""));
+}
+
+#[test]
+fn test_patents_section() {
+  let doc = create_test_document();
+  let source = r#"
+[[patents]]
+title = "Method of <weaving> patterns"
+number = "US 10,123,456 B2"
+status = "Granted"
+year = 2021
+
+[[patents]]
+title = "Analytical engine"
+status = "Pending"
+"#;
+  let options = srg::build::BuildOptions {
+    extras: srg::build::Extras {
+      patents: srg::sections::patents(source).unwrap(),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let layout = srg::layout::Layout::parse("patents\n  title\n  number\n  status\n  year\n").unwrap();
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+
+  assert!(html.contains("<section id=\"patents\" class=\"section section-patents\">"));
+  assert!(html.contains("<ul class=\"patents-list\">"));
+  assert!(html.contains("<li id=\"patents-method-of-weaving-patterns\" class=\"patents-item\">"));
+  assert!(html.contains("<span class=\"patents-title\">Method of &lt;weaving&gt; patterns</span>"));
+  assert!(html.contains("<span class=\"patents-number\">US 10,123,456 B2</span>"));
+  assert!(html.contains("<span class=\"patents-year\">2021</span>"));
+  assert!(html.contains(".patents-item > * + *::before"));
+
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(text.contains("Method of <weaving> patterns · US 10,123,456 B2 · Granted · 2021"));
+  assert!(text.contains("Analytical engine · Pending"));
+
+  // Nothing to show without patents.
+  let html = srg::build::generate_test_html(&doc, None, &layout).unwrap();
+  assert!(!html.contains("id=\"patents\""));
+}
+
+#[test]
+fn test_bundled_themes_keep_the_contract() {
+  for theme in srg::themes::THEMES {
+    assert_eq!(srg::contract::check_theme(theme), Vec::<String>::new(), "theme {}", theme);
+  }
+}
+
+#[test]
+fn test_contract_catches_broken_pages() {
+  let doc = srg::contract::sample_document();
+  let layout = srg::layout::Layout::parse("person\n  name\n\nawards\n").unwrap();
+  let page = format!("<html><body><header id=\"person\"><p>{}</p></header><div></body></html>", srg::contract::PROBE);
+  let violations = srg::contract::check_page(&page, &layout, &doc, &srg::contract::sample_extras());
+
+  assert!(violations.iter().any(|v| v.contains("\"awards\" isn't one srg renders")));
+  assert!(violations.iter().any(|v| v.contains("unescaped")));
+  assert!(violations.iter().any(|v| v.contains("<div> is never closed")));
+}
+
+#[test]
+fn test_strict_markup_check() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("person\n  name\n").unwrap();
+  let dir = tempfile::tempdir().unwrap();
+  let css = dir.path().join("custom.css");
+  std::fs::write(&css, "p { color: red; }\n</style><div>\n").unwrap();
+  let mut options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Html],
+    css_mode: srg::build::CssMode::Inline,
+    ..Default::default()
+  };
+
+  let out = dir.path().join("out");
+  srg::build::build_resume(&doc, &out, None, &layout, Some(&css), &options).unwrap();
+  assert!(out.join("index.html").exists());
+
+  options.strict = true;
+  let strict_out = dir.path().join("strict");
+  let err = srg::build::build_resume(&doc, &strict_out, None, &layout, Some(&css), &options).unwrap_err();
+  let message = format!("{:#}", err);
+  assert!(message.contains("index.html isn't well-formed HTML"), "{}", message);
+  assert!(message.contains("</style> has no matching <style>"), "{}", message);
+  assert!(!strict_out.join("index.html").exists());
+}
+
+#[test]
+fn test_booking_block_and_calendar() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("person\n  name\n  booking\n").unwrap();
+  let booking: srg::build::booking::Booking = toml::from_str(
+    "url = \"https://cal.com/test/intro\"\ndays = [\"thu\", \"tue\"]\nhours = \"14:00-16:30\"\ntimezone = \"Europe/London\"\n",
+  )
+  .unwrap();
+  booking.check().unwrap();
+
+  let mut options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Html],
+    ..Default::default()
+  };
+  options.extras.booking = Some(booking.clone());
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(html.contains("<div class=\"person-booking\">"));
+  assert!(html.contains("<a class=\"booking-link\" href=\"https://cal.com/test/intro\">Book a chat</a>"));
+  assert!(html.contains("<span class=\"booking-hours\">Tue, Thu 14:00–16:30 (Europe/London)</span>"));
+  assert!(html.contains("<a class=\"booking-calendar\" href=\"availability.ics\">Add to calendar</a>"));
+  assert!(html.contains("/* Booking */"));
+
+  let dir = tempfile::tempdir().unwrap();
+  srg::build::build_resume(&doc, dir.path(), None, &layout, None, &options).unwrap();
+  let ics = std::fs::read_to_string(dir.path().join("availability.ics")).unwrap();
+  assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+  assert!(ics.contains("\r\nDTSTART;TZID=Europe/London:20240102T140000\r\n"));
+  assert!(ics.contains("\r\nDTEND;TZID=Europe/London:20240102T163000\r\n"));
+  assert!(ics.contains("\r\nRRULE:FREQ=WEEKLY;BYDAY=TU,TH\r\n"));
+  assert!(ics.contains("\r\nSUMMARY:Office hours: Test User\r\n"));
+  assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+
+  // Without hours there's only the link, and no calendar.
+  options.extras.booking = Some(srg::build::booking::Booking { hours: None, days: Vec::new(), ..booking.clone() });
+  let html = srg::build::generate_test_html_with_options(&doc, None, &layout, &options).unwrap();
+  assert!(!html.contains("booking-hours"));
+  let dir = tempfile::tempdir().unwrap();
+  srg::build::build_resume(&doc, dir.path(), None, &layout, None, &options).unwrap();
+  assert!(!dir.path().join("availability.ics").exists());
+
+  for bad in ["4pm", "16:00-14:00"] {
+    let bad = srg::build::booking::Booking { hours: Some(bad.to_string()), ..booking.clone() };
+    assert!(bad.check().is_err());
+  }
+  let bad = srg::build::booking::Booking { url: "cal.com/test".to_string(), ..booking };
+  assert!(bad.check().is_err());
+}
+
+#[test]
+fn test_txt_wraps_lines() {
+  let mut doc = create_test_document();
+  let words = "Cut build times from forty minutes to six by caching dependencies and splitting the test suite across runners";
+  doc.experience[0].summary = Some(words.to_string());
+  doc.experience[0].highlights = vec![
+    words.to_string(),
+    "See https://example.com/a/very/long/path/that/goes/on/and/on/well/past/the/wrap/column/of/the/export".to_string(),
+  ];
+  let layout = srg::layout::Layout::parse("experience\n  title\n  summary\n  highlights\n").unwrap();
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &Default::default()).unwrap();
+
+  assert!(text.contains(
+    "Cut build times from forty minutes to six by caching dependencies and splitting\nthe test suite across runners\n"
+  ));
+  assert!(text.contains(
+    "- Cut build times from forty minutes to six by caching dependencies and\n  splitting the test suite across runners\n"
+  ));
+  // A URL too long for a line is moved to its own, not broken.
+  assert!(text.contains("- See\n  https://example.com/a/very/long/path/"));
+  assert!(!text.contains("&amp;") && !text.contains('<'));
+
+  let options = srg::build::BuildOptions { txt_width: 0, ..Default::default() };
+  let text = srg::build::render_export(srg::build::OutputFormat::Txt, &doc, &layout, &options).unwrap();
+  assert!(text.contains(&format!("- {}\n", words)));
+}
+
+#[test]
+fn test_typst_source() {
+  let mut doc = create_test_document();
+  doc.experience[0].highlights = vec!["Shipped \"v2\" in #3 weeks".to_string()];
+  let layout = srg::layout::Layout::parse("person\n  name\n  website\n\nexperience\n  title\n  highlights\n").unwrap();
+  let options = srg::build::BuildOptions {
+    pdf_backend: srg::build::PdfBackend::Typst,
+    paper: srg::build::Paper::A4,
+    watermark: Some("DRAFT".to_string()),
+    ..Default::default()
+  };
+  let typ = srg::build::typst_source(&doc, &layout, &options);
+
+  assert!(typ.contains("#set page(width: 8.27in, height: 11.69in"));
+  assert!(typ.contains("\"DRAFT\""));
+  assert!(typ.contains("#text(size: 1.8em, weight: \"bold\", \"Test User\")"));
+  assert!(typ.contains("#link(\"https://example.com\", \"https://example.com\")"));
+  assert!(typ.contains("#heading(\"Experience\")"));
+  assert!(typ.contains("- #\"Shipped \\\"v2\\\" in #3 weeks\"\n"));
+}
+
+#[test]
+fn test_odt_output() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("person\n  name\n\nexperience\n  title\n  highlights\n").unwrap();
+  let options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Odt],
+    ..Default::default()
+  };
+  let dir = tempfile::tempdir().unwrap();
+  let written = srg::build::build_resume(&doc, dir.path(), None, &layout, None, &options).unwrap();
+
+  assert_eq!(written, vec![(srg::build::OutputFormat::Odt, dir.path().join("resume.odt"))]);
+  let odt = std::fs::read(dir.path().join("resume.odt")).unwrap();
+  // The uncompressed mimetype comes first, so the file identifies itself.
+  assert_eq!(&odt[30..38], b"mimetype");
+  assert!(odt[38..].starts_with(b"application/vnd.oasis.opendocument.text"));
+  assert!(!dir.path().join("index.html").exists());
+  assert!(srg::build::render_export(srg::build::OutputFormat::Odt, &doc, &layout, &options).is_none());
+}
+
+#[test]
+fn test_pdf_only_build_leaves_no_page() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("jake").unwrap();
+  let options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Pdf],
+    ..Default::default()
+  };
+  let dir = tempfile::tempdir().unwrap();
+  // Chrome may not be installed here; either way, the page it prints
+  // from and the theme's fonts aren't left in the output directory.
+  let built = srg::build::build_resume(&doc, dir.path(), Some("jake"), &layout, None, &options);
+  let mut names: Vec<String> = std::fs::read_dir(dir.path())
+    .unwrap()
+    .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+    .collect();
+  names.sort();
+  match built {
+    Ok(written) => {
+      assert_eq!(written, vec![(srg::build::OutputFormat::Pdf, dir.path().join("resume.pdf"))]);
+      assert_eq!(names, ["resume.pdf"]);
+    }
+    Err(_) => assert!(names.is_empty(), "{:?}", names),
+  }
+}
+
+#[test]
+fn test_epub_output() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::from_theme("minimal").unwrap();
+  let options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Epub],
+    ..Default::default()
+  };
+  let dir = tempfile::tempdir().unwrap();
+  let written = srg::build::build_resume(&doc, dir.path(), Some("minimal"), &layout, None, &options).unwrap();
+
+  assert_eq!(written, vec![(srg::build::OutputFormat::Epub, dir.path().join("resume.epub"))]);
+  let epub = std::fs::read(dir.path().join("resume.epub")).unwrap();
+  assert_eq!(&epub[30..38], b"mimetype");
+  assert!(epub[38..].starts_with(b"application/epub+zip"));
+  assert!(!dir.path().join("index.html").exists());
+}
+
+#[test]
+fn test_html_fragment() {
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("---\nsearch = true\n---\nperson\n  name\nexperience\n  title\n").unwrap();
+  let options = srg::build::BuildOptions {
+    formats: vec![srg::build::OutputFormat::Html],
+    css_mode: srg::build::CssMode::External,
+    fragment: true,
+    strict: true,
+    ..Default::default()
+  };
+
+  let dir = tempfile::tempdir().unwrap();
+  srg::build::build_resume(&doc, dir.path(), Some("minimal"), &layout, None, &options).unwrap();
+  let html = std::fs::read_to_string(dir.path().join("index.html")).unwrap();
+  assert!(html.starts_with("    <header"), "{}", html);
+  assert!(html.contains("class=\"person-name p-name\">Test User<"));
+  assert!(html.contains("experience-item p-experience h-event"));
+  assert!(html.contains("<script>"));
+  for wrapper in ["<!DOCTYPE", "<html", "<head>", "<body", "<main", "<style"] {
+    assert!(!html.contains(wrapper), "{} in {}", wrapper, html);
+  }
+  assert!(dir.path().join("style.css").exists());
+}
+
+#[test]
+fn test_markdown_import() {
+  let note = "---\nname: Ada Lovelace\npronouns: she/her\ntags: [cv]\n---\n\n## Experience\n\n### Analyst at Engine Co\ndates:: 1842 - 1843\n\n- Wrote the first algorithm\n";
+  let draft = srg::import::markdown::convert(note).unwrap();
+  let source = draft.to_jobl().unwrap();
+
+  // The draft is a JOBL file srg builds from, [person.meta] included.
+  let stripped = srg::sections::strip(&source).unwrap().unwrap_or(source.clone());
+  let doc = jobl::parse_str(&stripped).unwrap();
+  assert_eq!(doc.experience[0].company, "Engine Co");
+  assert_eq!(doc.experience[0].highlights, vec!["Wrote the first algorithm"]);
+  let person = srg::sections::person_fields(&source).unwrap();
+  assert_eq!(person.meta.get("pronouns").map(String::as_str), Some("she/her"));
+  assert!(draft.notes.is_empty(), "{:?}", draft.notes);
+}
+
+#[test]
+fn test_text_import() {
+  let text = "Ada Lovelace\nada@example.com | London\n\nEXPERIENCE\n\nAnalyst, Engine Co    Jan 1842 - Present\n- Wrote the first algorithm\n";
+  let draft = srg::import::text::convert(text).unwrap();
+  let doc = jobl::parse_str(&draft.to_jobl().unwrap()).unwrap();
+  assert_eq!(doc.person.email.as_deref(), Some("ada@example.com"));
+  assert_eq!(doc.person.location.as_deref(), Some("London"));
+  let exp = &doc.experience[0];
+  assert_eq!((exp.title.as_str(), exp.company.as_str()), ("Analyst", "Engine Co"));
+  assert_eq!((exp.start.as_deref(), exp.end.as_deref()), (Some("1842-01"), Some("Present")));
+  assert_eq!(exp.highlights, vec!["Wrote the first algorithm"]);
+  assert!(draft.notes.is_empty(), "{:?}", draft.notes);
+}
+
+#[test]
+fn test_formats_build_concurrently() {
+  use srg::build::OutputFormat;
+  let doc = create_test_document();
+  let layout = srg::layout::Layout::parse("person\n  name\n\nexperience\n  title\n  highlights\n").unwrap();
+  let formats = vec![OutputFormat::Txt, OutputFormat::Markdown, OutputFormat::Org, OutputFormat::Rst, OutputFormat::Odt];
+  let options = srg::build::BuildOptions { formats: formats.clone(), ..Default::default() };
+  let dir = tempfile::tempdir().unwrap();
+  let written = srg::build::build_resume(&doc, dir.path(), None, &layout, None, &options).unwrap();
+
+  // Every format, in a fixed order whichever thread finished first.
+  let order: Vec<OutputFormat> = written.iter().map(|(format, _)| *format).collect();
+  assert_eq!(order, [OutputFormat::Odt, OutputFormat::Txt, OutputFormat::Markdown, OutputFormat::Org, OutputFormat::Rst]);
+
+  // A format that can't be written fails alone; the rest are built.
+  let out = dir.path().join("blocked");
+  std::fs::create_dir_all(out.join(OutputFormat::Markdown.file_name())).unwrap();
+  let err = srg::build::build_resume(&doc, &out, None, &layout, None, &options).unwrap_err();
+  let message = format!("{:#}", err);
+  assert!(message.starts_with("1 of the formats failed:\n  Markdown: Failed to write"), "{}", message);
+  assert!(out.join(OutputFormat::Txt.file_name()).exists());
+  assert!(out.join(OutputFormat::Rst.file_name()).exists());
+}
+
+#[test]
+fn test_linkedin_import() {
+  let mut zip = srg::archive::ZipWriter::new(Vec::new());
+  zip.add_file("Profile.csv", b"First Name,Last Name,Headline\nAda,Lovelace,Analyst\n").unwrap();
+  zip.add_file("Positions.csv", b"Company Name,Title,Description,Location,Started On,Finished On\nEngine Co,Analyst,,London,Jan 1842,Sep 1843\n").unwrap();
+  let draft = srg::import::linkedin::convert(&zip.finish().unwrap()).unwrap();
+
+  let doc = jobl::parse_str(&draft.to_jobl().unwrap()).unwrap();
+  assert_eq!(doc.person.name, "Ada Lovelace");
+  let exp = &doc.experience[0];
+  assert_eq!((exp.company.as_str(), exp.location.as_deref()), ("Engine Co", Some("London")));
+  assert_eq!((exp.start.as_deref(), exp.end.as_deref()), (Some("1842-01"), Some("1843-09")));
+}
+
+#[test]
+fn test_layered_resumes() {
+  let base = "[person]\nname = \"Ada Lovelace\"\nheadline = \"Analyst\"\n\n[[experience]]\ntitle = \"Analyst\"\ncompany = \"Engine Co\"\nhighlights = [\"Wrote the first algorithm\"]\n";
+  let variant = "[person]\nheadline = \"Consultant\"\n\n[[experience]]\ntitle = \"Consultant\"\ncompany = \"Self\"\n";
+  let merged = srg::merge::merge_sources([("base.jobl", base), ("variant.jobl", variant)]).unwrap();
+
+  // The merged source is a JOBL file like any other.
+  let doc = jobl::parse_str(&merged).unwrap();
+  assert_eq!(doc.person.name, "Ada Lovelace");
+  assert_eq!(doc.person.headline.as_deref(), Some("Consultant"));
+  let companies: Vec<&str> = doc.experience.iter().map(|e| e.company.as_str()).collect();
+  assert_eq!(companies, ["Engine Co", "Self"]);
+  assert_eq!(doc.experience[0].highlights, ["Wrote the first algorithm"]);
+}
+
+#[test]
+fn test_ci_annotations() {
+  let source = "[person]\nname = \"Ada Lovelace\"\n\n[[experience]]\ntitle = \"Analyst\"\ncompany = \"Engine Co\"\nhighlights = [\n  \"Improved the documentation.\",\n]\n";
+  let doc = jobl::parse_str(source).unwrap();
+  let findings = srg::lint::lint(&doc, &srg::lint::LintConfig::default());
+  let finding = findings.iter().find(|f| f.rule == "quantify").unwrap();
+
+  let line = srg::diagnostics::line_of_location(source, &doc, &finding.location);
+  assert_eq!(line, Some(8));
+  let annotation = srg::diagnostics::Diagnostic::warning(finding.message.clone())
+    .at(std::path::Path::new("resume.jobl"), line)
+    .annotation();
+  assert!(annotation.starts_with("::warning file=resume.jobl,line=8::no numbers"), "{}", annotation);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_outputs_keep_plain_file_modes() {
+  use std::os::unix::fs::PermissionsExt;
+
+  let dir = tempfile::tempdir().unwrap();
+  let mode = |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+  // A new file gets what a plain write would give it.
+  let plain = dir.path().join("plain.txt");
+  std::fs::write(&plain, "plain").unwrap();
+  let new = dir.path().join("resume.txt");
+  srg::build::write_atomic(&new, "resume").unwrap();
+  assert_eq!(mode(&new), mode(&plain));
+
+  // An existing file keeps its own.
+  std::fs::set_permissions(&new, std::fs::Permissions::from_mode(0o640)).unwrap();
+  srg::build::write_atomic(&new, "rebuilt").unwrap();
+  assert_eq!(mode(&new), 0o640);
+  assert_eq!(std::fs::read_to_string(&new).unwrap(), "rebuilt");
+}